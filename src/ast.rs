@@ -0,0 +1,175 @@
+use std::fmt;
+
+use num_bigint::BigInt;
+
+use crate::calculator::{Error, Function, Operator, Token};
+use crate::constants::Constant;
+use crate::units::Unit;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Expr {
+    Number(f64),
+    Integer(BigInt),
+    Imaginary(f64),
+    Text(String),
+    Variable(String),
+    Constant(&'static Constant),
+    Unit(&'static Unit),
+    Neg(Box<Expr>),
+    Binary(Operator, Box<Expr>, Box<Expr>),
+    Function(Function, Vec<Expr>),
+    Factorial(Box<Expr>),
+    // [a, b, …], a row or, of rows, a matrix
+    List(Vec<Expr>),
+    // {a, b, …}, a list of numbers
+    Braces(Vec<Expr>),
+}
+
+impl Expr {
+    // Builds a tree from the RPN produced by `Calculator::expression`
+    pub fn from_rpn(tokens: Vec<Token>) -> Result<Expr, Error> {
+        let missing = || Error::InvalidOperation("No hay suficientes operandos".to_string());
+        let mut stack: Vec<Expr> = Vec::new();
+        for token in tokens {
+            let node = match token {
+                Token::Number(n) => Expr::Number(n),
+                Token::Integer(n) => Expr::Integer(n),
+                Token::Imaginary(n) => Expr::Imaginary(n),
+                Token::Text(text) => Expr::Text(text),
+                Token::Variable(name) => Expr::Variable(name),
+                Token::Constant(c) => Expr::Constant(c),
+                Token::Unit(unit) => Expr::Unit(unit),
+                Token::Op(op) => {
+                    let right = stack.pop().ok_or_else(missing)?;
+                    let left = stack.pop().ok_or_else(missing)?;
+                    // The parser writes unary minus as -1 * x
+                    if op == Operator::Mul && left == Expr::Number(-1.0) {
+                        Expr::Neg(Box::new(right))
+                    } else {
+                        Expr::Binary(op, Box::new(left), Box::new(right))
+                    }
+                },
+                Token::Function(func) => {
+                    if stack.len() < func.arity() {
+                        return Err(missing());
+                    }
+                    Expr::Function(func, stack.split_off(stack.len() - func.arity()))
+                },
+                Token::Factorial => Expr::Factorial(Box::new(stack.pop().ok_or_else(missing)?)),
+                Token::List(n) => {
+                    if stack.len() < n {
+                        return Err(missing());
+                    }
+                    Expr::List(stack.split_off(stack.len() - n))
+                },
+                Token::Braces(n) => {
+                    if stack.len() < n {
+                        return Err(missing());
+                    }
+                    Expr::Braces(stack.split_off(stack.len() - n))
+                },
+                Token::Bracket(_) | Token::Comma => continue,
+            };
+            stack.push(node);
+        }
+        if stack.len() != 1 {
+            return Err(Error::InvalidOperation("Expresión inválida".to_string()));
+        }
+        Ok(stack.pop().unwrap())
+    }
+
+    // Binding strength used to decide where parentheses are needed
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Binary(op, _, _) => op.precedence(),
+            Expr::Neg(_) => Operator::Mul.precedence(),
+            _ => u8::MAX,
+        }
+    }
+}
+
+fn operator_symbol(op: &Operator) -> &'static str {
+    match op {
+        Operator::Add => " + ",
+        Operator::Sub => " - ",
+        Operator::Mul => " * ",
+        Operator::Div => " / ",
+        Operator::Pow => "^",
+        Operator::Mod => " mod ",
+        Operator::PlusMinus => "±",
+        Operator::Juxtapose => "",
+        Operator::And => " & ",
+        Operator::Or => " | ",
+        Operator::Xor => " xor ",
+        Operator::Not => "~",
+        Operator::Shl => " << ",
+        Operator::Shr => " >> ",
+    }
+}
+
+fn write_child(f: &mut fmt::Formatter, child: &Expr, parens: bool) -> fmt::Result {
+    if parens {
+        write!(f, "({})", child)
+    } else {
+        write!(f, "{}", child)
+    }
+}
+
+// Pretty-printer that keeps only the parentheses the parser needs
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Number(n) => write!(f, "{}", n),
+            Expr::Integer(n) => write!(f, "{}", n),
+            Expr::Imaginary(n) => write!(f, "{}i", n),
+            Expr::Text(text) => write!(f, "\"{}\"", text),
+            Expr::Variable(name) => write!(f, "{}", name),
+            Expr::Constant(c) => write!(f, "{}", c.name),
+            Expr::Unit(unit) => write!(f, "{}", unit.symbol),
+            Expr::Neg(inner) => {
+                write!(f, "-")?;
+                write_child(f, inner, inner.precedence() <= Operator::Add.precedence())
+            },
+            Expr::Binary(Operator::Juxtapose, left, right) => {
+                let precedence = Operator::Juxtapose.precedence();
+                let wrap = |child: &Expr, parens: bool| if parens { format!("({})", child) } else { child.to_string() };
+                let left = wrap(left, left.precedence() < precedence);
+                let right = wrap(right, right.precedence() <= precedence);
+                // Side by side, with a space where names or digits would merge (x y, 2 e)
+                let joins = left.ends_with(|c: char| c.is_ascii_alphanumeric())
+                    && right.starts_with(|c: char| c.is_ascii_alphanumeric());
+                let safe = left.ends_with(|c: char| c.is_ascii_digit())
+                    && right.starts_with(|c: char| c.is_ascii_alphabetic() && !"eEiI".contains(c));
+                write!(f, "{}{}{}", left, if joins && !safe { " " } else { "" }, right)
+            },
+            Expr::Binary(op, left, right) => {
+                let precedence = op.precedence();
+                // Every operator parses left-associative, so only an equal-precedence
+                // right operand needs parentheses, unless regrouping can't change it
+                let regroupable = matches!(right.as_ref(), Expr::Binary(inner, _, _)
+                    if inner == op && matches!(op, Operator::Add | Operator::Mul));
+                write_child(f, left, left.precedence() < precedence)?;
+                write!(f, "{}", operator_symbol(op))?;
+                write_child(f, right, right.precedence() < precedence
+                    || (right.precedence() == precedence && !regroupable))
+            },
+            Expr::Function(func, args) => {
+                let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+                write!(f, "{}({})", func, args.join(", "))
+            },
+            Expr::List(items) => {
+                let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+                write!(f, "[{}]", items.join(", "))
+            },
+            Expr::Braces(items) => {
+                let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+                write!(f, "{{{}}}", items.join(", "))
+            },
+            Expr::Factorial(inner) => {
+                let atomic = matches!(inner.as_ref(), Expr::Number(_) | Expr::Integer(_) | Expr::Variable(_) | Expr::Constant(_) | Expr::Unit(_) | Expr::Function(_, _) | Expr::Factorial(_));
+                write_child(f, inner, !atomic)?;
+                write!(f, "!")
+            },
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use crate::calculator::{Calculator, Error};
+use crate::value::Value;
+
+type Outcome = (Result<(Value, Vec<String>), Error>, BTreeMap<String, Value>);
+
+pub struct Finished {
+    pub result: Result<(Value, Vec<String>), Error>,
+    // Variables the expression set, to copy into the app's calculator
+    pub assigned: Vec<(String, Value)>,
+}
+
+// An expression evaluated on its own thread, so a long calculation such as
+// 100000! in integer mode doesn't freeze the window
+pub struct Evaluation {
+    pub expression: String,
+    started: Instant,
+    variables: BTreeMap<String, Value>,
+    receiver: mpsc::Receiver<Outcome>,
+}
+
+impl Evaluation {
+    pub fn start(calculator: &Calculator, expression: &str) -> Evaluation {
+        let mut calculator = calculator.clone();
+        let variables = calculator.variables.clone();
+        let (sender, receiver) = mpsc::channel();
+        let input = expression.to_string();
+        std::thread::spawn(move || {
+            let result = calculator.execute(&input);
+            // Nobody is waiting any more if it was cancelled
+            let _ = sender.send((result, calculator.variables));
+        });
+        Evaluation { expression: expression.to_string(), started: Instant::now(), variables, receiver }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    // The result if it's ready within `wait`
+    pub fn finished(&self, wait: Duration) -> Option<Finished> {
+        match self.receiver.recv_timeout(wait) {
+            Ok((result, variables)) => {
+                let assigned = variables.into_iter().filter(|(name, value)| self.variables.get(name) != Some(value)).collect();
+                Some(Finished { result, assigned })
+            },
+            Err(mpsc::RecvTimeoutError::Timeout) => None,
+            Err(mpsc::RecvTimeoutError::Disconnected) => Some(Finished {
+                result: Err(Error::InvalidOperation("La evaluación se interrumpió".to_string())),
+                assigned: Vec::new(),
+            }),
+        }
+    }
+}
+
+// A desktop notification with the result, for when the window is in the background
+pub fn notify(expression: &str, result: &str) {
+    let shown = notify_rust::Notification::new()
+        .appname("Calculadora Científica Guizar")
+        .summary("Cálculo terminado")
+        .body(&format!("{} = {}", expression, result))
+        .show();
+    if let Err(e) = shown {
+        log::debug!(target: "calc::notify", "{}", e);
+    }
+}
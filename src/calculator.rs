@@ -1,15 +1,61 @@
-use std::f64::consts::{E, PI};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Range;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use crate::ast::Expr;
+use crate::complex::Complex;
+use crate::constants::{self, Constant};
+use crate::currency::{self, Rates};
+use crate::derivative;
+use crate::distribution;
+use crate::eigen::Eigen;
+use crate::exact::{self, Exact};
+use crate::fetch;
+use crate::finance;
+use crate::format;
+use crate::integer;
+use crate::limits;
+use crate::list;
+use crate::matrix::Matrix;
+use crate::messages;
+use crate::precision;
+use crate::quantity::Quantity;
+use crate::separators::Separators;
+use crate::simplify;
+use crate::solver;
+use crate::special;
+use crate::uncertainty::Measurement;
+use crate::units::{self, Category, Unit};
+use crate::value::Value;
+use crate::words::{self, Locale};
 
 #[derive(Debug, PartialEq, PartialOrd, Clone)]
 pub enum Token {
     Number(f64),
+    Integer(BigInt),
+    Imaginary(f64),
+    Text(String),
+    Variable(String),
     Op(Operator),
+    Factorial,
+    Comma,
     Bracket(char),
+    // The ']' closing a [a, b, …] literal of this many elements
+    List(usize),
+    // The '}' closing a {a, b, …} list of this many elements
+    Braces(usize),
     Function(Function),
-    Constant(Constant),
+    Constant(&'static Constant),
+    // Only in unit mode, where names like km replace variables
+    Unit(&'static Unit),
 }
 
-#[derive(Debug, PartialEq, PartialOrd, Clone)]
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy)]
 pub enum Operator {
     Add,      // +
     Sub,      // -
@@ -17,13 +63,31 @@ pub enum Operator {
     Div,      // /
     Pow,      // ^
     Mod,      // %
+    PlusMinus, // ±
+    Juxtapose, // implicit multiplication, e.g. 2pi
+    // Bitwise operators, only produced by the programmer mode lexer
+    And,      // &
+    Or,       // |
+    Xor,      // xor
+    Not,      // ~, written as -1 xor x like unary minus is -1 * x
+    Shl,      // <<
+    Shr,      // >>
 }
 
-#[derive(Debug, PartialEq, PartialOrd, Clone)]
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy)]
 pub enum Function {
     Sin,
     Cos,
     Tan,
+    Asin,
+    Acos,
+    Atan,
+    Sinh,
+    Cosh,
+    Tanh,
+    Asinh,
+    Acosh,
+    Atanh,
     Sqrt,
     Cbrt,
     Log,
@@ -32,85 +96,545 @@ pub enum Function {
     Floor,
     Ceil,
     Round,
-}
-
-#[derive(Debug, PartialEq, PartialOrd, Clone)]
-pub enum Constant {
-    Pi,
-    E,
+    Gamma,
+    Lgamma,
+    Erf,
+    Erfc,
+    Words,
+    Exp,
+    Convert,
+    // Seconds since the Unix epoch, and whole days since it
+    Now,
+    Today,
+    // assert_eq(value, expected, tolerance): the value, or an error when it's off
+    AssertEq,
+    // fetch_num(url, json_pointer), only with downloads allowed in the settings
+    FetchNum,
+    // nderiv("expression", "variable", at): numeric derivative
+    Nderiv,
+    // solve("expression", "variable", guess): a root near guess
+    Solve,
+    Det,
+    Inv,
+    Transpose,
+    Rank,
+    // lu(A, "L"), qr(A, "Q"): one factor of the decomposition
+    Lu,
+    Qr,
+    // eig(A): eigenvalues, complex ones too, with their eigenvectors
+    Eig,
+    Dot,
+    Cross,
+    Norm,
+    // angle(u, v): between two vectors, in the angle mode
+    Angle,
+    // Aggregates of a {a, b, …} list
+    Sum,
+    Prod,
+    Mean,
+    Median,
+    Mode,
+    Sort,
+    Len,
+    // Probability distributions, with arguments in calculator order:
+    // normpdf(x, μ, σ), binompdf(n, p, k), poisspdf(λ, k), tcdf(t, ν)…
+    Normpdf,
+    Normcdf,
+    Binompdf,
+    Binomcdf,
+    Poisspdf,
+    Tcdf,
+    Chi2cdf,
+    // Time value of money, as in spreadsheets: pmt(rate, nper, pv)…
+    Pmt,
+    Pv,
+    Fv,
+    Nper,
+    Rate,
+    // npv(rate, {flows}) and irr({flows})
+    Npv,
+    Irr,
+    // Named like usd_to_eur, converted with the editable rate table
+    Currency(currency::Code, currency::Code),
 }
 
 #[derive(Debug)]
 pub enum Error {
-    BadToken(char),
-    MismatchedParens,
+    // Positions count chars from 0
+    BadToken { token: char, position: usize },
+    // Where the stray ')' or the first '(' left open is, when known
+    MismatchedParens { position: Option<usize> },
     InvalidNumber(String),
     DivisionByZero,
     InvalidOperation(String),
     UnknownFunction(String),
+    UndefinedVariable(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let english = messages::language() == Locale::En;
+        match (self, english) {
+            (Error::BadToken { token, position }, false) => write!(f, "Carácter no válido «{}» en la posición {}", token, position + 1),
+            (Error::BadToken { token, position }, true) => write!(f, "Invalid character '{}' at position {}", token, position + 1),
+            (Error::MismatchedParens { position: Some(position) }, false) => write!(f, "Paréntesis sin pareja en la posición {}", position + 1),
+            (Error::MismatchedParens { position: Some(position) }, true) => write!(f, "Unmatched parenthesis at position {}", position + 1),
+            (Error::MismatchedParens { position: None }, false) => write!(f, "Paréntesis desbalanceados"),
+            (Error::MismatchedParens { position: None }, true) => write!(f, "Unbalanced parentheses"),
+            (Error::InvalidNumber(number), false) => write!(f, "Número no válido: {}", number),
+            (Error::InvalidNumber(number), true) => write!(f, "Invalid number: {}", number),
+            (Error::DivisionByZero, false) => write!(f, "División por cero"),
+            (Error::DivisionByZero, true) => write!(f, "Division by zero"),
+            (Error::InvalidOperation(message), _) => write!(f, "{}", messages::translate(message)),
+            (Error::UnknownFunction(name), false) => write!(f, "Función desconocida: {}", name),
+            (Error::UnknownFunction(name), true) => write!(f, "Unknown function: {}", name),
+            (Error::UndefinedVariable(name), false) => write!(f, "Variable no definida: {}", name),
+            (Error::UndefinedVariable(name), true) => write!(f, "Undefined variable: {}", name),
+        }
+    }
 }
 
+impl std::error::Error for Error {}
+
 impl Operator {
-    fn precedence(&self) -> u8 {
+    pub fn precedence(&self) -> u8 {
+        match self {
+            // Same order as C: | below xor below & below shifts
+            Operator::Or => 1,
+            Operator::Xor => 2,
+            Operator::And => 3,
+            Operator::Shl | Operator::Shr => 4,
+            Operator::Add | Operator::Sub => 5,
+            Operator::Mul | Operator::Div | Operator::Mod => 6,
+            // Only reached in tight mode; loose mode turns it into Mul
+            Operator::Juxtapose => 7,
+            Operator::Pow => 8,
+            // Binds tightest so 9.81±0.02 * 2 scales the measurement
+            Operator::PlusMinus => 9,
+            Operator::Not => 10,
+        }
+    }
+
+    pub fn is_bitwise(&self) -> bool {
+        matches!(self, Operator::And | Operator::Or | Operator::Xor | Operator::Not | Operator::Shl | Operator::Shr)
+    }
+}
+
+pub const FUNCTIONS: &[(&str, Function)] = &[
+    ("sin", Function::Sin),
+    ("cos", Function::Cos),
+    ("tan", Function::Tan),
+    ("asin", Function::Asin),
+    ("acos", Function::Acos),
+    ("atan", Function::Atan),
+    ("sinh", Function::Sinh),
+    ("cosh", Function::Cosh),
+    ("tanh", Function::Tanh),
+    ("asinh", Function::Asinh),
+    ("acosh", Function::Acosh),
+    ("atanh", Function::Atanh),
+    ("sqrt", Function::Sqrt),
+    ("cbrt", Function::Cbrt),
+    ("log", Function::Log),
+    ("log10", Function::Log10),
+    ("abs", Function::Abs),
+    ("floor", Function::Floor),
+    ("ceil", Function::Ceil),
+    ("round", Function::Round),
+    ("gamma", Function::Gamma),
+    ("lgamma", Function::Lgamma),
+    ("erf", Function::Erf),
+    ("erfc", Function::Erfc),
+    ("words", Function::Words),
+    ("exp", Function::Exp),
+    ("convert", Function::Convert),
+    ("now", Function::Now),
+    ("today", Function::Today),
+    ("assert_eq", Function::AssertEq),
+    ("fetch_num", Function::FetchNum),
+    ("nderiv", Function::Nderiv),
+    ("solve", Function::Solve),
+    ("det", Function::Det),
+    ("inv", Function::Inv),
+    ("transpose", Function::Transpose),
+    ("rank", Function::Rank),
+    ("lu", Function::Lu),
+    ("qr", Function::Qr),
+    ("eig", Function::Eig),
+    ("dot", Function::Dot),
+    ("cross", Function::Cross),
+    ("norm", Function::Norm),
+    ("angle", Function::Angle),
+    ("sum", Function::Sum),
+    ("prod", Function::Prod),
+    ("mean", Function::Mean),
+    ("median", Function::Median),
+    ("mode", Function::Mode),
+    ("sort", Function::Sort),
+    ("len", Function::Len),
+    ("normpdf", Function::Normpdf),
+    ("normcdf", Function::Normcdf),
+    ("binompdf", Function::Binompdf),
+    ("binomcdf", Function::Binomcdf),
+    ("poisspdf", Function::Poisspdf),
+    ("tcdf", Function::Tcdf),
+    ("chi2cdf", Function::Chi2cdf),
+    ("pmt", Function::Pmt),
+    ("pv", Function::Pv),
+    ("fv", Function::Fv),
+    ("nper", Function::Nper),
+    ("rate", Function::Rate),
+    ("npv", Function::Npv),
+    ("irr", Function::Irr),
+];
+
+impl Function {
+    pub fn from_name(name: &str) -> Option<Function> {
+        FUNCTIONS.iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, func)| *func)
+            .or_else(|| {
+                let (from, to) = name.split_once("_to_")?;
+                Some(Function::Currency(currency::code(from)?, currency::code(to)?))
+            })
+    }
+
+    pub fn name(&self) -> &'static str {
+        FUNCTIONS.iter()
+            .find(|(_, func)| func == self)
+            .map(|(name, _)| *name)
+            .unwrap_or("?")
+    }
+
+    pub fn arity(&self) -> usize {
+        match self {
+            Function::Convert | Function::AssertEq | Function::Nderiv | Function::Solve | Function::Normpdf | Function::Normcdf
+            | Function::Binompdf | Function::Binomcdf | Function::Pmt | Function::Pv | Function::Nper | Function::Rate => 3,
+            Function::FetchNum | Function::Lu | Function::Qr | Function::Dot | Function::Cross | Function::Angle
+            | Function::Poisspdf | Function::Tcdf | Function::Chi2cdf | Function::Npv => 2,
+            Function::Fv => 4,
+            Function::Now | Function::Today => 0,
+            _ => 1,
+        }
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Function::Currency(from, to) => write!(
+                f, "{}_to_{}", currency::code_str(from).to_lowercase(), currency::code_str(to).to_lowercase()
+            ),
+            _ => write!(f, "{}", self.name()),
+        }
+    }
+}
+
+// How implicit multiplication binds: 1/2pi as 1/(2*pi) or as (1/2)*pi
+#[derive(Debug, Default, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum Juxtaposition {
+    #[default]
+    Tight,
+    Loose,
+}
+
+impl Juxtaposition {
+    pub const ALL: [Juxtaposition; 2] = [Juxtaposition::Tight, Juxtaposition::Loose];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Juxtaposition::Tight => "Fuerte: 1/2pi = 1/(2·pi)",
+            Juxtaposition::Loose => "Como *: 1/2pi = (1/2)·pi",
+        }
+    }
+}
+
+// Unit of the angles trigonometric functions take and inverse ones return
+#[derive(Debug, Default, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum AngleMode {
+    #[default]
+    Radians,
+    Degrees,
+}
+
+impl AngleMode {
+    pub const ALL: [AngleMode; 2] = [AngleMode::Radians, AngleMode::Degrees];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AngleMode::Radians => "Radianes",
+            AngleMode::Degrees => "Grados",
+        }
+    }
+
+    // An angle in this unit, in radians
+    fn radians(self, angle: f64) -> f64 {
         match self {
-            Operator::Add | Operator::Sub => 1,
-            Operator::Mul | Operator::Div | Operator::Mod => 2,
-            Operator::Pow => 3,
+            AngleMode::Radians => angle,
+            AngleMode::Degrees => angle.to_radians(),
+        }
+    }
+
+    // An angle in radians, in this unit
+    fn angle(self, radians: f64) -> f64 {
+        match self {
+            AngleMode::Radians => radians,
+            AngleMode::Degrees => radians.to_degrees(),
+        }
+    }
+}
+
+// Whether a token closes an operand, so a following operand multiplies it
+fn ends_operand(token: Option<&Token>) -> bool {
+    matches!(token, Some(
+        Token::Number(_) | Token::Integer(_) | Token::Imaginary(_) | Token::Constant(_)
+        | Token::Variable(_) | Token::Unit(_) | Token::Factorial | Token::Bracket(')') | Token::List(_) | Token::Braces(_)
+    ))
+}
+
+// What an open '(', '[' or '{' collects while lexing
+enum Group {
+    // The function being called and its argument count, if it's a call
+    Paren(Option<(Function, usize)>),
+    // Elements so far of a [a, b, …] literal
+    List(usize),
+    // Elements so far of a {a, b, …} list
+    Braces(usize),
+}
+
+// Whether an 'e' at `chars` starts an exponent (digits, optionally signed)
+fn exponent_follows(mut chars: impl Iterator<Item = char>) -> bool {
+    match chars.next() {
+        Some('e' | 'E') => {},
+        _ => return false,
+    }
+    match chars.next() {
+        Some('+' | '-') => chars.next().is_some_and(|c| c.is_ascii_digit()),
+        next => next.is_some_and(|c| c.is_ascii_digit()),
+    }
+}
+
+// Char index of the character `chars` last yielded from `expr`
+pub fn position(expr: &str, chars: &std::iter::Peekable<std::str::Chars>) -> usize {
+    expr.chars().count() - chars.clone().count() - 1
+}
+
+// Char index of the first '(' that is never closed
+pub fn first_unclosed(expr: &str) -> Option<usize> {
+    let mut open = Vec::new();
+    for (i, c) in expr.chars().enumerate() {
+        match c {
+            '(' => open.push(i),
+            ')' => {
+                open.pop();
+            },
+            _ => {},
         }
     }
+    open.first().copied()
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '°' || c == 'µ'
 }
 
-pub struct Calculator {}
+// Radix of a 0x/0b/0o literal right after its leading '0', if one starts
+// at `chars`; the prefix must be followed by a digit of that radix
+pub fn radix_prefix(mut chars: impl Iterator<Item = char>) -> Option<u32> {
+    let radix = match chars.next()?.to_ascii_lowercase() {
+        'x' => 16,
+        'b' => 2,
+        'o' => 8,
+        _ => return None,
+    };
+    chars.next()?.is_digit(radix).then_some(radix)
+}
+
+// Size measures of an expression, for telling when it gets unwieldy
+pub struct Metrics {
+    pub tokens: usize,
+    // Deepest parenthesis nesting
+    pub depth: usize,
+    // Rough evaluation work, in units of one arithmetic operation
+    pub cost: f64,
+}
+
+#[derive(Default, Clone, PartialEq)]
+pub struct Calculator {
+    pub locale: Locale,
+    // Reject complex results with domain errors instead
+    pub real_only: bool,
+    // Exact arbitrary-precision integer arithmetic
+    pub integer_mode: bool,
+    pub currencies: Rates,
+    // User variables; a variable named like a constant takes its place
+    pub variables: BTreeMap<String, Value>,
+    // Named values from the settings, kept between sessions; variables shadow them
+    pub user_constants: BTreeMap<String, f64>,
+    pub juxtaposition: Juxtaposition,
+    // Only real arguments and results are converted; complex ones stay in radians
+    pub angle: AngleMode,
+    // How typed numbers are grouped and where their decimals start
+    pub separators: Separators,
+    // Numbers may end in an SI prefix, 4.7k, to read back results in that notation
+    pub si_prefixes: bool,
+    // Unit symbols like km or kPa make values with dimensions
+    pub unit_mode: bool,
+    // fetch_num() may download; off unless the user turns it on
+    pub fetch: bool,
+}
 
 impl Calculator {
     pub fn parse<T: AsRef<str>>(expr: T) -> Result<Vec<Token>, Error> {
-        let expr = expr.as_ref().to_lowercase();
+        let expr = expr.as_ref();
         let mut tokens = Vec::new();
         let mut chars = expr.chars().peekable();
-        let mut parens = Vec::new();
+        // A leading @deg or @rad is read by `execute` and `calculate`
+        let (_, body) = Calculator::angle_directive(expr);
+        for _ in expr[..expr.len() - body.len()].chars() {
+            chars.next();
+        }
+        // One entry per open '(' or '['
+        let mut parens: Vec<Group> = Vec::new();
 
         while let Some(c) = chars.next() {
             match c {
+                '0' if radix_prefix(chars.clone()).is_some() => {
+                    let radix = radix_prefix(chars.clone()).unwrap();
+                    chars.next();
+                    let mut digits = String::new();
+                    while let Some(&next) = chars.peek() {
+                        if next.is_digit(radix) {
+                            digits.push(chars.next().unwrap());
+                        } else if next == '_' {
+                            chars.next();
+                        } else {
+                            break;
+                        }
+                    }
+                    // 0b102 or 0xFG is a typo, not 0b10 times 2
+                    if let Some(&next) = chars.peek().filter(|c| c.is_ascii_alphanumeric()) {
+                        return Err(Error::InvalidNumber(format!("{}{}", digits, next)));
+                    }
+                    if digits.len() as u64 > limits::MAX_DIGITS {
+                        return Err(limits::too_large());
+                    }
+                    tokens.push(Token::Integer(BigInt::parse_bytes(digits.as_bytes(), radix).unwrap()));
+                },
                 '0'..='9' | '.' => {
                     let mut number = String::from(c);
                     while let Some(&next) = chars.peek() {
-                        if next.is_digit(10) || next == '.' || next == 'e' {
+                        if next.is_ascii_digit() || next == '.' {
                             number.push(chars.next().unwrap());
-                            if next == 'e' {
-                                if let Some(&sign) = chars.peek() {
-                                    if sign == '+' || sign == '-' {
-                                        number.push(chars.next().unwrap());
-                                    }
+                        } else if next == '_' && number.ends_with(|c: char| c.is_ascii_digit())
+                            && chars.clone().nth(1).is_some_and(|c| c.is_ascii_digit())
+                        {
+                            // Digit grouping, as in 1_000_000
+                            chars.next();
+                        } else if exponent_follows(chars.clone()) {
+                            // 2e5 is an exponent, while 2e alone is 2 times e
+                            number.push(chars.next().unwrap());
+                            if let Some(&sign) = chars.peek() {
+                                if sign == '+' || sign == '-' {
+                                    number.push(chars.next().unwrap());
                                 }
                             }
                         } else {
                             break;
                         }
                     }
-                    match number.parse::<f64>() {
-                        Ok(n) => tokens.push(Token::Number(n)),
+                    let n = match number.parse::<f64>() {
+                        Ok(n) => n,
                         Err(_) => return Err(Error::InvalidNumber(number)),
+                    };
+                    // A trailing 'i' makes an imaginary literal, e.g. 3i
+                    let mut ahead = chars.clone();
+                    if ahead.next().map(|c| c.to_ascii_lowercase()) == Some('i') && !ahead.peek().is_some_and(|c| c.is_ascii_alphanumeric()) {
+                        chars.next();
+                        tokens.push(Token::Imaginary(n));
+                    } else if number.bytes().all(|b| b.is_ascii_digit()) {
+                        if number.len() as u64 > limits::MAX_DIGITS {
+                            return Err(limits::too_large());
+                        }
+                        // Keep every digit so integer mode stays exact
+                        tokens.push(Token::Integer(number.parse().unwrap()));
+                    } else {
+                        tokens.push(Token::Number(n));
                     }
                 },
                 '(' => {
+                    let call = match tokens.last() {
+                        // now() takes no arguments at all
+                        Some(Token::Function(func)) if chars.clone().find(|c| !c.is_whitespace()) == Some(')') => Some((*func, 0)),
+                        Some(Token::Function(func)) => Some((*func, 1)),
+                        _ => None,
+                    };
+                    if ends_operand(tokens.last()) {
+                        tokens.push(Token::Op(Operator::Juxtapose));
+                    }
                     tokens.push(Token::Bracket('('));
-                    parens.push(c);
+                    parens.push(Group::Paren(call));
                 },
                 ')' => {
                     tokens.push(Token::Bracket(')'));
-                    if let Some(p) = parens.pop() {
-                        if p != '(' {
-                            return Err(Error::MismatchedParens);
+                    match parens.pop() {
+                        Some(Group::Paren(Some((func, args)))) if args != func.arity() => {
+                            return Err(Error::InvalidOperation(format!(
+                                "{} espera {} argumento(s)", func, func.arity()
+                            )));
+                        },
+                        Some(Group::Paren(_)) => {},
+                        Some(Group::List(_) | Group::Braces(_)) | None => return Err(Error::MismatchedParens { position: Some(position(expr, &chars)) }),
+                    }
+                },
+                '[' => {
+                    if ends_operand(tokens.last()) {
+                        tokens.push(Token::Op(Operator::Juxtapose));
+                    }
+                    tokens.push(Token::Bracket('['));
+                    // [] has no elements, [a] one
+                    let empty = chars.clone().find(|c| !c.is_whitespace()) == Some(']');
+                    parens.push(Group::List(if empty { 0 } else { 1 }));
+                },
+                ']' => match parens.pop() {
+                    Some(Group::List(elements)) => tokens.push(Token::List(elements)),
+                    _ => return Err(Error::BadToken { token: c, position: position(expr, &chars) }),
+                },
+                '{' => {
+                    if ends_operand(tokens.last()) {
+                        tokens.push(Token::Op(Operator::Juxtapose));
+                    }
+                    tokens.push(Token::Bracket('{'));
+                    let empty = chars.clone().find(|c| !c.is_whitespace()) == Some('}');
+                    parens.push(Group::Braces(if empty { 0 } else { 1 }));
+                },
+                '}' => match parens.pop() {
+                    Some(Group::Braces(elements)) => tokens.push(Token::Braces(elements)),
+                    _ => return Err(Error::BadToken { token: c, position: position(expr, &chars) }),
+                },
+                ',' => {
+                    match parens.last_mut() {
+                        Some(Group::Paren(Some((_, args)))) | Some(Group::List(args)) | Some(Group::Braces(args)) => *args += 1,
+                        _ => return Err(Error::BadToken { token: c, position: position(expr, &chars) }),
+                    }
+                    tokens.push(Token::Comma);
+                },
+                '"' => {
+                    let mut text = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('"') => break,
+                            Some(next) => text.push(next),
+                            None => return Err(Error::InvalidOperation("Falta cerrar las comillas".to_string())),
                         }
-                    } else {
-                        return Err(Error::MismatchedParens);
                     }
+                    tokens.push(Token::Text(text));
                 },
                 '+' => tokens.push(Token::Op(Operator::Add)),
                 '-' => {
                     // Handle negative numbers
                     if tokens.is_empty() || matches!(tokens.last(), 
-                        Some(Token::Op(_)) | Some(Token::Bracket('('))) {
+                        Some(Token::Op(_)) | Some(Token::Bracket('(' | '[' | '{')) | Some(Token::Comma)) {
                         tokens.push(Token::Number(-1.0));
                         tokens.push(Token::Op(Operator::Mul));
                     } else {
@@ -121,97 +645,123 @@ impl Calculator {
                 '/' => tokens.push(Token::Op(Operator::Div)),
                 '^' => tokens.push(Token::Op(Operator::Pow)),
                 '%' => tokens.push(Token::Op(Operator::Mod)),
-                'p' => {
-                    if expr[expr.find(c).unwrap()..].starts_with("pi") {
-                        tokens.push(Token::Constant(Constant::Pi));
-                        chars.next(); // skip 'i'
-                    } else {
-                        return Err(Error::BadToken(c));
+                '±' => tokens.push(Token::Op(Operator::PlusMinus)),
+                '!' => tokens.push(Token::Factorial),
+                c if c.is_ascii_alphabetic() || c == '°' || c == 'µ' => {
+                    let mut word = String::from(c);
+                    while let Some(&next) = chars.peek() {
+                        if next.is_ascii_alphanumeric() || next == '_' {
+                            word.push(chars.next().unwrap());
+                        } else {
+                            break;
+                        }
                     }
-                },
-                'e' => {
-                    if chars.peek().is_none() || !chars.peek().unwrap().is_alphabetic() {
-                        tokens.push(Token::Constant(Constant::E));
+                    // Function names are case-insensitive, variables are not
+                    let lower = word.to_ascii_lowercase();
+                    if lower != "mod" && ends_operand(tokens.last()) {
+                        tokens.push(Token::Op(Operator::Juxtapose));
                     }
-                },
-                's' => {
-                    if expr[expr.find(c).unwrap()..].starts_with("sin") {
-                        tokens.push(Token::Function(Function::Sin));
-                        chars.next(); chars.next(); // skip "in"
-                    } else if expr[expr.find(c).unwrap()..].starts_with("sqrt") {
-                        tokens.push(Token::Function(Function::Sqrt));
-                        chars.next(); chars.next(); chars.next(); // skip "qrt"
+                    if let Some(func) = Function::from_name(&lower) {
+                        tokens.push(Token::Function(func));
+                    } else if let Some(constant) = constants::find(&word) {
+                        tokens.push(Token::Constant(constant));
+                    } else if lower == "mod" {
+                        tokens.push(Token::Op(Operator::Mod));
+                    } else if chars.peek() == Some(&'(') {
+                        return Err(Error::UnknownFunction(lower));
                     } else {
-                        return Err(Error::UnknownFunction(c.to_string()));
-                    }
-                },
-                'c' => {
-                    if expr[expr.find(c).unwrap()..].starts_with("cos") {
-                        tokens.push(Token::Function(Function::Cos));
-                        chars.next(); chars.next(); // skip "os"
-                    } else if expr[expr.find(c).unwrap()..].starts_with("cbrt") {
-                        tokens.push(Token::Function(Function::Cbrt));
-                        chars.next(); chars.next(); chars.next(); // skip "brt"
-                    } else if expr[expr.find(c).unwrap()..].starts_with("ceil") {
-                        tokens.push(Token::Function(Function::Ceil));
-                        chars.next(); chars.next(); chars.next(); // skip "eil"
-                    } else {
-                        return Err(Error::UnknownFunction(c.to_string()));
+                        tokens.push(Token::Variable(word));
                     }
                 },
-                't' => {
-                    if expr[expr.find(c).unwrap()..].starts_with("tan") {
-                        tokens.push(Token::Function(Function::Tan));
-                        chars.next(); chars.next(); // skip "an"
-                    } else {
-                        return Err(Error::UnknownFunction(c.to_string()));
-                    }
+                ' ' | '\n' => {},
+                _ => return Err(Error::BadToken { token: c, position: position(expr, &chars) })
+            }
+        }
+        
+        if parens.iter().any(|group| matches!(group, Group::List(_))) {
+            return Err(Error::InvalidOperation("Falta cerrar un corchete ]".to_string()));
+        }
+        if parens.iter().any(|group| matches!(group, Group::Braces(_))) {
+            return Err(Error::InvalidOperation("Falta cerrar una llave }".to_string()));
+        }
+        if !parens.is_empty() {
+            return Err(Error::MismatchedParens { position: first_unclosed(expr) });
+        }
+        
+        Ok(tokens)
+    }
+
+    // Byte ranges of the tokens in `expr`, following the same boundaries as `parse`.
+    // Never fails, so it also works on half-typed input. A function name
+    // and the '(' right after it form a single span.
+    pub fn spans(expr: &str) -> Vec<Range<usize>> {
+        let chars: Vec<(usize, char)> = expr.char_indices().collect();
+        let offset = |i: usize| chars.get(i).map_or(expr.len(), |&(pos, _)| pos);
+        let at = |i: usize| chars.get(i).map(|&(_, c)| c);
+        let mut spans = Vec::new();
+        let mut i = 0;
+
+        while let Some(c) = at(i) {
+            let end = match c {
+                ' ' | '\n' => {
+                    i += 1;
+                    continue;
                 },
-                'l' => {
-                    if expr[expr.find(c).unwrap()..].starts_with("log10") {
-                        tokens.push(Token::Function(Function::Log10));
-                        chars.next(); chars.next(); chars.next(); chars.next(); // skip "og10"
-                    } else if expr[expr.find(c).unwrap()..].starts_with("log") {
-                        tokens.push(Token::Function(Function::Log));
-                        chars.next(); chars.next(); // skip "og"
-                    } else {
-                        return Err(Error::UnknownFunction(c.to_string()));
+                '0' if radix_prefix(chars[i + 1..].iter().map(|&(_, c)| c)).is_some() => {
+                    let mut j = i + 2;
+                    while at(j).is_some_and(|c| c.is_ascii_alphanumeric() || c == '_') {
+                        j += 1;
                     }
+                    j
                 },
-                'a' => {
-                    if expr[expr.find(c).unwrap()..].starts_with("abs") {
-                        tokens.push(Token::Function(Function::Abs));
-                        chars.next(); chars.next(); // skip "bs"
-                    } else {
-                        return Err(Error::UnknownFunction(c.to_string()));
+                '0'..='9' | '.' => {
+                    let mut j = i + 1;
+                    while let Some(next) = at(j) {
+                        match next {
+                            '0'..='9' | '.' => j += 1,
+                            '_' if at(j + 1).is_some_and(|c| c.is_ascii_digit()) => j += 1,
+                            'e' | 'E' if exponent_follows(chars[j..].iter().map(|&(_, c)| c)) => {
+                                j += 1;
+                                if matches!(at(j), Some('+' | '-')) {
+                                    j += 1;
+                                }
+                            },
+                            _ => break,
+                        }
+                    }
+                    if matches!(at(j), Some('i' | 'I')) && !at(j + 1).is_some_and(|c| c.is_ascii_alphanumeric()) {
+                        j += 1;
                     }
+                    j
                 },
-                'f' => {
-                    if expr[expr.find(c).unwrap()..].starts_with("floor") {
-                        tokens.push(Token::Function(Function::Floor));
-                        chars.next(); chars.next(); chars.next(); chars.next(); // skip "loor"
-                    } else {
-                        return Err(Error::UnknownFunction(c.to_string()));
+                c if c.is_ascii_alphabetic() || c == '°' || c == 'µ' => {
+                    let mut j = i + 1;
+                    while at(j).is_some_and(|c| c.is_ascii_alphanumeric() || c == '_') {
+                        j += 1;
+                    }
+                    if at(j) == Some('(') {
+                        j += 1;
                     }
+                    j
                 },
-                'r' => {
-                    if expr[expr.find(c).unwrap()..].starts_with("round") {
-                        tokens.push(Token::Function(Function::Round));
-                        chars.next(); chars.next(); chars.next(); chars.next(); // skip "ound"
-                    } else {
-                        return Err(Error::UnknownFunction(c.to_string()));
+                '"' => chars[i + 1..].iter()
+                    .position(|&(_, c)| c == '"')
+                    .map_or(chars.len(), |closing| i + closing + 2),
+                // @deg or @rad
+                '@' => {
+                    let mut j = i + 1;
+                    while at(j).is_some_and(|c| c.is_ascii_alphabetic()) {
+                        j += 1;
                     }
+                    j
                 },
-                ' ' | '\n' => {},
-                _ => return Err(Error::BadToken(c))
-            }
-        }
-        
-        if !parens.is_empty() {
-            return Err(Error::MismatchedParens);
+                _ => i + 1,
+            };
+            spans.push(offset(i)..offset(end));
+            i = end;
         }
-        
-        Ok(tokens)
+
+        spans
     }
 
     pub fn expression(mut tokens: Vec<Token>) -> Vec<Token> {
@@ -222,8 +772,10 @@ impl Calculator {
         
         while let Some(token) = tokens.pop() {
             match &token {
-                Token::Number(_) => queue.push(token),
-                Token::Constant(_) => queue.push(token),
+                Token::Number(_) | Token::Integer(_) | Token::Imaginary(_) | Token::Text(_) => queue.push(token),
+                // Postfix operators bind tightest and go straight to the output
+                Token::Factorial => queue.push(token),
+                Token::Constant(_) | Token::Variable(_) | Token::Unit(_) => queue.push(token),
                 Token::Op(op) => {
                     while let Some(Token::Op(top_op)) = stack.last() {
                         if op.precedence() <= top_op.precedence() {
@@ -235,7 +787,34 @@ impl Calculator {
                     stack.push(token);
                 },
                 Token::Function(_) => stack.push(token),
-                Token::Bracket('(') => stack.push(token),
+                Token::Bracket('(' | '[' | '{') => stack.push(token),
+                // Finish the current argument or element
+                Token::Comma => {
+                    while let Some(top) = stack.last() {
+                        if matches!(top, Token::Bracket('(' | '[' | '{')) {
+                            break;
+                        }
+                        queue.push(stack.pop().unwrap());
+                    }
+                },
+                Token::List(_) => {
+                    while let Some(top) = stack.pop() {
+                        if matches!(top, Token::Bracket('[')) {
+                            break;
+                        }
+                        queue.push(top);
+                    }
+                    queue.push(token);
+                },
+                Token::Braces(_) => {
+                    while let Some(top) = stack.pop() {
+                        if matches!(top, Token::Bracket('{')) {
+                            break;
+                        }
+                        queue.push(top);
+                    }
+                    queue.push(token);
+                },
                 Token::Bracket(')') => {
                     while let Some(top) = stack.last() {
                         if matches!(top, Token::Bracket('(')) {
@@ -261,82 +840,530 @@ impl Calculator {
         queue
     }
 
-    pub fn evaluate(mut tokens: Vec<Token>) -> Result<f64, Error> {
+    // Number of '(' left open at the end, or None if a ')' has no match
+    pub fn unclosed_parens(expr: &str) -> Option<usize> {
+        let mut depth: usize = 0;
+        for c in expr.chars() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth = depth.checked_sub(1)?,
+                _ => {}
+            }
+        }
+        Some(depth)
+    }
+
+    // Rewrites an expression with normalized spacing and minimal parentheses
+    pub fn format(&self, expr: &str) -> Result<String, Error> {
+        if let Some((name, expr)) = Calculator::assignment(expr) {
+            return Ok(format!("{} = {}", Calculator::variable_name(name)?, self.format(expr)?));
+        }
+        if let Some((expr, target)) = self.conversion(expr) {
+            return Ok(format!("{} to {}", self.format(expr)?, target.trim()));
+        }
+        Ok(self.ast(expr)?.to_string())
+    }
+
+    // Like `format`, with the tree simplified first: 2x + x*1 + 0 → 3x
+    pub fn simplify(&self, expr: &str) -> Result<String, Error> {
+        if let Some((name, expr)) = Calculator::assignment(expr) {
+            return Ok(format!("{} = {}", Calculator::variable_name(name)?, self.simplify(expr)?));
+        }
+        if let Some((expr, target)) = self.conversion(expr) {
+            return Ok(format!("{} to {}", self.simplify(expr)?, target.trim()));
+        }
+        Ok(simplify::simplify(self, self.ast(expr)?).to_string())
+    }
+
+    // The exact form of the result `input` gave, 2√2 for sqrt(8) or 5π/6 for
+    // pi/2 + pi/3; None when some step has none
+    pub fn exact(&self, input: &str, result: &Value) -> Option<Exact> {
+        let (angle, rest) = Calculator::angle_directive(input);
+        let expr = Calculator::assigned(input).ok()?.map_or(rest, |(_, expr)| expr);
+        if self.conversion(expr).is_some() {
+            return None;
+        }
+        exact::exact(self, angle.unwrap_or(self.angle), &self.ast(expr).ok()?, result)
+    }
+
+    // Folds a prefix letter right after a number into it, 4.7k → 4700, unless
+    // the letter is one of the user's variables or constants; this takes
+    // precedence over built-in constants such as G
+    fn read_prefixes(&self, tokens: Vec<Token>) -> Vec<Token> {
+        let mut read = Vec::with_capacity(tokens.len());
+        let mut tokens = tokens.into_iter();
+        while let Some(token) = tokens.next() {
+            let number = match &token {
+                Token::Number(n) => Some(n.to_string()),
+                Token::Integer(n) => Some(n.to_string()),
+                _ => None,
+            };
+            let mut ahead = tokens.clone();
+            let exponent = match (number.is_some(), ahead.next(), ahead.next()) {
+                (true, Some(Token::Op(Operator::Juxtapose)), Some(Token::Variable(name)))
+                    if !self.variables.contains_key(&name) && !self.user_constants.contains_key(&name) => format::si_prefix(&name),
+                (true, Some(Token::Op(Operator::Juxtapose)), Some(Token::Constant(constant))) => format::si_prefix(constant.name),
+                _ => None,
+            };
+            match (number, exponent) {
+                (Some(number), Some(exponent)) => {
+                    // Through the decimal text, so 4.7k is exactly 4700
+                    read.push(Token::Number(format!("{}e{}", number, exponent).parse().unwrap_or(f64::NAN)));
+                    tokens.next();
+                    tokens.next();
+                },
+                _ => read.push(token),
+            }
+        }
+        read
+    }
+
+    // `expr` in evaluation order, to evaluate many times with `evaluate_at`
+    pub fn compile(&self, expr: &str) -> Result<Vec<Token>, Error> {
+        Ok(Calculator::expression(self.tokens(expr)?))
+    }
+
+    // Evaluates compiled tokens with the variable `name` set to `x`
+    pub fn evaluate_at(&self, compiled: &[Token], name: &str, x: f64) -> Result<Value, Error> {
+        let tokens = compiled.iter().map(|token| match token {
+            Token::Variable(variable) if variable == name => Token::Number(x),
+            _ => token.clone(),
+        }).collect();
+        self.evaluate(tokens)
+    }
+
+    pub fn ast(&self, expr: &str) -> Result<Expr, Error> {
+        Expr::from_rpn(Calculator::expression(self.tokens(expr)?))
+    }
+
+    // Splits `expr to unit` or `expr in unit` at the last such word outside
+    // parentheses and quotes. `in` is also the inch, so it only counts when
+    // the left side is a quantity and the right side a unit: 3 in in cm.
+    fn conversion<'a>(&self, expr: &'a str) -> Option<(&'a str, &'a str)> {
+        let chars: Vec<(usize, char)> = expr.char_indices().collect();
+        let mut depth = 0;
+        let mut quoted = false;
+        let mut keywords = Vec::new();
+        for (k, &(i, c)) in chars.iter().enumerate() {
+            match c {
+                '"' => quoted = !quoted,
+                '(' if !quoted => depth += 1,
+                ')' if !quoted => depth -= 1,
+                't' | 'i' if !quoted && depth == 0 => {
+                    let keyword = &expr[i..];
+                    let starts = k == 0 || !is_word_char(chars[k - 1].1);
+                    let ends = !keyword[1..].chars().nth(1).is_some_and(is_word_char);
+                    if starts && ends && (keyword.starts_with("to") || keyword.starts_with("in")) {
+                        keywords.push(i);
+                    }
+                },
+                _ => {},
+            }
+        }
+        if keywords.is_empty() {
+            return None;
+        }
+        let units = Calculator { unit_mode: true, integer_mode: false, ..self.clone() };
+        let is_quantity = |expr: &str| matches!(units.calculate(expr), Ok(Value::Quantity(_)));
+        keywords.into_iter().rev()
+            .map(|i| (&expr[i..i + 2], &expr[..i], &expr[i + 2..]))
+            .find(|&(keyword, left, right)| {
+                !left.trim().is_empty() && !right.trim().is_empty()
+                    && (keyword == "to" || is_quantity(left) && is_quantity(right))
+            })
+            .map(|(_, left, right)| (left, right))
+    }
+
+    // Evaluates `expr to unit` with units on, keeping the unit for display
+    fn convert_to(&self, expr: &str, target: &str) -> Result<Value, Error> {
+        let units = Calculator { unit_mode: true, integer_mode: false, ..self.clone() };
+        let target = target.trim();
+        if let Some(value) = units.absolute_temperature(expr, target)? {
+            return Ok(value);
+        }
+        let value = units.calculate(expr)?.quantity()?;
+        let unit = units.calculate(target)?.quantity()?;
+        if unit.dim != value.dim {
+            let from = if value.dim.is_none() { "un número sin unidades".to_string() } else { value.dim.simplified() };
+            return Err(Error::InvalidOperation(format!(
+                "No se puede convertir {} a {} ({})", from, target, unit.dim.simplified()
+            )));
+        }
+        if unit.value == 0.0 || !unit.value.is_finite() {
+            return Err(Error::InvalidOperation(format!("«{}» no es una unidad válida", target)));
+        }
+        Ok(Value::Quantity(Quantity { unit: Some((target.to_string(), unit.value)), ..value }))
+    }
+
+    // 20 °C to °F converts the reading, not a temperature difference
+    fn absolute_temperature(&self, expr: &str, target: &str) -> Result<Option<Value>, Error> {
+        let Some(to) = units::symbol(target).filter(|unit| unit.category == Category::Temperature) else {
+            return Ok(None);
+        };
+        let expr = expr.trim_end();
+        let Some(from) = Category::Temperature.units().find(|unit| expr.ends_with(unit.symbol)) else {
+            return Ok(None);
+        };
+        if from.offset == 0.0 && to.offset == 0.0 {
+            return Ok(None);
+        }
+        let reading = match self.calculate(&expr[..expr.len() - from.symbol.len()]) {
+            Ok(Value::Number(n)) => n,
+            _ => return Ok(None),
+        };
+        let value = units::convert(reading, from.symbol, to.symbol)?;
+        let mut quantity = Quantity::new(value * to.factor, Category::Temperature.dimension());
+        quantity.unit = Some((to.symbol.to_string(), to.factor));
+        Ok(Some(Value::Quantity(quantity)))
+    }
+
+    // Parses with implicit multiplication resolved according to the setting
+    fn tokens(&self, expr: &str) -> Result<Vec<Token>, Error> {
+        let expr = &self.separators.canonical(expr);
+        let mut tokens = Calculator::parse(expr)
+            .inspect_err(|e| log::debug!(target: "calc::lexer", "{:?} -> {:?}", expr, e))?;
+        // With units on, 3m stays three metres
+        if self.si_prefixes && !self.unit_mode {
+            tokens = self.read_prefixes(tokens);
+        }
+        if self.juxtaposition == Juxtaposition::Loose {
+            for token in &mut tokens {
+                if *token == Token::Op(Operator::Juxtapose) {
+                    *token = Token::Op(Operator::Mul);
+                }
+            }
+        }
+        // Unit symbols take over names that would be variables or constants, like h
+        if self.unit_mode {
+            for token in &mut tokens {
+                let unit = match token {
+                    Token::Variable(name) => units::symbol(name),
+                    Token::Constant(constant) => units::symbol(constant.name),
+                    _ => None,
+                };
+                if let Some(unit) = unit {
+                    *token = Token::Unit(unit);
+                }
+            }
+        }
+        log::debug!(target: "calc::lexer", "{:?} -> {:?}", expr, tokens);
+        Ok(tokens)
+    }
+
+    // None when `input` doesn't parse
+    pub fn metrics(&self, input: &str) -> Option<Metrics> {
+        let tokens = self.tokens(input).ok()?;
+        let (mut depth, mut deepest, mut cost) = (0usize, 0, 0.0);
+        for (i, token) in tokens.iter().enumerate() {
+            cost += match token {
+                Token::Bracket('(') => {
+                    depth += 1;
+                    deepest = deepest.max(depth);
+                    0.0
+                },
+                Token::Bracket(')') => {
+                    depth = depth.saturating_sub(1);
+                    0.0
+                },
+                // Library functions take tens of operations
+                Token::Function(_) | Token::Op(Operator::Pow) => 20.0,
+                // One multiplication per factor, when the argument is written out
+                Token::Factorial => match i.checked_sub(1).map(|i| &tokens[i]) {
+                    Some(Token::Integer(n)) => n.to_f64().unwrap_or(f64::INFINITY),
+                    _ => 20.0,
+                },
+                _ => 1.0,
+            };
+        }
+        Some(Metrics { tokens: tokens.len(), depth: deepest, cost })
+    }
+
+    // Whether `input` gives a different result over time, through the clock,
+    // currency rates that may be reloaded or downloaded data
+    pub fn volatile(&self, input: &str) -> bool {
+        self.tokens(input).is_ok_and(|tokens| tokens.iter().any(|token| {
+            matches!(token, Token::Function(Function::Now | Function::Today | Function::Currency(..) | Function::FetchNum))
+        }))
+    }
+
+    // Functions `input` calls, once per call, for the session statistics
+    pub fn functions(&self, input: &str) -> Vec<Function> {
+        self.tokens(input).map_or_else(|_| Vec::new(), |tokens| tokens.into_iter().filter_map(|token| match token {
+            Token::Function(func) => Some(func),
+            _ => None,
+        }).collect())
+    }
+
+    // Splits a leading @deg or @rad, which sets the angle unit for that
+    // expression only
+    pub fn angle_directive(input: &str) -> (Option<AngleMode>, &str) {
+        let trimmed = input.trim_start();
+        for (directive, angle) in [("@deg", AngleMode::Degrees), ("@rad", AngleMode::Radians)] {
+            let Some(rest) = trimmed.get(..directive.len())
+                .filter(|start| start.eq_ignore_ascii_case(directive))
+                .map(|_| &trimmed[directive.len()..])
+            else {
+                continue;
+            };
+            if !rest.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+                return (Some(angle), rest);
+            }
+        }
+        (None, input)
+    }
+
+    fn with_angle(&self, angle: AngleMode) -> Calculator {
+        Calculator { angle, ..self.clone() }
+    }
+
+    // The right-hand side of an assignment, which can't have its own directive
+    fn assigned(input: &str) -> Result<Option<(&str, &str)>, Error> {
+        let assignment = Calculator::assignment(Calculator::angle_directive(input).1);
+        if assignment.is_some_and(|(_, expr)| Calculator::angle_directive(expr).0.is_some()) {
+            return Err(Error::InvalidOperation("@deg y @rad van al principio, p. ej. @deg x = sin(30)".to_string()));
+        }
+        Ok(assignment)
+    }
+
+    // Evaluates `input` as execute would, without assigning anything
+    pub fn check(&self, input: &str) -> Result<Value, Error> {
+        let Some((_, expr)) = Calculator::assigned(input)? else {
+            return self.calculate(input);
+        };
+        match Calculator::angle_directive(input).0 {
+            Some(angle) => self.with_angle(angle).calculate(expr),
+            None => self.calculate(expr),
+        }
+    }
+
+    // Splits `name = expr`; None when the input has no assignment
+    fn assignment(input: &str) -> Option<(&str, &str)> {
+        let (name, expr) = input.split_once('=')?;
+        Some((name.trim(), expr))
+    }
+
+    // Evaluates an expression or an assignment, returning the value and any
+    // warnings about names that shadow built-in constants
+    pub fn execute(&mut self, input: &str) -> Result<(Value, Vec<String>), Error> {
+        if let Some(angle) = Calculator::angle_directive(input).0.filter(|&angle| angle != self.angle) {
+            let global = std::mem::replace(&mut self.angle, angle);
+            let result = self.execute(input);
+            self.angle = global;
+            return result;
+        }
+        let (name, expr) = match Calculator::assigned(input)? {
+            Some((name, expr)) => (Some(Calculator::variable_name(name)?), expr),
+            None => (None, input),
+        };
+        let tokens = self.tokens(expr)?;
+        let mut warnings = Vec::new();
+        for token in &tokens {
+            let shadowed = match token {
+                Token::Constant(constant) => Some(constant.name),
+                Token::Variable(name) if self.user_constants.contains_key(name) => Some(name.as_str()),
+                _ => None,
+            };
+            if let Some(value) = shadowed.and_then(|name| self.variables.get(name)) {
+                let warning = format!(
+                    "«{}» es tu variable (= {}), no la constante", shadowed.unwrap(), value
+                );
+                if !warnings.contains(&warning) {
+                    warnings.push(warning);
+                }
+            }
+        }
+        let value = match self.conversion(expr) {
+            Some((expr, target)) => self.convert_to(expr, target)?,
+            None => self.evaluate(Calculator::expression(tokens))?,
+        };
+        if matches!(value, Value::Number(_)) {
+            warnings.extend(precision::check(self, expr));
+        }
+        if let Some(name) = name {
+            if let Some(constant) = constants::find(&name) {
+                warnings.push(format!(
+                    "La variable «{}» oculta la constante {}; bórrala en Variables para recuperarla",
+                    name, constant.name
+                ));
+            } else if self.user_constants.contains_key(&name) {
+                warnings.push(format!(
+                    "La variable «{}» oculta tu constante del mismo nombre; bórrala en Variables para recuperarla",
+                    name
+                ));
+            }
+            self.variables.insert(name, value.clone());
+        }
+        Ok((value, warnings))
+    }
+
+    // Stores `value` in the variable `name`, as `name = …` would
+    pub fn assign(&mut self, name: &str, value: Value) -> Result<(), Error> {
+        let name = Calculator::variable_name(name.trim())?;
+        self.variables.insert(name, value);
+        Ok(())
+    }
+
+    // The variables `input` reads, with their values, in order of first use
+    pub fn referenced(&self, input: &str) -> Vec<(String, Value)> {
+        let expr = match Calculator::assigned(input) {
+            Ok(Some((_, expr))) => expr,
+            _ => input,
+        };
+        let Ok(tokens) = self.tokens(expr) else {
+            return Vec::new();
+        };
+        let mut referenced: Vec<(String, Value)> = Vec::new();
+        for token in tokens {
+            // Constants and units count when a variable shadows them
+            let name = match &token {
+                Token::Variable(name) => name.as_str(),
+                Token::Constant(constant) => constant.name,
+                Token::Unit(unit) => unit.symbol,
+                _ => continue,
+            };
+            if let Some(value) = self.variables.get(name) {
+                if !referenced.iter().any(|(known, _)| known == name) {
+                    referenced.push((name.to_string(), value.clone()));
+                }
+            }
+        }
+        referenced
+    }
+
+    // Validates and stores a user constant; it may not collide with a built-in name
+    pub fn define_constant(&mut self, name: &str, value: f64) -> Result<(), Error> {
+        let name = Calculator::variable_name(name.trim())?;
+        if let Some(constant) = constants::find(&name) {
+            return Err(Error::InvalidOperation(format!("«{}» ya es una constante incorporada", constant.name)));
+        }
+        if !value.is_finite() {
+            return Err(Error::InvalidOperation("El valor debe ser un número finito".to_string()));
+        }
+        self.user_constants.insert(name, value);
+        Ok(())
+    }
+
+    // A variable, or failing that a user constant
+    fn lookup(&self, name: &str) -> Option<Value> {
+        self.variables.get(name).cloned()
+            .or_else(|| self.user_constants.get(name).map(|&value| Value::Number(value)))
+    }
+
+    // Checks the left-hand side of an assignment
+    fn variable_name(name: &str) -> Result<String, Error> {
+        let valid = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(Error::InvalidOperation("Solo se puede asignar a un nombre, p. ej. x = 2".to_string()));
+        }
+        let lower = name.to_ascii_lowercase();
+        if Function::from_name(&lower).is_some() {
+            return Err(Error::InvalidOperation(format!("«{}» es una función incorporada", name)));
+        }
+        if lower == "mod" {
+            return Err(Error::InvalidOperation(format!("«{}» es un nombre reservado", name)));
+        }
+        // Spell it the way the lexer resolves it, so e.g. PI still shadows pi
+        Ok(constants::find(name).map_or(name.to_string(), |constant| constant.name.to_string()))
+    }
+
+    // Parses and evaluates an expression in one step
+    pub fn calculate(&self, expr: &str) -> Result<Value, Error> {
+        if let Some(angle) = Calculator::angle_directive(expr).0.filter(|&angle| angle != self.angle) {
+            return self.with_angle(angle).calculate(expr);
+        }
+        if let Some((expr, target)) = self.conversion(expr) {
+            return self.convert_to(expr, target);
+        }
+        let tokens = self.tokens(expr)?;
+        self.evaluate(Calculator::expression(tokens))
+    }
+
+    pub fn evaluate(&self, mut tokens: Vec<Token>) -> Result<Value, Error> {
+        log::debug!(target: "calc::rpn", "{:?}", tokens);
+        if log::log_enabled!(target: "calc::ast", log::Level::Debug) {
+            match Expr::from_rpn(tokens.clone()) {
+                Ok(ast) => log::debug!(target: "calc::ast", "{} <- {:?}", ast, ast),
+                Err(e) => log::debug!(target: "calc::ast", "{:?}", e),
+            }
+        }
+        if self.integer_mode {
+            let result = integer::evaluate(tokens, &|name| self.lookup(name));
+            log::debug!(target: "calc::eval", "modo entero -> {:?}", result);
+            return result.map(Value::Integer);
+        }
+
         tokens.reverse();
         
-        let mut stack: Vec<f64> = Vec::new();
+        let mut stack: Vec<Value> = Vec::new();
         
         while let Some(token) = tokens.pop() {
             match token {
-                Token::Number(num) => stack.push(num),
-                Token::Constant(constant) => {
-                    match constant {
-                        Constant::Pi => stack.push(PI),
-                        Constant::E => stack.push(E),
+                Token::Number(num) => stack.push(Value::Number(num)),
+                Token::Integer(num) => stack.push(Value::Number(num.to_f64().unwrap_or(f64::INFINITY))),
+                Token::Imaginary(num) => stack.push(self.imaginary(num)?),
+                Token::Text(text) => stack.push(Value::Text(text)),
+                // Shadowing rule: a user variable wins over the built-in constant
+                Token::Constant(constant) if self.variables.contains_key(constant.name) => {
+                    stack.push(self.variables[constant.name].clone());
+                },
+                Token::Variable(name) => {
+                    match self.lookup(&name) {
+                        Some(value) => stack.push(value),
+                        None => return Err(Error::UndefinedVariable(name)),
                     }
                 },
+                // A variable named like a unit also wins
+                Token::Unit(unit) => match self.variables.get(unit.symbol) {
+                    Some(value) => stack.push(value.clone()),
+                    None => stack.push(Value::Quantity(Quantity::unit(unit))),
+                },
+                Token::Constant(constant) if constant.imaginary => stack.push(self.imaginary(constant.value)?),
+                Token::Constant(constant) => stack.push(Value::Number(constant.value)),
                 Token::Op(op) => {
                     if stack.len() < 2 {
                         return Err(Error::InvalidOperation("No hay suficientes operandos".to_string()));
                     }
                     let right = stack.pop().unwrap();
                     let left = stack.pop().unwrap();
-                    
-                    let result = match op {
-                        Operator::Add => left + right,
-                        Operator::Sub => left - right,
-                        Operator::Mul => left * right,
-                        Operator::Div => {
-                            if right == 0.0 {
-                                return Err(Error::DivisionByZero);
-                            }
-                            left / right
-                        },
-                        Operator::Pow => left.powf(right),
-                        Operator::Mod => {
-                            if right == 0.0 {
-                                return Err(Error::DivisionByZero);
-                            }
-                            left % right
-                        },
-                    };
-                    stack.push(result);
+                    let step = log::log_enabled!(target: "calc::eval", log::Level::Debug)
+                        .then(|| format!("{:?}({}, {})", op, left, right));
+                    let result = self.apply_operator(op, left, right);
+                    if let Some(step) = step {
+                        log::debug!(target: "calc::eval", "{} -> {:?}", step, result);
+                    }
+                    stack.push(result?);
                 },
                 Token::Function(func) => {
-                    if stack.is_empty() {
+                    if stack.len() < func.arity() {
                         return Err(Error::InvalidOperation("No hay suficientes operandos para la función".to_string()));
                     }
-                    let val = stack.pop().unwrap();
-                    
-                    let result = match func {
-                        Function::Sin => val.sin(),
-                        Function::Cos => val.cos(),
-                        Function::Tan => val.tan(),
-                        Function::Sqrt => {
-                            if val < 0.0 {
-                                return Err(Error::InvalidOperation("No se puede sacar raíz cuadrada de un número negativo".to_string()));
-                            }
-                            val.sqrt()
-                        },
-                        Function::Cbrt => val.cbrt(),
-                        Function::Log => {
-                            if val <= 0.0 {
-                                return Err(Error::InvalidOperation("No se puede tomar el logaritmo de un número no positivo".to_string()));
-                            }
-                            val.ln()
-                        },
-                        Function::Log10 => {
-                            if val <= 0.0 {
-                                return Err(Error::InvalidOperation("No se puede tomar el logaritmo de un número no positivo".to_string()));
-                            }
-                            val.log10()
-                        },
-                        Function::Abs => val.abs(),
-                        Function::Floor => val.floor(),
-                        Function::Ceil => val.ceil(),
-                        Function::Round => val.round(),
-                    };
-                    stack.push(result);
+                    let args = stack.split_off(stack.len() - func.arity());
+                    let step = log::log_enabled!(target: "calc::eval", log::Level::Debug)
+                        .then(|| format!("{}({})", func, args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>().join(", ")));
+                    let result = self.call(func, args);
+                    if let Some(step) = step {
+                        log::debug!(target: "calc::eval", "{} -> {:?}", step, result);
+                    }
+                    stack.push(result?);
+                },
+                Token::Factorial => {
+                    let value = stack.pop().ok_or_else(|| Error::InvalidOperation("No hay suficientes operandos".to_string()))?;
+                    stack.push(Calculator::factorial(value)?);
+                },
+                Token::List(elements) => {
+                    if stack.len() < elements {
+                        return Err(Error::InvalidOperation("No hay suficientes operandos".to_string()));
+                    }
+                    let items = stack.split_off(stack.len() - elements);
+                    stack.push(Value::Matrix(Matrix::from_list(items)?));
+                },
+                Token::Braces(elements) => {
+                    if stack.len() < elements {
+                        return Err(Error::InvalidOperation("No hay suficientes operandos".to_string()));
+                    }
+                    let items = stack.split_off(stack.len() - elements);
+                    stack.push(list::from_items(items)?);
                 },
                 _ => {}
             }
@@ -348,4 +1375,444 @@ impl Calculator {
         
         Ok(stack.pop().unwrap())
     }
-}
\ No newline at end of file
+
+    fn imaginary(&self, im: f64) -> Result<Value, Error> {
+        if self.real_only {
+            return Err(Error::InvalidOperation("Los números complejos están desactivados".to_string()));
+        }
+        Ok(Value::Complex(Complex::new(0.0, im)))
+    }
+
+    pub fn factorial(value: Value) -> Result<Value, Error> {
+        match value {
+            Value::Number(n) if n < 0.0 && n.fract() == 0.0 => {
+                Err(Error::InvalidOperation("El factorial no está definido para enteros negativos".to_string()))
+            },
+            Value::Number(n) => Ok(Value::Number(special::gamma(n + 1.0))),
+            _ => Err(Error::InvalidOperation("El factorial requiere un número real".to_string())),
+        }
+    }
+
+    pub fn apply_operator(&self, op: Operator, left: Value, right: Value) -> Result<Value, Error> {
+        if op.is_bitwise() {
+            return Err(Error::InvalidOperation("Los operadores de bits solo están disponibles en el modo programador".to_string()));
+        }
+        if matches!(left, Value::List(_)) || matches!(right, Value::List(_)) {
+            return list::combine(op, left, right);
+        }
+        if matches!(left, Value::Matrix(_)) || matches!(right, Value::Matrix(_)) {
+            return Matrix::combine(op, left, right);
+        }
+        let uncertain = matches!(left, Value::Uncertain(_)) || matches!(right, Value::Uncertain(_));
+        if op == Operator::PlusMinus || uncertain {
+            let left = left.measurement()?;
+            let right = right.measurement()?;
+            if op == Operator::PlusMinus && (left.sigma != 0.0 || right.sigma != 0.0) {
+                return Err(Error::InvalidOperation("No se puede anidar ±".to_string()));
+            }
+            return Ok(Value::Uncertain(Measurement::combine(&op, left, right)?));
+        }
+        if matches!(left, Value::Quantity(_)) || matches!(right, Value::Quantity(_)) {
+            return Quantity::combine(op, left.quantity()?, right.quantity()?);
+        }
+
+        if let (Value::Number(left), Value::Number(right)) = (&left, &right) {
+            let (left, right) = (*left, *right);
+            // Negative bases with fractional exponents leave the real line
+            let complex_pow = op == Operator::Pow && left < 0.0 && right.fract() != 0.0;
+            if !complex_pow || self.real_only {
+                let result = match op {
+                    Operator::Add => left + right,
+                    Operator::Sub => left - right,
+                    Operator::Mul | Operator::Juxtapose => left * right,
+                    Operator::Div => {
+                        if right == 0.0 {
+                            return Err(Error::DivisionByZero);
+                        }
+                        left / right
+                    },
+                    Operator::Pow => left.powf(right),
+                    Operator::Mod => {
+                        if right == 0.0 {
+                            return Err(Error::DivisionByZero);
+                        }
+                        left % right
+                    },
+                    // ± and the bitwise operators returned above
+                    _ => unreachable!(),
+                };
+                return Ok(Value::Number(result));
+            }
+        }
+
+        let left = left.complex()?;
+        let right = right.complex()?;
+        let result = match op {
+            Operator::Add => left + right,
+            Operator::Sub => left - right,
+            Operator::Mul | Operator::Juxtapose => left * right,
+            Operator::Div => {
+                if right.is_zero() {
+                    return Err(Error::DivisionByZero);
+                }
+                left / right
+            },
+            Operator::Pow => left.powc(right),
+            Operator::Mod => {
+                return Err(Error::InvalidOperation("mod no está definido para números complejos".to_string()));
+            },
+            _ => unreachable!(),
+        };
+        Ok(Value::from_complex(result))
+    }
+
+    pub fn call(&self, func: Function, mut args: Vec<Value>) -> Result<Value, Error> {
+        match func {
+            Function::Convert => {
+                let to = args.pop().unwrap().text()?;
+                let from = args.pop().unwrap().text()?;
+                // Currency codes share convert() with physical units
+                if self.currencies.contains(&from) && self.currencies.contains(&to) {
+                    convert_value(args.pop().unwrap(), |x| self.currencies.convert(x, &from, &to))
+                } else {
+                    convert_value(args.pop().unwrap(), |x| units::convert(x, &from, &to))
+                }
+            },
+            Function::Currency(from, to) => {
+                let (from, to) = (currency::code_str(&from), currency::code_str(&to));
+                convert_value(args.pop().unwrap(), |x| self.currencies.convert(x, from, to))
+            },
+            Function::Now | Function::Today => {
+                let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0.0, |elapsed| elapsed.as_secs_f64());
+                Ok(Value::Number(if func == Function::Now { seconds } else { (seconds / 86400.0).floor() }))
+            },
+            Function::FetchNum => {
+                if !self.fetch {
+                    return Err(Error::InvalidOperation("fetch_num() está desactivada; actívala en ⚙ Configuración".to_string()));
+                }
+                let pointer = args.pop().unwrap().text()?;
+                let url = args.pop().unwrap().text()?;
+                Ok(Value::Number(fetch::number(&url, &pointer)?))
+            },
+            Function::AssertEq => {
+                let tolerance = args.pop().unwrap().real()?;
+                let expected = args.pop().unwrap();
+                let actual = args.pop().unwrap();
+                self.assert_eq(actual, expected, tolerance)
+            },
+            Function::Nderiv => {
+                let at = args.pop().unwrap().real()?;
+                let name = args.pop().unwrap().text()?;
+                let expr = args.pop().unwrap().text()?;
+                self.nderiv(&expr, &name, at)
+            },
+            Function::Det | Function::Inv | Function::Transpose | Function::Rank | Function::Norm => args.pop().unwrap().matrix()?.apply(func),
+            Function::Sum | Function::Prod | Function::Mean | Function::Median | Function::Mode | Function::Sort | Function::Len => {
+                list::aggregate(func, args.pop().unwrap().list()?)
+            },
+            Function::Dot | Function::Cross | Function::Angle => {
+                let v = args.pop().unwrap().matrix()?;
+                let u = args.pop().unwrap().matrix()?;
+                match func {
+                    Function::Dot => u.dot(&v).map(Value::Number),
+                    Function::Cross => u.cross(&v).map(Value::Matrix),
+                    _ => Ok(Value::Number(self.angle.angle(u.angle(&v)?))),
+                }
+            },
+            Function::Normpdf | Function::Normcdf | Function::Binompdf | Function::Binomcdf | Function::Poisspdf | Function::Tcdf
+            | Function::Chi2cdf => {
+                let args = args.into_iter().map(Value::real).collect::<Result<Vec<f64>, Error>>()?;
+                distribution::apply(func, &args).map(Value::Number)
+            },
+            Function::Pmt | Function::Pv | Function::Fv | Function::Nper | Function::Rate => {
+                let args = args.into_iter().map(Value::real).collect::<Result<Vec<f64>, Error>>()?;
+                finance::apply(func, &args).map(Value::Number)
+            },
+            Function::Npv => {
+                let flows = args.pop().unwrap().list()?;
+                finance::npv(args.pop().unwrap().real()?, &flows).map(Value::Number)
+            },
+            Function::Irr => finance::irr(&args.pop().unwrap().list()?).map(Value::Number),
+            Function::Eig => Ok(Value::Eigen(Eigen::of(&args.pop().unwrap().matrix()?)?)),
+            Function::Lu | Function::Qr => {
+                let part = args.pop().unwrap().text()?;
+                Ok(Value::Matrix(args.pop().unwrap().matrix()?.factor(func, &part)?))
+            },
+            Function::Solve => {
+                let guess = args.pop().unwrap().real()?;
+                let name = args.pop().unwrap().text()?;
+                let expr = args.pop().unwrap().text()?;
+                self.solve(&expr, &name, guess)
+            },
+            _ => self.apply_function(func, args.pop().unwrap()),
+        }
+    }
+
+    // The derivative of `expr` with respect to the variable `name` at `at`;
+    // evaluation errors at that point are reported as they are
+    fn nderiv(&self, expr: &str, name: &str, at: f64) -> Result<Value, Error> {
+        let compiled = self.compile(expr)?;
+        self.evaluate_at(&compiled, name, at)?.real()?;
+        let f = |x| self.evaluate_at(&compiled, name, x).and_then(|value| value.real()).unwrap_or(f64::NAN);
+        derivative::central(f, at).map(Value::Number).ok_or_else(|| Error::InvalidOperation(
+            format!("nderiv: la función no es derivable o no es real cerca de {} = {}", name, at)
+        ))
+    }
+
+    // A root of `expr` in the variable `name` near `guess`, by Newton's method
+    pub fn solve(&self, expr: &str, name: &str, guess: f64) -> Result<Value, Error> {
+        let compiled = self.compile(expr)?;
+        self.evaluate_at(&compiled, name, guess)?.real()?;
+        let f = |x| self.evaluate_at(&compiled, name, x).and_then(|value| value.real()).unwrap_or(f64::NAN);
+        solver::newton(f, guess).map(Value::Number).map_err(Error::InvalidOperation)
+    }
+
+    // Texts must match exactly; anything else may differ by up to `tolerance`
+    fn assert_eq(&self, actual: Value, expected: Value, tolerance: f64) -> Result<Value, Error> {
+        if tolerance.is_nan() || tolerance < 0.0 {
+            return Err(Error::InvalidOperation("La tolerancia debe ser un número no negativo".to_string()));
+        }
+        let matches = match (&actual, &expected) {
+            (Value::Text(a), Value::Text(b)) => a == b,
+            _ => {
+                let difference = self.apply_operator(Operator::Sub, actual.clone(), expected.clone())?;
+                let difference = match self.apply_function(Function::Abs, difference)? {
+                    Value::Quantity(q) => q.value,
+                    Value::Uncertain(m) => m.value,
+                    difference => difference.real()?,
+                };
+                difference <= tolerance
+            },
+        };
+        if matches {
+            Ok(actual)
+        } else {
+            Err(Error::InvalidOperation(format!("Comprobación fallida: {} ≠ {} (tolerancia {})", actual, expected, tolerance)))
+        }
+    }
+
+    fn apply_function(&self, func: Function, val: Value) -> Result<Value, Error> {
+        match val {
+            Value::Number(val) => {
+                if !self.real_only {
+                    // Domain errors in the reals have principal complex values
+                    let leaves_reals = match func {
+                        Function::Sqrt => val < 0.0,
+                        Function::Log | Function::Log10 => val < 0.0,
+                        Function::Asin | Function::Acos | Function::Atanh => val.abs() > 1.0,
+                        Function::Acosh => val < 1.0,
+                        _ => false,
+                    };
+                    if leaves_reals {
+                        return self.apply_complex_function(func, Complex::from(val));
+                    }
+                }
+                self.apply_real_function(func, val)
+            },
+            Value::Integer(n) => self.apply_function(func, Value::Number(n.to_f64().unwrap_or(f64::INFINITY))),
+            Value::Complex(z) => self.apply_complex_function(func, z),
+            Value::Uncertain(m) => {
+                let result = m.apply(|x| match self.apply_real_function(func, x)? {
+                    Value::Number(n) => Ok(n),
+                    _ => Err(Error::InvalidOperation(format!("{} no admite incertidumbre", func))),
+                })?;
+                Ok(Value::Uncertain(result))
+            },
+            Value::Quantity(q) => Quantity::apply(func, q),
+            Value::Text(_) => Err(Error::InvalidOperation("Se esperaba un número".to_string())),
+            Value::Matrix(_) | Value::Eigen(_) => Err(Error::InvalidOperation(format!("{} no admite matrices", func))),
+            Value::List(_) => Err(Error::InvalidOperation(format!("{} no admite listas", func))),
+        }
+    }
+
+    fn apply_real_function(&self, func: Function, val: f64) -> Result<Value, Error> {
+        // In degrees, multiples of 90° give exact results instead of the
+        // rounding error of converting to radians, so sin(180) is 0
+        if self.angle == AngleMode::Degrees && val % 90.0 == 0.0 {
+            let quarter = (val / 90.0).rem_euclid(4.0) as usize;
+            match func {
+                Function::Sin => return Ok(Value::Number([0.0, 1.0, 0.0, -1.0][quarter])),
+                Function::Cos => return Ok(Value::Number([1.0, 0.0, -1.0, 0.0][quarter])),
+                _ => {},
+            }
+        }
+        let result = match func {
+            Function::Sin => self.angle.radians(val).sin(),
+            Function::Cos => self.angle.radians(val).cos(),
+            Function::Tan => self.angle.radians(val).tan(),
+            Function::Asin | Function::Acos => {
+                if val.abs() > 1.0 {
+                    return Err(Error::InvalidOperation(format!("{} requiere un valor entre -1 y 1", func)));
+                }
+                self.angle.angle(if func == Function::Asin { val.asin() } else { val.acos() })
+            },
+            Function::Atan => self.angle.angle(val.atan()),
+            Function::Sinh => val.sinh(),
+            Function::Cosh => val.cosh(),
+            Function::Tanh => val.tanh(),
+            Function::Asinh => val.asinh(),
+            Function::Acosh => {
+                if val < 1.0 {
+                    return Err(Error::InvalidOperation("acosh requiere un valor mayor o igual que 1".to_string()));
+                }
+                val.acosh()
+            },
+            Function::Atanh => {
+                if val.abs() > 1.0 {
+                    return Err(Error::InvalidOperation("atanh requiere un valor entre -1 y 1".to_string()));
+                }
+                val.atanh()
+            },
+            Function::Sqrt => {
+                if val < 0.0 {
+                    return Err(Error::InvalidOperation("No se puede sacar raíz cuadrada de un número negativo".to_string()));
+                }
+                val.sqrt()
+            },
+            Function::Cbrt => val.cbrt(),
+            Function::Log => {
+                if val <= 0.0 {
+                    return Err(Error::InvalidOperation("No se puede tomar el logaritmo de un número no positivo".to_string()));
+                }
+                val.ln()
+            },
+            Function::Log10 => {
+                if val <= 0.0 {
+                    return Err(Error::InvalidOperation("No se puede tomar el logaritmo de un número no positivo".to_string()));
+                }
+                val.log10()
+            },
+            Function::Abs => val.abs(),
+            Function::Floor => val.floor(),
+            Function::Ceil => val.ceil(),
+            Function::Round => val.round(),
+            Function::Gamma | Function::Lgamma => {
+                if val <= 0.0 && val.fract() == 0.0 {
+                    return Err(Error::InvalidOperation("La función gamma no está definida para enteros no positivos".to_string()));
+                }
+                if func == Function::Gamma {
+                    special::gamma(val)
+                } else {
+                    special::lgamma(val)
+                }
+            },
+            Function::Erf => special::erf(val),
+            Function::Erfc => special::erfc(val),
+            Function::Exp => val.exp(),
+            Function::Words => return Ok(Value::Text(words::to_words(val, self.locale)?)),
+            // Conversions are dispatched by `call`
+            Function::Convert | Function::Currency(..) | Function::Now | Function::Today | Function::AssertEq
+            | Function::FetchNum | Function::Nderiv | Function::Solve | Function::Det | Function::Inv | Function::Transpose
+            | Function::Rank | Function::Lu | Function::Qr | Function::Eig | Function::Dot | Function::Cross
+            | Function::Norm | Function::Angle | Function::Sum | Function::Prod | Function::Mean | Function::Median | Function::Mode
+            | Function::Sort | Function::Len | Function::Normpdf | Function::Normcdf | Function::Binompdf | Function::Binomcdf
+            | Function::Poisspdf | Function::Tcdf | Function::Chi2cdf | Function::Pmt | Function::Pv | Function::Fv | Function::Nper
+            | Function::Rate | Function::Npv | Function::Irr => unreachable!(),
+        };
+        Ok(Value::Number(result))
+    }
+
+    fn apply_complex_function(&self, func: Function, z: Complex) -> Result<Value, Error> {
+        let result = match func {
+            Function::Sin => z.sin(),
+            Function::Cos => z.cos(),
+            Function::Tan => z.tan(),
+            Function::Asin => z.asin(),
+            Function::Acos => z.acos(),
+            Function::Atan => z.atan(),
+            Function::Sinh => z.sinh(),
+            Function::Cosh => z.cosh(),
+            Function::Tanh => z.tanh(),
+            Function::Asinh => z.asinh(),
+            Function::Acosh => z.acosh(),
+            Function::Atanh => z.atanh(),
+            Function::Sqrt => z.sqrt(),
+            Function::Cbrt => z.cbrt(),
+            Function::Log | Function::Log10 => {
+                if z.is_zero() {
+                    return Err(Error::InvalidOperation("No se puede tomar el logaritmo de cero".to_string()));
+                }
+                if func == Function::Log {
+                    z.ln()
+                } else {
+                    z.ln() / Complex::from(std::f64::consts::LN_10)
+                }
+            },
+            Function::Abs => return Ok(Value::Number(z.abs())),
+            Function::Exp => z.exp(),
+            _ => {
+                return Err(Error::InvalidOperation(format!("{} no está definida para números complejos", func)));
+            },
+        };
+        Ok(Value::from_complex(result))
+    }
+}
+
+// Applies a real conversion, carrying any uncertainty along
+fn convert_value<F>(value: Value, f: F) -> Result<Value, Error>
+where
+    F: Fn(f64) -> Result<f64, Error>,
+{
+    match value {
+        Value::Uncertain(m) => Ok(Value::Uncertain(m.apply(f)?)),
+        value => Ok(Value::Number(f(value.measurement()?.value)?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::close;
+
+    fn number(calculator: &Calculator, expr: &str) -> f64 {
+        match calculator.calculate(expr) {
+            Ok(Value::Number(n)) => n,
+            other => panic!("{}: {:?}", expr, other.map(|value| value.to_string())),
+        }
+    }
+
+    #[test]
+    fn juxtaposition_binds_tighter_or_like_a_product() {
+        let mut tight = Calculator::default();
+        tight.assign("x", Value::Number(4.0)).unwrap();
+        let loose = Calculator { juxtaposition: Juxtaposition::Loose, ..tight.clone() };
+        for expr in ["1/2pi", "1/2x", "1/2(4)"] {
+            let (a, b) = (number(&tight, expr), number(&loose, expr));
+            assert!(a < 1.0 && b > 1.0, "{}: {} {}", expr, a, b);
+        }
+        assert!(close(number(&tight, "1/2pi"), 1.0 / std::f64::consts::TAU));
+        assert!(close(number(&loose, "1/2pi"), std::f64::consts::FRAC_PI_2));
+        // Without a division in front, both read the same
+        for (expr, expected) in [("2pi", std::f64::consts::TAU), ("3x", 12.0), ("2(3+1)", 8.0), ("(1+2)(3+4)", 21.0), ("-2pi", -std::f64::consts::TAU)] {
+            assert!(close(number(&tight, expr), expected) && close(number(&loose, expr), expected), "{}", expr);
+        }
+        assert!(tight.calculate("2 3").is_err());
+    }
+
+    #[test]
+    fn base_prefixed_literals() {
+        let calculator = Calculator::default();
+        for (expr, expected) in [("0x1F", 31.0), ("0XAB", 171.0), ("0b101", 5.0), ("0B11", 3.0), ("0o17", 15.0), ("0xff + 1", 256.0)] {
+            assert_eq!(number(&calculator, expr), expected, "{}", expr);
+        }
+        // A prefix needs a digit of its base after it
+        for expr in ["0b2", "0x", "0b1.5"] {
+            assert!(calculator.calculate(expr).is_err(), "{}", expr);
+        }
+        let integer = Calculator { integer_mode: true, ..Calculator::default() };
+        assert_eq!(integer.calculate("0xFFFFFFFFFFFFFFFFFF").unwrap().to_string(), "4722366482869645213695");
+    }
+
+    #[test]
+    fn inline_conversions() {
+        let units = Calculator { unit_mode: true, ..Calculator::default() };
+        let result = |calculator: &Calculator, expr: &str| calculator.calculate(expr).map(|value| value.to_string());
+        assert_eq!(result(&units, "5 km to mi").unwrap(), "3.10685596119 mi");
+        assert_eq!(result(&units, "100 °C to °F").unwrap(), "212 °F");
+        assert_eq!(result(&units, "25 °C to K").unwrap(), "298.15 K");
+        assert_eq!(result(&units, "2 m * 3 m to ft2").unwrap(), "64.5834625003 ft2");
+        assert!(result(&units, "(3 m) to kg").is_err());
+        // Also outside unit mode, for a number followed by its unit
+        assert_eq!(result(&Calculator::default(), "5 km to mi").unwrap(), "3.10685596119 mi");
+    }
+}
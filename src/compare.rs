@@ -0,0 +1,103 @@
+use std::ops::Range;
+
+use crate::calculator::Calculator;
+use crate::format::NumberFormat;
+use crate::history::Entry;
+use crate::value::Value;
+
+const REMOVED_COLOR: egui::Color32 = egui::Color32::from_rgb(230, 90, 90);
+const ADDED_COLOR: egui::Color32 = egui::Color32::from_rgb(90, 200, 90);
+
+// A token's byte range and whether the other side lacks it
+type Tokens = Vec<(Range<usize>, bool)>;
+
+// Tokens of `a` and `b` marked by longest common subsequence, so spacing
+// doesn't count as a change
+pub fn diff(a: &str, b: &str) -> (Tokens, Tokens) {
+    let left = Calculator::spans(a);
+    let right = Calculator::spans(b);
+    let same = |i: usize, j: usize| a[left[i].clone()] == b[right[j].clone()];
+
+    // lengths[i][j]: common tokens of left[i..] and right[j..]
+    let mut lengths = vec![vec![0usize; right.len() + 1]; left.len() + 1];
+    for i in (0..left.len()).rev() {
+        for j in (0..right.len()).rev() {
+            lengths[i][j] = if same(i, j) {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut left_changed = vec![true; left.len()];
+    let mut right_changed = vec![true; right.len()];
+    let (mut i, mut j) = (0, 0);
+    while i < left.len() && j < right.len() {
+        if same(i, j) {
+            left_changed[i] = false;
+            right_changed[j] = false;
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    (left.into_iter().zip(left_changed).collect(), right.into_iter().zip(right_changed).collect())
+}
+
+// Result of `after` minus `before`, evaluated from the results as printed
+pub fn delta(calculator: &Calculator, before: &str, after: &str) -> Option<Value> {
+    calculator.calculate(&format!("({}) - ({})", after, before)).ok()
+}
+
+fn highlighted(ui: &egui::Ui, text: &str, tokens: &Tokens, color: egui::Color32) -> egui::text::LayoutJob {
+    let font = egui::TextStyle::Monospace.resolve(ui.style());
+    let plain = egui::TextFormat { font_id: font.clone(), color: ui.visuals().text_color(), ..Default::default() };
+    let changed = egui::TextFormat { font_id: font, color, background: color.gamma_multiply(0.2), ..Default::default() };
+    let mut job = egui::text::LayoutJob::default();
+    let mut position = 0;
+    for (range, is_changed) in tokens {
+        job.append(&text[position..range.start], 0.0, plain.clone());
+        job.append(&text[range.clone()], 0.0, if *is_changed { changed.clone() } else { plain.clone() });
+        position = range.end;
+    }
+    job.append(&text[position..], 0.0, plain);
+    job
+}
+
+// Two history entries side by side, the older one on the left
+pub fn show(ui: &mut egui::Ui, before: &Entry, after: &Entry, calculator: &Calculator, format: &NumberFormat) {
+    let (left, right) = diff(&before.expression, &after.expression);
+    egui::Grid::new("comparison").num_columns(2).spacing([24.0, 6.0]).show(ui, |ui| {
+        ui.strong("Antes");
+        ui.strong("Después");
+        ui.end_row();
+        ui.label(highlighted(ui, &before.expression, &left, REMOVED_COLOR));
+        ui.label(highlighted(ui, &after.expression, &right, ADDED_COLOR));
+        ui.end_row();
+        ui.monospace(format!("= {}", before.result));
+        ui.monospace(format!("= {}", after.result));
+        ui.end_row();
+    });
+    ui.separator();
+
+    match delta(calculator, &before.result, &after.result) {
+        Some(difference) => {
+            ui.label(egui::RichText::new(format!("Diferencia: {}", format.value(&difference))).monospace().strong());
+            // A relative change only means something between plain numbers
+            let relative = match (difference, calculator.calculate(&before.result)) {
+                (Value::Number(d), Ok(Value::Number(b))) if b != 0.0 => Some(d / b.abs() * 100.0),
+                _ => None,
+            };
+            if let Some(percent) = relative {
+                ui.small(format!("{:+.4} % respecto al anterior", percent));
+            }
+        },
+        None => {
+            ui.small("No se puede calcular la diferencia entre estos resultados.");
+        },
+    }
+}
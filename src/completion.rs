@@ -0,0 +1,100 @@
+use std::ops::Range;
+
+use crate::calculator::Calculator;
+use crate::quantity::Dimension;
+use crate::units::{Unit, UNITS};
+use crate::value::Value;
+
+const MAX_SUGGESTIONS: usize = 8;
+
+// A half-typed unit symbol and the units that could finish it
+pub struct Completion {
+    // Byte range of the partial word in the expression
+    pub range: Range<usize>,
+    pub units: Vec<&'static Unit>,
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '°' || c == 'µ'
+}
+
+// Units completing the word that ends at byte offset `cursor`, in unit mode.
+// When the word is added to or subtracted from a quantity, as in 3 m + 5 k,
+// only units of that dimension are offered.
+pub fn units(calculator: &Calculator, expr: &str, cursor: usize) -> Option<Completion> {
+    if !calculator.unit_mode || expr[cursor..].starts_with(is_word_char) {
+        return None;
+    }
+    let before = &expr[..cursor];
+    let start = before.char_indices().rev()
+        .take_while(|&(_, c)| is_word_char(c))
+        .last()
+        .map_or(cursor, |(i, _)| i);
+    let partial = &before[start..];
+    if partial.is_empty() || partial.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let expected = expected_dimension(calculator, &before[..start]);
+    let units: Vec<&'static Unit> = UNITS.iter()
+        // Compound symbols are written with operators in expressions
+        .filter(|unit| unit.symbol.starts_with(partial) && !unit.symbol.contains('/'))
+        .filter(|unit| expected.is_none_or(|dim| unit.category.dimension() == dim))
+        .take(MAX_SUGGESTIONS)
+        .collect();
+    // Nothing left to complete
+    if units.is_empty() || units.len() == 1 && units[0].symbol == partial {
+        return None;
+    }
+    Some(Completion { range: start..cursor, units })
+}
+
+// Dimension of what is being converted with `to`/`in`, or of the quantity the
+// current term is added to or subtracted from when the term so far is a plain
+// coefficient
+fn expected_dimension(calculator: &Calculator, before: &str) -> Option<Dimension> {
+    let trimmed = before.trim_end();
+    for keyword in ["to", "in"] {
+        let Some(left) = trimmed.strip_suffix(keyword).filter(|left| left.ends_with(' ')) else {
+            continue;
+        };
+        if let Ok(Value::Quantity(q)) = calculator.calculate(left) {
+            return Some(q.dim);
+        }
+    }
+    let mut depth = 0;
+    let mut operator = None;
+    for (i, c) in before.char_indices().rev() {
+        match c {
+            ')' => depth += 1,
+            '(' | ',' if depth == 0 => return None,
+            '(' => depth -= 1,
+            '+' | '-' if depth == 0 => {
+                operator = Some(i);
+                break;
+            },
+            _ => {},
+        }
+    }
+    let operator = operator?;
+    let term = before[operator + 1..].trim();
+    if !term.is_empty() && !matches!(calculator.calculate(term), Ok(Value::Number(_) | Value::Integer(_))) {
+        return None;
+    }
+    // The left operand runs back to the enclosing '(' or ','
+    let mut depth = 0;
+    let left_start = before[..operator].char_indices().rev()
+        .find(|&(_, c)| {
+            match c {
+                ')' => depth += 1,
+                '(' | ',' if depth == 0 => return true,
+                '(' => depth -= 1,
+                _ => {},
+            }
+            false
+        })
+        .map_or(0, |(i, c)| i + c.len_utf8());
+    match calculator.calculate(&before[left_start..operator]) {
+        Ok(Value::Quantity(q)) => Some(q.dim),
+        _ => None,
+    }
+}
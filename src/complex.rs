@@ -0,0 +1,221 @@
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub fn new(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+
+    pub fn from_polar(r: f64, theta: f64) -> Self {
+        Self::new(r * theta.cos(), r * theta.sin())
+    }
+
+    pub fn abs(&self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    pub fn arg(&self) -> f64 {
+        self.im.atan2(self.re)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.re == 0.0 && self.im == 0.0
+    }
+
+    pub fn exp(self) -> Self {
+        Self::from_polar(self.re.exp(), self.im)
+    }
+
+    // Principal branch of the natural logarithm
+    pub fn ln(self) -> Self {
+        Self::new(self.abs().ln(), self.arg())
+    }
+
+    pub fn powc(self, exp: Complex) -> Self {
+        if self.is_zero() {
+            return if exp.is_zero() { Self::new(1.0, 0.0) } else { Self::new(0.0, 0.0) };
+        }
+        (self.ln() * exp).exp()
+    }
+
+    pub fn sqrt(self) -> Self {
+        Self::from_polar(self.abs().sqrt(), self.arg() / 2.0)
+    }
+
+    pub fn cbrt(self) -> Self {
+        Self::from_polar(self.abs().cbrt(), self.arg() / 3.0)
+    }
+
+    pub fn sin(self) -> Self {
+        Self::new(self.re.sin() * self.im.cosh(), self.re.cos() * self.im.sinh())
+    }
+
+    pub fn cos(self) -> Self {
+        Self::new(self.re.cos() * self.im.cosh(), -self.re.sin() * self.im.sinh())
+    }
+
+    pub fn tan(self) -> Self {
+        self.sin() / self.cos()
+    }
+
+    // Principal branches, from the logarithm forms
+    pub fn asin(self) -> Self {
+        let i = Self::new(0.0, 1.0);
+        -i * (i * self + (Self::from(1.0) - self * self).sqrt()).ln()
+    }
+
+    pub fn acos(self) -> Self {
+        Self::from(std::f64::consts::FRAC_PI_2) - self.asin()
+    }
+
+    pub fn atan(self) -> Self {
+        let i = Self::new(0.0, 1.0);
+        Self::new(0.0, 0.5) * ((Self::from(1.0) - i * self).ln() - (Self::from(1.0) + i * self).ln())
+    }
+
+    pub fn sinh(self) -> Self {
+        (self.exp() - (-self).exp()) / Self::from(2.0)
+    }
+
+    pub fn cosh(self) -> Self {
+        (self.exp() + (-self).exp()) / Self::from(2.0)
+    }
+
+    pub fn tanh(self) -> Self {
+        self.sinh() / self.cosh()
+    }
+
+    pub fn asinh(self) -> Self {
+        (self + (self * self + Self::from(1.0)).sqrt()).ln()
+    }
+
+    pub fn acosh(self) -> Self {
+        (self + (self + Self::from(1.0)).sqrt() * (self - Self::from(1.0)).sqrt()).ln()
+    }
+
+    pub fn atanh(self) -> Self {
+        Self::from(0.5) * ((Self::from(1.0) + self).ln() - (Self::from(1.0) - self).ln())
+    }
+}
+
+impl From<f64> for Complex {
+    fn from(re: f64) -> Self {
+        Self::new(re, 0.0)
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+    fn add(self, rhs: Complex) -> Complex {
+        Complex::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+    fn sub(self, rhs: Complex) -> Complex {
+        Complex::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+    fn mul(self, rhs: Complex) -> Complex {
+        Complex::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        )
+    }
+}
+
+impl Div for Complex {
+    type Output = Complex;
+    fn div(self, rhs: Complex) -> Complex {
+        let denom = rhs.re * rhs.re + rhs.im * rhs.im;
+        Complex::new(
+            (self.re * rhs.re + self.im * rhs.im) / denom,
+            (self.im * rhs.re - self.re * rhs.im) / denom,
+        )
+    }
+}
+
+impl Neg for Complex {
+    type Output = Complex;
+    fn neg(self) -> Complex {
+        Complex::new(-self.re, -self.im)
+    }
+}
+
+impl Complex {
+    // Renders as a+bi, with `number` formatting each part
+    pub fn format(&self, number: impl Fn(f64) -> String) -> String {
+        let imag = match self.im.abs() {
+            1.0 => "i".to_string(),
+            m => format!("{}i", number(m)),
+        };
+        if self.re == 0.0 {
+            if self.im < 0.0 {
+                format!("-{}", imag)
+            } else {
+                imag
+            }
+        } else if self.im < 0.0 {
+            format!("{}-{}", number(self.re), imag)
+        } else {
+            format!("{}+{}", number(self.re), imag)
+        }
+    }
+}
+
+impl fmt::Display for Complex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.format(|x| x.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculator::Calculator;
+    use crate::testing::close;
+
+    fn near(z: Complex, re: f64, im: f64) -> bool {
+        close(z.re, re) && close(z.im, im)
+    }
+
+    #[test]
+    fn arithmetic_and_functions() {
+        let (a, b) = (Complex::new(1.0, 2.0), Complex::new(3.0, -1.0));
+        assert_eq!(a * b, Complex::new(5.0, 5.0));
+        assert!(near(Complex::new(1.0, 0.0) / Complex::new(1.0, 1.0), 0.5, -0.5));
+        assert!(near(Complex::new(-4.0, 0.0).sqrt(), 0.0, 2.0));
+        assert!(near(Complex::new(0.0, std::f64::consts::PI).exp(), -1.0, 0.0));
+        assert!(near(Complex::new(-1.0, 0.0).ln(), 0.0, std::f64::consts::PI));
+        assert!(close(Complex::new(3.0, 4.0).abs(), 5.0));
+        // Inverse functions undo theirs on the principal branch
+        let z = Complex::new(0.3, 0.4);
+        assert!(near(z.sin().asin(), z.re, z.im));
+        assert!(near(z.tanh().atanh(), z.re, z.im));
+    }
+
+    #[test]
+    fn in_expressions() {
+        let calculator = Calculator::default();
+        let result = |expr: &str| calculator.calculate(expr).map(|value| value.to_string()).unwrap();
+        assert_eq!(result("(1+2i)*(3-i)"), "5+5i");
+        assert_eq!(result("sqrt(-4)"), "2i");
+        assert_eq!(result("i^2"), "-1");
+        assert_eq!(result("(2+3i)-(2+3i)"), "0");
+        let real = Calculator { real_only: true, ..Calculator::default() };
+        assert!(real.calculate("sqrt(-4)").is_err());
+        assert!(real.calculate("2i").is_err());
+    }
+}
@@ -0,0 +1,65 @@
+use std::f64::consts::{E, PI, TAU};
+
+// An entry of the constant registry; the lexer resolves names through `find`
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct Constant {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub value: f64,
+    pub unit: &'static str,
+    // The value is the coefficient of i rather than a real number
+    pub imaginary: bool,
+}
+
+const fn constant(name: &'static str, description: &'static str, value: f64, unit: &'static str) -> Constant {
+    Constant { name, description, value, unit, imaginary: false }
+}
+
+// Mathematical constants; their names match in any case (pi, PI)
+pub const MATH_CONSTANTS: &[Constant] = &[
+    constant("pi", "razón entre circunferencia y diámetro", PI, ""),
+    constant("e", "número de Euler", E, ""),
+    Constant { name: "i", description: "unidad imaginaria", value: 1.0, unit: "", imaginary: true },
+    constant("tau", "2·pi, una vuelta completa en radianes", TAU, ""),
+    constant("phi", "número áureo", 1.618_033_988_749_895, ""),
+    constant("euler_gamma", "constante de Euler-Mascheroni", 0.577_215_664_901_532_9, ""),
+];
+
+// Physical constants are matched case-sensitively (G is not g).
+// CODATA 2018 recommended values; the SI defining constants are exact
+pub const PHYSICAL_CONSTANTS: &[Constant] = &[
+    constant("c", "velocidad de la luz en el vacío", 299_792_458.0, "m/s"),
+    constant("G", "constante de gravitación universal", 6.674_30e-11, "m³/(kg·s²)"),
+    constant("g_n", "gravedad estándar", 9.806_65, "m/s²"),
+    constant("h", "constante de Planck", 6.626_070_15e-34, "J·s"),
+    constant("hbar", "constante de Planck reducida", 1.054_571_817e-34, "J·s"),
+    constant("k_B", "constante de Boltzmann", 1.380_649e-23, "J/K"),
+    constant("N_A", "número de Avogadro", 6.022_140_76e23, "1/mol"),
+    constant("R", "constante de los gases ideales", 8.314_462_618, "J/(mol·K)"),
+    constant("F", "constante de Faraday", 96_485.332_12, "C/mol"),
+    constant("q_e", "carga elemental", 1.602_176_634e-19, "C"),
+    constant("m_e", "masa del electrón", 9.109_383_701_5e-31, "kg"),
+    constant("m_p", "masa del protón", 1.672_621_923_69e-27, "kg"),
+    constant("m_n", "masa del neutrón", 1.674_927_498_04e-27, "kg"),
+    constant("m_u", "unidad de masa atómica", 1.660_539_066_60e-27, "kg"),
+    constant("eps_0", "permitividad del vacío", 8.854_187_812_8e-12, "F/m"),
+    constant("mu_0", "permeabilidad del vacío", 1.256_637_062_12e-6, "N/A²"),
+    constant("sigma_SB", "constante de Stefan-Boltzmann", 5.670_374_419e-8, "W/(m²·K⁴)"),
+    constant("alpha", "constante de estructura fina", 7.297_352_569_3e-3, ""),
+    constant("a_0", "radio de Bohr", 5.291_772_109_03e-11, "m"),
+    constant("R_inf", "constante de Rydberg", 10_973_731.568_160, "1/m"),
+];
+
+pub fn find(name: &str) -> Option<&'static Constant> {
+    MATH_CONSTANTS.iter()
+        .find(|constant| constant.name.eq_ignore_ascii_case(name))
+        .or_else(|| PHYSICAL_CONSTANTS.iter().find(|constant| constant.name == name))
+}
+
+// Case-insensitive match on name or description, for the physical constants picker
+pub fn search(query: &str) -> impl Iterator<Item = &'static Constant> {
+    let query = query.trim().to_lowercase();
+    PHYSICAL_CONSTANTS.iter().filter(move |constant| {
+        constant.name.to_lowercase().contains(&query) || constant.description.to_lowercase().contains(&query)
+    })
+}
@@ -0,0 +1,233 @@
+use crate::calculator::Calculator;
+use crate::currency::Rates;
+use crate::units::{self, Category};
+
+// State of the unit conversion tab
+pub struct Converter {
+    pub category: Category,
+    pub value: String,
+    pub from: &'static str,
+    pub to: &'static str,
+}
+
+impl Default for Converter {
+    fn default() -> Self {
+        Self {
+            category: Category::Length,
+            value: "1".to_string(),
+            from: "km",
+            to: "mi",
+        }
+    }
+}
+
+impl Converter {
+    // Keeps both units inside the selected category
+    fn set_category(&mut self, category: Category) {
+        let mut units = category.units();
+        self.category = category;
+        self.from = units.next().map_or("", |unit| unit.symbol);
+        self.to = units.next().map_or(self.from, |unit| unit.symbol);
+    }
+
+    // Reselects units by symbol, e.g. from a saved workspace; unknown or
+    // mismatched symbols leave the current units
+    pub fn restore(&mut self, value: String, from: &str, to: &str) {
+        self.value = value;
+        if let (Some(from), Some(to)) = (units::find(from), units::find(to)) {
+            if from.category == to.category {
+                self.category = from.category;
+                self.from = from.symbol;
+                self.to = to.symbol;
+            }
+        }
+    }
+
+    // Goes through convert() so the value field accepts any expression
+    pub fn expression(&self) -> String {
+        format!("convert({}, \"{}\", \"{}\")", self.value, self.from, self.to)
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, calculator: &Calculator) {
+        ui.heading("Conversión de unidades");
+        ui.add_space(10.0);
+
+        let mut category = self.category;
+        egui::ComboBox::from_label("Magnitud")
+            .selected_text(category.name())
+            .show_ui(ui, |ui| {
+                for option in Category::ALL {
+                    ui.selectable_value(&mut category, option, option.name());
+                }
+            });
+        if category != self.category {
+            self.set_category(category);
+        }
+
+        ui.add(egui::TextEdit::singleline(&mut self.value).hint_text("Valor o expresión"));
+        ui.horizontal(|ui| {
+            unit_combo(ui, "converter_from", self.category, &mut self.from);
+            if ui.button("⇄").on_hover_text("Intercambiar unidades").clicked() {
+                std::mem::swap(&mut self.from, &mut self.to);
+            }
+            unit_combo(ui, "converter_to", self.category, &mut self.to);
+        });
+
+        ui.add_space(10.0);
+        if self.value.trim().is_empty() {
+            return;
+        }
+        // Text arguments have no meaning in integer mode
+        let calculator = Calculator { integer_mode: false, ..calculator.clone() };
+        match calculator.calculate(&self.expression()) {
+            Ok(result) => {
+                let text = format!("{} {} = {} {}", self.value.trim(), self.from, result, self.to);
+                ui.add(egui::Label::new(egui::RichText::new(&text).size(20.0).color(egui::Color32::GREEN)).wrap());
+                if ui.small_button("Copiar resultado").clicked() {
+                    ui.ctx().copy_text(result.to_string());
+                }
+            },
+            Err(e) => {
+                ui.colored_label(egui::Color32::RED, e.to_string());
+            },
+        }
+        ui.small(format!("En la calculadora: {}", self.expression()));
+    }
+}
+
+fn unit_combo(ui: &mut egui::Ui, id: &str, category: Category, selected: &mut &'static str) {
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(*selected)
+        .show_ui(ui, |ui| {
+            for unit in category.units() {
+                ui.selectable_value(selected, unit.symbol, format!("{} ({})", unit.symbol, unit.name));
+            }
+        });
+}
+
+// State of the currency tab; the rates themselves live in the calculator
+pub struct CurrencyConverter {
+    pub amount: String,
+    pub from: String,
+    pub to: String,
+    new_code: String,
+    new_rate: f64,
+    json_path: String,
+    // Outcome of the last table edit or file load
+    status: Option<Result<String, String>>,
+}
+
+impl Default for CurrencyConverter {
+    fn default() -> Self {
+        Self {
+            amount: "1".to_string(),
+            from: "USD".to_string(),
+            to: "EUR".to_string(),
+            new_code: String::new(),
+            new_rate: 1.0,
+            json_path: String::new(),
+            status: None,
+        }
+    }
+}
+
+impl CurrencyConverter {
+    pub fn expression(&self) -> String {
+        format!("{}_to_{}({})", self.from.to_lowercase(), self.to.to_lowercase(), self.amount)
+    }
+
+    fn load(&mut self, calculator: &mut Calculator) {
+        self.status = Some(std::fs::read_to_string(self.json_path.trim())
+            .map_err(|e| format!("No se pudo leer el archivo: {}", e))
+            .and_then(|json| calculator.currencies.load_json(&json))
+            .map(|count| format!("Se cargaron {} tasas", count)));
+    }
+
+    // Reads the rates file again, if one was loaded, for files kept up to date elsewhere
+    pub fn reload(&mut self, calculator: &mut Calculator) {
+        if !self.json_path.trim().is_empty() {
+            self.load(calculator);
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, calculator: &mut Calculator) {
+        ui.heading("Conversión de divisas");
+        ui.add_space(10.0);
+
+        ui.add(egui::TextEdit::singleline(&mut self.amount).hint_text("Cantidad o expresión"));
+        ui.horizontal(|ui| {
+            currency_combo(ui, "currency_from", &calculator.currencies, &mut self.from);
+            if ui.button("⇄").on_hover_text("Intercambiar monedas").clicked() {
+                std::mem::swap(&mut self.from, &mut self.to);
+            }
+            currency_combo(ui, "currency_to", &calculator.currencies, &mut self.to);
+        });
+        if !self.amount.trim().is_empty() {
+            let evaluator = Calculator { integer_mode: false, ..calculator.clone() };
+            match evaluator.calculate(&self.expression()) {
+                Ok(result) => {
+                    let text = format!("{} {} = {} {}", self.amount.trim(), self.from, result, self.to);
+                    ui.add(egui::Label::new(egui::RichText::new(&text).size(20.0).color(egui::Color32::GREEN)).wrap());
+                },
+                Err(e) => {
+                    ui.colored_label(egui::Color32::RED, e.to_string());
+                },
+            }
+            ui.small(format!("En la calculadora: {}", self.expression()));
+        }
+
+        ui.add_space(10.0);
+        ui.separator();
+        ui.label("Tasas (unidades de cada moneda por unidad base):");
+        let mut removed = None;
+        egui::Grid::new("currency_rates").striped(true).show(ui, |ui| {
+            for (code, rate) in calculator.currencies.rates.iter_mut() {
+                ui.monospace(code);
+                let speed = *rate * 0.001;
+                ui.add(egui::DragValue::new(rate).speed(speed).range(f64::MIN_POSITIVE..=f64::MAX).max_decimals(6));
+                if ui.small_button("✖").on_hover_text("Quitar").clicked() {
+                    removed = Some(code.clone());
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(code) = removed {
+            calculator.currencies.rates.remove(&code);
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.new_code).hint_text("Código").desired_width(50.0));
+            ui.add(egui::DragValue::new(&mut self.new_rate).speed(0.01).max_decimals(6));
+            if ui.button("Agregar").clicked() {
+                self.status = Some(calculator.currencies.set(self.new_code.trim(), self.new_rate)
+                    .map(|_| format!("Tasa de {} guardada", self.new_code.trim().to_uppercase())));
+                self.new_code.clear();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.json_path).hint_text("Ruta a un archivo JSON de tasas").desired_width(200.0));
+            if ui.button("Cargar").clicked() {
+                self.load(calculator);
+            }
+        });
+        if ui.button("Restablecer tasas predeterminadas").clicked() {
+            calculator.currencies = Rates::default();
+            self.status = None;
+        }
+        match &self.status {
+            Some(Ok(message)) => { ui.small(message); },
+            Some(Err(message)) => { ui.colored_label(egui::Color32::RED, message); },
+            None => {},
+        }
+    }
+}
+
+fn currency_combo(ui: &mut egui::Ui, id: &str, rates: &Rates, selected: &mut String) {
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(selected.as_str())
+        .show_ui(ui, |ui| {
+            for code in rates.rates.keys() {
+                ui.selectable_value(selected, code.clone(), code);
+            }
+        });
+}
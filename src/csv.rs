@@ -0,0 +1,149 @@
+use std::path::{Path, PathBuf};
+
+use crate::calculator::Calculator;
+use crate::value::Value;
+
+// Rows of the file shown before importing
+const PREVIEW_ROWS: usize = 5;
+
+// The delimiter most used in the first line: ';' in locales with a decimal
+// comma, a tab from some exports, ',' otherwise
+fn delimiter(first: &str) -> char {
+    [',', ';', '\t'].into_iter().max_by_key(|d| first.matches(*d).count()).filter(|d| first.contains(*d)).unwrap_or(',')
+}
+
+fn numeric(field: &str) -> bool {
+    field.starts_with(|c: char| c.is_ascii_digit() || "+-.".contains(c))
+}
+
+// Fields of a line, with "quoted, fields" and "" for a quote inside them
+fn fields(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            },
+            '"' => quoted = !quoted,
+            c if c == delimiter && !quoted => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields.into_iter().map(|field| field.trim().to_string()).collect()
+}
+
+// A CSV file being imported into the statistics data: which columns become
+// x and y, and whether the first row holds their names
+pub struct CsvImport {
+    path: PathBuf,
+    rows: Vec<Vec<String>>,
+    header: bool,
+    x: usize,
+    y: usize,
+}
+
+impl CsvImport {
+    pub fn open(path: &Path) -> Result<CsvImport, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("No se pudo abrir {}: {}", path.display(), e))?;
+        let text = text.trim_start_matches('\u{feff}');
+        let delimiter = delimiter(text.lines().next().unwrap_or(""));
+        let rows: Vec<Vec<String>> = text.lines().filter(|line| !line.trim().is_empty()).map(|line| fields(line, delimiter)).collect();
+        if rows.is_empty() {
+            return Err(format!("{} está vacío", path.display()));
+        }
+        // A first row with no numbers at all is taken as the header
+        let header = !rows[0].iter().any(|field| numeric(field));
+        // x and y start as the first columns with numbers, skipping names
+        let first = rows.get(usize::from(header)).unwrap_or(&rows[0]);
+        let mut columns = (0..first.len()).filter(|&i| numeric(&first[i]));
+        let x = columns.next().unwrap_or(0);
+        let y = columns.next().unwrap_or(x);
+        Ok(CsvImport { path: path.to_path_buf(), rows, header, x, y })
+    }
+
+    pub fn pick() -> Option<Result<CsvImport, String>> {
+        rfd::FileDialog::new().add_filter("CSV", &["csv", "txt", "tsv"]).pick_file().map(|path| CsvImport::open(&path))
+    }
+
+    fn columns(&self) -> usize {
+        self.rows.iter().map(Vec::len).max().unwrap_or(0)
+    }
+
+    fn column_name(&self, i: usize) -> String {
+        match self.rows[0].get(i).filter(|name| self.header && !name.is_empty()) {
+            Some(name) => format!("{}: {}", i + 1, name),
+            None => format!("Columna {}", i + 1),
+        }
+    }
+
+    fn column_picker(&self, ui: &mut egui::Ui, label: &str, selected: &mut usize) {
+        egui::ComboBox::from_label(label).selected_text(self.column_name(*selected)).show_ui(ui, |ui| {
+            for i in 0..self.columns() {
+                ui.selectable_value(selected, i, self.column_name(i));
+            }
+        });
+    }
+
+    // The chosen columns as data lines, tab-separated, with the rows that
+    // aren't numbers left out and reported by their row in the file
+    pub fn read(&self, two_columns: bool, calculator: &Calculator) -> (String, Vec<String>) {
+        let (mut lines, mut errors) = (Vec::new(), Vec::new());
+        let columns: &[usize] = if two_columns { &[self.x, self.y] } else { &[self.x] };
+        for (i, row) in self.rows.iter().enumerate().skip(usize::from(self.header)) {
+            let cells: Result<Vec<String>, String> = columns.iter().map(|&column| {
+                let cell = row.get(column).map(String::as_str).unwrap_or("");
+                if cell.is_empty() {
+                    return Err(format!("falta la columna {}", column + 1));
+                }
+                calculator.calculate(cell).and_then(Value::real).map(|_| cell.to_string()).map_err(|_| format!("«{}» no es un número", cell))
+            }).collect();
+            match cells {
+                Ok(cells) => lines.push(cells.join("\t")),
+                Err(e) => errors.push(format!("Fila {}: {}", i + 1, e)),
+            }
+        }
+        (lines.join("\n"), errors)
+    }
+
+    // Options and a preview; Some(true) to import, Some(false) to cancel
+    pub fn show(&mut self, ui: &mut egui::Ui, two_columns: bool) -> Option<bool> {
+        let mut done = None;
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            ui.strong(format!("Importar {}", self.path.file_name().map_or_else(String::new, |name| name.to_string_lossy().into_owned())));
+            ui.checkbox(&mut self.header, "La primera fila son los nombres de las columnas");
+            ui.horizontal(|ui| {
+                let mut x = self.x;
+                self.column_picker(ui, if two_columns { "x" } else { "Datos" }, &mut x);
+                self.x = x;
+                if two_columns {
+                    let mut y = self.y;
+                    self.column_picker(ui, "y", &mut y);
+                    self.y = y;
+                }
+            });
+            egui::Grid::new("csv preview").striped(true).show(ui, |ui| {
+                for row in self.rows.iter().take(PREVIEW_ROWS) {
+                    for field in row {
+                        ui.monospace(field);
+                    }
+                    ui.end_row();
+                }
+            });
+            if self.rows.len() > PREVIEW_ROWS {
+                ui.weak(format!("… {} filas en total", self.rows.len()));
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Importar").clicked() {
+                    done = Some(true);
+                }
+                if ui.button("Cancelar").clicked() {
+                    done = Some(false);
+                }
+            });
+        });
+        done
+    }
+}
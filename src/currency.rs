@@ -0,0 +1,95 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::calculator::Error;
+
+// ISO 4217 code, stored uppercase so functions like usd_to_eur stay `Copy`
+pub type Code = [u8; 3];
+
+pub fn code(text: &str) -> Option<Code> {
+    let bytes: Code = text.as_bytes().try_into().ok()?;
+    bytes.iter().all(u8::is_ascii_alphabetic).then(|| bytes.map(|b| b.to_ascii_uppercase()))
+}
+
+pub fn code_str(code: &Code) -> &str {
+    std::str::from_utf8(code).unwrap_or("???")
+}
+
+// Exchange rates as units of each currency per unit of a common base.
+// Only the ratios matter, so the base itself is never stored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rates {
+    pub rates: BTreeMap<String, f64>,
+}
+
+impl Default for Rates {
+    // Approximate reference values; users are expected to keep them current
+    fn default() -> Self {
+        let rates = [
+            ("USD", 1.0),
+            ("EUR", 0.92),
+            ("GBP", 0.79),
+            ("JPY", 150.0),
+            ("CHF", 0.88),
+            ("CAD", 1.36),
+            ("CNY", 7.2),
+            ("MXN", 17.0),
+            ("BRL", 5.0),
+            ("ARS", 850.0),
+            ("COP", 3900.0),
+            ("CLP", 950.0),
+        ];
+        Self {
+            rates: rates.iter().map(|(code, rate)| (code.to_string(), *rate)).collect(),
+        }
+    }
+}
+
+impl Rates {
+    fn rate(&self, code: &str) -> Result<f64, Error> {
+        self.rates.get(&code.to_ascii_uppercase())
+            .copied()
+            .ok_or_else(|| Error::InvalidOperation(format!("Moneda desconocida: {}", code)))
+    }
+
+    pub fn contains(&self, code: &str) -> bool {
+        self.rates.contains_key(&code.to_ascii_uppercase())
+    }
+
+    pub fn convert(&self, value: f64, from: &str, to: &str) -> Result<f64, Error> {
+        Ok(value / self.rate(from)? * self.rate(to)?)
+    }
+
+    pub fn set(&mut self, code: &str, rate: f64) -> Result<(), String> {
+        let code = self::code(code).ok_or_else(|| "El código debe tener tres letras, p. ej. EUR".to_string())?;
+        if !(rate.is_finite() && rate > 0.0) {
+            return Err("La tasa debe ser un número positivo".to_string());
+        }
+        self.rates.insert(code_str(&code).to_string(), rate);
+        Ok(())
+    }
+
+    // Replaces the table with one read from JSON, either a flat {"EUR": 0.92, ...}
+    // object or the common {"base": "USD", "rates": {...}} layout
+    pub fn load_json(&mut self, json: &str) -> Result<usize, String> {
+        let root: serde_json::Value = serde_json::from_str(json).map_err(|e| format!("JSON no válido: {}", e))?;
+        let table = root.get("rates").unwrap_or(&root)
+            .as_object()
+            .ok_or_else(|| "Se esperaba un objeto con las tasas".to_string())?;
+        let mut rates = Rates { rates: BTreeMap::new() };
+        if let Some(base) = root.get("base").and_then(|base| base.as_str()) {
+            rates.set(base, 1.0)?;
+        }
+        for (code, rate) in table {
+            let rate = rate.as_f64().ok_or_else(|| format!("La tasa de {} no es un número", code))?;
+            rates.set(code, rate).map_err(|e| format!("{}: {}", code, e))?;
+        }
+        if rates.rates.is_empty() {
+            return Err("El archivo no contiene tasas".to_string());
+        }
+        let count = rates.rates.len();
+        *self = rates;
+        Ok(count)
+    }
+}
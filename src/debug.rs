@@ -0,0 +1,132 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+// Engine records use targets under this prefix, e.g. calc::lexer
+pub const TARGET_PREFIX: &str = "calc::";
+
+// Only the most recent records are kept for the debug panel
+const CAPACITY: usize = 1000;
+
+#[derive(Clone)]
+pub struct Entry {
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+// Forwards everything to env_logger and, while capturing, also keeps the
+// engine's records in memory
+struct Logger {
+    env: env_logger::Logger,
+    capture: AtomicBool,
+    entries: Mutex<VecDeque<Entry>>,
+}
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+fn is_engine(target: &str) -> bool {
+    target.starts_with(TARGET_PREFIX)
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.env.enabled(metadata) || (self.capture.load(Ordering::Relaxed) && is_engine(metadata.target()))
+    }
+
+    fn log(&self, record: &Record) {
+        self.env.log(record);
+        if self.capture.load(Ordering::Relaxed) && is_engine(record.target()) {
+            let mut entries = self.entries.lock().unwrap();
+            if entries.len() == CAPACITY {
+                entries.pop_front();
+            }
+            entries.push_back(Entry {
+                level: record.level(),
+                target: record.target().to_string(),
+                message: record.args().to_string(),
+            });
+        }
+    }
+
+    fn flush(&self) {
+        self.env.flush();
+    }
+}
+
+// Takes the place of env_logger::init; RUST_LOG still controls the terminal output
+pub fn init() {
+    let logger = LOGGER.get_or_init(|| Logger {
+        env: env_logger::Builder::from_default_env().build(),
+        capture: AtomicBool::new(false),
+        entries: Mutex::new(VecDeque::new()),
+    });
+    if log::set_logger(logger).is_ok() {
+        log::set_max_level(logger.env.filter());
+    }
+}
+
+pub fn set_capture(capture: bool) {
+    if let Some(logger) = LOGGER.get() {
+        logger.capture.store(capture, Ordering::Relaxed);
+        // Engine records are debug level; keep them free while nobody is looking
+        let level = if capture { logger.env.filter().max(LevelFilter::Debug) } else { logger.env.filter() };
+        log::set_max_level(level);
+    }
+}
+
+pub fn entries() -> Vec<Entry> {
+    LOGGER.get().map_or_else(Vec::new, |logger| logger.entries.lock().unwrap().iter().cloned().collect())
+}
+
+pub fn clear() {
+    if let Some(logger) = LOGGER.get() {
+        logger.entries.lock().unwrap().clear();
+    }
+}
+
+// Plain-text copy of the captured records, for pasting into a bug report
+pub fn export(entries: &[Entry]) -> String {
+    entries.iter()
+        .map(|entry| format!("{:5} {} {}", entry.level, entry.target, entry.message))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Floating panel with the captured records, newest at the bottom
+pub fn show_panel(ctx: &egui::Context, open: &mut bool) {
+    egui::Window::new("Depuración del motor")
+        .open(open)
+        .default_width(380.0)
+        .show(ctx, |ui| {
+            let entries = entries();
+            ui.horizontal(|ui| {
+                ui.label(format!("{} registros", entries.len()));
+                if ui.button("Copiar").clicked() {
+                    ui.ctx().copy_text(export(&entries));
+                }
+                if ui.button("Limpiar").clicked() {
+                    clear();
+                }
+            });
+            ui.separator();
+            let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+            egui::ScrollArea::both()
+                .stick_to_bottom(true)
+                .auto_shrink([false, false])
+                .show_rows(ui, row_height, entries.len(), |ui, rows| {
+                    for entry in &entries[rows] {
+                        let color = match entry.level {
+                            Level::Error => egui::Color32::RED,
+                            Level::Warn => egui::Color32::from_rgb(230, 150, 30),
+                            _ => ui.style().visuals.text_color(),
+                        };
+                        let target = entry.target.trim_start_matches(TARGET_PREFIX);
+                        let text = egui::RichText::new(format!("{:<6} {}", target, entry.message)).monospace().color(color);
+                        ui.add(egui::Label::new(text).extend());
+                    }
+                });
+        });
+}
@@ -0,0 +1,93 @@
+// Ridders' extrapolation of central differences: the step shrinks by this
+// factor each round, for at most ROUNDS rounds
+const SHRINK: f64 = 1.4;
+const ROUNDS: usize = 10;
+// Stop once the estimates get this much worse than the best one, as
+// rounding error starts to dominate
+const SAFE: f64 = 2.0;
+// First step, relative to x (absolute below 1), and how many times it may
+// be cut tenfold to stay where f is defined, as log's near 0
+const INITIAL_STEP: f64 = 0.1;
+const NARROWINGS: usize = 12;
+// One-sided slopes are compared at this step, relative to x, and a tenth
+// of it, and told apart when they differ by more than the tolerance,
+// relative to them
+const KINK_STEP: f64 = 1e-5;
+const KINK_TOLERANCE: f64 = 1e-3;
+
+// Whether the slopes from the left and from the right of x disagree, as at
+// the corner of abs(x). At a smooth point their gap shrinks with the step,
+// however steep f is; at a corner it stays
+fn kink(f: &impl Fn(f64) -> f64, x: f64) -> bool {
+    let gap = |h: f64| {
+        let left = (f(x) - f(x - h)) / h;
+        let right = (f(x + h) - f(x)) / h;
+        ((right - left).abs(), left.abs().max(right.abs()))
+    };
+    let h = KINK_STEP * x.abs().max(1.0);
+    let ((coarse, _), (fine, slope)) = (gap(h), gap(h / 10.0));
+    fine > KINK_TOLERANCE * slope.max(1.0) && fine > coarse / 2.0
+}
+
+// f'(x) from central differences (f(x+h) - f(x-h)) / 2h, with h shrinking
+// and the results extrapolated to h = 0. Keeps the estimate whose error
+// looks smallest, which adapts the step to f. None if f isn't finite around
+// x or has a corner there
+pub fn central(f: impl Fn(f64) -> f64, x: f64) -> Option<f64> {
+    let difference = |h: f64| {
+        let slope = (f(x + h) - f(x - h)) / (2.0 * h);
+        slope.is_finite().then_some(slope)
+    };
+    let mut h = INITIAL_STEP * x.abs().max(1.0);
+    // table[j][i]: the estimate with step i extrapolated j times
+    let mut table = [[0.0; ROUNDS]; ROUNDS];
+    table[0][0] = (0..NARROWINGS).find_map(|_| difference(h).or_else(|| {
+        h /= 10.0;
+        None
+    }))?;
+    let (mut best, mut error) = (table[0][0], f64::INFINITY);
+    for i in 1..ROUNDS {
+        h /= SHRINK;
+        table[0][i] = difference(h)?;
+        let mut factor = SHRINK * SHRINK;
+        for j in 1..=i {
+            table[j][i] = (table[j - 1][i] * factor - table[j - 1][i - 1]) / (factor - 1.0);
+            factor *= SHRINK * SHRINK;
+            let estimate = (table[j][i] - table[j - 1][i]).abs().max((table[j][i] - table[j - 1][i - 1]).abs());
+            if estimate <= error {
+                (best, error) = (table[j][i], estimate);
+            }
+        }
+        if (table[i][i] - table[i - 1][i - 1]).abs() >= SAFE * error {
+            break;
+        }
+    }
+    (!kink(&f, x)).then_some(best)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::central;
+    use crate::testing;
+
+    fn close(a: Option<f64>, b: f64) -> bool {
+        a.is_some_and(|a| testing::close_to(a, b, 1e-8))
+    }
+
+    #[test]
+    fn smooth() {
+        assert!(close(central(f64::sin, 1.0), 1f64.cos()));
+        assert!(close(central(|x| x * x, 0.0), 0.0));
+        assert!(close(central(f64::exp, 20.0), 20f64.exp()));
+        assert!(close(central(|x| (10.0 * x).sin(), 0.3), 10.0 * 3f64.cos()));
+    }
+
+    #[test]
+    fn corners() {
+        assert_eq!(central(f64::abs, 0.0), None);
+        assert_eq!(central(|x: f64| (x - 2.0).abs() + x * x, 2.0), None);
+        assert_eq!(central(|x: f64| x.max(0.0), 0.0), None);
+        // Beside the corner, not on it
+        assert!(close(central(f64::abs, 0.5), 1.0));
+    }
+}
@@ -0,0 +1,187 @@
+use std::ops::Range;
+
+use crate::ast::Expr;
+use crate::calculator::{Calculator, Function, Operator};
+use crate::quantity::Dimension;
+use crate::units::Category;
+use crate::value::Value;
+
+// Colors for the operands, in order
+const OPERAND_COLORS: [egui::Color32; 2] = [
+    egui::Color32::from_rgb(90, 170, 255),
+    egui::Color32::from_rgb(230, 150, 40),
+];
+
+// The first operation, innermost first, whose operands' dimensions don't fit
+pub struct Mismatch {
+    // The expression the ranges point into
+    pub expr: String,
+    // What was attempted, e.g. "la suma"
+    pub operation: String,
+    // The whole sub-expression, as a byte range of the input
+    pub at: Range<usize>,
+    // Each operand involved with its dimension written out
+    pub operands: Vec<(Range<usize>, String)>,
+}
+
+// "m (longitud)", or just the base units when no category has them
+pub fn describe(dim: Dimension) -> String {
+    if dim.is_none() {
+        return "sin unidades".to_string();
+    }
+    match Category::ALL.iter().find(|category| category.dimension() == dim) {
+        Some(category) => format!("{} ({})", dim.simplified(), category.name().to_lowercase()),
+        None => dim.simplified(),
+    }
+}
+
+// Spans of the tokens that become leaves of the tree, in input order: numbers,
+// names and function calls, but not operators such as mod
+fn leaf_spans(expr: &str) -> Vec<Range<usize>> {
+    Calculator::spans(expr).into_iter()
+        .filter(|span| {
+            let text = &expr[span.clone()];
+            text.starts_with(|c: char| c.is_ascii_alphanumeric() || matches!(c, '.' | '"' | '°' | 'µ'))
+                && !text.eq_ignore_ascii_case("mod")
+        })
+        .collect()
+}
+
+// Widens `range` until its parentheses match
+fn balance(expr: &str, mut range: Range<usize>) -> Range<usize> {
+    let depth = |range: &Range<usize>| expr[range.clone()].chars().fold(0i32, |depth, c| match c {
+        '(' => depth + 1,
+        ')' => depth - 1,
+        _ => depth,
+    });
+    while depth(&range) > 0 {
+        match expr[range.end..].find(')') {
+            Some(offset) => range.end += offset + 1,
+            None => break,
+        }
+    }
+    while depth(&range) < 0 {
+        match expr[..range.start].rfind('(') {
+            Some(start) => range.start = start,
+            None => break,
+        }
+    }
+    range
+}
+
+struct Checker<'a> {
+    calculator: &'a Calculator,
+    expr: &'a str,
+    leaves: std::vec::IntoIter<Range<usize>>,
+}
+
+impl Checker<'_> {
+    fn dimension(&self, range: &Range<usize>) -> Option<Dimension> {
+        match self.calculator.calculate(&self.expr[range.clone()]) {
+            Ok(Value::Quantity(q)) => Some(q.dim),
+            Ok(Value::Number(_) | Value::Integer(_)) => Some(Dimension::NONE),
+            _ => None,
+        }
+    }
+
+    fn mismatch(&self, operation: &str, at: Range<usize>, operands: &[(Range<usize>, Dimension)]) -> Mismatch {
+        Mismatch {
+            expr: self.expr.to_string(),
+            operation: operation.to_string(),
+            at,
+            operands: operands.iter().map(|(range, dim)| (range.clone(), describe(*dim))).collect(),
+        }
+    }
+
+    // Source range of `node`, checking its operations on the way back up
+    fn check(&mut self, node: &Expr) -> Result<Option<Range<usize>>, Mismatch> {
+        let range = match node {
+            Expr::Number(_) | Expr::Integer(_) | Expr::Imaginary(_) | Expr::Text(_)
+            | Expr::Variable(_) | Expr::Constant(_) | Expr::Unit(_) => self.leaves.next(),
+            Expr::Neg(inner) | Expr::Factorial(inner) => self.check(inner)?,
+            // Matrices have no units
+            Expr::List(_) | Expr::Braces(_) => return Ok(None),
+            Expr::Binary(op, left, right) => {
+                let (Some(left), Some(right)) = (self.check(left)?, self.check(right)?) else {
+                    return Ok(None);
+                };
+                let at = balance(self.expr, left.start..right.end);
+                let operation = match op {
+                    Operator::Add => Some("la suma"),
+                    Operator::Sub => Some("la resta"),
+                    Operator::Mod => Some("mod"),
+                    _ => None,
+                };
+                if let (Some(a), Some(b)) = (self.dimension(&left), self.dimension(&right)) {
+                    if let Some(operation) = operation.filter(|_| a != b) {
+                        return Err(self.mismatch(operation, at, &[(left, a), (right, b)]));
+                    }
+                    if *op == Operator::Pow && !b.is_none() {
+                        return Err(self.mismatch("el exponente", at, &[(right, b)]));
+                    }
+                }
+                Some(at)
+            },
+            Expr::Function(func, args) => {
+                let Some(name) = self.leaves.next() else {
+                    return Ok(None);
+                };
+                let mut ranges = Vec::new();
+                for arg in args {
+                    match self.check(arg)? {
+                        Some(range) => ranges.push(range),
+                        None => return Ok(None),
+                    }
+                }
+                let at = balance(self.expr, name.start..ranges.last().map_or(name.end, |range| range.end));
+                let keeps_units = matches!(func, Function::Abs | Function::Floor | Function::Ceil | Function::Round
+                    | Function::Sqrt | Function::Cbrt | Function::Convert | Function::Currency(..));
+                if let (false, [arg]) = (keeps_units, ranges.as_slice()) {
+                    if let Some(dim) = self.dimension(arg).filter(|dim| !dim.is_none()) {
+                        return Err(self.mismatch(&format!("{}()", func), at, &[(arg.clone(), dim)]));
+                    }
+                }
+                Some(at)
+            },
+        };
+        Ok(range)
+    }
+}
+
+// Where a unit-mode expression stops making dimensional sense, if it does
+pub fn explain(calculator: &Calculator, expr: &str) -> Option<Mismatch> {
+    let tree = calculator.ast(expr).ok()?;
+    let mut checker = Checker { calculator, expr, leaves: leaf_spans(expr).into_iter() };
+    checker.check(&tree).err()
+}
+
+// The expression with the clashing operands colored, then one line per operand
+pub fn show(ui: &mut egui::Ui, mismatch: &Mismatch) {
+    let font = egui::TextStyle::Monospace.resolve(ui.style());
+    let plain = egui::TextFormat { font_id: font.clone(), color: ui.visuals().weak_text_color(), ..Default::default() };
+    let mut job = egui::text::LayoutJob::default();
+    let mut position = 0;
+    let mut operands: Vec<_> = mismatch.operands.iter().zip(OPERAND_COLORS.iter().cycle()).collect();
+    operands.sort_by_key(|((range, _), _)| range.start);
+    for ((range, _), &color) in operands {
+        job.append(&mismatch.expr[position..range.start], 0.0, plain.clone());
+        job.append(&mismatch.expr[range.clone()], 0.0, egui::TextFormat {
+            font_id: font.clone(),
+            color,
+            underline: egui::Stroke::new(1.0, color),
+            ..Default::default()
+        });
+        position = range.end;
+    }
+    job.append(&mismatch.expr[position..], 0.0, plain);
+    ui.label(job);
+
+    for ((range, dimension), &color) in mismatch.operands.iter().zip(OPERAND_COLORS.iter().cycle()) {
+        ui.horizontal_wrapped(|ui| {
+            ui.spacing_mut().item_spacing.x = 4.0;
+            ui.label(egui::RichText::new(&mismatch.expr[range.clone()]).monospace().color(color));
+            ui.label(format!("es {}", dimension));
+        });
+    }
+    ui.small(format!("Incompatibles en {} «{}»", mismatch.operation, &mismatch.expr[mismatch.at.clone()]));
+}
@@ -0,0 +1,148 @@
+use std::f64::consts::PI;
+
+use crate::calculator::{Error, Function};
+use crate::special;
+
+// Most cases for which the binomial is worked out term by term
+const MAX_DIRECT: f64 = 1000.0;
+
+fn invalid(message: String) -> Error {
+    Error::InvalidOperation(message)
+}
+
+fn deviation(func: Function, sigma: f64) -> Result<f64, Error> {
+    if sigma > 0.0 { Ok(sigma) } else { Err(invalid(format!("{} requiere σ > 0", func))) }
+}
+
+fn probability(func: Function, p: f64) -> Result<f64, Error> {
+    if (0.0..=1.0).contains(&p) { Ok(p) } else { Err(invalid(format!("{} requiere una probabilidad entre 0 y 1", func))) }
+}
+
+fn whole(func: Function, n: f64) -> Result<f64, Error> {
+    if n >= 0.0 && n.fract() == 0.0 { Ok(n) } else { Err(invalid(format!("{} requiere un número entero no negativo de casos", func))) }
+}
+
+fn freedom(func: Function, nu: f64) -> Result<f64, Error> {
+    if nu > 0.0 { Ok(nu) } else { Err(invalid(format!("{} requiere grados de libertad > 0", func))) }
+}
+
+// P(X = k) for X ~ B(n, p). Up to MAX_DIRECT cases as C(n, k)·p^k·(1 - p)^(n - k),
+// which gives 0.24609375 for binompdf(10, 0.5, 5) to the last digit; past
+// that, or when the powers underflow, in logarithms
+fn binomial_pdf(n: f64, p: f64, k: f64) -> f64 {
+    if k < 0.0 || k > n || k.fract() != 0.0 {
+        return 0.0;
+    }
+    let logarithms = || (special::lgamma(n + 1.0) - special::lgamma(k + 1.0) - special::lgamma(n - k + 1.0) + k * p.ln() + (n - k) * (1.0 - p).ln()).exp();
+    match p {
+        0.0 => if k == 0.0 { 1.0 } else { 0.0 },
+        1.0 => if k == n { 1.0 } else { 0.0 },
+        _ if n <= MAX_DIRECT => {
+            // C(n, i) = C(n, i - 1)·(n - j + i)/i stays a whole number, exact below 2^53
+            let j = k.min(n - k);
+            let c = (1..=j as i32).fold(1.0, |c, i| c * (n - j + f64::from(i)) / f64::from(i));
+            let direct = c * p.powi(k as i32) * (1.0 - p).powi((n - k) as i32);
+            if direct.is_normal() { direct } else { logarithms() }
+        },
+        _ => logarithms(),
+    }
+}
+
+// normpdf(x, μ, σ), normcdf(x, μ, σ) = P(X ≤ x), binompdf(n, p, k),
+// binomcdf(n, p, k) = P(X ≤ k), poisspdf(λ, k), tcdf(t, ν) = P(T ≤ t) and
+// chi2cdf(x, ν) = P(X ≤ x), with arguments in the order calculators use
+pub fn apply(func: Function, args: &[f64]) -> Result<f64, Error> {
+    match (func, args) {
+        (Function::Normpdf, &[x, mu, sigma]) => {
+            let sigma = deviation(func, sigma)?;
+            Ok((-0.5 * ((x - mu) / sigma).powi(2)).exp() / (sigma * (2.0 * PI).sqrt()))
+        },
+        (Function::Normcdf, &[x, mu, sigma]) => {
+            let sigma = deviation(func, sigma)?;
+            Ok(0.5 * special::erfc(-(x - mu) / (sigma * std::f64::consts::SQRT_2)))
+        },
+        (Function::Binompdf, &[n, p, k]) => Ok(binomial_pdf(whole(func, n)?, probability(func, p)?, k)),
+        (Function::Binomcdf, &[n, p, k]) => {
+            let (n, p, k) = (whole(func, n)?, probability(func, p)?, k.floor());
+            Ok(if k < 0.0 {
+                0.0
+            } else if k >= n {
+                1.0
+            } else if p == 0.0 || p == 1.0 {
+                // Every case fails or every case succeeds
+                if p == 0.0 { 1.0 } else { 0.0 }
+            } else if n <= MAX_DIRECT {
+                // Term by term, so sums of exact terms stay exact
+                (0..=k as i32).map(|i| binomial_pdf(n, p, f64::from(i))).sum::<f64>().min(1.0)
+            } else {
+                // P(X ≤ k) = I_{1-p}(n - k, k + 1)
+                special::beta_i(n - k, k + 1.0, 1.0 - p)
+            })
+        },
+        (Function::Poisspdf, &[lambda, k]) => {
+            if lambda <= 0.0 {
+                return Err(invalid(format!("{} requiere λ > 0", func)));
+            }
+            if k < 0.0 || k.fract() != 0.0 {
+                return Ok(0.0);
+            }
+            Ok((k * lambda.ln() - lambda - special::lgamma(k + 1.0)).exp())
+        },
+        (Function::Tcdf, &[t, nu]) => {
+            let nu = freedom(func, nu)?;
+            // Both tails together are I_x(ν/2, 1/2) with x = ν / (ν + t²)
+            let tail = 0.5 * special::beta_i(nu / 2.0, 0.5, nu / (nu + t * t));
+            Ok(if t > 0.0 { 1.0 - tail } else { tail })
+        },
+        (Function::Chi2cdf, &[x, nu]) => Ok(special::gamma_p(freedom(func, nu)? / 2.0, x / 2.0)),
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    fn close(func: Function, args: &[f64], expected: f64) {
+        let value = apply(func, args).unwrap();
+        assert!(testing::relative(value, expected, 1e-12), "{}{:?} = {}, not {}", func, args, value, expected);
+    }
+
+    #[test]
+    fn binomial_exact_for_small_n() {
+        assert_eq!(apply(Function::Binompdf, &[10.0, 0.5, 5.0]).unwrap(), 0.24609375);
+        assert_eq!(apply(Function::Binomcdf, &[10.0, 0.5, 5.0]).unwrap(), 0.623046875);
+        assert_eq!(apply(Function::Binompdf, &[3.0, 0.5, 0.0]).unwrap(), 0.125);
+        close(Function::Binomcdf, &[10.0, 0.3, 2.0], 0.3827827864);
+    }
+
+    #[test]
+    fn binomial_large_n() {
+        // Past MAX_DIRECT, and where p^k alone underflows
+        close(Function::Binompdf, &[2000.0, 0.5, 1000.0], 0.01783901114585432);
+        close(Function::Binomcdf, &[2000.0, 0.5, 999.0], 0.49108049442707286);
+        let tiny = apply(Function::Binompdf, &[1000.0, 0.1, 300.0]).unwrap();
+        assert!(testing::relative(tiny, 5.063099384040454e-69, 1e-9), "{}", tiny);
+    }
+
+    #[test]
+    fn continuous() {
+        close(Function::Normcdf, &[0.0, 0.0, 1.0], 0.5);
+        close(Function::Normcdf, &[1.96, 0.0, 1.0], 0.9750021048517795);
+        close(Function::Normpdf, &[0.0, 0.0, 1.0], 0.3989422804014327);
+        close(Function::Tcdf, &[2.0, 10.0], 0.9633059826146297);
+        close(Function::Tcdf, &[0.0, 3.0], 0.5);
+        close(Function::Chi2cdf, &[3.84, 1.0], 0.9499564787512954);
+        close(Function::Chi2cdf, &[20.0, 10.0], 0.9707473119230389);
+    }
+
+    #[test]
+    fn domains() {
+        assert!(apply(Function::Normcdf, &[0.0, 0.0, -1.0]).is_err());
+        assert!(apply(Function::Binompdf, &[10.5, 0.5, 2.0]).is_err());
+        assert!(apply(Function::Binompdf, &[10.0, 1.5, 2.0]).is_err());
+        assert!(apply(Function::Poisspdf, &[0.0, 2.0]).is_err());
+        assert_eq!(apply(Function::Binompdf, &[10.0, 0.5, 2.5]).unwrap(), 0.0);
+    }
+}
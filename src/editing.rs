@@ -0,0 +1,154 @@
+use std::ops::Range;
+
+use crate::calculator::Calculator;
+
+// Token spans in char indices, which is what the text cursor uses
+fn char_spans(expr: &str) -> Vec<Range<usize>> {
+    let char_index = |byte: usize| expr[..byte].chars().count();
+    Calculator::spans(expr)
+        .into_iter()
+        .map(|span| char_index(span.start)..char_index(span.end))
+        .collect()
+}
+
+// Start of the token the cursor is in or right after
+pub fn previous_boundary(expr: &str, cursor: usize) -> usize {
+    char_spans(expr).iter().rev()
+        .map(|span| span.start)
+        .find(|&start| start < cursor)
+        .unwrap_or(0)
+}
+
+// End of the token the cursor is in or right before
+pub fn next_boundary(expr: &str, cursor: usize) -> usize {
+    char_spans(expr).iter()
+        .map(|span| span.end)
+        .find(|&end| end > cursor)
+        .unwrap_or_else(|| expr.chars().count())
+}
+
+// Removes the token before the cursor and returns the new cursor position
+pub fn delete_previous_token(expr: &mut String, cursor: usize) -> usize {
+    let start = previous_boundary(expr, cursor);
+    let byte = |index: usize| expr.char_indices().nth(index).map_or(expr.len(), |(byte, _)| byte);
+    let range = byte(start)..byte(cursor);
+    expr.replace_range(range, "");
+    start
+}
+
+// Puts `open` and `close` around the selected chars, so a function button
+// wraps the selection as its argument; with nothing selected it just inserts
+// `open` at the cursor. Returns the cursor position after the insertion.
+pub fn insert(expr: &mut String, selection: Range<usize>, open: &str, close: &str) -> usize {
+    let byte = |index: usize| expr.char_indices().nth(index).map_or(expr.len(), |(byte, _)| byte);
+    let range = byte(selection.start)..byte(selection.end);
+    let selected = &expr[range.clone()];
+    let close = if selected.is_empty() { "" } else { close };
+    let text = format!("{}{}{}", open, selected, close);
+    expr.replace_range(range, &text);
+    selection.start + text.chars().count()
+}
+
+// Characters that come up in text copied from documents and web pages, and
+// what the parser knows them as
+const REPLACEMENTS: &[(char, &str)] = &[
+    ('−', "-"), ('–', "-"), ('—', "-"),
+    ('×', "*"), ('·', "*"), ('⋅', "*"), ('∗', "*"),
+    ('÷', "/"), ('∕', "/"),
+    ('²', "^2"), ('³', "^3"),
+    ('“', "\""), ('”', "\""),
+    ('\u{a0}', " "), ('\u{202f}', " "), ('\u{2009}', " "), ('\t', " "), ('\n', " "), ('\r', " "),
+];
+
+// A number written with separators: 1,234,567.8 and 1.234.567,8 keep the
+// last separator as the decimal point. Commas alone are thousands when they
+// group by three and a decimal comma otherwise, but only outside parentheses,
+// brackets and braces, where they could separate arguments or elements.
+fn normalize_number(number: &str, nested: bool) -> String {
+    let last_dot = number.rfind('.');
+    let last_comma = number.rfind(',');
+    match (last_dot, last_comma) {
+        (Some(dot), Some(comma)) => {
+            let (decimal, grouping) = if comma > dot { (',', '.') } else { ('.', ',') };
+            number.chars().filter(|&c| c != grouping).map(|c| if c == decimal { '.' } else { c }).collect()
+        },
+        (None, Some(_)) if !nested => {
+            let groups: Vec<&str> = number.split(',').collect();
+            let thousands = groups[0].len() <= 3 && groups[1..].iter().all(|group| group.len() == 3);
+            if thousands {
+                number.replace(',', "")
+            } else if groups.len() == 2 {
+                number.replace(',', ".")
+            } else {
+                number.to_string()
+            }
+        },
+        _ => number.to_string(),
+    }
+}
+
+// Makes pasted text parseable: typographic operators become ASCII, line
+// breaks become spaces, number separators are resolved and a trailing '='
+// is dropped
+pub fn sanitize(text: &str) -> String {
+    let mut replaced = String::new();
+    for c in text.chars() {
+        match REPLACEMENTS.iter().find(|(from, _)| *from == c) {
+            Some((_, to)) => replaced.push_str(to),
+            None => replaced.push(c),
+        }
+    }
+    let replaced = replaced.trim().trim_end_matches('=').trim_end();
+
+    let chars: Vec<char> = replaced.chars().collect();
+    let mut sanitized = String::new();
+    let mut depth = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let starts_number = c.is_ascii_digit()
+            && !sanitized.ends_with(|c: char| c.is_ascii_alphanumeric() || c == '_');
+        if !starts_number {
+            match c {
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth -= 1,
+                _ => {},
+            }
+            sanitized.push(c);
+            i += 1;
+            continue;
+        }
+        // Digits with single separators between them
+        let mut end = i;
+        while end < chars.len() {
+            let separator = matches!(chars[end], '.' | ',') && chars.get(end + 1).is_some_and(char::is_ascii_digit);
+            if chars[end].is_ascii_digit() || separator {
+                end += 1;
+            } else {
+                break;
+            }
+        }
+        let number: String = chars[i..end].iter().collect();
+        sanitized.push_str(&normalize_number(&number, depth > 0));
+        i = end;
+    }
+    sanitized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sanitize;
+
+    #[test]
+    fn thousands_outside_brackets() {
+        assert_eq!(sanitize("1,234,567 ="), "1234567");
+        assert_eq!(sanitize("2,5 × 3"), "2.5 * 3");
+    }
+
+    #[test]
+    fn commas_inside_brackets_separate() {
+        assert_eq!(sanitize("max(1,234)"), "max(1,234)");
+        assert_eq!(sanitize("{1,234,567}"), "{1,234,567}");
+        assert_eq!(sanitize("[[1,234],[5,6]]"), "[[1,234],[5,6]]");
+    }
+}
@@ -0,0 +1,278 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::calculator::{Error, Function};
+use crate::complex::Complex;
+use crate::format::NumberFormat;
+use crate::matrix::Matrix;
+
+// QR steps allowed per eigenvalue before giving up
+const MAX_ITERATIONS: usize = 60;
+// Steps of inverse iteration for each eigenvector
+const REFINEMENTS: usize = 3;
+// Subdiagonal elements this small relative to their neighbours count as 0
+const NEGLIGIBLE: f64 = f64::EPSILON;
+// Parts smaller than this fraction of the matrix norm are rounding noise
+const NOISE: f64 = 1e-12;
+
+fn conj(z: Complex) -> Complex {
+    Complex::new(z.re, -z.im)
+}
+
+// Eigenvalues of a square matrix, each with an eigenvector of unit length;
+// a repeated eigenvalue appears as many times as its multiplicity, and a
+// defective one has fewer independent eigenvectors than that
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Eigen {
+    pub values: Vec<Complex>,
+    pub vectors: Vec<Option<Vec<Complex>>>,
+}
+
+impl Eigen {
+    pub fn of(matrix: &Matrix) -> Result<Eigen, Error> {
+        matrix.square(Function::Eig)?;
+        let n = matrix.rows;
+        let norm = (0..n).flat_map(|i| matrix.row(i).iter()).fold(0.0, |norm: f64, x| norm.max(x.abs())).max(f64::MIN_POSITIVE);
+        let a: Vec<Vec<Complex>> = (0..n).map(|i| matrix.row(i).iter().map(|&x| Complex::from(x)).collect()).collect();
+        let mut values = qr_iteration(hessenberg(matrix))?;
+        for value in values.iter_mut() {
+            *value = clean(*value, norm);
+        }
+        // Largest real part first, conjugate pairs together
+        values.sort_by(|a, b| b.re.total_cmp(&a.re).then(b.im.total_cmp(&a.im)));
+        let mut vectors: Vec<Option<Vec<Complex>>> = Vec::new();
+        for (k, &value) in values.iter().enumerate() {
+            // Repeats of an eigenvalue start elsewhere and stay orthogonal to
+            // the vectors already found for it, when there are more
+            let same: Vec<usize> = (0..k).filter(|&j| (values[j] - value).abs() <= 1e-8 * norm).collect();
+            let mut start = vec![Complex::from(1.0); n];
+            if !same.is_empty() {
+                start = (0..n).map(|i| Complex::from(if i == same.len() % n { 1.0 } else { 0.0 })).collect();
+            }
+            let previous: Vec<&Vec<Complex>> = same.iter().filter_map(|&j| vectors[j].as_ref()).collect();
+            vectors.push(inverse_iteration(&a, value, start, &previous, norm));
+        }
+        Ok(Eigen { values, vectors })
+    }
+
+    // λ = 3, -1 with `number` writing each part
+    pub fn format(&self, number: impl Fn(f64) -> String, separator: &str) -> String {
+        let values: Vec<String> = self.values.iter().map(|&z| element(z, &number)).collect();
+        format!("λ = {}", values.join(separator))
+    }
+}
+
+impl fmt::Display for Eigen {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.format(|x| x.to_string(), ", "))
+    }
+}
+
+// Real parts alone when there is no imaginary one
+fn element(z: Complex, number: impl Fn(f64) -> String) -> String {
+    if z.im == 0.0 { number(z.re) } else { z.format(number) }
+}
+
+fn clean(z: Complex, scale: f64) -> Complex {
+    let noise = NOISE * scale;
+    Complex::new(if z.re.abs() <= noise { 0.0 } else { z.re }, if z.im.abs() <= noise { 0.0 } else { z.im })
+}
+
+// A similar upper Hessenberg matrix, by Householder reflections, so each QR
+// step only has the subdiagonal to clear
+fn hessenberg(matrix: &Matrix) -> Vec<Vec<Complex>> {
+    let n = matrix.rows;
+    let mut h: Vec<Vec<f64>> = (0..n).map(|i| matrix.row(i).to_vec()).collect();
+    for k in 0..n.saturating_sub(2) {
+        let norm = (k + 1..n).map(|i| h[i][k].powi(2)).sum::<f64>().sqrt();
+        let alpha = if h[k + 1][k] > 0.0 { -norm } else { norm };
+        let mut v: Vec<f64> = (k + 1..n).map(|i| h[i][k]).collect();
+        v[0] -= alpha;
+        let vv: f64 = v.iter().map(|x| x * x).sum();
+        if vv == 0.0 {
+            continue;
+        }
+        let dots: Vec<f64> = (0..n).map(|j| (k + 1..n).map(|i| v[i - k - 1] * h[i][j]).sum()).collect();
+        for (row, v) in h[k + 1..].iter_mut().zip(&v) {
+            for (x, dot) in row.iter_mut().zip(&dots) {
+                *x -= 2.0 * v * dot / vv;
+            }
+        }
+        for row in h.iter_mut() {
+            let dot: f64 = (k + 1..n).map(|j| row[j] * v[j - k - 1]).sum();
+            for j in k + 1..n {
+                row[j] -= 2.0 * dot * v[j - k - 1] / vv;
+            }
+        }
+    }
+    h.into_iter().map(|row| row.into_iter().map(Complex::from).collect()).collect()
+}
+
+// The eigenvalue of [[a, b], [c, d]] nearest d, the Wilkinson shift
+fn shift(a: Complex, b: Complex, c: Complex, d: Complex) -> Complex {
+    let half = (a - d) * Complex::from(0.5);
+    let root = (half * half + b * c).sqrt();
+    let mean = (a + d) * Complex::from(0.5);
+    let (first, second) = (mean + root, mean - root);
+    if (first - d).abs() < (second - d).abs() { first } else { second }
+}
+
+// Shifted QR iteration in complex arithmetic, so complex pairs of a real
+// matrix split apart; an eigenvalue is taken off the bottom each time its
+// subdiagonal element vanishes
+fn qr_iteration(mut h: Vec<Vec<Complex>>) -> Result<Vec<Complex>, Error> {
+    let n = h.len();
+    let mut values = Vec::with_capacity(n);
+    let mut iterations = 0;
+    let mut hi = n;
+    while hi > 0 {
+        let last = hi - 1;
+        // Start of the unreduced block that ends at `last`
+        let mut lo = 0;
+        for k in (1..=last).rev() {
+            let scale = h[k - 1][k - 1].abs() + h[k][k].abs();
+            if h[k][k - 1].abs() <= NEGLIGIBLE * scale || h[k][k - 1].is_zero() {
+                h[k][k - 1] = Complex::from(0.0);
+                lo = k;
+                break;
+            }
+        }
+        if lo == last {
+            values.push(h[last][last]);
+            hi = last;
+            iterations = 0;
+            continue;
+        }
+        iterations += 1;
+        if iterations > MAX_ITERATIONS {
+            return Err(Error::InvalidOperation("eig no converge para esta matriz".to_string()));
+        }
+        // Now and then an unusual shift breaks a cycle
+        let mu = if iterations % 11 == 0 {
+            h[last][last] + Complex::from(h[last][last - 1].abs() * 0.75)
+        } else {
+            shift(h[last - 1][last - 1], h[last - 1][last], h[last][last - 1], h[last][last])
+        };
+        for (k, row) in h.iter_mut().enumerate().take(last + 1).skip(lo) {
+            row[k] = row[k] - mu;
+        }
+        // H - μI = QR by Givens rotations, then RQ + μI
+        let mut rotations = Vec::with_capacity(last - lo);
+        for k in lo..last {
+            let (x, y) = (h[k][k], h[k + 1][k]);
+            let r = (x.abs().powi(2) + y.abs().powi(2)).sqrt();
+            let (c, s) = if r == 0.0 { (Complex::from(1.0), Complex::from(0.0)) } else { (x / Complex::from(r), y / Complex::from(r)) };
+            let (top, bottom) = h.split_at_mut(k + 1);
+            for (p, q) in top[k][k..=last].iter_mut().zip(&mut bottom[0][k..=last]) {
+                (*p, *q) = (conj(c) * *p + conj(s) * *q, c * *q - s * *p);
+            }
+            rotations.push((c, s));
+        }
+        for (k, &(c, s)) in (lo..last).zip(&rotations) {
+            for row in h.iter_mut().take((k + 2).min(last) + 1).skip(lo) {
+                let (p, q) = (row[k], row[k + 1]);
+                row[k] = p * c + q * s;
+                row[k + 1] = q * conj(c) - p * conj(s);
+            }
+        }
+        for (k, row) in h.iter_mut().enumerate().take(last + 1).skip(lo) {
+            row[k] = row[k] + mu;
+        }
+    }
+    Ok(values)
+}
+
+// (A - λI)x = b by Gaussian elimination with partial pivoting; a zero pivot,
+// which an exact eigenvalue gives, is replaced by a tiny one
+fn solve_shifted(a: &[Vec<Complex>], value: Complex, b: &[Complex], norm: f64) -> Vec<Complex> {
+    let n = a.len();
+    let mut m: Vec<Vec<Complex>> = a.to_vec();
+    for (i, row) in m.iter_mut().enumerate() {
+        row[i] = row[i] - value;
+    }
+    let mut x = b.to_vec();
+    let tiny = f64::EPSILON * norm;
+    for k in 0..n {
+        let pivot = (k..n).max_by(|&p, &q| m[p][k].abs().total_cmp(&m[q][k].abs())).unwrap();
+        m.swap(k, pivot);
+        x.swap(k, pivot);
+        if m[k][k].abs() < tiny {
+            m[k][k] = Complex::from(tiny);
+        }
+        let (top, bottom) = m.split_at_mut(k + 1);
+        let (done, rest) = x.split_at_mut(k + 1);
+        for (row, x) in bottom.iter_mut().zip(rest) {
+            let factor = row[k] / top[k][k];
+            for (a, &b) in row[k..].iter_mut().zip(&top[k][k..]) {
+                *a = *a - factor * b;
+            }
+            *x = *x - factor * done[k];
+        }
+    }
+    for k in (0..n).rev() {
+        let sum = (k + 1..n).fold(x[k], |sum, j| sum - m[k][j] * x[j]);
+        x[k] = sum / m[k][k];
+    }
+    x
+}
+
+fn length(x: &[Complex]) -> f64 {
+    x.iter().map(|z| z.abs().powi(2)).sum::<f64>().sqrt()
+}
+
+// An eigenvector by inverse iteration, of unit length with its largest
+// element real and positive so real eigenvectors come out real; None when
+// all there is lies along the vectors already found for this eigenvalue
+fn inverse_iteration(a: &[Vec<Complex>], value: Complex, start: Vec<Complex>, previous: &[&Vec<Complex>], norm: f64) -> Option<Vec<Complex>> {
+    let mut x = start;
+    for _ in 0..REFINEMENTS {
+        x = solve_shifted(a, value, &x, norm);
+        let before = length(&x);
+        for v in previous {
+            let dot = v.iter().zip(&x).fold(Complex::from(0.0), |dot, (&v, &x)| dot + conj(v) * x);
+            for (x, &v) in x.iter_mut().zip(v.iter()) {
+                *x = *x - dot * v;
+            }
+        }
+        let length = length(&x);
+        if length <= 1e-8 * before || !length.is_finite() {
+            return None;
+        }
+        for z in x.iter_mut() {
+            *z = *z / Complex::from(length);
+        }
+    }
+    let largest = x.iter().copied().max_by(|p, q| p.abs().total_cmp(&q.abs())).unwrap_or(Complex::from(1.0));
+    let phase = if largest.is_zero() { Complex::from(1.0) } else { conj(largest) / Complex::from(largest.abs()) };
+    Some(x.into_iter().map(|z| clean(z * phase, 1.0)).collect())
+}
+
+// The eigenvalues with their eigenvectors, one per line
+pub fn show(ui: &mut egui::Ui, eigen: &Eigen, format: &NumberFormat, size: f32) {
+    let number = |x: f64| format.number(x);
+    egui::Frame::none()
+        .stroke(egui::Stroke::new(1.0, egui::Color32::GREEN))
+        .inner_margin(egui::Margin::symmetric(8.0, 4.0))
+        .show(ui, |ui| {
+            egui::Grid::new("eigen").num_columns(2).spacing([24.0, 4.0]).show(ui, |ui| {
+                ui.label(egui::RichText::new("Valor propio").small());
+                ui.label(egui::RichText::new("Vector propio").small());
+                ui.end_row();
+                for (value, vector) in eigen.values.iter().zip(&eigen.vectors) {
+                    ui.label(egui::RichText::new(format!("λ = {}", element(*value, number))).monospace().size(size).color(egui::Color32::GREEN));
+                    match vector {
+                        Some(vector) => {
+                            let elements: Vec<String> = vector.iter().map(|&z| element(z, number)).collect();
+                            ui.label(egui::RichText::new(format!("[{}]", elements.join(", "))).monospace().size(size).color(egui::Color32::GREEN));
+                        },
+                        // A defective matrix: no more independent eigenvectors
+                        None => {
+                            ui.label(egui::RichText::new("(ninguno más independiente)").size(size).weak());
+                        },
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+}
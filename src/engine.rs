@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+
+use crate::calculator::{AngleMode, Calculator, Juxtaposition};
+use crate::format::NumberFormat;
+use crate::history::Entry;
+
+// The evaluation engine goes up with the app
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// What a saved result depended on besides its expression: the engine that
+// computed it and the settings that change the number or how it's rounded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Conditions {
+    pub version: String,
+    pub angle: AngleMode,
+    // Significant digits (0 for all) and decimals the result was shown with
+    pub digits: usize,
+    pub decimals: usize,
+    pub integer_mode: bool,
+    pub real_only: bool,
+    pub juxtaposition: Juxtaposition,
+}
+
+fn yes_no(on: bool) -> &'static str {
+    if on { "sí" } else { "no" }
+}
+
+impl Conditions {
+    pub fn current(calculator: &Calculator, format: &NumberFormat) -> Conditions {
+        Conditions {
+            version: VERSION.to_string(),
+            angle: calculator.angle,
+            digits: format.digits,
+            decimals: format.decimals,
+            integer_mode: calculator.integer_mode,
+            real_only: calculator.real_only,
+            juxtaposition: calculator.juxtaposition,
+        }
+    }
+
+    // One line for the history's tooltips
+    pub fn summary(&self) -> String {
+        let digits = if self.digits == 0 { "todas las cifras".to_string() } else { format!("{} cifras", self.digits) };
+        format!("Motor {} · {} · {}", self.version, self.angle.name(), digits)
+    }
+
+    // What is different in `now`, one description per setting
+    pub fn changes(&self, now: &Conditions) -> Vec<String> {
+        let mut changes = Vec::new();
+        if self.version != now.version {
+            changes.push(format!("motor {} → {}", self.version, now.version));
+        }
+        if self.angle != now.angle {
+            changes.push(format!("ángulos en {} → {}", self.angle.name(), now.angle.name()));
+        }
+        if self.digits != now.digits {
+            changes.push(format!("cifras significativas {} → {}", self.digits, now.digits));
+        }
+        if self.decimals != now.decimals {
+            changes.push(format!("decimales {} → {}", self.decimals, now.decimals));
+        }
+        if self.integer_mode != now.integer_mode {
+            changes.push(format!("enteros exactos: {} → {}", yes_no(self.integer_mode), yes_no(now.integer_mode)));
+        }
+        if self.real_only != now.real_only {
+            changes.push(format!("solo reales: {} → {}", yes_no(self.real_only), yes_no(now.real_only)));
+        }
+        if self.juxtaposition != now.juxtaposition {
+            changes.push(format!("multiplicación implícita: {}", now.juxtaposition.name()));
+        }
+        changes
+    }
+}
+
+// An entry whose result comes out different today
+pub struct Changed {
+    pub expression: String,
+    pub before: String,
+    pub after: String,
+}
+
+// The warning shown after reopening results saved under other conditions,
+// with what re-evaluating them gave once asked for
+pub struct Reproducibility {
+    pub changes: Vec<String>,
+    rechecked: Option<(usize, Vec<Changed>)>,
+}
+
+impl Reproducibility {
+    // None when `saved` is what would be used now, or unknown
+    pub fn check(saved: Option<&Conditions>, now: &Conditions) -> Option<Reproducibility> {
+        let changes = saved?.changes(now);
+        (!changes.is_empty()).then_some(Reproducibility { changes, rechecked: None })
+    }
+
+    // Evaluates the entries again in order, so their assignments take effect
+    // as they did, on a copy of the calculator
+    fn recheck(calculator: &Calculator, format: &NumberFormat, entries: &[Entry]) -> Vec<Changed> {
+        let mut calculator = calculator.clone();
+        entries.iter().filter_map(|entry| {
+            let after = match calculator.execute(&entry.expression) {
+                Ok((value, _)) => format.value(&value),
+                Err(e) => e.to_string(),
+            };
+            (after != entry.result).then(|| Changed { expression: entry.expression.clone(), before: entry.result.clone(), after })
+        }).collect()
+    }
+
+    // False once dismissed
+    pub fn show(&mut self, ui: &mut egui::Ui, calculator: &Calculator, format: &NumberFormat, entries: &[Entry]) -> bool {
+        let mut keep = true;
+        let warning = egui::Color32::from_rgb(230, 150, 30);
+        ui.horizontal_wrapped(|ui| {
+            ui.colored_label(warning, format!("⚠ Resultados guardados con otros ajustes: {}", self.changes.join(", ")));
+            if ui.small_button("Recalcular").on_hover_text("Evaluar el historial otra vez con los ajustes actuales").clicked() {
+                self.rechecked = Some((entries.len(), Reproducibility::recheck(calculator, format, entries)));
+            }
+            if ui.small_button("✖").on_hover_text("Descartar el aviso").clicked() {
+                keep = false;
+            }
+        });
+        if let Some((total, changed)) = &self.rechecked {
+            if changed.is_empty() {
+                ui.small(format!("Los {} resultados del historial se mantienen", total));
+            } else {
+                ui.collapsing(format!("{} de {} resultados cambian", changed.len(), total), |ui| {
+                    egui::Grid::new("rechecked").num_columns(3).striped(true).show(ui, |ui| {
+                        for change in changed {
+                            ui.monospace(&change.expression);
+                            ui.monospace(&change.before);
+                            ui.monospace(format!("→ {}", change.after));
+                            ui.end_row();
+                        }
+                    });
+                });
+            }
+        }
+        keep
+    }
+}
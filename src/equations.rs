@@ -0,0 +1,97 @@
+use crate::calculator::Calculator;
+use crate::format::NumberFormat;
+use crate::value::Value;
+
+// A root with what the two sides of the equation differ by there
+struct Solution {
+    root: f64,
+    residual: f64,
+}
+
+// State of the solver tab: an equation in one unknown, f(x) = g(x) or just
+// f(x) for f(x) = 0, solved from a starting guess with solve()'s method
+pub struct Equations {
+    equation: String,
+    variable: String,
+    guess: String,
+    result: Option<Result<Solution, String>>,
+}
+
+impl Default for Equations {
+    fn default() -> Self {
+        Equations { equation: String::new(), variable: "x".to_string(), guess: String::new(), result: None }
+    }
+}
+
+impl Equations {
+    // The equation as one expression that is 0 at its roots
+    fn difference(&self) -> String {
+        match self.equation.split_once('=') {
+            Some((left, right)) => format!("({}) - ({})", left.trim(), right.trim()),
+            None => self.equation.trim().to_string(),
+        }
+    }
+
+    fn solve(&self, calculator: &Calculator) -> Result<Solution, String> {
+        let variable = self.variable.trim();
+        if self.equation.trim().is_empty() {
+            return Err("Escribe una ecuación".to_string());
+        }
+        let guess = match self.guess.trim() {
+            "" => 1.0,
+            guess => calculator.calculate(guess).and_then(Value::real).map_err(|e| e.to_string())?,
+        };
+        let difference = self.difference();
+        let root = calculator.solve(&difference, variable, guess).and_then(Value::real).map_err(|e| e.to_string())?;
+        let compiled = calculator.compile(&difference).map_err(|e| e.to_string())?;
+        let residual = calculator.evaluate_at(&compiled, variable, root).and_then(Value::real).map_err(|e| e.to_string())?;
+        Ok(Solution { root, residual })
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, calculator: &Calculator, format: &NumberFormat) {
+        ui.heading("Resolver");
+        ui.small("Una ecuación en una incógnita, f(x) = g(x) o f(x) para f(x) = 0; se busca la raíz más cercana al valor inicial por el método de Newton.");
+        ui.add_space(10.0);
+        let mut enter = false;
+        egui::Grid::new("equation").num_columns(2).show(ui, |ui| {
+            ui.label("Ecuación");
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.equation)
+                    .font(egui::TextStyle::Monospace)
+                    .desired_width(320.0)
+                    .hint_text("cos(x) = x")
+            );
+            enter |= response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            ui.end_row();
+            ui.label("Incógnita");
+            ui.add(egui::TextEdit::singleline(&mut self.variable).font(egui::TextStyle::Monospace).desired_width(60.0));
+            ui.end_row();
+            ui.label("Valor inicial");
+            let response = ui.add(egui::TextEdit::singleline(&mut self.guess).hint_text("1").desired_width(160.0));
+            enter |= response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            ui.end_row();
+        });
+        ui.add_space(10.0);
+
+        let solve = egui::Button::new("Resolver").fill(egui::Color32::from_rgb(0, 150, 0));
+        if ui.add(solve).clicked() || enter {
+            self.result = Some(self.solve(calculator));
+        }
+        match &self.result {
+            Some(Ok(solution)) => {
+                ui.horizontal(|ui| {
+                    let text = format!("{} = {}", self.variable.trim(), format.number(solution.root));
+                    ui.label(egui::RichText::new(&text).size(20.0).color(egui::Color32::GREEN));
+                    if ui.small_button("Copiar").clicked() {
+                        ui.ctx().copy_text(format.number(solution.root));
+                    }
+                });
+                ui.small(format!("Diferencia entre los dos lados ahí: {}", format.number(solution.residual)));
+            },
+            Some(Err(error)) => {
+                ui.colored_label(egui::Color32::RED, error);
+            },
+            None => {},
+        }
+    }
+}
@@ -0,0 +1,419 @@
+use std::fmt;
+
+use num_traits::ToPrimitive;
+
+use crate::ast::Expr;
+use crate::calculator::{AngleMode, Calculator, Function, Operator};
+use crate::value::Value;
+
+// Largest power an exact form is raised to by repeated multiplication
+const MAX_POWER: i64 = 64;
+// Decimals whose fraction needs a larger denominator are left as decimals
+const MAX_DENOMINATOR: i64 = 1_000_000;
+// Roots of larger numbers aren't simplified: finding their square factors
+// would take too long, and the form would be no use anyway
+const MAX_RADICAND: i64 = 1_000_000_000_000;
+
+// On magnitudes, so i64::MIN doesn't overflow; None when the divisor is 2^63
+fn gcd(a: i64, b: i64) -> Option<i64> {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a.try_into().ok()
+}
+
+// Reduced fraction with a positive denominator; None on overflow
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Rational {
+    numerator: i64,
+    denominator: i64,
+}
+
+impl Rational {
+    fn new(numerator: i64, denominator: i64) -> Option<Rational> {
+        if denominator == 0 {
+            return None;
+        }
+        let divisor = gcd(numerator, denominator)?;
+        let sign = denominator.signum();
+        Some(Rational { numerator: (numerator / divisor).checked_mul(sign)?, denominator: (denominator / divisor).checked_mul(sign)? })
+    }
+
+    fn integer(n: i64) -> Rational {
+        Rational { numerator: n, denominator: 1 }
+    }
+
+    // The fraction a decimal was written as, 0.25 → 1/4
+    fn from_f64(x: f64) -> Option<Rational> {
+        if x.fract() == 0.0 {
+            return x.to_i64().map(Rational::integer);
+        }
+        let mut denominator = 1i64;
+        while denominator <= MAX_DENOMINATOR {
+            let numerator = x * denominator as f64;
+            if numerator.fract() == 0.0 {
+                return Rational::new(numerator.to_i64()?, denominator);
+            }
+            denominator *= 10;
+        }
+        None
+    }
+
+    fn add(self, other: Rational) -> Option<Rational> {
+        let numerator = self.numerator.checked_mul(other.denominator)?.checked_add(other.numerator.checked_mul(self.denominator)?)?;
+        Rational::new(numerator, self.denominator.checked_mul(other.denominator)?)
+    }
+
+    fn mul(self, other: Rational) -> Option<Rational> {
+        Rational::new(self.numerator.checked_mul(other.numerator)?, self.denominator.checked_mul(other.denominator)?)
+    }
+
+    fn inverse(self) -> Option<Rational> {
+        Rational::new(self.denominator, self.numerator)
+    }
+
+    fn neg(self) -> Option<Rational> {
+        Some(Rational { numerator: self.numerator.checked_neg()?, ..self })
+    }
+
+    fn value(self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+}
+
+// k and r with n = k²·r and r free of squares, so √n = k√r; None past
+// MAX_RADICAND
+fn square_free(n: i64) -> Option<(i64, i64)> {
+    if n > MAX_RADICAND {
+        return None;
+    }
+    let (mut k, mut r) = (1, n);
+    let mut d = 2;
+    while d * d <= r {
+        while r % (d * d) == 0 {
+            r /= d * d;
+            k *= d;
+        }
+        d += 1;
+    }
+    Some((k, r))
+}
+
+// The irrational part of a term
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Basis {
+    One,
+    // √n with n > 1 and free of squares
+    Sqrt(i64),
+    Pi,
+}
+
+impl Basis {
+    fn value(self) -> f64 {
+        match self {
+            Basis::One => 1.0,
+            Basis::Sqrt(n) => (n as f64).sqrt(),
+            Basis::Pi => std::f64::consts::PI,
+        }
+    }
+
+    // The product of two bases as a rational factor and a basis
+    fn mul(self, other: Basis) -> Option<(Rational, Basis)> {
+        match (self, other) {
+            (Basis::One, basis) | (basis, Basis::One) => Some((Rational::integer(1), basis)),
+            (Basis::Sqrt(a), Basis::Sqrt(b)) => {
+                let (k, r) = square_free(a.checked_mul(b)?)?;
+                Some((Rational::integer(k), if r == 1 { Basis::One } else { Basis::Sqrt(r) }))
+            },
+            _ => None,
+        }
+    }
+}
+
+// A sum of rational multiples of 1, square roots and π, such as 1 + 2√2 or
+// 5π/6, kept exact through the operations that allow it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exact {
+    // Sorted by basis, without zero coefficients
+    terms: Vec<(Basis, Rational)>,
+}
+
+impl Exact {
+    fn term(basis: Basis, coefficient: Rational) -> Exact {
+        let terms = if coefficient.numerator == 0 { Vec::new() } else { vec![(basis, coefficient)] };
+        Exact { terms }
+    }
+
+    fn rational(q: Rational) -> Exact {
+        Exact::term(Basis::One, q)
+    }
+
+    // The fraction when there are no roots or π
+    fn as_rational(&self) -> Option<Rational> {
+        match self.terms.as_slice() {
+            [] => Some(Rational::integer(0)),
+            [(Basis::One, q)] => Some(*q),
+            _ => None,
+        }
+    }
+
+    // A rational multiple of π, including 0
+    fn pi_multiple(&self) -> Option<Rational> {
+        match self.terms.as_slice() {
+            [] => Some(Rational::integer(0)),
+            [(Basis::Pi, q)] => Some(*q),
+            _ => None,
+        }
+    }
+
+    pub fn value(&self) -> f64 {
+        self.terms.iter().map(|(basis, q)| q.value() * basis.value()).sum()
+    }
+
+    // Worth showing next to the decimal result: anything but a whole number
+    pub fn is_informative(&self) -> bool {
+        self.as_rational().is_none_or(|q| q.denominator != 1)
+    }
+
+    fn add(&self, other: &Exact) -> Option<Exact> {
+        let mut terms = self.terms.clone();
+        for (basis, q) in &other.terms {
+            match terms.iter_mut().find(|(b, _)| b == basis) {
+                Some((_, sum)) => *sum = sum.add(*q)?,
+                None => terms.push((*basis, *q)),
+            }
+        }
+        terms.retain(|(_, q)| q.numerator != 0);
+        terms.sort_by_key(|(basis, _)| *basis);
+        Some(Exact { terms })
+    }
+
+    fn neg(&self) -> Option<Exact> {
+        let terms = self.terms.iter().map(|(basis, q)| Some((*basis, q.neg()?))).collect::<Option<_>>()?;
+        Some(Exact { terms })
+    }
+
+    fn mul(&self, other: &Exact) -> Option<Exact> {
+        let mut product = Exact { terms: Vec::new() };
+        for (a, p) in &self.terms {
+            for (b, q) in &other.terms {
+                let (factor, basis) = a.mul(*b)?;
+                product = product.add(&Exact::term(basis, p.mul(*q)?.mul(factor)?))?;
+            }
+        }
+        Some(product)
+    }
+
+    // Only by a single term: 1/(q√n) = √n/(qn), and π multiples by π
+    fn div(&self, other: &Exact) -> Option<Exact> {
+        let [(basis, q)] = other.terms.as_slice() else {
+            return None;
+        };
+        match basis {
+            Basis::One => self.mul(&Exact::rational(q.inverse()?)),
+            Basis::Sqrt(n) => self.mul(&Exact::term(Basis::Sqrt(*n), q.mul(Rational::integer(*n))?.inverse()?)),
+            Basis::Pi => Some(Exact::rational(self.pi_multiple()?.mul(q.inverse()?)?)),
+        }
+    }
+
+    fn pow(&self, exponent: &Exact) -> Option<Exact> {
+        let exponent = exponent.as_rational()?;
+        if exponent == Rational::new(1, 2)? {
+            return self.sqrt();
+        }
+        if exponent.denominator != 1 || exponent.numerator.abs() > MAX_POWER {
+            return None;
+        }
+        let mut power = Exact::rational(Rational::integer(1));
+        for _ in 0..exponent.numerator.abs() {
+            power = power.mul(self)?;
+        }
+        if exponent.numerator < 0 { Exact::rational(Rational::integer(1)).div(&power) } else { Some(power) }
+    }
+
+    // √(p/q) = √(pq)/q, for non-negative fractions
+    fn sqrt(&self) -> Option<Exact> {
+        let q = self.as_rational().filter(|q| q.numerator >= 0)?;
+        let (k, r) = square_free(q.numerator.checked_mul(q.denominator)?)?;
+        let basis = if r == 1 { Basis::One } else { Basis::Sqrt(r) };
+        Some(Exact::term(basis, Rational::new(k, q.denominator)?))
+    }
+
+    // sin at a multiple of 15°, given in those steps; exact at multiples of
+    // 30° and 45°
+    fn sin_steps(steps: i64) -> Option<Exact> {
+        let steps = steps.rem_euclid(24);
+        let (quadrant_steps, negative) = match steps {
+            0..=6 => (steps, false),
+            7..=12 => (12 - steps, false),
+            13..=18 => (steps - 12, true),
+            _ => (24 - steps, true),
+        };
+        let value = match quadrant_steps {
+            0 => Exact::rational(Rational::integer(0)),
+            2 => Exact::rational(Rational::new(1, 2)?),
+            3 => Exact::term(Basis::Sqrt(2), Rational::new(1, 2)?),
+            4 => Exact::term(Basis::Sqrt(3), Rational::new(1, 2)?),
+            6 => Exact::rational(Rational::integer(1)),
+            _ => return None,
+        };
+        if negative { value.neg() } else { Some(value) }
+    }
+
+    // The angle in steps of 15°, when it is a whole number of them
+    fn angle_steps(&self, angle: AngleMode) -> Option<i64> {
+        let steps = match angle {
+            AngleMode::Radians => self.pi_multiple()?.mul(Rational::integer(12))?,
+            AngleMode::Degrees => self.as_rational()?.mul(Rational::new(1, 15)?)?,
+        };
+        (steps.denominator == 1).then_some(steps.numerator)
+    }
+
+    fn function(func: Function, arg: &Exact, angle: AngleMode) -> Option<Exact> {
+        match func {
+            Function::Sqrt => arg.sqrt(),
+            Function::Sin => Exact::sin_steps(arg.angle_steps(angle)?),
+            Function::Cos => Exact::sin_steps(arg.angle_steps(angle)? + 6),
+            Function::Tan => {
+                let steps = arg.angle_steps(angle)?;
+                Exact::sin_steps(steps)?.div(&Exact::sin_steps(steps + 6)?)
+            },
+            Function::Abs if arg.value() < 0.0 => arg.neg(),
+            Function::Abs => Some(arg.clone()),
+            _ => None,
+        }
+    }
+}
+
+// The coefficient in front of a basis, 5π/6 or 3√2/4, without its sign
+fn write_term(f: &mut fmt::Formatter, basis: Basis, q: Rational) -> fmt::Result {
+    let numerator = q.numerator.abs();
+    let symbol = match basis {
+        Basis::One => return if q.denominator == 1 { write!(f, "{}", numerator) } else { write!(f, "{}/{}", numerator, q.denominator) },
+        Basis::Sqrt(n) => format!("√{}", n),
+        Basis::Pi => "π".to_string(),
+    };
+    if numerator != 1 {
+        write!(f, "{}", numerator)?;
+    }
+    write!(f, "{}", symbol)?;
+    if q.denominator != 1 {
+        write!(f, "/{}", q.denominator)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for Exact {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.terms.is_empty() {
+            return write!(f, "0");
+        }
+        for (i, (basis, q)) in self.terms.iter().enumerate() {
+            match (i, q.numerator < 0) {
+                (0, true) => write!(f, "-")?,
+                (0, false) => {},
+                (_, true) => write!(f, " - ")?,
+                (_, false) => write!(f, " + ")?,
+            }
+            write_term(f, *basis, *q)?;
+        }
+        Ok(())
+    }
+}
+
+fn exact_value(value: &Value) -> Option<Exact> {
+    match value {
+        Value::Number(x) => Some(Exact::rational(Rational::from_f64(*x)?)),
+        Value::Integer(n) => Some(Exact::rational(Rational::integer(n.to_i64()?))),
+        _ => None,
+    }
+}
+
+struct Evaluator<'a> {
+    calculator: &'a Calculator,
+    angle: AngleMode,
+}
+
+impl Evaluator<'_> {
+    fn evaluate(&self, expr: &Expr) -> Option<Exact> {
+        match expr {
+            Expr::Number(x) => exact_value(&Value::Number(*x)),
+            Expr::Integer(n) => Some(Exact::rational(Rational::integer(n.to_i64()?))),
+            Expr::Constant(constant) => match constant.name {
+                "pi" => Some(Exact::term(Basis::Pi, Rational::integer(1))),
+                "tau" => Some(Exact::term(Basis::Pi, Rational::integer(2))),
+                _ => None,
+            },
+            Expr::Variable(name) => exact_value(self.calculator.variables.get(name)?),
+            Expr::Neg(inner) => self.evaluate(inner)?.neg(),
+            Expr::Binary(op, left, right) => {
+                let (left, right) = (self.evaluate(left)?, self.evaluate(right)?);
+                match op {
+                    Operator::Add => left.add(&right),
+                    Operator::Sub => left.add(&right.neg()?),
+                    Operator::Mul | Operator::Juxtapose => left.mul(&right),
+                    Operator::Div => left.div(&right),
+                    Operator::Pow => left.pow(&right),
+                    _ => None,
+                }
+            },
+            Expr::Function(func, args) => match args.as_slice() {
+                [arg] => Exact::function(*func, &self.evaluate(arg)?, self.angle),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+// The exact form of `expr` when every step keeps one, checked against the
+// decimal `result` so a form that disagrees is never shown
+pub fn exact(calculator: &Calculator, angle: AngleMode, expr: &Expr, result: &Value) -> Option<Exact> {
+    let exact = Evaluator { calculator, angle }.evaluate(expr)?;
+    let decimal = match result {
+        Value::Number(x) => *x,
+        Value::Integer(n) => n.to_f64()?,
+        _ => return None,
+    };
+    let agrees = (exact.value() - decimal).abs() <= 1e-9 * decimal.abs().max(1.0);
+    agrees.then_some(exact)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::calculator::Calculator;
+    use crate::value::Value;
+
+    fn exact(calculator: &Calculator, expr: &str) -> Option<String> {
+        let result = calculator.calculate(expr).ok()?;
+        calculator.exact(expr, &result).map(|exact| exact.to_string())
+    }
+
+    #[test]
+    fn forms() {
+        let calculator = Calculator::default();
+        assert_eq!(exact(&calculator, "sqrt(8)").as_deref(), Some("2√2"));
+        assert_eq!(exact(&calculator, "pi/2 + pi/3").as_deref(), Some("5π/6"));
+        assert_eq!(exact(&calculator, "1/sqrt(2) - 1").as_deref(), Some("-1 + √2/2"));
+        assert_eq!(exact(&calculator, "sin(pi/4)").as_deref(), Some("√2/2"));
+    }
+
+    // i64::MIN has no negative and 2^63 no i64, so these have no exact form
+    #[test]
+    fn overflow() {
+        let mut calculator = Calculator::default();
+        calculator.assign("x", Value::Number(-(2f64.powi(63)))).unwrap();
+        for expr in ["-x", "0 - x", "x/(-1)", "x*(-1)", "abs(x)", "x/x"] {
+            let result = exact(&calculator, expr);
+            assert!(result.is_none() || result.as_deref() == Some("1"), "{}: {:?}", expr, result);
+        }
+    }
+
+    #[test]
+    fn large_radicands() {
+        let calculator = Calculator::default();
+        assert_eq!(exact(&calculator, "sqrt(9007199254740.881)"), None);
+        assert_eq!(exact(&calculator, "sqrt(999999999999)").as_deref(), Some("3√111111111111"));
+    }
+}
@@ -0,0 +1,61 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::history::Entry;
+
+// Seconds since the Unix epoch
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs())
+}
+
+// ISO 8601 in UTC, e.g. 2026-10-16T14:03:22Z; empty for entries saved
+// before timestamps were recorded
+pub fn timestamp(time: u64) -> String {
+    if time == 0 {
+        return String::new();
+    }
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let days = (time / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    let seconds = time % 86_400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, seconds / 3600, seconds / 60 % 60, seconds % 60
+    )
+}
+
+// Quoted only when needed, doubling inner quotes
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+pub fn csv(entries: &[Entry]) -> String {
+    let mut csv = String::from("fecha,expresión,resultado\n");
+    for entry in entries {
+        csv.push_str(&format!(
+            "{},{},{}\n",
+            timestamp(entry.time), csv_field(&entry.expression), csv_field(&entry.result)
+        ));
+    }
+    csv
+}
+
+// One calculation per line, for pasting into a report
+pub fn text(entries: &[Entry]) -> String {
+    entries.iter()
+        .map(|entry| match timestamp(entry.time) {
+            time if time.is_empty() => format!("{} = {}\n", entry.expression, entry.result),
+            time => format!("[{}] {} = {}\n", time, entry.expression, entry.result),
+        })
+        .collect()
+}
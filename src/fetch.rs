@@ -0,0 +1,58 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::calculator::Error;
+
+// A document is downloaded again once it's older than this
+const CACHE_TIME: Duration = Duration::from_secs(60);
+// Evaluation waits for the download, so a slow server can't hang it for long
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+// Downloaded JSON, or why it couldn't be had
+type Download = Result<serde_json::Value, String>;
+
+// Downloads by URL, shared by every copy of the calculator (worksheets, plots)
+// so a sheet with many fetch_num() lines asks each server once, even one that is down
+static CACHE: Mutex<BTreeMap<String, (Instant, Download)>> = Mutex::new(BTreeMap::new());
+
+fn document(url: &str) -> Result<serde_json::Value, Error> {
+    if let Some((fetched, document)) = CACHE.lock().unwrap().get(url) {
+        if fetched.elapsed() < CACHE_TIME {
+            return document.clone().map_err(Error::InvalidOperation);
+        }
+    }
+    let document = download(url);
+    CACHE.lock().unwrap().insert(url.to_string(), (Instant::now(), document.clone()));
+    document.map_err(Error::InvalidOperation)
+}
+
+fn download(url: &str) -> Download {
+    let failed = |e: &dyn std::fmt::Display| format!("No se pudo descargar {}: {}", url, e);
+    // ureq's own messages repeat the URL
+    let response = ureq::get(url).timeout(TIMEOUT).call().map_err(|e| match e {
+        ureq::Error::Status(code, _) => failed(&format!("HTTP {}", code)),
+        ureq::Error::Transport(transport) => failed(&transport.kind()),
+    })?;
+    let text = response.into_string().map_err(|e| failed(&e))?;
+    let document: serde_json::Value = serde_json::from_str(&text)
+        .map_err(|_| format!("{} no devolvió JSON", url))?;
+    log::debug!(target: "calc::fetch", "{} -> {} bytes", url, text.len());
+    Ok(document)
+}
+
+// The number at `pointer`, like /data/0/price, in the JSON at `url`; an
+// empty pointer is the whole document, and numbers sent as text count too
+pub fn number(url: &str, pointer: &str) -> Result<f64, Error> {
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err(Error::InvalidOperation(format!("fetch_num solo admite direcciones http:// o https://, no {}", url)));
+    }
+    let document = document(url)?;
+    let not_a_number = || Error::InvalidOperation(format!("{} no es un número en {}", pointer, url));
+    match document.pointer(pointer) {
+        Some(serde_json::Value::Number(n)) => n.as_f64().ok_or_else(not_a_number),
+        Some(serde_json::Value::String(text)) => text.trim().parse().map_err(|_| not_a_number()),
+        Some(_) => Err(not_a_number()),
+        None => Err(Error::InvalidOperation(format!("No hay nada en {} de {}", pointer, url))),
+    }
+}
@@ -0,0 +1,322 @@
+use crate::calculator::{Calculator, Error, Function};
+use crate::format::NumberFormat;
+use crate::value::Value;
+
+const MAX_ITERATIONS: usize = 100;
+// Where Newton's method starts for rate and irr, as spreadsheets do
+const GUESS: f64 = 0.1;
+
+fn invalid(message: &str) -> Error {
+    Error::InvalidOperation(message.to_string())
+}
+
+// A loan or a savings plan over n periods at a rate r per period. Money
+// received is positive and money paid out negative, so the present value,
+// the payments and the future value always balance:
+// pv·(1 + r)^n + pmt·(1 + r·t)·((1 + r)^n - 1)/r + fv = 0,
+// with t = 1 when payments fall at the start of each period
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Tvm {
+    pub n: f64,
+    pub rate: f64,
+    pub pv: f64,
+    pub pmt: f64,
+    pub fv: f64,
+    pub begin: bool,
+}
+
+impl Tvm {
+    // (1 + r)^n, through ln_1p so small rates keep their digits
+    fn growth(&self) -> f64 {
+        (self.n * self.rate.ln_1p()).exp()
+    }
+
+    // What each unit paid per period is worth at the end
+    fn annuity(&self) -> f64 {
+        if self.rate == 0.0 {
+            self.n
+        } else {
+            (1.0 + self.rate * f64::from(u8::from(self.begin))) * (self.growth() - 1.0) / self.rate
+        }
+    }
+
+    fn balance(&self) -> f64 {
+        self.pv * self.growth() + self.pmt * self.annuity() + self.fv
+    }
+
+    fn check(&self) -> Result<(), Error> {
+        if self.rate <= -1.0 {
+            return Err(invalid("El tipo de interés debe ser mayor que -100 %"));
+        }
+        Ok(())
+    }
+
+    pub fn fv(&self) -> Result<f64, Error> {
+        self.check()?;
+        Ok(-(self.pv * self.growth() + self.pmt * self.annuity()))
+    }
+
+    pub fn pv(&self) -> Result<f64, Error> {
+        self.check()?;
+        Ok(-(self.fv + self.pmt * self.annuity()) / self.growth())
+    }
+
+    pub fn pmt(&self) -> Result<f64, Error> {
+        self.check()?;
+        if self.n == 0.0 {
+            return Err(invalid("Hace falta al menos un periodo"));
+        }
+        Ok(-(self.fv + self.pv * self.growth()) / self.annuity())
+    }
+
+    pub fn nper(&self) -> Result<f64, Error> {
+        self.check()?;
+        let n = if self.rate == 0.0 {
+            -(self.pv + self.fv) / self.pmt
+        } else {
+            // (1 + r)^n = (A - fv) / (A + pv) with A = pmt·(1 + r·t)/r
+            let a = self.pmt * (1.0 + self.rate * f64::from(u8::from(self.begin))) / self.rate;
+            ((a - self.fv) / (a + self.pv)).ln() / self.rate.ln_1p()
+        };
+        if n.is_finite() && n >= 0.0 { Ok(n) } else { Err(invalid("Ningún número de periodos cumple estos valores")) }
+    }
+
+    // By Newton's method, with the slope taken numerically
+    pub fn rate(&self) -> Result<f64, Error> {
+        if self.n <= 0.0 {
+            return Err(invalid("Hace falta al menos un periodo"));
+        }
+        let at = |rate: f64| Tvm { rate, ..*self }.balance();
+        let mut rate = GUESS;
+        for _ in 0..MAX_ITERATIONS {
+            let h = 1e-7 * rate.abs().max(1e-3);
+            let slope = (at(rate + h) - at(rate - h)) / (2.0 * h);
+            let step = at(rate) / slope;
+            rate -= step;
+            if !rate.is_finite() || rate <= -1.0 {
+                break;
+            }
+            if step.abs() <= 1e-12 * rate.abs().max(1e-3) {
+                return Ok(rate);
+            }
+        }
+        Err(invalid("rate no converge para estos valores"))
+    }
+}
+
+// Excel's npv: the first flow comes one period from now
+pub fn npv(rate: f64, flows: &[f64]) -> Result<f64, Error> {
+    if rate <= -1.0 {
+        return Err(invalid("El tipo de interés debe ser mayor que -100 %"));
+    }
+    Ok(flows.iter().rev().fold(0.0, |sum, flow| (sum + flow) / (1.0 + rate)))
+}
+
+// The rate at which the flows, the first one today, are worth nothing
+pub fn irr(flows: &[f64]) -> Result<f64, Error> {
+    if !flows.iter().any(|flow| *flow > 0.0) || !flows.iter().any(|flow| *flow < 0.0) {
+        return Err(invalid("irr requiere flujos de caja positivos y negativos"));
+    }
+    let mut rate = GUESS;
+    for _ in 0..MAX_ITERATIONS {
+        let discount = 1.0 / (1.0 + rate);
+        let (value, slope) = flows.iter().enumerate().fold((0.0, 0.0), |(value, slope), (i, flow)| {
+            let term = flow * discount.powi(i as i32);
+            (value + term, slope - i as f64 * term * discount)
+        });
+        let step = value / slope;
+        rate -= step;
+        if !rate.is_finite() || rate <= -1.0 {
+            break;
+        }
+        if step.abs() <= 1e-12 * rate.abs().max(1e-3) {
+            return Ok(rate);
+        }
+    }
+    Err(invalid("irr no converge para estos flujos"))
+}
+
+// pmt(rate, nper, pv), pv(rate, nper, pmt), fv(rate, nper, pmt, pv),
+// nper(rate, pmt, pv) and rate(nper, pmt, pv), in the order and with the
+// signs of spreadsheets: the rate per period, payments at the end of each
+// period and, except for fv, nothing left at the end
+pub fn apply(func: Function, args: &[f64]) -> Result<f64, Error> {
+    match (func, args) {
+        (Function::Pmt, &[rate, n, pv]) => Tvm { n, rate, pv, ..Tvm::default() }.pmt(),
+        (Function::Pv, &[rate, n, pmt]) => Tvm { n, rate, pmt, ..Tvm::default() }.pv(),
+        (Function::Fv, &[rate, n, pmt, pv]) => Tvm { n, rate, pv, pmt, ..Tvm::default() }.fv(),
+        (Function::Nper, &[rate, pmt, pv]) => Tvm { rate, pv, pmt, ..Tvm::default() }.nper(),
+        (Function::Rate, &[n, pmt, pv]) => Tvm { n, pv, pmt, ..Tvm::default() }.rate(),
+        _ => unreachable!(),
+    }
+}
+
+// The value the finance tab works out from the other four
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum Unknown {
+    N,
+    Rate,
+    Pv,
+    #[default]
+    Pmt,
+    Fv,
+}
+
+impl Unknown {
+    pub const ALL: [Unknown; 5] = [Unknown::N, Unknown::Rate, Unknown::Pv, Unknown::Pmt, Unknown::Fv];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Unknown::N => "Número de pagos (n)",
+            Unknown::Rate => "Interés anual (%)",
+            Unknown::Pv => "Valor actual (VA)",
+            Unknown::Pmt => "Pago (PAGO)",
+            Unknown::Fv => "Valor final (VF)",
+        }
+    }
+}
+
+// State of the finance tab: each field takes an expression, and the rate is
+// the yearly nominal one, split among the payments of a year
+pub struct Finance {
+    fields: [String; 5],
+    per_year: String,
+    begin: bool,
+    unknown: Unknown,
+}
+
+impl Default for Finance {
+    fn default() -> Self {
+        Self {
+            fields: ["360".to_string(), "4".to_string(), "200000".to_string(), String::new(), "0".to_string()],
+            per_year: "12".to_string(),
+            begin: false,
+            unknown: Unknown::Pmt,
+        }
+    }
+}
+
+impl Finance {
+    fn solve(&self, calculator: &Calculator) -> Result<(Tvm, f64), Error> {
+        let read = |text: &str| calculator.calculate(text).and_then(Value::real);
+        let per_year = read(&self.per_year)?;
+        if per_year <= 0.0 {
+            return Err(invalid("Tiene que haber al menos un pago al año"));
+        }
+        let mut values = [0.0; 5];
+        for (unknown, value) in Unknown::ALL.iter().zip(values.iter_mut()) {
+            if *unknown != self.unknown {
+                *value = read(&self.fields[*unknown as usize])?;
+            }
+        }
+        let [n, rate, pv, pmt, fv] = values;
+        let tvm = Tvm { n, rate: rate / 100.0 / per_year, pv, pmt, fv, begin: self.begin };
+        let result = match self.unknown {
+            Unknown::N => tvm.nper()?,
+            Unknown::Rate => tvm.rate()? * per_year * 100.0,
+            Unknown::Pv => tvm.pv()?,
+            Unknown::Pmt => tvm.pmt()?,
+            Unknown::Fv => tvm.fv()?,
+        };
+        let mut solved = [n, tvm.rate, pv, pmt, fv];
+        solved[self.unknown as usize] = if self.unknown == Unknown::Rate { result / 100.0 / per_year } else { result };
+        let [n, rate, pv, pmt, fv] = solved;
+        Ok((Tvm { n, rate, pv, pmt, fv, begin: self.begin }, result))
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, calculator: &Calculator, format: &NumberFormat) {
+        ui.heading("Finanzas");
+        ui.small("Préstamos y ahorro: elige el valor que falta y escribe los demás. Lo que recibes es positivo y lo que pagas, negativo.");
+        ui.add_space(10.0);
+        let solved = self.solve(calculator);
+        egui::Grid::new("finance").num_columns(3).spacing([12.0, 6.0]).show(ui, |ui| {
+            for unknown in Unknown::ALL {
+                ui.radio_value(&mut self.unknown, unknown, "").on_hover_text("Calcular este valor");
+                ui.label(unknown.name());
+                if unknown == self.unknown {
+                    match &solved {
+                        Ok((_, result)) => {
+                            let text = format.number(*result);
+                            ui.horizontal(|ui| {
+                                ui.monospace(egui::RichText::new(&text).color(egui::Color32::GREEN));
+                                if ui.small_button("Copiar").clicked() {
+                                    ui.ctx().copy_text(text);
+                                }
+                            });
+                        },
+                        Err(_) => {
+                            ui.weak("—");
+                        },
+                    }
+                } else {
+                    ui.add(egui::TextEdit::singleline(&mut self.fields[unknown as usize]).desired_width(160.0));
+                }
+                ui.end_row();
+            }
+            ui.label("");
+            ui.label("Pagos al año");
+            ui.add(egui::TextEdit::singleline(&mut self.per_year).desired_width(160.0));
+            ui.end_row();
+        });
+        ui.horizontal(|ui| {
+            ui.label("Los pagos se hacen");
+            ui.radio_value(&mut self.begin, false, "al final de cada periodo");
+            ui.radio_value(&mut self.begin, true, "al principio");
+        });
+        ui.add_space(10.0);
+        match solved {
+            Ok((tvm, _)) => {
+                // What the payments don't account for is interest, paid or earned
+                let paid = tvm.pmt * tvm.n;
+                ui.label(format!("Total de los pagos: {}", format.number(paid)));
+                ui.label(format!("Intereses: {}", format.number((tvm.pv + paid + tvm.fv).abs())));
+            },
+            Err(e) => {
+                ui.colored_label(egui::Color32::RED, e.to_string());
+            },
+        }
+        ui.add_space(10.0);
+        ui.small("En la calculadora: pmt(tasa, n, va), pv(tasa, n, pago), fv(tasa, n, pago, va), nper(tasa, pago, va), rate(n, pago, va), npv(tasa, {flujos}) e irr({flujos}), con la tasa por periodo.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::close;
+
+    // Within a cent, as spreadsheets show them
+    fn cents(value: Result<f64, Error>, expected: f64) -> bool {
+        value.is_ok_and(|value| (value - expected).abs() < 0.005)
+    }
+
+    #[test]
+    fn spreadsheet_values() {
+        assert!(cents(apply(Function::Pmt, &[0.04 / 12.0, 360.0, 200000.0]), -954.83));
+        assert!(cents(apply(Function::Pv, &[0.05 / 12.0, 60.0, -500.0]), 26495.35));
+        assert!(cents(apply(Function::Fv, &[0.05 / 12.0, 120.0, -100.0, 0.0]), 15528.23));
+        assert!(cents(apply(Function::Fv, &[0.05, 10.0, 0.0, -1000.0]), 1628.89));
+        assert!(cents(apply(Function::Nper, &[0.01, -100.0, 1000.0]), 10.59));
+        assert!(cents(npv(0.1, &[-100.0, 50.0, 60.0]), -4.51));
+    }
+
+    #[test]
+    fn rates() {
+        let rate = apply(Function::Rate, &[360.0, -954.830590930919, 200000.0]).unwrap();
+        assert!(close(rate * 12.0, 0.04));
+        assert!(close(irr(&[-100.0, 50.0, 60.0]).unwrap(), 0.06394102980498528));
+        assert!(irr(&[1.0, 2.0]).is_err());
+        assert!(apply(Function::Rate, &[10.0, 0.0, -100.0]).is_err());
+    }
+
+    #[test]
+    fn edge_cases() {
+        // No interest, payments at the start, and values that can't balance
+        assert!(cents(apply(Function::Pmt, &[0.0, 10.0, 1000.0]), -100.0));
+        assert!(cents(apply(Function::Nper, &[0.0, -100.0, 1000.0]), 10.0));
+        assert!(cents(Tvm { n: 12.0, rate: 0.01, pmt: -100.0, begin: true, ..Tvm::default() }.fv(), 1280.93));
+        assert!(apply(Function::Nper, &[0.1, -10.0, 1000.0]).is_err());
+        assert!(apply(Function::Pmt, &[-1.0, 10.0, 1000.0]).is_err());
+        assert!(apply(Function::Pmt, &[0.05, 0.0, 1000.0]).is_err());
+    }
+}
@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+
+use crate::list;
+use crate::separators::Separators;
+use crate::units::round_significant;
+use crate::value::Value;
+
+// Powers of ten written as a letter in SI-prefix notation, as in 4.7k
+pub const SI_PREFIXES: [(i32, &str); 9] = [
+    (-12, "p"), (-9, "n"), (-6, "µ"), (-3, "m"), (0, ""), (3, "k"), (6, "M"), (9, "G"), (12, "T"),
+];
+
+// Power of ten of a prefix letter; u stands in for µ when typing
+pub fn si_prefix(symbol: &str) -> Option<i32> {
+    let symbol = if symbol == "u" { "µ" } else { symbol };
+    SI_PREFIXES.iter().find(|(_, prefix)| !prefix.is_empty() && *prefix == symbol).map(|(exponent, _)| *exponent)
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Notation {
+    // Plain, switching to scientific outside the configured range
+    #[default]
+    Auto,
+    // A fixed number of decimals
+    Fixed,
+    Scientific,
+    // Scientific with exponents in multiples of 3
+    Engineering,
+    // Engineering with the exponent as a prefix letter: 4.7k, 2.2µ
+    SiPrefix,
+}
+
+impl Notation {
+    pub const ALL: [Notation; 5] = [
+        Notation::Auto, Notation::Fixed, Notation::Scientific, Notation::Engineering, Notation::SiPrefix,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Notation::Auto => "Automática",
+            Notation::Fixed => "Decimales fijos",
+            Notation::Scientific => "Científica",
+            Notation::Engineering => "Ingeniería",
+            Notation::SiPrefix => "Prefijos SI",
+        }
+    }
+
+    // Label of the quick toggle under the result
+    pub fn short_name(&self) -> &'static str {
+        match self {
+            Notation::Auto => "AUTO",
+            Notation::Fixed => "FIX",
+            Notation::Scientific => "SCI",
+            Notation::Engineering => "ENG",
+            Notation::SiPrefix => "SI",
+        }
+    }
+
+    pub fn next(&self) -> Notation {
+        let i = Notation::ALL.iter().position(|notation| notation == self).unwrap_or(0);
+        Notation::ALL[(i + 1) % Notation::ALL.len()]
+    }
+}
+
+// How results are written out
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NumberFormat {
+    // Scientific notation from 10^sci_above up and below 10^sci_below
+    pub sci_above: i32,
+    pub sci_below: i32,
+    // Units as kg·m/s^2 rather than N
+    pub base_units: bool,
+    // Significant digits shown, 0 for all of them
+    pub digits: usize,
+    pub notation: Notation,
+    // Decimals in fixed notation
+    pub decimals: usize,
+    pub separators: Separators,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            sci_above: 15,
+            sci_below: -5,
+            base_units: false,
+            digits: 0,
+            notation: Notation::Auto,
+            decimals: 4,
+            separators: Separators::Plain,
+        }
+    }
+}
+
+impl NumberFormat {
+    pub fn number(&self, x: f64) -> String {
+        self.separators.group(&self.plain(x))
+    }
+
+    // The number with a plain decimal point and no grouping
+    fn plain(&self, x: f64) -> String {
+        // Rounding through the exponent form keeps whole numbers whole
+        let x = match self.digits {
+            0 => x,
+            digits => format!("{:.*e}", digits - 1, x).parse().unwrap_or(x),
+        };
+        if !x.is_finite() {
+            return x.to_string();
+        }
+        let magnitude = x.abs();
+        match self.notation {
+            Notation::Auto if x != 0.0 && (magnitude >= 10f64.powi(self.sci_above) || magnitude < 10f64.powi(self.sci_below)) => {
+                format!("{:e}", x)
+            },
+            Notation::Auto => x.to_string(),
+            Notation::Fixed => format!("{:.*}", self.decimals, x),
+            Notation::Scientific => format!("{:e}", x),
+            Notation::Engineering => NumberFormat::with_exponent(x, NumberFormat::engineering_exponent(x)),
+            // Out of the prefixes' range it falls back to scientific
+            Notation::SiPrefix => {
+                let exponent = NumberFormat::engineering_exponent(x);
+                match SI_PREFIXES.iter().find(|(prefix_exponent, _)| *prefix_exponent == exponent) {
+                    Some((_, prefix)) => format!("{}{}", round_significant(x / 10f64.powi(exponent)), prefix),
+                    None => format!("{:e}", x),
+                }
+            },
+        }
+    }
+
+    // x as mantissa and a chosen power of ten, e.g. 4650e-6
+    pub fn with_exponent(x: f64, exponent: i32) -> String {
+        format!("{}e{}", round_significant(x / 10f64.powi(exponent)), exponent)
+    }
+
+    // The multiple of 3 that leaves a mantissa from 1 to 999
+    pub fn engineering_exponent(x: f64) -> i32 {
+        if x == 0.0 || !x.is_finite() {
+            return 0;
+        }
+        (x.abs().log10().floor() as i32).div_euclid(3) * 3
+    }
+
+    // Exact integers, measurements and text keep their own formatting
+    pub fn value(&self, value: &Value) -> String {
+        match value {
+            Value::Number(n) => self.number(*n),
+            Value::Complex(z) => z.format(|x| self.number(x)),
+            Value::Quantity(q) => q.format(|x| self.number(x), self.base_units),
+            // Elements are separated as function arguments are typed
+            Value::Matrix(m) => m.format(|x| self.number(x), if self.separators == Separators::Comma { "; " } else { ", " }),
+            Value::Eigen(e) => e.format(|x| self.number(x), if self.separators == Separators::Comma { "; " } else { ", " }),
+            Value::List(items) => list::format(items, |x| self.number(x), if self.separators == Separators::Comma { "; " } else { ", " }),
+            _ => value.to_string(),
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use crate::calculator::Calculator;
+use crate::format::NumberFormat;
+use crate::solver;
+use crate::value::Value;
+
+pub struct Formula {
+    pub name: &'static str,
+    // Both sides are expressions in the variables below and the constants
+    pub equation: &'static str,
+    pub variables: &'static [(&'static str, &'static str)],
+}
+
+pub const FORMULAS: &[Formula] = &[
+    Formula {
+        name: "Interés compuesto",
+        equation: "A = P*(1 + r/n)^(n*t)",
+        variables: &[("A", "Monto final"), ("P", "Capital"), ("r", "Tasa anual (0.05 = 5 %)"), ("n", "Capitalizaciones por año"), ("t", "Años")],
+    },
+    Formula {
+        name: "Cuota de préstamo",
+        equation: "C = P*r/(1 - (1 + r)^(-n))",
+        variables: &[("C", "Cuota"), ("P", "Capital"), ("r", "Tasa por periodo"), ("n", "Número de cuotas")],
+    },
+    Formula {
+        name: "Ley de Ohm",
+        equation: "V = I*R",
+        variables: &[("V", "Tensión (V)"), ("I", "Corriente (A)"), ("R", "Resistencia (Ω)")],
+    },
+    Formula {
+        name: "Energía cinética",
+        equation: "K = m*v^2/2",
+        variables: &[("K", "Energía (J)"), ("m", "Masa (kg)"), ("v", "Velocidad (m/s)")],
+    },
+    Formula {
+        name: "Gas ideal",
+        equation: "P*V = n*R*T",
+        variables: &[("P", "Presión (Pa)"), ("V", "Volumen (m³)"), ("n", "Cantidad (mol)"), ("T", "Temperatura (K)")],
+    },
+    Formula {
+        name: "Caída libre",
+        equation: "h = 9.80665*t^2/2",
+        variables: &[("h", "Altura (m)"), ("t", "Tiempo (s)")],
+    },
+    Formula {
+        name: "Teorema de Pitágoras",
+        equation: "a^2 + b^2 = d^2",
+        variables: &[("a", "Cateto"), ("b", "Cateto"), ("d", "Hipotenusa")],
+    },
+];
+
+// State of the formula tab: the chosen formula, the variable to solve for
+// and what was typed for each variable
+#[derive(Default)]
+pub struct Formulas {
+    selected: usize,
+    unknown: usize,
+    inputs: Vec<String>,
+    result: Option<Result<f64, String>>,
+}
+
+impl Formulas {
+    fn select(&mut self, index: usize) {
+        self.selected = index;
+        self.unknown = 0;
+        self.inputs = vec![String::new(); FORMULAS[index].variables.len()];
+        self.result = None;
+    }
+
+    // Value of the unknown that makes both sides equal, with the typed value
+    // of the unknown, if any, as the starting guess
+    fn solve(&self, calculator: &Calculator) -> Result<f64, String> {
+        let formula = &FORMULAS[self.selected];
+        let (left, right) = formula.equation.split_once('=').unwrap();
+        let evaluate = |calculator: &Calculator, expr: &str| {
+            calculator.calculate(expr).and_then(Value::real).map_err(|e| e.to_string())
+        };
+        let mut solver = Calculator { unit_mode: false, integer_mode: false, real_only: true, ..calculator.clone() };
+        let mut guess = 1.0;
+        for (i, ((name, _), input)) in formula.variables.iter().zip(&self.inputs).enumerate() {
+            if i == self.unknown {
+                if !input.trim().is_empty() {
+                    guess = evaluate(calculator, input)?;
+                }
+            } else if input.trim().is_empty() {
+                return Err(format!("Falta el valor de {}", name));
+            } else {
+                // Through assign, so I is stored as the i it shadows
+                solver.assign(name, Value::Number(evaluate(calculator, input)?)).map_err(|e| e.to_string())?;
+            }
+        }
+        let unknown = formula.variables[self.unknown].0;
+        solver::secant(|x| {
+            let mut solver = solver.clone();
+            solver.assign(unknown, Value::Number(x)).map_err(|e| e.to_string())?;
+            Ok(evaluate(&solver, left)? - evaluate(&solver, right)?)
+        }, guess)
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, calculator: &Calculator, format: &NumberFormat) {
+        if self.inputs.len() != FORMULAS[self.selected].variables.len() {
+            self.select(self.selected);
+        }
+        ui.heading("Fórmulas");
+        ui.small("Elige la variable a despejar y da valor a las demás; se resuelve numéricamente.");
+        ui.add_space(10.0);
+
+        let mut selected = self.selected;
+        egui::ComboBox::from_id_salt("formula")
+            .selected_text(FORMULAS[selected].name)
+            .show_ui(ui, |ui| {
+                for (i, formula) in FORMULAS.iter().enumerate() {
+                    ui.selectable_value(&mut selected, i, formula.name);
+                }
+            });
+        if selected != self.selected {
+            self.select(selected);
+        }
+        let formula = &FORMULAS[self.selected];
+        ui.label(egui::RichText::new(formula.equation).monospace().size(18.0));
+        ui.add_space(10.0);
+
+        egui::Grid::new("formula_variables").num_columns(3).striped(true).show(ui, |ui| {
+            for (i, ((name, description), input)) in formula.variables.iter().zip(&mut self.inputs).enumerate() {
+                if ui.radio(self.unknown == i, egui::RichText::new(*name).monospace()).on_hover_text("Despejar").clicked() {
+                    self.unknown = i;
+                    self.result = None;
+                }
+                ui.label(*description);
+                let hint = if self.unknown == i { "Valor inicial (opcional)" } else { "Valor o expresión" };
+                ui.add(egui::TextEdit::singleline(input).hint_text(hint).desired_width(160.0));
+                ui.end_row();
+            }
+        });
+        ui.add_space(10.0);
+
+        let unknown = formula.variables[self.unknown].0;
+        let solve = egui::Button::new(format!("Despejar {}", unknown)).fill(egui::Color32::from_rgb(0, 150, 0));
+        if ui.add(solve).clicked() {
+            self.result = Some(self.solve(calculator));
+        }
+        match &self.result {
+            Some(Ok(x)) => {
+                ui.horizontal(|ui| {
+                    let text = format!("{} = {}", unknown, format.number(*x));
+                    ui.label(egui::RichText::new(&text).size(20.0).color(egui::Color32::GREEN));
+                    if ui.small_button("Copiar").clicked() {
+                        ui.ctx().copy_text(format.number(*x));
+                    }
+                });
+            },
+            Some(Err(error)) => {
+                ui.colored_label(egui::Color32::RED, error);
+            },
+            None => {},
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::close;
+
+    fn solving(formula: usize, unknown: usize, inputs: &[&str]) -> Result<f64, String> {
+        let formulas = Formulas { selected: formula, unknown, inputs: inputs.iter().map(|s| s.to_string()).collect(), result: None };
+        formulas.solve(&Calculator::default())
+    }
+
+    #[test]
+    fn ohm_solves_for_the_current() {
+        let ohm = FORMULAS.iter().position(|formula| formula.name == "Ley de Ohm").unwrap();
+        let current = solving(ohm, 1, &["6", "", "3"]).unwrap();
+        assert!(close(current, 2.0));
+    }
+
+    // No variable may be read as a constant such as i, which would make the
+    // equation complex and unsolvable
+    #[test]
+    fn every_variable_shadows_constants() {
+        for (i, formula) in FORMULAS.iter().enumerate() {
+            for unknown in 0..formula.variables.len() {
+                let inputs = vec!["2"; formula.variables.len()];
+                if let Err(e) = solving(i, unknown, &inputs) {
+                    assert!(!e.contains("complejos"), "{}, {}: {}", formula.name, formula.variables[unknown].0, e);
+                }
+            }
+        }
+    }
+}
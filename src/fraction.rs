@@ -0,0 +1,74 @@
+// How close a fraction must come, relative to the value (absolute below 1),
+// about the six decimals a result is usually read with
+const TOLERANCE: f64 = 1e-6;
+// Fractions with larger denominators aren't simple any more
+const MAX_DENOMINATOR: i64 = 1_000_000;
+
+pub struct Fraction {
+    pub numerator: i64,
+    pub denominator: i64,
+    // The fraction's value minus the one approximated
+    pub error: f64,
+}
+
+// The simplest fraction within the tolerance of `x`, e.g. 0.333333 → 1/3 and
+// 1.414214 → 1393/985: the first convergent of its continued fraction that
+// is close enough, or one of the fractions between it and the convergent
+// before. None for values too small to tell from 0
+pub fn approximate(x: f64) -> Option<Fraction> {
+    if !x.is_finite() {
+        return None;
+    }
+    let tolerance = TOLERANCE * x.abs().max(1.0);
+    let sign = if x < 0.0 { -1 } else { 1 };
+    // Numerators and denominators of the last two convergents
+    let (mut p, mut p_before, mut q, mut q_before) = (1i64, 0i64, 0i64, 1i64);
+    let mut rest = x.abs();
+    loop {
+        let term = rest.floor();
+        if term >= i64::MAX as f64 {
+            return None;
+        }
+        let term = term as i64;
+        let convergent = |k: i64| Some((k.checked_mul(p)?.checked_add(p_before)?, k.checked_mul(q)?.checked_add(q_before)?));
+        let error = |(numerator, denominator): (i64, i64)| numerator as f64 / denominator as f64 - x.abs();
+        let (numerator, denominator) = convergent(term)?;
+        if denominator > MAX_DENOMINATOR {
+            return None;
+        }
+        if error((numerator, denominator)).abs() <= tolerance {
+            if numerator == 0 {
+                return None;
+            }
+            // The intermediate fractions get closer as k grows
+            let k = (term / 2..term).find(|&k| k > 0 && error(convergent(k).unwrap()).abs() <= tolerance).unwrap_or(term);
+            let (numerator, denominator) = convergent(k)?;
+            return Some(Fraction { numerator: sign * numerator, denominator, error: sign as f64 * error((numerator, denominator)) });
+        }
+        rest = 1.0 / (rest - term as f64);
+        (p_before, p) = (p, numerator);
+        (q_before, q) = (q, denominator);
+    }
+}
+
+// `x` as a fraction under the result, exact or with how far off it is
+pub fn show(ui: &mut egui::Ui, x: f64) {
+    if x.fract() == 0.0 {
+        return;
+    }
+    match approximate(x) {
+        Some(fraction) if fraction.error.abs() <= f64::EPSILON * x.abs() => {
+            ui.monospace(format!("= {}/{}", fraction.numerator, fraction.denominator));
+        },
+        Some(fraction) => {
+            ui.horizontal(|ui| {
+                ui.monospace(format!("≈ {}/{}", fraction.numerator, fraction.denominator));
+                ui.weak(format!("error {:+.1e}", fraction.error))
+                    .on_hover_text("La fracción menos el resultado");
+            });
+        },
+        None => {
+            ui.weak("Sin fracción sencilla (denominador hasta 1 000 000)");
+        },
+    }
+}
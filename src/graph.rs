@@ -0,0 +1,612 @@
+use crate::calculator::Calculator;
+use crate::sampling::{self, SampleCache};
+use crate::solver;
+
+// Colors new curves get in turn
+const PALETTE: [egui::Color32; 6] = [
+    egui::Color32::from_rgb(90, 170, 255),
+    egui::Color32::from_rgb(255, 150, 60),
+    egui::Color32::from_rgb(90, 200, 90),
+    egui::Color32::from_rgb(230, 90, 90),
+    egui::Color32::from_rgb(180, 120, 230),
+    egui::Color32::from_rgb(220, 200, 60),
+];
+const DEFAULT_VIEW: [f64; 4] = [-10.0, 10.0, -5.0, 5.0];
+// About this many labelled grid lines across each axis
+const TICKS: f64 = 6.0;
+// Points the intersection search checks for sign changes across the view
+const INTERSECTION_SAMPLES: usize = 1000;
+// Share of the samples at each end the automatic y-range may leave out, so
+// an asymptote doesn't flatten the rest of the curve
+const OUTLIERS: f64 = 0.02;
+
+// Round steps for grid lines over `span`: 1, 2 or 5 times a power of ten
+fn tick_step(span: f64) -> f64 {
+    let rough = span / TICKS;
+    let power = 10f64.powf(rough.log10().floor());
+    [1.0, 2.0, 5.0, 10.0].into_iter().map(|m| m * power).find(|step| *step >= rough).unwrap_or(10.0 * power)
+}
+
+fn tick_label(value: f64, step: f64) -> String {
+    let decimals = (-step.log10().floor()).max(0.0) as usize;
+    // No "-0" at the origin
+    format!("{:.*}", decimals, if value.abs() < step / 2.0 { 0.0 } else { value })
+}
+
+// y range that shows the curve's samples, with some room above and below
+fn auto_range(ys: &mut [f64]) -> Option<(f64, f64)> {
+    if ys.is_empty() {
+        return None;
+    }
+    ys.sort_by(f64::total_cmp);
+    let (low, high) = (ys[0], ys[ys.len() - 1]);
+    let cut = (ys.len() as f64 * OUTLIERS) as usize;
+    let (inner_low, inner_high) = (ys[cut], ys[ys.len() - 1 - cut]);
+    // The extremes only count when they don't dwarf everything else
+    let (low, high) = if high - low <= 10.0 * (inner_high - inner_low) { (low, high) } else { (inner_low, inner_high) };
+    let margin = if high > low { (high - low) * 0.05 } else { low.abs().max(1.0) };
+    Some((low - margin, high + margin))
+}
+
+// What a click on the plot does, besides dragging to pan
+#[derive(PartialEq, Clone, Copy)]
+pub enum Tool {
+    Pan,
+    Trace,
+    Zero,
+    Intersect,
+}
+
+impl Tool {
+    pub const ALL: [Tool; 4] = [Tool::Pan, Tool::Trace, Tool::Zero, Tool::Intersect];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tool::Pan => "Mover",
+            Tool::Trace => "Rastrear",
+            Tool::Zero => "Buscar cero",
+            Tool::Intersect => "Intersecciones",
+        }
+    }
+
+    fn hint(&self) -> &'static str {
+        match self {
+            Tool::Pan => "Arrastrar y la rueda solo mueven y acercan la vista",
+            Tool::Trace => "El puntero o ←/→ recorren la curva (Mayús: 10 pasos); ↑/↓ cambian de curva",
+            Tool::Zero => "Dos clics a cada lado de un cruce con el eje x buscan el cero entre ellos",
+            Tool::Intersect => "Los puntos donde se cruzan dos funciones dentro del dominio visible",
+        }
+    }
+}
+
+// Lines break where the function is undefined and at jumps such as tan's
+// asymptotes, which would otherwise be drawn as steep lines
+pub fn draw_curve(painter: &egui::Painter, points: &[[f64; 2]], to_screen: impl Fn(f64, f64) -> egui::Pos2, color: egui::Color32) {
+    let rect = painter.clip_rect();
+    let stroke = egui::Stroke::new(2.0, color);
+    let mut line: Vec<egui::Pos2> = Vec::new();
+    for &[x, y] in points {
+        let point = y.is_finite().then(|| {
+            let point = to_screen(x, y);
+            egui::pos2(point.x, point.y.clamp(rect.top() - rect.height(), rect.bottom() + rect.height()))
+        });
+        let jump = match (line.last(), point) {
+            (Some(last), Some(point)) => (point.y - last.y).abs() > rect.height(),
+            _ => false,
+        };
+        if point.is_none() || jump {
+            painter.add(egui::Shape::line(std::mem::take(&mut line), stroke));
+        }
+        line.extend(point);
+    }
+    painter.add(egui::Shape::line(line, stroke));
+}
+
+// Grid lines over `view` with their values along the bottom and left edges,
+// and the axes when they are in sight
+pub fn draw_grid(ui: &egui::Ui, painter: &egui::Painter, rect: egui::Rect, view: [f64; 4], to_screen: impl Fn(f64, f64) -> egui::Pos2) {
+    let [x0, x1, y0, y1] = view;
+    let grid = egui::Stroke::new(1.0, ui.visuals().weak_text_color().gamma_multiply(0.25));
+    let font = egui::FontId::proportional(11.0);
+    let label_color = ui.visuals().weak_text_color();
+    let step = tick_step(x1 - x0);
+    for i in (x0 / step).ceil() as i64..=(x1 / step).floor() as i64 {
+        let x = to_screen(i as f64 * step, 0.0).x;
+        painter.line_segment([egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())], grid);
+        painter.text(egui::pos2(x + 2.0, rect.bottom() - 2.0), egui::Align2::LEFT_BOTTOM, tick_label(i as f64 * step, step), font.clone(), label_color);
+    }
+    let step = tick_step(y1 - y0);
+    for i in (y0 / step).ceil() as i64..=(y1 / step).floor() as i64 {
+        let y = to_screen(0.0, i as f64 * step).y;
+        painter.line_segment([egui::pos2(rect.left(), y), egui::pos2(rect.right(), y)], grid);
+        painter.text(egui::pos2(rect.left() + 2.0, y - 2.0), egui::Align2::LEFT_BOTTOM, tick_label(i as f64 * step, step), font.clone(), label_color);
+    }
+    let axis = egui::Stroke::new(1.0, ui.visuals().weak_text_color());
+    if x0 < 0.0 && x1 > 0.0 {
+        let x = to_screen(0.0, 0.0).x;
+        painter.line_segment([egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())], axis);
+    }
+    if y0 < 0.0 && y1 > 0.0 {
+        let y = to_screen(0.0, 0.0).y;
+        painter.line_segment([egui::pos2(rect.left(), y), egui::pos2(rect.right(), y)], axis);
+    }
+}
+
+// One f(x) slot of the graph
+pub struct Curve {
+    pub expr: String,
+    pub color: egui::Color32,
+    pub visible: bool,
+    cache: SampleCache,
+    // Why it couldn't be drawn last frame
+    error: Option<String>,
+}
+
+impl Curve {
+    pub fn new(expr: &str, color: egui::Color32) -> Curve {
+        Curve { expr: expr.to_string(), color, visible: true, cache: SampleCache::default(), error: None }
+    }
+}
+
+// Plots of f(x), panned by dragging and zoomed with the wheel
+pub struct Graph {
+    pub curves: Vec<Curve>,
+    // Visible x and y ranges: x_min, x_max, y_min, y_max
+    pub view: [f64; 4],
+    // Fit the y range to the curves instead of keeping view's
+    pub auto_y: bool,
+    pub tool: Tool,
+    // Curve and x of the trace marker
+    traced: Option<(usize, f64)>,
+    // First end of the zero search's interval, with the curve clicked on
+    bracket: Option<(usize, f64)>,
+    // Zeros found, by curve, and why the last search failed
+    zeros: Vec<(usize, f64)>,
+    zero_error: Option<String>,
+    // The two curves compared and where they cross
+    pair: (usize, usize),
+    intersections: Vec<[f64; 2]>,
+    intersection_error: Option<String>,
+}
+
+impl Default for Graph {
+    fn default() -> Self {
+        Self { curves: vec![Curve::new("sin(x)", PALETTE[0])], view: DEFAULT_VIEW, auto_y: true, tool: Tool::Pan, traced: None, bracket: None, zeros: Vec::new(), zero_error: None, pair: (0, 1), intersections: Vec::new(), intersection_error: None }
+    }
+}
+
+impl Graph {
+    // Moves and scales the view as the pointer drags or the wheel turns over `rect`
+    fn navigate(&mut self, ui: &egui::Ui, response: &egui::Response, rect: egui::Rect) {
+        let [x0, x1, y0, y1] = self.view;
+        let (width, height) = (x1 - x0, y1 - y0);
+        if response.double_clicked() {
+            self.view = DEFAULT_VIEW;
+            return;
+        }
+        if response.dragged() {
+            let delta = response.drag_delta();
+            let dx = -delta.x as f64 * width / rect.width() as f64;
+            let dy = delta.y as f64 * height / rect.height() as f64;
+            self.view = [x0 + dx, x1 + dx, y0 + dy, y1 + dy];
+        }
+        let scroll = ui.input(|i| i.smooth_scroll_delta.y);
+        if let Some(pointer) = response.hover_pos().filter(|_| scroll != 0.0) {
+            let factor = (-scroll as f64 * 0.002).exp();
+            let px = x0 + (pointer.x - rect.left()) as f64 / rect.width() as f64 * width;
+            let py = y1 - (pointer.y - rect.top()) as f64 / rect.height() as f64 * height;
+            let [x0, x1, y0, y1] = self.view;
+            self.view = [px + (x0 - px) * factor, px + (x1 - px) * factor, py + (y0 - py) * factor, py + (y1 - py) * factor];
+        }
+    }
+
+    // The function slots: color, visibility, expression and any error. True
+    // when a function was edited or removed
+    fn show_curves(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        let mut removed = None;
+        let mut derived = None;
+        let removable = self.curves.len() > 1;
+        egui::Grid::new("graph_curves").num_columns(4).show(ui, |ui| {
+            for (i, curve) in self.curves.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    egui::color_picker::color_edit_button_srgba(ui, &mut curve.color, egui::color_picker::Alpha::Opaque);
+                    ui.checkbox(&mut curve.visible, "").on_hover_text("Mostrar u ocultar");
+                });
+                ui.label(format!("f{}(x) =", i + 1));
+                let field = egui::TextEdit::singleline(&mut curve.expr).font(egui::TextStyle::Monospace).desired_width(f32::INFINITY);
+                changed |= ui.add(field).changed();
+                ui.horizontal(|ui| {
+                    // The expression goes into nderiv's quotes as it is
+                    let derivable = !curve.expr.trim().is_empty() && !curve.expr.contains('"');
+                    if ui.add_enabled(derivable, egui::Button::new("d/dx").small()).on_hover_text("Añadir su derivada numérica").clicked() {
+                        derived = Some(format!("nderiv(\"{}\", \"x\", x)", curve.expr.trim()));
+                    }
+                    if ui.add_enabled(removable, egui::Button::new("✖").small()).on_hover_text("Quitar la función").clicked() {
+                        removed = Some(i);
+                    }
+                });
+                ui.end_row();
+                if let Some(error) = curve.error.as_ref().filter(|_| curve.visible) {
+                    ui.label("");
+                    ui.label("");
+                    ui.colored_label(egui::Color32::RED, error);
+                    ui.end_row();
+                }
+            }
+        });
+        if let Some(i) = removed {
+            self.curves.remove(i);
+        }
+        let added = if ui.small_button("+ Función").clicked() { Some(String::new()) } else { derived };
+        if let Some(expr) = added {
+            let color = PALETTE[self.curves.len() % PALETTE.len()];
+            self.curves.push(Curve::new(&expr, color));
+        }
+        changed || removed.is_some()
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, calculator: &Calculator) {
+        ui.heading("Gráfica");
+        // Results that belonged to the old functions
+        if self.show_curves(ui) {
+            self.bracket = None;
+            self.zeros.clear();
+            self.zero_error = None;
+            self.intersections.clear();
+            self.intersection_error = None;
+        }
+        let mut view = self.view;
+        ui.horizontal(|ui| {
+            let speed = (view[1] - view[0]) * 0.01;
+            ui.label("x de");
+            ui.add(egui::DragValue::new(&mut view[0]).speed(speed));
+            ui.label("a");
+            ui.add(egui::DragValue::new(&mut view[1]).speed(speed));
+            ui.separator();
+            ui.checkbox(&mut self.auto_y, "y automática");
+            if !self.auto_y {
+                let speed = (view[3] - view[2]) * 0.01;
+                ui.label("y de");
+                ui.add(egui::DragValue::new(&mut view[2]).speed(speed));
+                ui.label("a");
+                ui.add(egui::DragValue::new(&mut view[3]).speed(speed));
+            }
+        });
+        ui.horizontal(|ui| {
+            for tool in Tool::ALL {
+                ui.selectable_value(&mut self.tool, tool, tool.name()).on_hover_text(tool.hint());
+            }
+        });
+        match self.tool {
+            Tool::Zero => self.show_zeros(ui),
+            Tool::Intersect => self.show_intersections(ui, calculator),
+            Tool::Pan | Tool::Trace => {},
+        }
+        // An empty or reversed range is ignored until it's fixed
+        if view[0] < view[1] && view[2] < view[3] {
+            self.view = view;
+        }
+        ui.small("Arrastra para mover, rueda para acercar, doble clic para volver a la vista inicial.");
+
+        let (response, painter) = ui.allocate_painter(ui.available_size(), egui::Sense::click_and_drag());
+        let rect = response.rect;
+        self.navigate(ui, &response, rect);
+        let [x0, x1, ..] = self.view;
+        painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+
+        // Samples of the visible curves that could be evaluated, with a little
+        // beyond each side so lines reach the edges
+        let margin = (x1 - x0) * 0.01;
+        let mut plotted: Vec<(usize, Vec<[f64; 2]>)> = Vec::new();
+        for (i, curve) in self.curves.iter_mut().enumerate().filter(|(_, curve)| curve.visible && !curve.expr.trim().is_empty()) {
+            let error = match curve.cache.get(calculator, &curve.expr, x0..=x1, rect.width().max(1.0) as usize) {
+                Ok(points) => {
+                    plotted.push((i, points.iter().filter(|[x, _]| *x >= x0 - margin && *x <= x1 + margin).copied().collect()));
+                    None
+                },
+                Err(error) => Some(error),
+            };
+            if curve.error != error {
+                curve.error = error;
+                ui.ctx().request_repaint();
+            }
+        }
+        if self.auto_y {
+            let mut ys: Vec<f64> = plotted.iter()
+                .flat_map(|(_, points)| points.iter().filter(|[x, y]| *x >= x0 && *x <= x1 && y.is_finite()).map(|[_, y]| *y))
+                .collect();
+            if let Some((low, high)) = auto_range(&mut ys) {
+                self.view[2] = low;
+                self.view[3] = high;
+            }
+        }
+        let [x0, x1, y0, y1] = self.view;
+        let to_screen = |x: f64, y: f64| egui::pos2(
+            rect.left() + ((x - x0) / (x1 - x0)) as f32 * rect.width(),
+            rect.bottom() - ((y - y0) / (y1 - y0)) as f32 * rect.height(),
+        );
+
+        draw_grid(ui, &painter, rect, self.view, to_screen);
+
+        let painter = painter.with_clip_rect(rect);
+        for (i, points) in &plotted {
+            draw_curve(&painter, points, to_screen, self.curves[*i].color);
+        }
+        match self.tool {
+            Tool::Pan => {},
+            Tool::Trace => self.show_trace(ui, &response, &painter, calculator, &plotted, to_screen),
+            Tool::Zero => self.find_zero(&response, calculator, &plotted, to_screen),
+            Tool::Intersect => self.draw_intersections(ui, &painter, to_screen),
+        }
+        self.draw_zeros(ui, &painter, &plotted, to_screen);
+        self.show_legend(ui, &painter, rect, &plotted);
+    }
+
+    // The curve among `drawn` that passes closest on screen to `screen_y` at x
+    fn nearest(&self, calculator: &Calculator, drawn: &[usize], x: f64, screen_y: f32, to_screen: impl Fn(f64, f64) -> egui::Pos2) -> Option<usize> {
+        let distance = |i: &usize| {
+            let y = sampling::value_at(calculator, &self.curves[*i].expr, x);
+            if y.is_finite() { (to_screen(x, y).y - screen_y).abs() } else { f32::INFINITY }
+        };
+        drawn.iter().copied().min_by(|a, b| distance(a).total_cmp(&distance(b)))
+    }
+
+    // The first click picks a curve and one end of the interval, the second
+    // the other end; Brent's method then looks for the zero between them
+    fn find_zero(
+        &mut self,
+        response: &egui::Response,
+        calculator: &Calculator,
+        plotted: &[(usize, Vec<[f64; 2]>)],
+        to_screen: impl Fn(f64, f64) -> egui::Pos2,
+    ) {
+        let Some(pointer) = response.interact_pointer_pos().filter(|_| response.clicked()) else {
+            return;
+        };
+        let rect = response.rect;
+        let [x0, x1, y0, y1] = self.view;
+        let x = x0 + (pointer.x - rect.left()) as f64 / rect.width() as f64 * (x1 - x0);
+        let Some((curve, start)) = self.bracket.take() else {
+            let drawn: Vec<usize> = plotted.iter().map(|(i, _)| *i).collect();
+            self.bracket = self.nearest(calculator, &drawn, x, pointer.y, to_screen).map(|curve| (curve, x));
+            self.zero_error = None;
+            return;
+        };
+        let (a, b) = if start < x { (start, x) } else { (x, start) };
+        let result = sampling::function(calculator, &self.curves[curve].expr).and_then(|f| {
+            let root = solver::brent(&f, a, b)?;
+            // A sign change across a pole, like tan's, converges on the pole
+            if f(root).abs() > 1e-6 * (y1 - y0) {
+                return Err(format!("En x = {} la función cambia de signo sin pasar por cero", root));
+            }
+            Ok(root)
+        });
+        match result {
+            Ok(root) => {
+                self.zeros.retain(|(i, x)| *i != curve || (x - root).abs() > 1e-9 * root.abs().max(1.0));
+                self.zeros.push((curve, root));
+            },
+            Err(error) => self.zero_error = Some(error),
+        }
+    }
+
+    // What the zero search is waiting for and the zeros found so far
+    fn show_zeros(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            match self.bracket {
+                Some((curve, x)) => ui.small(format!("f{} desde x = {:.4}: clic en el otro extremo", curve + 1, x)),
+                None => ui.small("Clic en un extremo del intervalo, cerca de la curva"),
+            };
+            if !self.zeros.is_empty() && ui.small_button("Borrar ceros").clicked() {
+                self.zeros.clear();
+            }
+        });
+        for (curve, x) in &self.zeros {
+            ui.monospace(format!("f{}(x) = 0 en x = {}", curve + 1, x));
+        }
+        if let Some(error) = &self.zero_error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+    }
+
+    // Where curves `pair` meet within the visible x range: each sign change
+    // of their difference between samples, refined by Brent's method.
+    // Crossings that only touch without changing sign are missed
+    fn intersect(&self, calculator: &Calculator) -> Result<Vec<[f64; 2]>, String> {
+        let (first, second) = self.pair;
+        let f = sampling::function(calculator, &self.curves[first].expr)?;
+        let g = sampling::function(calculator, &self.curves[second].expr)?;
+        let difference = |x: f64| f(x) - g(x);
+        let [x0, x1, y0, y1] = self.view;
+        let step = (x1 - x0) / INTERSECTION_SAMPLES as f64;
+        let mut found: Vec<[f64; 2]> = Vec::new();
+        let mut before = difference(x0);
+        for i in 1..=INTERSECTION_SAMPLES {
+            let (a, b) = (x0 + (i - 1) as f64 * step, x0 + i as f64 * step);
+            let after = difference(b);
+            let crossing = before.is_finite() && after.is_finite() && (before == 0.0 || before.signum() != after.signum());
+            before = after;
+            if !crossing {
+                continue;
+            }
+            // Jumps across poles change sign too, without the curves meeting
+            let Ok(x) = solver::brent(difference, a, b) else {
+                continue;
+            };
+            if difference(x).abs() > 1e-6 * (y1 - y0) || found.last().is_some_and(|[last, _]| (x - last).abs() < step / 2.0) {
+                continue;
+            }
+            found.push([x, f(x)]);
+        }
+        Ok(found)
+    }
+
+    // The two functions to compare and the crossings found, as a table
+    fn show_intersections(&mut self, ui: &mut egui::Ui, calculator: &Calculator) {
+        if self.curves.len() < 2 {
+            ui.small("Hacen falta al menos dos funciones; añade otra con + Función");
+            return;
+        }
+        let count = self.curves.len();
+        let (first, second) = &mut self.pair;
+        *first = (*first).min(count - 1);
+        *second = (*second).min(count - 1);
+        let search = ui.horizontal(|ui| {
+            for (id, slot) in [("intersect_first", first), ("intersect_second", second)] {
+                egui::ComboBox::from_id_salt(id).selected_text(format!("f{}", *slot + 1)).show_ui(ui, |ui| {
+                    for i in 0..count {
+                        ui.selectable_value(slot, i, format!("f{}", i + 1));
+                    }
+                });
+            }
+            ui.button("Buscar").on_hover_text("Dentro del dominio visible").clicked()
+        }).inner;
+        if search {
+            self.intersection_error = None;
+            self.intersections.clear();
+            if self.pair.0 == self.pair.1 {
+                self.intersection_error = Some("Elige dos funciones distintas".to_string());
+            } else {
+                match self.intersect(calculator) {
+                    Ok(found) => self.intersections = found,
+                    Err(error) => self.intersection_error = Some(error),
+                }
+                if self.intersections.is_empty() && self.intersection_error.is_none() {
+                    self.intersection_error = Some("No se cruzan en el dominio visible".to_string());
+                }
+            }
+        }
+        if let Some(error) = &self.intersection_error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+        if !self.intersections.is_empty() {
+            egui::Grid::new("intersections").num_columns(2).striped(true).show(ui, |ui| {
+                ui.strong("x");
+                ui.strong("y");
+                ui.end_row();
+                for [x, y] in &self.intersections {
+                    ui.monospace(x.to_string());
+                    ui.monospace(y.to_string());
+                    ui.end_row();
+                }
+            });
+        }
+    }
+
+    fn draw_intersections(&self, ui: &egui::Ui, painter: &egui::Painter, to_screen: impl Fn(f64, f64) -> egui::Pos2) {
+        for [x, y] in &self.intersections {
+            let point = to_screen(*x, *y);
+            painter.circle(point, 5.0, ui.visuals().extreme_bg_color, egui::Stroke::new(2.0, ui.visuals().strong_text_color()));
+            painter.text(point + egui::vec2(0.0, -8.0), egui::Align2::CENTER_BOTTOM, format!("({:.4}, {:.4})", x, y), egui::FontId::monospace(11.0), ui.visuals().text_color());
+        }
+    }
+
+    // Marks the zeros of the visible curves and the pending end of an interval
+    fn draw_zeros(&self, ui: &egui::Ui, painter: &egui::Painter, plotted: &[(usize, Vec<[f64; 2]>)], to_screen: impl Fn(f64, f64) -> egui::Pos2) {
+        let rect = painter.clip_rect();
+        let shown = |curve: &usize| plotted.iter().any(|(i, _)| i == curve);
+        for (curve, x) in self.zeros.iter().filter(|(curve, _)| shown(curve)) {
+            let point = to_screen(*x, 0.0);
+            painter.circle(point, 5.0, ui.visuals().extreme_bg_color, egui::Stroke::new(2.0, self.curves[*curve].color));
+            painter.text(point + egui::vec2(0.0, -8.0), egui::Align2::CENTER_BOTTOM, format!("{:.6}", x), egui::FontId::monospace(11.0), ui.visuals().text_color());
+        }
+        if let Some((curve, x)) = self.bracket.filter(|(curve, _)| self.tool == Tool::Zero && shown(curve)) {
+            let at = to_screen(x, 0.0).x;
+            let stroke = egui::Stroke::new(1.0, self.curves[curve].color);
+            painter.add(egui::Shape::dashed_line(&[egui::pos2(at, rect.top()), egui::pos2(at, rect.bottom())], stroke, 6.0, 4.0));
+        }
+    }
+
+    // Moves the trace marker with the pointer, or a pixel's width at a time
+    // with the arrow keys, and marks the exact (x, f(x)) on the curve
+    fn show_trace(
+        &mut self,
+        ui: &egui::Ui,
+        response: &egui::Response,
+        painter: &egui::Painter,
+        calculator: &Calculator,
+        plotted: &[(usize, Vec<[f64; 2]>)],
+        to_screen: impl Fn(f64, f64) -> egui::Pos2,
+    ) {
+        let rect = response.rect;
+        let [x0, x1, ..] = self.view;
+        let pixel = (x1 - x0) / rect.width() as f64;
+        let drawn: Vec<usize> = plotted.iter().map(|(i, _)| *i).collect();
+        let Some(&first) = drawn.first() else {
+            return;
+        };
+        let (mut curve, mut x) = self.traced.filter(|(curve, _)| drawn.contains(curve)).unwrap_or((first, (x0 + x1) / 2.0));
+
+        if let Some(pointer) = response.hover_pos().filter(|_| ui.input(|i| i.pointer.is_moving())) {
+            x = x0 + (pointer.x - rect.left()) as f64 * pixel;
+            curve = self.nearest(calculator, &drawn, x, pointer.y, &to_screen).unwrap_or(curve);
+        }
+        // Keys only while no text field is being typed in
+        if ui.memory(|memory| memory.focused().is_none()) {
+            let steps = if ui.input(|i| i.modifiers.shift) { 10.0 } else { 1.0 };
+            let position = drawn.iter().position(|i| *i == curve).unwrap_or(0);
+            ui.input(|i| {
+                if i.key_pressed(egui::Key::ArrowLeft) {
+                    x -= steps * pixel;
+                }
+                if i.key_pressed(egui::Key::ArrowRight) {
+                    x += steps * pixel;
+                }
+                if i.key_pressed(egui::Key::ArrowUp) {
+                    curve = drawn[(position + 1) % drawn.len()];
+                }
+                if i.key_pressed(egui::Key::ArrowDown) {
+                    curve = drawn[(position + drawn.len() - 1) % drawn.len()];
+                }
+            });
+        }
+        // x to the digits a pixel can tell apart, so the readout is the exact
+        // value at a short x
+        let decimals = (-pixel.log10()).ceil().max(0.0) as i32;
+        let scale = 10f64.powi(decimals);
+        x = (x * scale).round() / scale;
+        self.traced = Some((curve, x));
+
+        let color = self.curves[curve].color;
+        let at = to_screen(x, 0.0).x;
+        painter.line_segment([egui::pos2(at, rect.top()), egui::pos2(at, rect.bottom())], egui::Stroke::new(1.0, color.gamma_multiply(0.4)));
+        let y = sampling::value_at(calculator, &self.curves[curve].expr, x);
+        let readout = format!("x = {:.*}\nf{}(x) = {}", decimals as usize, x, curve + 1, if y.is_finite() { y.to_string() } else { "—".to_string() });
+        let marker = if y.is_finite() { to_screen(x, y) } else { egui::pos2(at, rect.center().y) };
+        if y.is_finite() {
+            painter.circle(marker, 4.0, color, egui::Stroke::new(1.0, ui.visuals().extreme_bg_color));
+        }
+        let text = painter.layout_no_wrap(readout, egui::FontId::monospace(12.0), ui.visuals().text_color());
+        // Beside the marker, on whichever side has room, and inside the plot
+        let offset = if at > rect.center().x { -text.size().x - 10.0 } else { 10.0 };
+        let top = (marker.y - text.size().y - 10.0).clamp(rect.top() + 4.0, rect.bottom() - text.size().y - 4.0);
+        let frame = egui::Rect::from_min_size(egui::pos2(marker.x + offset, top), text.size()).expand(3.0);
+        painter.rect(frame, 3.0, ui.visuals().window_fill.gamma_multiply(0.9), egui::Stroke::new(1.0, color));
+        painter.galley(frame.min + egui::vec2(3.0, 3.0), text, ui.visuals().text_color());
+    }
+
+    // Color and expression of each drawn curve, in the top right corner
+    fn show_legend(&self, ui: &egui::Ui, painter: &egui::Painter, rect: egui::Rect, plotted: &[(usize, Vec<[f64; 2]>)]) {
+        if plotted.is_empty() {
+            return;
+        }
+        let font = egui::FontId::monospace(12.0);
+        let rows: Vec<_> = plotted.iter().map(|(i, _)| {
+            let curve = &self.curves[*i];
+            (curve.color, painter.layout_no_wrap(curve.expr.trim().to_string(), font.clone(), ui.visuals().text_color()))
+        }).collect();
+        let (swatch, gap, padding) = (16.0, 6.0, 6.0);
+        let width = rows.iter().map(|(_, text)| text.size().x).fold(0.0, f32::max) + swatch + gap + 2.0 * padding;
+        let row_height = rows.iter().map(|(_, text)| text.size().y).fold(0.0, f32::max);
+        let height = row_height * rows.len() as f32 + 2.0 * padding;
+        let frame = egui::Rect::from_min_size(egui::pos2(rect.right() - width - 8.0, rect.top() + 8.0), egui::vec2(width, height));
+        painter.rect(frame, 4.0, ui.visuals().window_fill.gamma_multiply(0.9), ui.visuals().window_stroke);
+        for (row, (color, text)) in rows.into_iter().enumerate() {
+            let top = frame.top() + padding + row as f32 * row_height;
+            let y = top + row_height / 2.0;
+            painter.line_segment([egui::pos2(frame.left() + padding, y), egui::pos2(frame.left() + padding + swatch, y)], egui::Stroke::new(2.0, color));
+            painter.galley(egui::pos2(frame.left() + padding + swatch + gap, top), text, ui.visuals().text_color());
+        }
+    }
+}
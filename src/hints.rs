@@ -0,0 +1,124 @@
+use crate::calculator::{Error, FUNCTIONS};
+
+// Common names from other calculators or languages
+const ALIASES: &[(&str, &str)] = &[
+    ("ln", "log"),
+    ("sen", "sin"),
+    ("tg", "tan"),
+    ("raiz", "sqrt"),
+    ("root", "sqrt"),
+    ("lg", "log10"),
+    ("fact", "!"),
+];
+
+// Suggests likely fixes for an expression that failed to evaluate
+pub fn suggest(expr: &str, error: &Error) -> Vec<String> {
+    let expr = expr.to_lowercase();
+    let mut hints = Vec::new();
+
+    // A function name split from its digits, e.g. "log 10("
+    let compact: String = expr.chars().filter(|c| !c.is_whitespace()).collect();
+    for (name, _) in FUNCTIONS {
+        if let Some((prefix, suffix)) = split_name(name) {
+            let spaced = format!("{} {}(", prefix, suffix);
+            if expr.contains(&spaced) && compact.contains(&format!("{}(", name)) {
+                hints.push(format!("¿Quisiste decir `{}(` en lugar de `{}`?", name, spaced));
+            }
+        }
+    }
+
+    match error {
+        Error::MismatchedParens { .. } => {
+            let open = expr.matches('(').count();
+            let close = expr.matches(')').count();
+            if open > close {
+                hints.push(format!("Falta cerrar paréntesis: agrega {} ')'", open - close));
+            } else if close > open {
+                hints.push(format!("Sobran {} ')' o falta abrir paréntesis", close - open));
+            } else {
+                hints.push("Hay un ')' antes de su '(' correspondiente".to_string());
+            }
+        },
+        Error::UndefinedVariable(name) => {
+            match closest_name(name) {
+                Some(closest) => hints.push(format!("¿Quisiste decir `{}`?", closest)),
+                None => hints.push(format!("Define la variable primero, p. ej. `{} = 2`", name)),
+            }
+        },
+        Error::UnknownFunction(word) => {
+            if let Some(name) = closest_name(word) {
+                hints.push(format!("¿Quisiste decir `{}`?", name));
+            }
+        },
+        Error::BadToken { token: c, .. } => {
+            let fix = match c {
+                ',' => Some("Usa '.' como separador decimal"),
+                '×' | '·' => Some("Usa '*' para multiplicar"),
+                '÷' | ':' => Some("Usa '/' para dividir"),
+                '−' | '–' => Some("Usa '-' para restar"),
+                '²' => Some("Usa '^2' para elevar al cuadrado"),
+                '√' => Some("Usa sqrt(x) para la raíz cuadrada"),
+                'π' => Some("Escribe pi en lugar de π"),
+                _ => None,
+            };
+            if let Some(fix) = fix {
+                hints.push(fix.to_string());
+            }
+        },
+        Error::InvalidNumber(number) => {
+            if number.matches('.').count() > 1 {
+                hints.push(format!("`{}` tiene más de un punto decimal", number));
+            }
+        },
+        Error::InvalidOperation(message) => {
+            if message.starts_with("No hay suficientes operandos") {
+                if compact.contains("()") {
+                    hints.push("Hay paréntesis vacíos: escribe un valor dentro de `()`".to_string());
+                } else {
+                    hints.push("A un operador le falta un número a uno de sus lados".to_string());
+                }
+            } else if message == "Expresión inválida" && hints.is_empty() {
+                hints.push("¿Falta un operador? Usa '*' para multiplicar, p. ej. `2*pi` o `2*(3+1)`".to_string());
+            }
+        },
+        Error::DivisionByZero => {},
+    }
+
+    hints
+}
+
+// Splits names like "log10" into ("log", "10")
+fn split_name(name: &str) -> Option<(&str, &str)> {
+    let digits = name.find(|c: char| c.is_ascii_digit())?;
+    Some(name.split_at(digits))
+}
+
+fn closest_name(word: &str) -> Option<&'static str> {
+    if let Some((_, name)) = ALIASES.iter().find(|(alias, _)| *alias == word) {
+        return Some(name);
+    }
+    FUNCTIONS.iter()
+        .map(|(name, _)| (*name, edit_distance(word, name)))
+        .filter(|(_, distance)| *distance <= 2 && *distance < word.len())
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(name, _)| name)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == cb {
+                previous
+            } else {
+                1 + previous.min(row[j]).min(current)
+            };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
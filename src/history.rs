@@ -0,0 +1,174 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::Conditions;
+use crate::export;
+use crate::markdown;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub expression: String,
+    pub result: String,
+    // Seconds since the Unix epoch; 0 for entries from older versions
+    #[serde(default)]
+    pub time: u64,
+    // Engine and settings it was computed with; None for older entries
+    #[serde(default)]
+    pub conditions: Option<Conditions>,
+}
+
+// What a click in the history panel asks the calculator to do
+pub enum Action {
+    // Put the expression back in the display
+    Load(String),
+    // Insert the value at the end of the display
+    Insert(String),
+    // Show two entries side by side, the older first
+    Compare(Entry, Entry),
+}
+
+// Kept between sessions; the oldest entries go once there are more than `limit`
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct History {
+    pub entries: Vec<Entry>,
+    pub limit: usize,
+    // Indices ticked for copying as a Markdown table or comparing
+    #[serde(skip)]
+    selected: BTreeSet<usize>,
+    // Outcome of the last export
+    #[serde(skip)]
+    status: Option<Result<String, String>>,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            limit: 500,
+            selected: BTreeSet::new(),
+            status: None,
+        }
+    }
+}
+
+impl History {
+    pub fn push(&mut self, expression: &str, result: &str, conditions: Conditions) {
+        let entry = Entry {
+            expression: expression.trim().to_string(),
+            result: result.to_string(),
+            time: export::now(),
+            conditions: Some(conditions),
+        };
+        // Pressing Enter twice shouldn't fill the list with copies
+        let repeated = self.entries.last()
+            .is_some_and(|last| last.expression == entry.expression && last.result == entry.result);
+        if !repeated {
+            self.entries.push(entry);
+        }
+        self.truncate();
+    }
+
+    // Drops the oldest entries beyond the limit, keeping the selection on the same rows
+    pub fn truncate(&mut self) {
+        let excess = self.entries.len().saturating_sub(self.limit);
+        if excess > 0 {
+            self.entries.drain(..excess);
+            self.selected = self.selected.iter().filter_map(|&i| i.checked_sub(excess)).collect();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.selected.clear();
+    }
+
+    // Asks where to save; a .txt name gets plain text, anything else CSV
+    fn export(&self) -> Option<Result<String, String>> {
+        let path = rfd::FileDialog::new()
+            .set_file_name("historial.csv")
+            .add_filter("CSV", &["csv"])
+            .add_filter("Texto", &["txt"])
+            .save_file()?;
+        let text = match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("txt") => export::text(&self.entries),
+            _ => export::csv(&self.entries),
+        };
+        Some(match std::fs::write(&path, text) {
+            Ok(()) => Ok(format!("Exportado a {}", path.display())),
+            Err(e) => Err(e.to_string()),
+        })
+    }
+
+    // `hide_last` keeps a result that teacher mode hasn't revealed yet out of view
+    pub fn show(&mut self, ui: &mut egui::Ui, hide_last: bool) -> Option<Action> {
+        let mut action = None;
+        ui.horizontal(|ui| {
+            ui.heading("Historial");
+            if ui.small_button("Borrar").clicked() {
+                self.clear();
+            }
+            if ui.add_enabled(!self.entries.is_empty(), egui::Button::new("Exportar…").small())
+                .on_hover_text("CSV o texto, con fecha y hora")
+                .clicked()
+            {
+                if let Some(status) = self.export() {
+                    self.status = Some(status);
+                }
+            }
+        });
+        match &self.status {
+            Some(Ok(message)) => {
+                ui.small(message);
+            },
+            Some(Err(error)) => {
+                ui.colored_label(egui::Color32::RED, error);
+            },
+            None => {},
+        }
+        if self.entries.is_empty() {
+            ui.small("Los cálculos aparecerán aquí.");
+            return None;
+        }
+        ui.small("Clic en la expresión para recuperarla, en el resultado para insertarlo.");
+        if !self.selected.is_empty() && ui.button(format!("Copiar {} como tabla Markdown", self.selected.len())).clicked() {
+            let rows = self.selected.iter().map(|&i| (self.entries[i].expression.as_str(), self.entries[i].result.as_str()));
+            ui.ctx().copy_text(markdown::table(rows));
+        }
+        if let [before, after] = self.selected.iter().collect::<Vec<_>>()[..] {
+            if ui.button("Comparar las 2 seleccionadas").clicked() {
+                action = Some(Action::Compare(self.entries[*before].clone(), self.entries[*after].clone()));
+            }
+        }
+        ui.separator();
+        let last = self.entries.len() - 1;
+        egui::ScrollArea::vertical().stick_to_bottom(true).auto_shrink([false, false]).show(ui, |ui| {
+            for (i, entry) in self.entries.iter().enumerate() {
+                ui.horizontal_wrapped(|ui| {
+                    let mut selected = self.selected.contains(&i);
+                    if ui.checkbox(&mut selected, "").changed() {
+                        if selected {
+                            self.selected.insert(i);
+                        } else {
+                            self.selected.remove(&i);
+                        }
+                    }
+                    if ui.link(egui::RichText::new(&entry.expression).monospace()).clicked() {
+                        action = Some(Action::Load(entry.expression.clone()));
+                    }
+                    ui.label("=");
+                    if hide_last && i == last {
+                        ui.label("•••••");
+                    } else if ui.link(egui::RichText::new(&entry.result).monospace().color(egui::Color32::GREEN))
+                        .on_hover_text(entry.conditions.as_ref().map_or("Ajustes desconocidos".to_string(), |conditions| conditions.summary()))
+                        .clicked()
+                    {
+                        action = Some(Action::Insert(entry.result.clone()));
+                    }
+                });
+            }
+        });
+        action
+    }
+}
@@ -0,0 +1,104 @@
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    Startup,
+    Result,
+    Error,
+}
+
+impl Event {
+    pub const ALL: [Event; 3] = [Event::Startup, Event::Result, Event::Error];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::Startup => "on_startup",
+            Event::Result => "on_result",
+            Event::Error => "on_error",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            Event::Startup => "Al abrir la calculadora",
+            Event::Result => "Tras cada resultado",
+            Event::Error => "Tras cada error",
+        }
+    }
+}
+
+// Shell commands run on events, kept with the settings, e.g.
+// echo "$CALC_EXPRESSION = $CALC_RESULT" >> ~/resultados.txt as on_result.
+// They get the event, the expression and the result or error message in
+// CALC_EVENT, CALC_EXPRESSION and CALC_RESULT; an empty command does nothing
+#[derive(Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Hooks {
+    pub on_startup: String,
+    pub on_result: String,
+    pub on_error: String,
+}
+
+impl Hooks {
+    fn command(&self, event: Event) -> &str {
+        match event {
+            Event::Startup => &self.on_startup,
+            Event::Result => &self.on_result,
+            Event::Error => &self.on_error,
+        }
+    }
+
+    fn command_mut(&mut self, event: Event) -> &mut String {
+        match event {
+            Event::Startup => &mut self.on_startup,
+            Event::Result => &mut self.on_result,
+            Event::Error => &mut self.on_error,
+        }
+    }
+
+    // Starts the event's command without waiting for it; failures go to the
+    // engine log, so a broken hook never gets in the way of a calculation
+    pub fn run(&self, event: Event, expression: &str, result: &str) {
+        let command = self.command(event).trim();
+        if command.is_empty() {
+            return;
+        }
+        let mut shell = if cfg!(windows) { Command::new("cmd") } else { Command::new("sh") };
+        shell.arg(if cfg!(windows) { "/C" } else { "-c" })
+            .arg(command)
+            .env("CALC_EVENT", event.name())
+            .env("CALC_EXPRESSION", expression)
+            .env("CALC_RESULT", result);
+        match shell.spawn() {
+            // Waited for on its own thread so it doesn't linger as a zombie
+            Ok(mut child) => {
+                let name = event.name();
+                std::thread::spawn(move || match child.wait() {
+                    Ok(status) if !status.success() => log::warn!(target: "calc::hooks", "{} terminó con {}", name, status),
+                    Err(e) => log::warn!(target: "calc::hooks", "{}: {}", name, e),
+                    Ok(_) => {},
+                });
+            },
+            Err(e) => log::warn!(target: "calc::hooks", "{} no se pudo ejecutar: {}", event.name(), e),
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("hooks").num_columns(2).show(ui, |ui| {
+            for event in Event::ALL {
+                ui.monospace(event.name()).on_hover_text(event.description());
+                ui.add(
+                    egui::TextEdit::singleline(self.command_mut(event))
+                        .font(egui::TextStyle::Monospace)
+                        .desired_width(320.0)
+                        .hint_text("(ninguno)")
+                );
+                ui.end_row();
+            }
+        });
+        ui.small("Variables: $CALC_EVENT, $CALC_EXPRESSION y $CALC_RESULT (el resultado o el mensaje de error).");
+        ui.small("Los fallos quedan en el registro de depuración del motor.");
+    }
+}
@@ -0,0 +1,102 @@
+use std::num::FpCategory;
+
+const SIGN_COLOR: egui::Color32 = egui::Color32::from_rgb(220, 80, 80);
+const EXPONENT_COLOR: egui::Color32 = egui::Color32::from_rgb(80, 190, 80);
+const MANTISSA_COLOR: egui::Color32 = egui::Color32::from_rgb(90, 140, 230);
+
+// Fields of an f64: 1 sign bit, 11 exponent bits, 52 mantissa bits
+pub struct Fields {
+    pub sign: u64,
+    pub exponent: u64,
+    pub mantissa: u64,
+}
+
+pub fn fields(x: f64) -> Fields {
+    let bits = x.to_bits();
+    Fields {
+        sign: bits >> 63,
+        exponent: (bits >> 52) & 0x7ff,
+        mantissa: bits & ((1 << 52) - 1),
+    }
+}
+
+// Distance to the next representable value away from zero; at the largest
+// finite value, the gap below it
+pub fn ulp(x: f64) -> f64 {
+    let x = x.abs();
+    if !x.is_finite() {
+        return f64::NAN;
+    }
+    match x.next_up() {
+        next if next.is_finite() => next - x,
+        _ => x - x.next_down(),
+    }
+}
+
+fn describe(x: f64) -> &'static str {
+    match x.classify() {
+        FpCategory::Nan => "NaN",
+        FpCategory::Infinite => "infinito",
+        FpCategory::Zero => "cero",
+        FpCategory::Subnormal => "subnormal",
+        FpCategory::Normal => "normal",
+    }
+}
+
+fn exponent_text(x: f64, exponent: u64) -> String {
+    match x.classify() {
+        FpCategory::Nan | FpCategory::Infinite => format!("{} (reservado)", exponent),
+        // Subnormals use the minimum exponent without the implicit leading 1
+        FpCategory::Zero | FpCategory::Subnormal => format!("{} → 2^-1022", exponent),
+        FpCategory::Normal => format!("{} → 2^{}", exponent, exponent as i64 - 1023),
+    }
+}
+
+// Sign, exponent and mantissa bits in distinct colors
+fn bit_pattern(ui: &egui::Ui, x: f64) -> egui::text::LayoutJob {
+    let bits = format!("{:064b}", x.to_bits());
+    let font = egui::TextStyle::Monospace.resolve(ui.style());
+    let mut job = egui::text::LayoutJob::default();
+    for (range, color) in [(0..1, SIGN_COLOR), (1..12, EXPONENT_COLOR), (12..64, MANTISSA_COLOR)] {
+        let text = format!("{} ", &bits[range]);
+        job.append(&text, 0.0, egui::TextFormat::simple(font.clone(), color));
+    }
+    job.wrap.max_width = ui.available_width();
+    job
+}
+
+pub fn show(ui: &mut egui::Ui, x: f64) {
+    let Fields { sign, exponent, mantissa } = fields(x);
+    ui.add(egui::Label::new(bit_pattern(ui, x)).wrap());
+    egui::Grid::new("ieee754").num_columns(2).striped(true).show(ui, |ui| {
+        ui.label("Valor");
+        ui.monospace(format!("{:e}", x));
+        ui.end_row();
+        ui.label("Hex");
+        ui.monospace(format!("0x{:016X}", x.to_bits()));
+        ui.end_row();
+        ui.colored_label(SIGN_COLOR, "Signo");
+        ui.monospace(format!("{} ({})", sign, if sign == 1 { "-" } else { "+" }));
+        ui.end_row();
+        ui.colored_label(EXPONENT_COLOR, "Exponente");
+        ui.monospace(exponent_text(x, exponent));
+        ui.end_row();
+        ui.colored_label(MANTISSA_COLOR, "Mantisa");
+        ui.monospace(format!("0x{:013X}", mantissa));
+        ui.end_row();
+        ui.label("Clase");
+        ui.label(describe(x));
+        ui.end_row();
+        if x.is_finite() {
+            ui.label("Anterior");
+            ui.monospace(format!("{:e}", x.next_down()));
+            ui.end_row();
+            ui.label("Siguiente");
+            ui.monospace(format!("{:e}", x.next_up()));
+            ui.end_row();
+            ui.label("ULP");
+            ui.monospace(format!("{:e}", ulp(x)));
+            ui.end_row();
+        }
+    });
+}
@@ -0,0 +1,300 @@
+use num_bigint::BigInt;
+use num_traits::{FromPrimitive, One, Signed, ToPrimitive, Zero};
+
+use crate::calculator::{Error, Function, Operator, Token};
+use crate::limits;
+use crate::special;
+use crate::value::Value;
+
+// Evaluates RPN tokens with exact arbitrary-precision integers
+pub fn evaluate(tokens: Vec<Token>, lookup: &dyn Fn(&str) -> Option<Value>) -> Result<BigInt, Error> {
+    evaluate_bits(tokens, lookup, None)
+}
+
+// With a width, every operand and result wraps to a signed `bits`-bit integer
+// like a fixed-size register; without one, bitwise operators use unbounded two's complement
+pub fn evaluate_bits(mut tokens: Vec<Token>, lookup: &dyn Fn(&str) -> Option<Value>, bits: Option<u32>) -> Result<BigInt, Error> {
+    tokens.reverse();
+    let mask = bits.map(|bits| (BigInt::one() << bits) - 1);
+    let register = |n: BigInt| match bits {
+        Some(bits) => wrap(&n, bits),
+        None => n,
+    };
+
+    let mut stack: Vec<BigInt> = Vec::new();
+
+    while let Some(token) = tokens.pop() {
+        match token {
+            Token::Integer(num) => stack.push(num),
+            Token::Number(num) => {
+                // Only the -1 the parser inserts for unary minus, or integral literals like 1e3
+                if num.fract() != 0.0 {
+                    return Err(Error::InvalidOperation("El modo entero solo admite números enteros".to_string()));
+                }
+                stack.push(BigInt::from_f64(num).ok_or_else(|| Error::InvalidNumber(num.to_string()))?);
+            },
+            Token::Variable(name) => stack.push(integer_variable(lookup, name)?),
+            // Same shadowing rule as the floating-point evaluator
+            Token::Constant(constant) if lookup(constant.name).is_some() => {
+                stack.push(integer_variable(lookup, constant.name.to_string())?);
+            },
+            Token::Op(op) => {
+                if stack.len() < 2 {
+                    return Err(Error::InvalidOperation("No hay suficientes operandos".to_string()));
+                }
+                // Exponents and shift counts are counts, not register values
+                let right = match op {
+                    Operator::Pow | Operator::Shl | Operator::Shr => stack.pop().unwrap(),
+                    _ => register(stack.pop().unwrap()),
+                };
+                let left = register(stack.pop().unwrap());
+                stack.push(register(apply_operator(op, left, right, mask.as_ref())?));
+            },
+            Token::Function(func) if func.arity() == 0 => {
+                return Err(Error::InvalidOperation(format!("{} no está disponible en modo entero", func)));
+            },
+            Token::Function(func) => {
+                let val = stack.pop().ok_or_else(|| {
+                    Error::InvalidOperation("No hay suficientes operandos para la función".to_string())
+                })?;
+                match func {
+                    Function::Abs => stack.push(register(register(val).abs())),
+                    _ => return Err(Error::InvalidOperation(format!("{} no está disponible en modo entero", func))),
+                }
+            },
+            Token::Factorial => {
+                let val = stack.pop().ok_or_else(|| {
+                    Error::InvalidOperation("No hay suficientes operandos".to_string())
+                })?;
+                stack.push(match bits {
+                    Some(bits) => register(wrapping_factorial(&wrap(&val, bits), bits)?),
+                    None => factorial(&val)?,
+                });
+            },
+            Token::Imaginary(_) | Token::Constant(_) | Token::Text(_) | Token::List(_) | Token::Braces(_) => {
+                return Err(Error::InvalidOperation("El modo entero solo admite números enteros".to_string()));
+            },
+            Token::Unit(_) => {
+                return Err(Error::InvalidOperation("El modo entero no admite unidades".to_string()));
+            },
+            _ => {}
+        }
+    }
+
+    if stack.len() != 1 {
+        return Err(Error::InvalidOperation("Expresión inválida".to_string()));
+    }
+
+    Ok(register(stack.pop().unwrap()))
+}
+
+// Reduces `n` to the signed range of a `bits`-bit two's complement integer
+pub fn wrap(n: &BigInt, bits: u32) -> BigInt {
+    let unsigned = unsigned(n, bits);
+    if unsigned.bit(u64::from(bits) - 1) {
+        unsigned - (BigInt::one() << bits)
+    } else {
+        unsigned
+    }
+}
+
+// The low `bits` bits of `n` read as an unsigned number, e.g. -1 -> 255 for 8 bits
+pub fn unsigned(n: &BigInt, bits: u32) -> BigInt {
+    n & ((BigInt::one() << bits) - 1)
+}
+
+fn integer_variable(lookup: &dyn Fn(&str) -> Option<Value>, name: String) -> Result<BigInt, Error> {
+    match lookup(&name) {
+        Some(Value::Integer(n)) => Ok(n),
+        // User constants are stored as floats
+        Some(Value::Number(n)) if n.fract() == 0.0 => BigInt::from_f64(n).ok_or(Error::InvalidNumber(n.to_string())),
+        Some(_) => Err(Error::InvalidOperation(format!("«{}» no es un entero", name))),
+        None => Err(Error::UndefinedVariable(name)),
+    }
+}
+
+// log10 |n| from its top 64 bits, which an f64 holds, for any size of n
+fn log10(n: &BigInt) -> f64 {
+    let shift = n.bits().saturating_sub(64);
+    (n.magnitude() >> shift).to_f64().unwrap_or(f64::INFINITY).log10() + shift as f64 * std::f64::consts::LOG10_2
+}
+
+fn apply_operator(op: Operator, left: BigInt, right: BigInt, mask: Option<&BigInt>) -> Result<BigInt, Error> {
+    if op.is_bitwise() {
+        return apply_bitwise(op, left, right, mask);
+    }
+    let result = match op {
+        Operator::Add => left + right,
+        Operator::Sub => left - right,
+        Operator::Mul | Operator::Juxtapose => {
+            limits::check_bits((left.bits() + right.bits()) as f64)?;
+            left * right
+        },
+        // Integer division truncates toward zero
+        Operator::Div => {
+            if right.is_zero() {
+                return Err(Error::DivisionByZero);
+            }
+            left / right
+        },
+        Operator::Mod => {
+            if right.is_zero() {
+                return Err(Error::DivisionByZero);
+            }
+            left % right
+        },
+        Operator::Pow => {
+            if right.is_negative() {
+                return Err(Error::InvalidOperation("El modo entero no admite exponentes negativos".to_string()));
+            }
+            // Only the low bits survive, so huge exponents stay cheap
+            if let Some(mask) = mask {
+                return Ok(left.modpow(&right, &(mask + 1)));
+            }
+            let exp = right.to_u32().ok_or_else(|| {
+                Error::InvalidOperation("Exponente demasiado grande".to_string())
+            })?;
+            // The result has floor(exp·log10|left|) + 1 digits; 0, 1 and -1 stay small
+            if left.magnitude().bits() > 1 {
+                limits::check_digits(exp as f64 * log10(&left))?;
+            }
+            left.pow(exp)
+        },
+        Operator::PlusMinus => {
+            return Err(Error::InvalidOperation("± no está disponible en modo entero".to_string()));
+        },
+        _ => unreachable!(),
+    };
+    Ok(result)
+}
+
+fn apply_bitwise(op: Operator, left: BigInt, right: BigInt, mask: Option<&BigInt>) -> Result<BigInt, Error> {
+    // Negative operands become their two's complement within the width
+    let (left, right) = match mask {
+        Some(mask) => (left & mask, if matches!(op, Operator::Shl | Operator::Shr) { right } else { right & mask }),
+        None => (left, right),
+    };
+    let result = match op {
+        Operator::And => left & right,
+        Operator::Or => left | right,
+        // ~x is -1 xor x, and -1 within the width is all ones
+        Operator::Xor | Operator::Not => left ^ right,
+        Operator::Shl | Operator::Shr => {
+            if right.is_negative() {
+                return Err(Error::InvalidOperation("El desplazamiento no puede ser negativo".to_string()));
+            }
+            // Shifting by the width or more empties the register
+            let width = mask.map(|mask| mask.bits());
+            let shift = match (right.to_u64(), width) {
+                (Some(shift), Some(width)) => shift.min(width),
+                (None, Some(width)) => width,
+                (shift, None) => shift.ok_or_else(limits::too_large)?,
+            };
+            if op == Operator::Shr {
+                left >> shift
+            } else {
+                // Bits shifted past the width are dropped, so only check unbounded shifts
+                if mask.is_none() {
+                    limits::check_bits(left.bits() as f64 + shift as f64)?;
+                }
+                left << shift
+            }
+        },
+        _ => unreachable!(),
+    };
+    Ok(match mask {
+        Some(mask) => result & mask,
+        None => result,
+    })
+}
+
+// n! keeping only the low `bits` bits; from 2 * bits on, the product has
+// at least `bits` factors of two and wraps to zero
+fn wrapping_factorial(n: &BigInt, bits: u32) -> Result<BigInt, Error> {
+    if n.is_negative() {
+        return Err(Error::InvalidOperation("El factorial no está definido para enteros negativos".to_string()));
+    }
+    let limit = 2 * u64::from(bits);
+    let n = n.to_u64().unwrap_or(limit);
+    if n >= limit {
+        return Ok(BigInt::zero());
+    }
+    let mut result = BigInt::one();
+    for k in 2..=n {
+        result = unsigned(&(result * k), bits);
+    }
+    Ok(result)
+}
+
+fn factorial(n: &BigInt) -> Result<BigInt, Error> {
+    if n.is_negative() {
+        return Err(Error::InvalidOperation("El factorial no está definido para enteros negativos".to_string()));
+    }
+    let n = n.to_u64().ok_or_else(limits::too_large)?;
+    limits::check_bits(special::lgamma(n as f64 + 1.0) / std::f64::consts::LN_2)?;
+    let mut result = BigInt::one();
+    for k in 2..=n {
+        result *= k;
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::calculator::Calculator;
+    use crate::limits;
+    use crate::programmer::{self, Radix, WordSize};
+    use crate::value::Value;
+
+    fn integer(expr: &str) -> Result<Value, String> {
+        Calculator { integer_mode: true, ..Calculator::default() }.calculate(expr).map_err(|e| e.to_string())
+    }
+
+    fn digits(expr: &str) -> u64 {
+        match integer(expr) {
+            Ok(Value::Integer(n)) => n.magnitude().to_string().len() as u64,
+            other => panic!("{}: {:?}", expr, other.map(|v| v.to_string())),
+        }
+    }
+
+    #[test]
+    fn powers_up_to_the_digit_cap() {
+        assert_eq!(digits("10^99999"), limits::MAX_DIGITS);
+        assert_eq!(digits("3^209590"), limits::MAX_DIGITS);
+        assert_eq!(digits("2^0"), 1);
+        assert_eq!(digits("(-1)^1000001"), 1);
+    }
+
+    #[test]
+    fn powers_past_the_digit_cap() {
+        for expr in ["10^100000", "3^209592", "3^300000", "(-7)^200000"] {
+            assert!(integer(expr).is_err(), "{}", expr);
+        }
+    }
+
+    #[test]
+    fn products_past_the_digit_cap() {
+        assert_eq!(digits("10^49999 * 10^50000"), limits::MAX_DIGITS);
+        assert!(integer("10^50000 * 10^50000").is_err());
+    }
+
+    #[test]
+    fn word_sized_registers() {
+        let byte = |expr: &str| programmer::evaluate(expr, Radix::Dec, WordSize::Byte).map(|n| n.to_string()).map_err(|e| e.to_string());
+        // Exponents and shift counts are not wrapped to the width
+        assert_eq!(byte("2^200").unwrap(), "0");
+        assert_eq!(byte("3^256").unwrap(), "1");
+        assert_eq!(byte("1<<128").unwrap(), "0");
+        assert_eq!(byte("1<<7").unwrap(), "-128");
+        assert_eq!(byte("-64>>128").unwrap(), "0");
+        assert_eq!(byte("64>>8").unwrap(), "0");
+        assert_eq!(byte("-8>>1").unwrap(), "124");
+        // Operands and results still are
+        assert_eq!(byte("300").unwrap(), "44");
+        assert_eq!(byte("300/2").unwrap(), "22");
+        assert_eq!(byte("127+1").unwrap(), "-128");
+        assert_eq!(byte("6!").unwrap(), "-48");
+        assert!(byte("2^-1").is_err());
+        assert!(byte("1<<-1").is_err());
+    }
+}
@@ -0,0 +1,145 @@
+use egui::{Key, KeyboardShortcut, Modifiers};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Action {
+    Evaluate,
+    Clear,
+    ToggleAngle,
+    // Types the previous result at the cursor
+    InsertAns,
+}
+
+impl Action {
+    pub const ALL: [Action; 4] = [Action::Evaluate, Action::Clear, Action::ToggleAngle, Action::InsertAns];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Evaluate => "Calcular",
+            Action::Clear => "Borrar",
+            Action::ToggleAngle => "Cambiar radianes/grados",
+            Action::InsertAns => "Insertar el último resultado",
+        }
+    }
+
+    fn default_shortcut(&self) -> Option<KeyboardShortcut> {
+        Some(match self {
+            Action::Evaluate => KeyboardShortcut::new(Modifiers::NONE, Key::Enter),
+            Action::Clear => KeyboardShortcut::new(Modifiers::NONE, Key::Escape),
+            Action::ToggleAngle => KeyboardShortcut::new(Modifiers::COMMAND, Key::D),
+            Action::InsertAns => KeyboardShortcut::new(Modifiers::ALT, Key::A),
+        })
+    }
+}
+
+// The key for each action, kept with the settings; an action without one
+// is only on the buttons
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Keybindings {
+    pub evaluate: Option<KeyboardShortcut>,
+    pub clear: Option<KeyboardShortcut>,
+    pub toggle_angle: Option<KeyboardShortcut>,
+    pub insert_ans: Option<KeyboardShortcut>,
+    // The action waiting for its new key in the editor
+    #[serde(skip)]
+    capturing: Option<Action>,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Keybindings {
+            evaluate: Action::Evaluate.default_shortcut(),
+            clear: Action::Clear.default_shortcut(),
+            toggle_angle: Action::ToggleAngle.default_shortcut(),
+            insert_ans: Action::InsertAns.default_shortcut(),
+            capturing: None,
+        }
+    }
+}
+
+impl Keybindings {
+    pub fn shortcut(&self, action: Action) -> Option<KeyboardShortcut> {
+        match action {
+            Action::Evaluate => self.evaluate,
+            Action::Clear => self.clear,
+            Action::ToggleAngle => self.toggle_angle,
+            Action::InsertAns => self.insert_ans,
+        }
+    }
+
+    fn shortcut_mut(&mut self, action: Action) -> &mut Option<KeyboardShortcut> {
+        match action {
+            Action::Evaluate => &mut self.evaluate,
+            Action::Clear => &mut self.clear,
+            Action::ToggleAngle => &mut self.toggle_angle,
+            Action::InsertAns => &mut self.insert_ans,
+        }
+    }
+
+    // Actions whose keys went down this frame. Keys aren't consumed, so a
+    // text field with focus still sees its Enter; nothing fires while the
+    // editor waits for a key
+    pub fn pressed(&self, input: &egui::InputState) -> Vec<Action> {
+        if self.capturing.is_some() {
+            return Vec::new();
+        }
+        Action::ALL.into_iter()
+            .filter(|action| self.shortcut(*action).is_some_and(|shortcut| {
+                input.key_pressed(shortcut.logical_key) && input.modifiers.matches_logically(shortcut.modifiers)
+            }))
+            .collect()
+    }
+
+    // Gives `action` the key, taking it from any other action that had it
+    fn bind(&mut self, action: Action, shortcut: KeyboardShortcut) {
+        for other in Action::ALL {
+            if self.shortcut(other) == Some(shortcut) {
+                *self.shortcut_mut(other) = None;
+            }
+        }
+        *self.shortcut_mut(action) = Some(shortcut);
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        if let Some(action) = self.capturing {
+            let pressed = ui.input_mut(|i| {
+                let pressed = i.events.iter().find_map(|event| match event {
+                    egui::Event::Key { key, pressed: true, modifiers, .. } => Some(KeyboardShortcut::new(*modifiers, *key)),
+                    _ => None,
+                });
+                if let Some(shortcut) = pressed {
+                    i.consume_shortcut(&shortcut);
+                }
+                pressed
+            });
+            if let Some(shortcut) = pressed {
+                self.bind(action, shortcut);
+                self.capturing = None;
+            }
+        }
+        egui::Grid::new("keybindings").num_columns(3).striped(true).show(ui, |ui| {
+            for action in Action::ALL {
+                ui.label(action.name());
+                let text = if self.capturing == Some(action) {
+                    "Pulsa una tecla…".to_string()
+                } else {
+                    self.shortcut(action).map_or("(ninguna)".to_string(), |shortcut| ui.ctx().format_shortcut(&shortcut))
+                };
+                let button = ui.selectable_label(self.capturing == Some(action), text)
+                    .on_hover_text("Clic y luego la combinación de teclas; otro clic cancela");
+                if button.clicked() {
+                    self.capturing = if self.capturing == Some(action) { None } else { Some(action) };
+                }
+                if ui.small_button("✖").on_hover_text("Sin tecla").clicked() {
+                    *self.shortcut_mut(action) = None;
+                    self.capturing = None;
+                }
+                ui.end_row();
+            }
+        });
+        if ui.button("Restablecer").clicked() {
+            *self = Keybindings::default();
+        }
+    }
+}
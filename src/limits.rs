@@ -0,0 +1,54 @@
+use crate::calculator::Error;
+
+// Largest exact integer the calculator will build, so inputs like 9^9^9 fail fast
+// instead of exhausting memory
+pub const MAX_DIGITS: u64 = 100_000;
+const MAX_BITS: u64 = (MAX_DIGITS as f64 * std::f64::consts::LOG2_10) as u64 + 1;
+// Most elements in a matrix or a list, about 8 MB of numbers
+pub const MAX_ELEMENTS: usize = 1_000_000;
+
+pub fn too_large() -> Error {
+    Error::InvalidOperation(format!("El resultado excede el límite de {} dígitos", MAX_DIGITS))
+}
+
+// Fails when a value would need more than `bits` binary digits
+pub fn check_bits(bits: f64) -> Result<(), Error> {
+    if bits > MAX_BITS as f64 {
+        return Err(too_large());
+    }
+    Ok(())
+}
+
+// Fails when a value of `log10` would have more than MAX_DIGITS digits
+pub fn check_digits(log10: f64) -> Result<(), Error> {
+    if log10.floor() + 1.0 > MAX_DIGITS as f64 {
+        return Err(too_large());
+    }
+    Ok(())
+}
+
+// Fails when a rows × cols matrix, or a list of rows · cols, is too big
+pub fn check_elements(rows: usize, cols: usize) -> Result<(), Error> {
+    if rows.checked_mul(cols).is_none_or(|count| count > MAX_ELEMENTS) {
+        return Err(Error::InvalidOperation(format!("El resultado excede el límite de {} elementos", MAX_ELEMENTS)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digits() {
+        assert!(check_digits(MAX_DIGITS as f64 - 0.5).is_ok());
+        assert!(check_digits(MAX_DIGITS as f64).is_err());
+    }
+
+    #[test]
+    fn elements() {
+        assert!(check_elements(1000, 1000).is_ok());
+        assert!(check_elements(1, MAX_ELEMENTS + 1).is_err());
+        assert!(check_elements(usize::MAX, 2).is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use crate::calculator::{Error, Function, Operator};
+use crate::limits;
+use crate::matrix::Matrix;
+use crate::value::Value;
+
+// {1, 4, 9} with `number` writing each element; `separator` goes between
+// elements, as between function arguments
+pub fn format(items: &[f64], number: impl Fn(f64) -> String, separator: &str) -> String {
+    format!("{{{}}}", items.iter().map(|x| number(*x)).collect::<Vec<_>>().join(separator))
+}
+
+// What a {a, b, …} literal stands for
+pub fn from_items(items: Vec<Value>) -> Result<Value, Error> {
+    limits::check_elements(1, items.len())?;
+    let items = items.into_iter()
+        .map(|item| item.real().map_err(|_| Error::InvalidOperation("Los elementos de una lista deben ser números reales".to_string())))
+        .collect::<Result<Vec<f64>, Error>>()?;
+    Ok(Value::List(items))
+}
+
+// `left op right` where at least one side is a list: element by element,
+// with a number or a list of the same length, as for vectors
+pub fn combine(op: Operator, left: Value, right: Value) -> Result<Value, Error> {
+    if let (Value::List(a), Value::List(b)) = (&left, &right) {
+        if a.len() != b.len() {
+            return Err(Error::InvalidOperation(format!("Las listas deben tener la misma longitud: {} y {}", a.len(), b.len())));
+        }
+    }
+    let row = |value: Value| match value {
+        Value::List(items) => Ok(Value::Matrix(Matrix::new(1, items.len(), items))),
+        Value::Matrix(_) => Err(Error::InvalidOperation("Las listas solo se combinan con listas o números reales".to_string())),
+        value => Ok(value),
+    };
+    match Matrix::combine(op, row(left)?, row(right)?)? {
+        Value::Matrix(m) => Ok(Value::List(m.row(0).to_vec())),
+        value => Ok(value),
+    }
+}
+
+fn sorted(mut items: Vec<f64>) -> Vec<f64> {
+    items.sort_by(f64::total_cmp);
+    items
+}
+
+// sum, prod, mean, median, mode, sort and len of a list
+pub fn aggregate(func: Function, items: Vec<f64>) -> Result<Value, Error> {
+    let n = items.len();
+    if n == 0 && matches!(func, Function::Mean | Function::Median | Function::Mode) {
+        return Err(Error::InvalidOperation(format!("{} de una lista vacía", func)));
+    }
+    let result = match func {
+        Function::Sum => items.iter().fold(0.0, |sum, x| sum + x),
+        Function::Prod => items.iter().product(),
+        Function::Mean => items.iter().sum::<f64>() / n as f64,
+        Function::Median => {
+            let items = sorted(items);
+            if n % 2 == 1 { items[n / 2] } else { (items[n / 2 - 1] + items[n / 2]) / 2.0 }
+        },
+        // The most frequent value, or all of them in order when they tie
+        Function::Mode => {
+            let mut counts: Vec<(f64, usize)> = Vec::new();
+            for x in sorted(items) {
+                match counts.last_mut() {
+                    Some((y, count)) if *y == x => *count += 1,
+                    _ => counts.push((x, 1)),
+                }
+            }
+            let most = counts.iter().map(|(_, count)| *count).max().unwrap_or(0);
+            let modes: Vec<f64> = counts.into_iter().filter(|(_, count)| *count == most).map(|(x, _)| x).collect();
+            if let [mode] = modes.as_slice() {
+                *mode
+            } else {
+                return Ok(Value::List(modes));
+            }
+        },
+        Function::Sort => return Ok(Value::List(sorted(items))),
+        Function::Len => n as f64,
+        _ => return Err(Error::InvalidOperation(format!("{} no admite listas", func))),
+    };
+    Ok(Value::Number(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculator::Calculator;
+    use crate::limits::MAX_ELEMENTS;
+
+    fn result(expr: &str) -> Result<String, String> {
+        Calculator::default().calculate(expr).map(|value| value.to_string()).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn aggregates() {
+        for (expr, expected) in [
+            ("sum({1,2,3})", "6"), ("prod({1,2,3,4})", "24"), ("mean({1,2,3,4})", "2.5"),
+            ("median({3,1,2})", "2"), ("mode({1,2,2,3})", "2"), ("len({1,2,3})", "3"), ("sort({3,1,2})", "{1, 2, 3}"),
+        ] {
+            assert_eq!(result(expr).unwrap(), expected, "{}", expr);
+        }
+        assert!(result("mean({})").is_err());
+        assert!(result("sum(1,2,3)").is_err());
+    }
+
+    #[test]
+    fn element_wise() {
+        assert_eq!(result("{3,1,2}").unwrap(), "{3, 1, 2}");
+        assert_eq!(result("{1,2,3} * 2").unwrap(), "{2, 4, 6}");
+        assert_eq!(result("{1,2} + {10,20}").unwrap(), "{11, 22}");
+        assert!(result("{1,2} + {1,2,3}").is_err());
+    }
+
+    #[test]
+    fn literals_up_to_the_element_cap() {
+        assert!(from_items(vec![Value::Number(1.0); MAX_ELEMENTS]).is_ok());
+        assert!(from_items(vec![Value::Number(1.0); MAX_ELEMENTS + 1]).is_err());
+    }
+}
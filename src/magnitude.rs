@@ -0,0 +1,66 @@
+const RECENT_COLOR: egui::Color32 = egui::Color32::from_rgb(140, 140, 140);
+const CURRENT_COLOR: egui::Color32 = egui::Color32::from_rgb(80, 190, 80);
+const UNUSUAL_COLOR: egui::Color32 = egui::Color32::from_rgb(230, 150, 40);
+
+// Orders of magnitude away from the recent median that earn a warning
+const UNUSUAL: f64 = 3.0;
+
+fn magnitude(x: f64) -> Option<f64> {
+    (x != 0.0 && x.is_finite()).then(|| x.abs().log10())
+}
+
+fn median(values: &mut [f64]) -> Option<f64> {
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    match values.len() {
+        0 => None,
+        n if n % 2 == 0 => Some((values[mid - 1] + values[mid]) / 2.0),
+        _ => Some(values[mid]),
+    }
+}
+
+// Log-scale gauge of `x` against the recent results, to catch a result that
+// is off by orders of magnitude
+pub fn show(ui: &mut egui::Ui, x: f64, recent: &[f64]) {
+    let Some(current) = magnitude(x) else {
+        return;
+    };
+    let mut magnitudes: Vec<f64> = recent.iter().filter_map(|&x| magnitude(x)).collect();
+    let low = magnitudes.iter().copied().fold(current, f64::min).floor() - 1.0;
+    let high = magnitudes.iter().copied().fold(current, f64::max).ceil() + 1.0;
+    let unusual = median(&mut magnitudes).filter(|median| (current - median).abs() >= UNUSUAL);
+
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(ui.available_width(), 28.0), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    let bar = egui::Rect::from_min_max(rect.left_top() + egui::vec2(0.0, 8.0), rect.right_top() + egui::vec2(0.0, 14.0));
+    let at = |magnitude: f64| bar.left() + (magnitude - low) as f32 / (high - low) as f32 * bar.width();
+    painter.rect_filled(bar, 3.0, ui.visuals().extreme_bg_color);
+
+    // One tick per decade, labelled sparsely enough to stay readable
+    let decades = (high - low) as i32;
+    let step = (decades / 6).max(1);
+    for decade in (low as i32..=high as i32).step_by(step as usize) {
+        let position = at(decade as f64);
+        painter.line_segment([egui::pos2(position, bar.bottom()), egui::pos2(position, bar.bottom() + 3.0)], (1.0, RECENT_COLOR));
+        painter.text(
+            egui::pos2(position, bar.bottom() + 3.0),
+            egui::Align2::CENTER_TOP,
+            format!("1e{}", decade),
+            egui::FontId::proportional(9.0),
+            RECENT_COLOR,
+        );
+    }
+    for &magnitude in &magnitudes {
+        let position = at(magnitude);
+        painter.line_segment([egui::pos2(position, bar.top()), egui::pos2(position, bar.bottom())], (1.0, RECENT_COLOR));
+    }
+    let color = if unusual.is_some() { UNUSUAL_COLOR } else { CURRENT_COLOR };
+    let position = at(current);
+    painter.line_segment([egui::pos2(position, rect.top()), egui::pos2(position, bar.bottom())], (3.0, color));
+
+    if let Some(median) = unusual {
+        let factor = 10f64.powf((current - median).abs()).round();
+        let direction = if current > median { "mayor" } else { "menor" };
+        ui.colored_label(UNUSUAL_COLOR, format!("≈ {:e} veces {} que la mediana de los resultados recientes", factor, direction));
+    }
+}
@@ -1,11 +1,114 @@
+use std::ops::Range;
+use std::time::Duration;
+
 use egui::ViewportBuilder;
-use calculator::Calculator;
+use egui::text::{CCursor, CCursorRange};
+use num_bigint::BigInt;
+use num_traits::{FromPrimitive, ToPrimitive};
+use serde::{Deserialize, Serialize};
+use background::Evaluation;
+use calculator::{AngleMode, Calculator, Error, Function, Juxtaposition};
+use converter::{Converter, CurrencyConverter};
+use engine::{Conditions, Reproducibility};
+use equations::Equations;
+use finance::Finance;
+use format::{Notation, NumberFormat};
+use formulas::Formulas;
+use graph::{Curve, Graph};
+use history::History;
+use hooks::Hooks;
+use keybindings::Keybindings;
+use matrix::MatrixEditor;
+use modes::{GraphState, Modes};
+use polynomial::Polynomials;
+use programmer::Programmer;
+use quiz::{Difficulty, ProblemKind, Quiz};
+use rpn::Rpn;
+use separators::Separators;
+use settings::{ButtonSize, Settings, Theme};
+use share::SharedCalculation;
+use statistics::Statistics;
+use tape::Tape;
+use timeline::Timeline;
+use undo::Undo;
+use usage::Usage;
+use value::Value;
+use words::Locale;
+use worksheet::Worksheet;
+use workspace::{Workspace, Workspaces};
 
+mod ast;
+mod background;
 mod calculator;
+mod complex;
+mod compare;
+mod completion;
+mod constants;
+mod converter;
+mod csv;
+mod currency;
+mod debug;
+mod derivative;
+mod distribution;
+mod dimensions;
+mod editing;
+mod eigen;
+mod engine;
+mod equations;
+mod exact;
+mod export;
+mod fetch;
+mod finance;
+mod format;
+mod formulas;
+mod fraction;
+mod graph;
+mod hints;
+mod history;
+mod hooks;
+mod ieee;
+mod integer;
+mod keybindings;
+mod limits;
+mod list;
+mod magnitude;
+mod markdown;
+mod matrix;
+mod messages;
+mod modes;
+mod polynomial;
+mod precision;
+mod programmer;
+mod quantity;
+mod quiz;
+mod regression;
+mod report;
+mod rpn;
+mod sampling;
+mod selftest;
+mod separators;
+mod settings;
+mod share;
+mod simplify;
+mod solver;
+mod special;
+mod statistics;
+mod tape;
+#[cfg(test)]
+mod testing;
+mod timeline;
+mod uncertainty;
+mod undo;
+mod units;
+mod usage;
+mod value;
+mod words;
+mod worksheet;
+mod workspace;
 
 const BUTTONS: &[&str] = &[
-    // Row 1: Clear and parentheses
-    "C", "(", ")", "^", "mod",
+    // Row 1: Clear, delete and parentheses
+    "C", "⌫", "(", ")", "^",
     // Row 2: Numbers and basic operators
     "7", "8", "9", "/", "*",
     "4", "5", "6", "+", "-",
@@ -14,168 +117,1864 @@ const BUTTONS: &[&str] = &[
     "0", "pi", "e", "abs", "sqrt",
     // Row 4: Trigonometric functions
     "sin", "cos", "tan", "cbrt", "round",
-    // Row 5: Logarithmic and rounding functions
-    "log", "log10", "floor", "ceil", "="
+    // Row 5: Logarithmic and rounding functions; ceil is a long press on round or floor
+    "log", "log10", "floor", "mod", "2nd"
+];
+
+// Secondary key layer, toggled with "2nd"
+const SECOND_BUTTONS: &[&str] = &[
+    "C", "⌫", "(", ")", "^",
+    "7", "8", "9", "/", "*",
+    "4", "5", "6", "+", "-",
+    "1", "2", "3", ".", "=",
+    "0", "pi", "i", "abs", "exp",
+    // Row 4: Special functions
+    "gamma", "lgamma", "erf", "erfc", "words",
+    "log", "log10", "±", "!", "2nd"
+];
+
+// Related inserts offered on a long press or right click
+const ALTERNATES: &[(&str, &[&str])] = &[
+    ("sin", &["asin", "sinh", "asinh"]),
+    ("cos", &["acos", "cosh", "acosh"]),
+    ("tan", &["atan", "tanh", "atanh"]),
+    ("sqrt", &["cbrt", "^"]),
+    ("cbrt", &["sqrt", "^"]),
+    ("log", &["log10", "exp"]),
+    ("log10", &["log", "exp"]),
+    ("exp", &["log", "e"]),
+    ("round", &["floor", "ceil"]),
+    ("floor", &["ceil", "round"]),
+    ("ceil", &["floor", "round"]),
+    ("pi", &["tau", "phi"]),
+    ("e", &["exp", "log"]),
+    ("gamma", &["lgamma", "!"]),
+    ("erf", &["erfc"]),
+    ("^", &["sqrt", "cbrt"]),
+    ("mod", &["abs"]),
 ];
 
+// Holding a button this long (seconds) opens its alternates
+const LONG_PRESS: f64 = 0.5;
+
+// Evaluations done within this are shown at once instead of in the background
+const QUICK_EVALUATION: Duration = Duration::from_millis(200);
+
+// Storage keys for the user's exchange-rate table and constants
+const CURRENCIES_KEY: &str = "currencies";
+const USER_CONSTANTS_KEY: &str = "user_constants";
+const WORKSPACES_KEY: &str = "workspaces";
+const HISTORY_KEY: &str = "history";
+const TAPE_KEY: &str = "tape";
+const SETTINGS_KEY: &str = "settings";
+const MODES_KEY: &str = "modes";
+
+// Starts the locked-down mode for exams: nothing saved or loaded, no
+// clipboard, no downloads, no user constants and no event commands
+const EXAM_FLAG: &str = "--exam";
+
+#[derive(Default, PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum Tab {
+    #[default]
+    Calculator,
+    Converter,
+    Currency,
+    Programmer,
+    Tape,
+    Formulas,
+    Worksheet,
+    Graph,
+    Polynomial,
+    Solver,
+    Matrices,
+    Statistics,
+    Finance,
+}
+
+impl Tab {
+    const ALL: [Tab; 13] = [
+        Tab::Calculator, Tab::Converter, Tab::Currency, Tab::Programmer, Tab::Tape, Tab::Formulas, Tab::Worksheet, Tab::Graph,
+        Tab::Polynomial, Tab::Solver, Tab::Matrices, Tab::Statistics, Tab::Finance,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Tab::Calculator => "Calculadora",
+            Tab::Converter => "Conversión",
+            Tab::Currency => "Divisas",
+            Tab::Programmer => "Programador",
+            Tab::Tape => "Cinta",
+            Tab::Formulas => "Fórmulas",
+            Tab::Worksheet => "Hoja",
+            Tab::Graph => "Gráfica",
+            Tab::Polynomial => "Polinomios",
+            Tab::Solver => "Resolver",
+            Tab::Matrices => "Matrices",
+            Tab::Statistics => "Estadística",
+            Tab::Finance => "Finanzas",
+        }
+    }
+}
+
+#[derive(Default)]
 struct CalculatorApp {
+    tab: Tab,
     display: String,
+    // The display text as of the last frame, to notice changes made elsewhere
+    shown_display: String,
+    // Earlier display texts; `undo_seen` is the text last recorded and
+    // `typed` whether the keyboard changed it this frame
+    undo: Undo<String>,
+    undo_seen: String,
+    // Assignments and constant edits, undone separately from the display
+    timeline: Timeline,
+    typed: bool,
+    // History entry shown by Up/Down and the text that was there before
+    recall: Option<(usize, String)>,
     result: String,
+    // The value behind `result`, for views that need more than its text
+    value: Option<Value>,
     error: Option<String>,
+    // Where a unit-mode expression stopped making dimensional sense
+    mismatch: Option<dimensions::Mismatch>,
+    hints: Vec<String>,
+    // Power of ten the result is shown with after ENG→/←ENG
+    eng_exponent: Option<i32>,
+    // Informational message about an automatic correction
+    notice: Option<String>,
+    warnings: Vec<String>,
+    second: bool,
+    // Backspace removes a whole token such as sqrt( at once
+    token_backspace: bool,
+    // Log-scale gauge of the result against recent ones
+    magnitude_bar: bool,
+    // Seconds between recalculations of results that change over time, 0 for never
+    auto_recalc: u32,
+    // The expression behind the current result and when it was last evaluated
+    calculated: Option<String>,
+    calculated_at: f64,
+    calculator: Calculator,
+    // Keypad and Enter work on a stack instead of the display
+    rpn_mode: bool,
+    rpn: Rpn,
+    // Language of the error messages
+    language: Locale,
+    theme: Theme,
+    button_size: ButtonSize,
+    format: NumberFormat,
+    history: History,
+    show_history: bool,
+    // Two history entries shown side by side
+    comparison: Option<(history::Entry, history::Entry)>,
+    share_link: Option<String>,
+    import_link: String,
+    presentation: bool,
+    // Teacher mode hides each new result until revealed
+    teacher_mode: bool,
+    // Saved results that may come out different under the current settings
+    reproducibility: Option<Reproducibility>,
+    // Show number results also as the closest simple fraction
+    as_fraction: bool,
+    // Show results in exact form when they have one, 2√2 rather than 2.828…
+    exact_results: bool,
+    // The exact form of the current result and whether the decimal is shown
+    // instead for now
+    exact: Option<String>,
+    decimal: bool,
+    // Evaluation running in the background after =
+    evaluation: Option<Evaluation>,
+    // Seconds an evaluation must take to notify its result; 0 never does
+    notify_after: u32,
+    // Show integer results also in hex, octal and binary
+    bases: bool,
+    // Captures the engine's log records for the debug panel
+    debug_log: bool,
+    report: Option<report::Report>,
+    // Cases run and failures of the last self-test
+    self_test: Option<(usize, Vec<String>)>,
+    revealed: bool,
+    quiz: Quiz,
+    constant_search: String,
+    // "Constantes propias" dialog: open flag, name and value being added, last error
+    constants_open: bool,
+    settings_open: bool,
+    keybindings: Keybindings,
+    keybindings_open: bool,
+    // Shell commands run on startup, results and errors
+    hooks: Hooks,
+    hooks_open: bool,
+    new_constant: (String, String),
+    constant_error: Option<String>,
+    converter: Converter,
+    currency: CurrencyConverter,
+    programmer: Programmer,
+    tape: Tape,
+    formulas: Formulas,
+    polynomials: Polynomials,
+    equations: Equations,
+    matrix_editor: MatrixEditor,
+    statistics: Statistics,
+    finance: Finance,
+    worksheet: Worksheet,
+    graph: Graph,
+    workspaces: Workspaces,
+    // Startup picker, shown once when saved workspaces exist
+    workspace_picker: bool,
+    workspaces_open: bool,
+    // Counts of this session's evaluations and errors
+    usage: Usage,
+    usage_open: bool,
+    // Started with --exam; stays on until the app closes
+    exam: bool,
 }
 
-impl Default for CalculatorApp {
-    fn default() -> Self {
-        Self {
-            display: String::new(),
-            result: String::new(),
-            error: None,
-        }
+impl CalculatorApp {
+    // Keeps exam mode's restrictions in force whatever the settings or
+    // windows say, and drops clipboard keys before any widget sees them
+    fn lock_exam(&mut self, ctx: &egui::Context) {
+        self.calculator.fetch = false;
+        self.calculator.user_constants.clear();
+        self.constants_open = false;
+        self.workspaces_open = false;
+        self.hooks_open = false;
+        ctx.input_mut(|i| i.events.retain(|event| !matches!(event, egui::Event::Copy | egui::Event::Cut | egui::Event::Paste(_))));
     }
-}
 
-impl CalculatorApp {
     fn calculate(&mut self) {
         self.error = None;
-        match Calculator::parse(&self.display) {
-            Ok(tokens) => {
-                let expr = Calculator::expression(tokens);
-                match Calculator::evaluate(expr) {
-                    Ok(result) => {
-                        self.result = format!("{}", result);
-                    },
-                    Err(e) => {
-                        self.error = Some(format!("{:?}", e));
-                    }
+        self.mismatch = None;
+        self.revealed = false;
+        self.hints.clear();
+        self.notice = None;
+        self.warnings.clear();
+        self.value = None;
+        self.exact = None;
+        self.eng_exponent = None;
+        self.calculated = None;
+        // Close any parentheses left open, as most scientific calculators do
+        if let Some(missing @ 1..) = Calculator::unclosed_parens(&self.display) {
+            self.display.push_str(&")".repeat(missing));
+            self.notice = Some(format!("Se agregaron {} ')' automáticamente", missing));
+        }
+        self.evaluation = Some(Evaluation::start(&self.calculator, &self.display));
+        // Most results are ready at once and show without going through a frame
+        self.finish_evaluation(QUICK_EVALUATION);
+    }
+
+    // Takes the result of the running evaluation if it has finished within
+    // `wait`; false while it's still going
+    fn finish_evaluation(&mut self, wait: Duration) -> bool {
+        let Some(finished) = self.evaluation.as_ref().and_then(|evaluation| evaluation.finished(wait)) else {
+            return false;
+        };
+        let expression = self.evaluation.take().unwrap().expression;
+        let functions = self.calculator.functions(&expression);
+        self.usage.record(&expression, &functions, finished.result.as_ref().err());
+        self.calculator.variables.extend(finished.assigned);
+        match finished.result {
+            Ok((result, warnings)) => {
+                self.result = self.format.value(&result);
+                self.exact = self.exact_form(&expression, &result);
+                self.decimal = false;
+                self.history.push(&expression, &self.result, Conditions::current(&self.calculator, &self.format));
+                self.value = Some(result);
+                self.warnings = warnings;
+                if !self.exam {
+                    self.hooks.run(hooks::Event::Result, &expression, &self.result);
                 }
+                self.calculated = Some(expression);
             },
             Err(e) => {
-                self.error = Some(format!("{:?}", e));
+                self.show_error(&expression, e);
+                if !self.exam {
+                    self.hooks.run(hooks::Event::Error, &expression, self.error.as_deref().unwrap_or_default());
+                }
+            },
+        }
+        true
+    }
+
+    // Checks on a long evaluation each frame, with a notification when it
+    // ends while the window is in the background
+    fn poll_evaluation(&mut self, ctx: &egui::Context) {
+        let Some(evaluation) = &self.evaluation else {
+            return;
+        };
+        let (expression, elapsed) = (evaluation.expression.clone(), evaluation.elapsed());
+        if !self.finish_evaluation(Duration::ZERO) {
+            ctx.request_repaint_after(Duration::from_millis(100));
+            return;
+        }
+        let background = ctx.input(|i| i.viewport().focused == Some(false));
+        if self.notify_after > 0 && elapsed.as_secs() >= self.notify_after as u64 && background {
+            background::notify(&expression, self.error.as_deref().unwrap_or(&self.result));
+        }
+    }
+
+    // Evaluates the current result's expression again for a fresh now(),
+    // today() or currency rate, without adding to the history
+    fn recalculate(&mut self, ctx: &egui::Context) {
+        if self.auto_recalc == 0 || self.error.is_some() {
+            return;
+        }
+        let Some(expr) = self.calculated.clone().filter(|expr| self.calculator.volatile(expr)) else {
+            return;
+        };
+        let now = ctx.input(|i| i.time);
+        let interval = self.auto_recalc as f64;
+        if now - self.calculated_at >= interval {
+            self.calculated_at = now;
+            self.currency.reload(&mut self.calculator);
+            let executed = self.calculator.execute(&expr);
+            self.timeline.skip(&self.calculator);
+            match executed {
+                Ok((result, _)) => {
+                    self.result = self.format.value(&result);
+                    self.exact = self.exact_form(&expr, &result);
+                    self.value = Some(result);
+                    self.eng_exponent = None;
+                },
+                Err(e) => self.show_error(&expr, e),
             }
         }
+        ctx.request_repaint_after(std::time::Duration::from_secs_f64(interval - (now - self.calculated_at)));
     }
 
-    fn handle_input(&mut self, input: &str) {
-        match input {
-            "C" => {
-                self.display.clear();
-                self.result.clear();
-                self.error = None;
+    // Records the display as it was before any change since the last frame,
+    // from typing, buttons, clearing or anywhere else
+    fn track_undo(&mut self, ctx: &egui::Context) {
+        if self.display != self.undo_seen {
+            let previous = std::mem::replace(&mut self.undo_seen, self.display.clone());
+            self.undo.record(previous, ctx.input(|i| i.time), self.typed);
+        }
+        self.typed = false;
+    }
+
+    fn undo(&mut self) {
+        if let Some(previous) = self.undo.undo(self.display.clone()) {
+            self.display = previous;
+            self.undo_seen = self.display.clone();
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(next) = self.undo.redo(self.display.clone()) {
+            self.display = next;
+            self.undo_seen = self.display.clone();
+        }
+    }
+
+    // Shifts the result's exponent by 3 as the ENG keys of a scientific
+    // calculator do: ENG→ (`smaller` false) moves digits into the mantissa,
+    // 0.00465 → 4.65e-3 → 4650e-6, and ←ENG goes back
+    fn eng_step(&mut self, smaller: bool) {
+        let Some(Value::Number(x)) = self.value else {
+            return;
+        };
+        let exponent = match (self.eng_exponent, smaller) {
+            (None, false) => NumberFormat::engineering_exponent(x),
+            (None, true) => NumberFormat::engineering_exponent(x) + 3,
+            (Some(exponent), false) => exponent - 3,
+            (Some(exponent), true) => exponent + 3,
+        };
+        self.eng_exponent = Some(exponent);
+        self.result = NumberFormat::with_exponent(x, exponent);
+        self.decimal = true;
+    }
+
+    // The result as 2√2 or 5π/6 when exact results are on and it isn't
+    // just a whole number
+    fn exact_form(&self, expression: &str, result: &Value) -> Option<String> {
+        if !self.exact_results {
+            return None;
+        }
+        self.calculator.exact(expression, result).filter(|exact| exact.is_informative()).map(|exact| exact.to_string())
+    }
+
+    // The result as it's shown, the exact form unless asked for the decimal
+    fn shown_result(&self) -> &str {
+        match &self.exact {
+            Some(exact) if !self.decimal => exact,
+            _ => &self.result,
+        }
+    }
+
+    // Applies a new format, writing the current result out again with it
+    fn set_format(&mut self, format: NumberFormat) {
+        self.format = format;
+        self.eng_exponent = None;
+        if let Some(value) = &self.value {
+            self.result = self.format.value(value);
+        }
+    }
+
+    fn show_error(&mut self, expr: &str, e: Error) {
+        self.hints = hints::suggest(expr, &e);
+        if self.calculator.unit_mode {
+            self.mismatch = dimensions::explain(&self.calculator, expr);
+        }
+        self.error = Some(e.to_string());
+    }
+
+    // Reports what's wrong with the display before '=' is pressed, without
+    // recording or assigning anything
+    fn validate(&mut self) {
+        let mut expr = self.display.clone();
+        if let Some(missing @ 1..) = Calculator::unclosed_parens(&expr) {
+            expr.push_str(&")".repeat(missing));
+        }
+        self.error = None;
+        self.mismatch = None;
+        self.hints.clear();
+        if let Err(e) = self.calculator.check(&expr) {
+            self.show_error(&expr, e);
+        }
+    }
+
+    fn result_hidden(&self) -> bool {
+        self.teacher_mode && !self.revealed
+    }
+
+    fn share(&mut self, ctx: &egui::Context) {
+        let link = SharedCalculation {
+            expression: self.display.clone(),
+            real_only: self.calculator.real_only,
+            variables: self.calculator.referenced(&self.display),
+        }.to_link();
+        ctx.copy_text(link.clone());
+        self.share_link = Some(link);
+    }
+
+    fn load_shared(&mut self, shared: SharedCalculation) {
+        self.display = shared.expression;
+        self.calculator.real_only = shared.real_only;
+        // A hand-edited link may carry names that can't be assigned; those are skipped
+        for (name, value) in shared.variables {
+            let _ = self.calculator.assign(&name, value);
+        }
+        self.result.clear();
+        self.error = None;
+        self.calculate();
+    }
+
+    fn import(&mut self, link: &str) -> bool {
+        match SharedCalculation::from_link(link) {
+            Some(shared) => {
+                self.load_shared(shared);
+                true
             },
+            None => false,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.display.clear();
+        self.result.clear();
+        self.exact = None;
+        self.value = None;
+        self.eng_exponent = None;
+        self.calculated = None;
+        self.error = None;
+        self.mismatch = None;
+        self.notice = None;
+        self.warnings.clear();
+        self.share_link = None;
+    }
+
+    fn handle_input(&mut self, ctx: &egui::Context, input: &str) {
+        if self.rpn_mode && input != "2nd" {
+            self.rpn.key(input, &self.calculator);
+            return;
+        }
+        match input {
+            "C" => self.clear(),
+            "⌫" => self.backspace(ctx),
             "=" => self.calculate(),
-            _ => {
-                self.display.push_str(input);
-                // Add opening parenthesis automatically for functions
-                match input {
-                    "sin" | "cos" | "tan" | "sqrt" | "cbrt" | "log" | "log10" |
-                    "abs" | "floor" | "ceil" | "round" => {
-                        self.display.push('(');
-                    },
-                    _ => {}
+            "2nd" => self.second = !self.second,
+            // Add opening parenthesis automatically for functions
+            _ if Function::from_name(input).is_some() => self.insert(ctx, &format!("{}(", input), ")"),
+            _ => self.insert(ctx, input, ""),
+        }
+    }
+
+    // A result at the cursor; anything but a plain non-negative number needs
+    // parentheses to stay one operand
+    fn insert_value(&mut self, ctx: &egui::Context, value: &str) {
+        if value.parse::<f64>().is_ok_and(|n| n >= 0.0) {
+            self.insert(ctx, value, "");
+        } else {
+            self.insert(ctx, &format!("({})", value), "");
+        }
+    }
+
+    fn insert_ans(&mut self, ctx: &egui::Context) {
+        if let Some(entry) = self.history.entries.last() {
+            self.insert_value(ctx, &entry.result.clone());
+        }
+    }
+
+    // Deletes the selection, or else the char before the cursor, or the whole
+    // token before it with token-aware deletion on
+    fn backspace(&mut self, ctx: &egui::Context) {
+        let selection = self.selection(ctx);
+        let cursor = if !selection.is_empty() {
+            editing::insert(&mut self.display, selection, "", "")
+        } else if self.token_backspace {
+            editing::delete_previous_token(&mut self.display, selection.start)
+        } else {
+            let start = selection.start.saturating_sub(1);
+            editing::insert(&mut self.display, start..selection.start, "", "")
+        };
+        let mut state = egui::TextEdit::load_state(ctx, display_id()).unwrap_or_default();
+        state.cursor.set_char_range(Some(CCursorRange::one(CCursor::new(cursor))));
+        state.store(ctx, display_id());
+        self.shown_display = self.display.clone();
+    }
+
+    // Selected chars in the display, or the cursor as an empty range
+    fn selection(&self, ctx: &egui::Context) -> Range<usize> {
+        let len = self.display.chars().count();
+        egui::TextEdit::load_state(ctx, display_id())
+            .and_then(|state| state.cursor.char_range())
+            .map_or(len..len, |range| {
+                let (a, b) = (range.primary.index.min(len), range.secondary.index.min(len));
+                a.min(b)..a.max(b)
+            })
+    }
+
+    // Inserts at the cursor, replacing or wrapping the selection, and keeps
+    // the display focused with the cursor after the new text
+    fn insert(&mut self, ctx: &egui::Context, open: &str, close: &str) {
+        let selection = self.selection(ctx);
+        let cursor = editing::insert(&mut self.display, selection, open, close);
+        let mut state = egui::TextEdit::load_state(ctx, display_id()).unwrap_or_default();
+        state.cursor.set_char_range(Some(CCursorRange::one(CCursor::new(cursor))));
+        state.store(ctx, display_id());
+        ctx.memory_mut(|memory| memory.request_focus(display_id()));
+        self.shown_display = self.display.clone();
+    }
+
+    fn settings(&self) -> Settings {
+        Settings {
+            format: self.format,
+            angle: self.calculator.angle,
+            theme: self.theme,
+            language: self.language,
+            words_locale: self.calculator.locale,
+            button_size: self.button_size,
+            real_only: self.calculator.real_only,
+            integer_mode: self.calculator.integer_mode,
+            unit_mode: self.calculator.unit_mode,
+            juxtaposition: self.calculator.juxtaposition,
+            token_backspace: self.token_backspace,
+            magnitude_bar: self.magnitude_bar,
+            rpn_mode: self.rpn_mode,
+            auto_recalc: self.auto_recalc,
+            teacher_mode: self.teacher_mode,
+            as_fraction: self.as_fraction,
+            bases: self.bases,
+            fetch: self.calculator.fetch,
+            notify_after: self.notify_after,
+            keybindings: self.keybindings.clone(),
+            hooks: self.hooks.clone(),
+            exact_results: self.exact_results,
+        }
+    }
+
+    fn apply_settings(&mut self, settings: Settings) {
+        self.format = settings.format;
+        self.calculator.angle = settings.angle;
+        self.theme = settings.theme;
+        self.language = settings.language;
+        self.calculator.locale = settings.words_locale;
+        self.button_size = settings.button_size;
+        self.calculator.real_only = settings.real_only;
+        self.calculator.integer_mode = settings.integer_mode;
+        self.calculator.unit_mode = settings.unit_mode;
+        self.calculator.juxtaposition = settings.juxtaposition;
+        self.token_backspace = settings.token_backspace;
+        self.magnitude_bar = settings.magnitude_bar;
+        self.rpn_mode = settings.rpn_mode;
+        self.auto_recalc = settings.auto_recalc;
+        self.teacher_mode = settings.teacher_mode;
+        self.as_fraction = settings.as_fraction;
+        self.bases = settings.bases;
+        self.calculator.fetch = settings.fetch;
+        self.notify_after = settings.notify_after;
+        self.keybindings = settings.keybindings;
+        self.hooks = settings.hooks;
+        self.exact_results = settings.exact_results;
+    }
+
+    fn modes(&self) -> Modes {
+        Modes {
+            tab: self.tab,
+            display: self.display.clone(),
+            converter: (self.converter.value.clone(), self.converter.from.to_string(), self.converter.to.to_string()),
+            currency: (self.currency.amount.clone(), self.currency.from.clone(), self.currency.to.clone()),
+            programmer: (self.programmer.radix, self.programmer.word, self.programmer.display.clone()),
+            graph: Some(self.graph_state()),
+        }
+    }
+
+    fn graph_state(&self) -> GraphState {
+        (
+            self.graph.curves.iter().map(|curve| (curve.expr.clone(), curve.color, curve.visible)).collect(),
+            self.graph.view,
+            self.graph.auto_y,
+        )
+    }
+
+    // Older saves have no curves; they get the default graph
+    fn restore_graph(&mut self, graph: Option<GraphState>) {
+        self.graph = Graph::default();
+        if let Some((curves, view, auto_y)) = graph.filter(|(curves, ..)| !curves.is_empty()) {
+            self.graph.curves = curves.into_iter().map(|(expr, color, visible)| {
+                let mut curve = Curve::new(&expr, color);
+                curve.visible = visible;
+                curve
+            }).collect();
+            (self.graph.view, self.graph.auto_y) = (view, auto_y);
+        }
+    }
+
+    // What the last session left. An exam starts blank: only the settings
+    // carry over, less the hooks, so nothing typed, plotted or scripted
+    // beforehand is there when it begins
+    fn restore(&mut self, storage: &dyn eframe::Storage) {
+        if let Some(settings) = eframe::get_value(storage, SETTINGS_KEY) {
+            self.apply_settings(settings);
+        }
+        if self.exam {
+            self.hooks = Hooks::default();
+            return;
+        }
+        if let Some(modes) = eframe::get_value(storage, MODES_KEY) {
+            self.restore_modes(modes);
+        }
+        if let Some(currencies) = eframe::get_value(storage, CURRENCIES_KEY) {
+            self.calculator.currencies = currencies;
+        }
+        if let Some(constants) = eframe::get_value(storage, USER_CONSTANTS_KEY) {
+            self.calculator.user_constants = constants;
+        }
+        if let Some(history) = eframe::get_value::<History>(storage, HISTORY_KEY) {
+            let saved = history.entries.last().and_then(|entry| entry.conditions.as_ref());
+            self.reproducibility = Reproducibility::check(saved, &Conditions::current(&self.calculator, &self.format));
+            self.history = history;
+        }
+        if let Some(tape) = eframe::get_value(storage, TAPE_KEY) {
+            self.tape = tape;
+        }
+        if let Some(mut workspaces) = eframe::get_value::<Workspaces>(storage, WORKSPACES_KEY) {
+            // Nothing is loaded until the picker says so, so nothing gets overwritten
+            workspaces.last = workspaces.current.take();
+            self.workspace_picker = !workspaces.saved.is_empty();
+            self.workspaces = workspaces;
+        }
+    }
+
+    fn restore_modes(&mut self, modes: Modes) {
+        self.tab = modes.tab;
+        self.display = modes.display;
+        let (value, from, to) = modes.converter;
+        self.converter.restore(value, &from, &to);
+        (self.currency.amount, self.currency.from, self.currency.to) = modes.currency;
+        (self.programmer.radix, self.programmer.word, self.programmer.display) = modes.programmer;
+        self.restore_graph(modes.graph);
+    }
+
+    fn workspace(&self) -> Workspace {
+        Workspace {
+            tab: self.tab,
+            display: self.display.clone(),
+            result: self.result.clone(),
+            value: self.value.clone(),
+            variables: self.calculator.variables.clone(),
+            history: self.history.entries.clone(),
+            real_only: self.calculator.real_only,
+            integer_mode: self.calculator.integer_mode,
+            unit_mode: self.calculator.unit_mode,
+            juxtaposition: self.calculator.juxtaposition,
+            locale: self.calculator.locale,
+            format: self.format,
+            converter: (self.converter.value.clone(), self.converter.from.to_string(), self.converter.to.to_string()),
+            currency: (self.currency.amount.clone(), self.currency.from.clone(), self.currency.to.clone()),
+            programmer: (self.programmer.radix, self.programmer.word, self.programmer.display.clone()),
+            worksheet: (self.worksheet.lines.clone(), self.worksheet.path.clone()),
+            graph: Some(self.graph_state()),
+            usage: self.usage.clone(),
+            conditions: Some(Conditions::current(&self.calculator, &self.format)),
+        }
+    }
+
+    fn load_workspace(&mut self, workspace: Workspace) {
+        self.clear();
+        self.tab = workspace.tab;
+        self.display = workspace.display;
+        self.result = workspace.result;
+        self.exact = None;
+        self.value = workspace.value;
+        self.calculator.variables = workspace.variables;
+        self.history.clear();
+        self.history.entries = workspace.history;
+        self.history.truncate();
+        self.calculator.real_only = workspace.real_only;
+        self.calculator.integer_mode = workspace.integer_mode;
+        self.calculator.unit_mode = workspace.unit_mode;
+        self.calculator.juxtaposition = workspace.juxtaposition;
+        self.calculator.locale = workspace.locale;
+        self.format = workspace.format;
+        let (value, from, to) = workspace.converter;
+        self.converter.restore(value, &from, &to);
+        (self.currency.amount, self.currency.from, self.currency.to) = workspace.currency;
+        (self.programmer.radix, self.programmer.word, self.programmer.display) = workspace.programmer;
+        let (lines, path) = workspace.worksheet;
+        self.worksheet.restore(lines, path);
+        self.restore_graph(workspace.graph);
+        self.usage = workspace.usage;
+        self.reproducibility = Reproducibility::check(workspace.conditions.as_ref(), &Conditions::current(&self.calculator, &self.format));
+    }
+
+    fn workspace_action(&mut self, action: workspace::Action) {
+        match action {
+            workspace::Action::Open(name) => {
+                // Keep the work in progress before switching away from it
+                if let Some(current) = self.workspaces.current.clone() {
+                    self.workspaces.saved.insert(current, self.workspace());
+                }
+                if let Some(workspace) = self.workspaces.saved.get(&name).cloned() {
+                    self.load_workspace(workspace);
+                    self.workspaces.current = Some(name);
+                }
+            },
+            workspace::Action::SaveAs(name) => {
+                self.workspaces.saved.insert(name.clone(), self.workspace());
+                self.workspaces.current = Some(name);
+            },
+            workspace::Action::Delete(name) => {
+                self.workspaces.saved.remove(&name);
+                if self.workspaces.current.as_ref() == Some(&name) {
+                    self.workspaces.current = None;
                 }
+            },
+            workspace::Action::Skip => self.workspaces.current = None,
+        }
+    }
+
+    // Read-only view for projecting: large display, no keypad
+    fn show_presentation(&mut self, ui: &mut egui::Ui) {
+        ui.vertical_centered(|ui| {
+            if ui.button("Salir de presentación (F5)").clicked() {
+                self.presentation = false;
             }
+            ui.add_space(30.0);
+            ui.add(egui::Label::new(egui::RichText::new(&self.display).monospace().size(48.0)).wrap());
+            ui.add_space(20.0);
+            if let Some(error) = &self.error {
+                ui.label(egui::RichText::new(error).size(32.0).color(egui::Color32::RED));
+            } else if !self.result.is_empty() && self.result_hidden() {
+                if ui.button(egui::RichText::new("= ••••• (revelar)").size(48.0)).clicked() {
+                    self.revealed = true;
+                }
+            } else if let Some(Value::Matrix(m)) = &self.value {
+                matrix::show(ui, m, &self.format, 32.0);
+            } else if let Some(Value::Eigen(e)) = &self.value {
+                eigen::show(ui, e, &self.format, 32.0);
+            } else if self.result.len() > LONG_RESULT {
+                show_long_result(ui, &self.result, 32.0);
+            } else if !self.result.is_empty() {
+                ui.add(egui::Label::new(
+                    egui::RichText::new(format!("= {}", self.shown_result())).size(64.0).strong().color(egui::Color32::GREEN)
+                ).wrap());
+            }
+        });
+    }
+
+    // Steps to an older or newer expression from the history, like a shell;
+    // past the newest, the text being written before comes back
+    fn recall(&mut self, older: bool) {
+        let entries = &self.history.entries;
+        // Editing a recalled expression starts over from the newest
+        let current = self.recall.as_ref()
+            .map(|(i, _)| *i)
+            .filter(|&i| entries.get(i).is_some_and(|entry| entry.expression == self.display));
+        if current.is_none() {
+            self.recall = None;
+        }
+        // Repeats of what is already shown are skipped
+        let differs = |&i: &usize| entries[i].expression != self.display;
+        let next = match (current, older) {
+            (None, true) => (0..entries.len()).rev().find(differs),
+            (None, false) => return,
+            (Some(i), true) => (0..i).rev().find(differs),
+            (Some(i), false) => (i + 1..entries.len()).find(differs),
+        };
+        // Nothing older: stay on the oldest
+        if next.is_none() && older {
+            return;
+        }
+        match next {
+            Some(i) => {
+                let draft = self.recall.take().map_or_else(|| self.display.clone(), |(_, draft)| draft);
+                self.display = entries[i].expression.clone();
+                self.recall = Some((i, draft));
+            },
+            None => {
+                if let Some((_, draft)) = self.recall.take() {
+                    self.display = draft;
+                }
+            },
         }
     }
-}
 
-impl eframe::App for CalculatorApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.vertical(|ui| {
-                // Title
-                ui.heading("Calculadora Científica Guizar");
-                
-                // Display area with border and padding
-                ui.add_space(10.0);
-                egui::Frame::dark_canvas(ui.style()).show(ui, |ui| {
-                    ui.add_space(5.0);
+    // Editable display; Ctrl+Backspace and Ctrl+Left/Right work a whole token at a time
+    fn show_display(&mut self, ui: &mut egui::Ui) {
+        let id = display_id();
+        if ui.memory(|memory| memory.has_focus(id)) {
+            if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp)) {
+                self.recall(true);
+            } else if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown)) {
+                self.recall(false);
+            }
+        }
+        // Text replaced from elsewhere (results, history, workspaces) puts the cursor at the end
+        if self.display != self.shown_display {
+            if let Some(mut state) = egui::TextEdit::load_state(ui.ctx(), id) {
+                state.cursor.set_char_range(Some(CCursorRange::one(CCursor::new(self.display.chars().count()))));
+                state.store(ui.ctx(), id);
+            }
+        }
+        if ui.memory(|memory| memory.has_focus(id)) {
+            if let Some(mut state) = egui::TextEdit::load_state(ui.ctx(), id) {
+                let range = state.cursor.char_range()
+                    .unwrap_or_else(|| CCursorRange::one(CCursor::new(self.display.chars().count())));
+                let cursor = range.primary.index;
+                let selecting = ui.input(|i| i.modifiers.shift);
+                let moved = ui.input_mut(|i| {
+                    // With a selection, Ctrl+Backspace keeps its usual meaning
+                    let token_key = if self.token_backspace { egui::Modifiers::NONE } else { egui::Modifiers::COMMAND };
+                    if range.primary == range.secondary && i.consume_key(token_key, egui::Key::Backspace) {
+                        Some(editing::delete_previous_token(&mut self.display, cursor))
+                    } else if i.consume_key(egui::Modifiers::COMMAND, egui::Key::ArrowLeft) {
+                        Some(editing::previous_boundary(&self.display, cursor))
+                    } else if i.consume_key(egui::Modifiers::COMMAND, egui::Key::ArrowRight) {
+                        Some(editing::next_boundary(&self.display, cursor))
+                    } else {
+                        None
+                    }
+                });
+                if let Some(moved) = moved {
+                    let secondary = if selecting { range.secondary } else { CCursor::new(moved) };
+                    state.cursor.set_char_range(Some(CCursorRange { primary: CCursor::new(moved), secondary }));
+                    state.store(ui.ctx(), id);
+                }
+            }
+        }
+        // Pasted text is cleaned up before the display gets it; with nothing
+        // focused it goes in at the cursor
+        let focused = ui.memory(|memory| memory.has_focus(id));
+        let unfocused = ui.memory(|memory| memory.focused().is_none());
+        let mut pasted = false;
+        let mut pasted_text = Vec::new();
+        if focused || unfocused {
+            ui.input_mut(|i| i.events.retain_mut(|event| match event {
+                egui::Event::Paste(text) if !text.trim().starts_with(share::SCHEME) => {
+                    pasted = true;
+                    *text = editing::sanitize(text);
+                    if unfocused {
+                        pasted_text.push(std::mem::take(text));
+                    }
+                    focused
+                },
+                _ => true,
+            }));
+        }
+        for text in pasted_text {
+            self.insert(ui.ctx(), &text, "");
+        }
+        let cursor = egui::TextEdit::load_state(ui.ctx(), id)
+            .and_then(|state| state.cursor.char_range())
+            .map_or(self.display.len(), |range| {
+                self.display.char_indices().nth(range.primary.index).map_or(self.display.len(), |(i, _)| i)
+            });
+        let completion = completion::units(&self.calculator, &self.display, cursor);
+        let mut accepted = None;
+        if let Some(completion) = &completion {
+            if ui.memory(|memory| memory.has_focus(id)) && ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Tab)) {
+                accepted = Some(completion.units[0]);
+            }
+        }
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut self.display)
+                .id(id)
+                .font(egui::TextStyle::Monospace)
+                .frame(false)
+                .desired_width(f32::INFINITY)
+                // Tab accepts the first suggestion instead of moving focus
+                .lock_focus(completion.is_some())
+        );
+        self.typed |= response.changed();
+        if pasted {
+            self.validate();
+        }
+        if let Some(completion) = &completion {
+            ui.horizontal_wrapped(|ui| {
+                for unit in &completion.units {
+                    let hover = format!("{} ({})", unit.name, unit.category.name());
+                    if ui.small_button(unit.symbol).on_hover_text(hover).clicked() {
+                        accepted = Some(unit);
+                    }
+                }
+                ui.weak("Tab completa");
+            });
+        }
+        if let (Some(completion), Some(unit)) = (completion, accepted) {
+            self.display.replace_range(completion.range.clone(), unit.symbol);
+            let end = self.display[..completion.range.start].chars().count() + unit.symbol.chars().count();
+            if let Some(mut state) = egui::TextEdit::load_state(ui.ctx(), id) {
+                state.cursor.set_char_range(Some(CCursorRange::one(CCursor::new(end))));
+                state.store(ui.ctx(), id);
+            }
+            ui.memory_mut(|memory| memory.request_focus(id));
+        }
+        self.shown_display = self.display.clone();
+    }
+
+    // A quiet note when the display holds a very large expression
+    fn show_complexity(&mut self, ui: &mut egui::Ui) {
+        let Some(metrics) = self.calculator.metrics(&self.display) else {
+            return;
+        };
+        if metrics.tokens <= LARGE_TOKENS && metrics.depth <= LARGE_DEPTH && metrics.cost <= LARGE_COST {
+            return;
+        }
+        ui.horizontal(|ui| {
+            ui.weak(format!(
+                "Expresión grande: {} tokens, {} niveles de paréntesis, coste ≈ {:.0}",
+                metrics.tokens, metrics.depth, metrics.cost
+            ));
+            if ui.small_button("Pasar a una hoja").on_hover_text("Dividirla en líneas con variables es más fácil de revisar").clicked() {
+                if self.worksheet.lines.last().is_some_and(|line| line.trim().is_empty()) {
+                    self.worksheet.lines.pop();
+                }
+                self.worksheet.lines.push(self.display.clone());
+                self.tab = Tab::Worksheet;
+            }
+        });
+    }
+
+    fn show_comparison_window(&mut self, ctx: &egui::Context) {
+        let Some((before, after)) = &self.comparison else {
+            return;
+        };
+        let mut open = true;
+        egui::Window::new("Comparar").open(&mut open).resizable(true).show(ctx, |ui| {
+            compare::show(ui, before, after, &self.calculator, &self.format);
+        });
+        if !open {
+            self.comparison = None;
+        }
+    }
+
+    fn show_constants_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.constants_open;
+        egui::Window::new("Constantes propias").open(&mut open).resizable(false).show(ctx, |ui| {
+            ui.small("Se guardan entre sesiones y se usan por su nombre en cualquier expresión.");
+            let mut removed = None;
+            egui::Grid::new("user_constants").striped(true).show(ui, |ui| {
+                for (name, value) in self.calculator.user_constants.iter_mut() {
+                    ui.monospace(name);
+                    ui.add(egui::DragValue::new(value).speed(0.01).max_decimals(12));
+                    if ui.small_button("✖").on_hover_text("Borrar").clicked() {
+                        removed = Some(name.clone());
+                    }
+                    ui.end_row();
+                }
+            });
+            if let Some(name) = removed {
+                self.calculator.user_constants.remove(&name);
+            }
+            ui.separator();
+            ui.horizontal(|ui| {
+                let (name, value) = &mut self.new_constant;
+                ui.add(egui::TextEdit::singleline(name).hint_text("rho").desired_width(60.0));
+                ui.label("=");
+                ui.add(egui::TextEdit::singleline(value).hint_text("1.225").desired_width(100.0));
+                if ui.button("Agregar").clicked() {
+                    self.constant_error = self.add_constant().err();
+                }
+            });
+            if let Some(error) = &self.constant_error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+        });
+        self.constants_open = open;
+    }
+
+    fn show_keybindings_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.keybindings_open;
+        egui::Window::new("Atajos de teclado").open(&mut open).resizable(false).show(ctx, |ui| {
+            ui.small("Se guardan con la configuración. Una tecla ya usada pasa a la nueva acción.");
+            self.keybindings.show(ui);
+        });
+        self.keybindings_open = open;
+    }
+
+    fn show_hooks_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.hooks_open;
+        egui::Window::new("Comandos por evento").open(&mut open).resizable(false).show(ctx, |ui| {
+            ui.small("Se ejecutan con el intérprete del sistema sin esperar a que terminen, p. ej. para anotar cada resultado en un archivo.");
+            self.hooks.show(ui);
+        });
+        self.hooks_open = open;
+    }
+
+    fn show_usage_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.usage_open;
+        egui::Window::new("Estadísticas de la sesión").open(&mut open).resizable(false).show(ctx, |ui| {
+            let scope = self.workspaces.current.as_deref().map_or("Esta sesión".to_string(), |name| format!("Espacio «{}»", name));
+            ui.small(scope);
+            self.usage.show(ui);
+        });
+        self.usage_open = open;
+    }
+
+    fn show_settings_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.settings_open;
+        egui::Window::new("Configuración").open(&mut open).resizable(false).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.add_enabled(!self.exam, egui::Button::new("Constantes propias…")).clicked() {
+                    self.constants_open = true;
+                }
+                if ui.button("Atajos de teclado…").clicked() {
+                    self.keybindings_open = true;
+                }
+                if ui.add_enabled(!self.exam, egui::Button::new("Comandos por evento…")).clicked() {
+                    self.hooks_open = true;
+                }
+            });
+            egui::ComboBox::from_label("Ángulos")
+                .selected_text(self.calculator.angle.name())
+                .show_ui(ui, |ui| {
+                    for angle in AngleMode::ALL {
+                        ui.selectable_value(&mut self.calculator.angle, angle, angle.name());
+                    }
+                });
+            ui.checkbox(&mut self.calculator.real_only, "Solo números reales (sin complejos)");
+            ui.checkbox(&mut self.calculator.integer_mode, "Modo entero exacto (precisión arbitraria)");
+            ui.checkbox(&mut self.calculator.unit_mode, "Modo unidades (5 km + 300 m)");
+            ui.add_enabled(!self.exam, egui::Checkbox::new(&mut self.calculator.fetch, "Permitir que fetch_num() descargue datos de internet"));
+            ui.checkbox(&mut self.token_backspace, "Retroceso borra tokens completos (sqrt( de una vez)");
+            ui.checkbox(&mut self.magnitude_bar, "Barra de magnitud bajo el resultado");
+            ui.checkbox(&mut self.bases, "HEX, OCT y BIN bajo los resultados enteros");
+            ui.checkbox(&mut self.exact_results, "Resultados exactos cuando se pueda (2√2, 5π/6)");
+            ui.checkbox(&mut self.rpn_mode, "Entrada RPN: pila con ENTER, DROP y SWAP");
+            ui.horizontal(|ui| {
+                ui.label("Recalcular now(), today() y divisas cada:");
+                ui.add(egui::DragValue::new(&mut self.auto_recalc).range(0..=3600).suffix(" s"));
+                if self.auto_recalc == 0 {
+                    ui.weak("(nunca)");
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Avisar con una notificación de los cálculos de más de:");
+                ui.add(egui::DragValue::new(&mut self.notify_after).range(0..=3600).suffix(" s"));
+                if self.notify_after == 0 {
+                    ui.weak("(nunca)");
+                }
+            });
+            ui.checkbox(&mut self.teacher_mode, "Modo profesor (ocultar el resultado hasta revelarlo)");
+            if ui.checkbox(&mut self.debug_log, "Registro de depuración del motor").changed() {
+                debug::set_capture(self.debug_log);
+            }
+            ui.horizontal(|ui| {
+                ui.label("Máximo de entradas del historial:");
+                if ui.add(egui::DragValue::new(&mut self.history.limit).range(1..=100_000)).changed() {
+                    self.history.truncate();
+                }
+                if ui.button("Borrar historial").clicked() {
+                    self.history.clear();
+                }
+            });
+            let mut format = self.format;
+            egui::ComboBox::from_label("Notación")
+                .selected_text(format.notation.name())
+                .show_ui(ui, |ui| {
+                    for notation in Notation::ALL {
+                        ui.selectable_value(&mut format.notation, notation, notation.name());
+                    }
+                });
+            match format.notation {
+                Notation::Auto => {
+                    ui.horizontal(|ui| {
+                        ui.label("Notación científica desde |x| ≥ 1e");
+                        ui.add(egui::DragValue::new(&mut format.sci_above).range(1..=308));
+                    });
                     ui.horizontal(|ui| {
-                        ui.monospace(&self.display);
+                        ui.label("y por debajo de |x| < 1e");
+                        ui.add(egui::DragValue::new(&mut format.sci_below).range(-308..=0));
                     });
-                    ui.add_space(5.0);
+                },
+                Notation::Fixed => {
+                    ui.horizontal(|ui| {
+                        ui.label("Decimales:");
+                        ui.add(egui::DragValue::new(&mut format.decimals).range(0..=17));
+                    });
+                },
+                Notation::SiPrefix => {
+                    ui.weak("p n µ m k M G T; se pueden escribir igual, p. ej. 4.7k + 300 (u vale por µ)");
+                },
+                Notation::Scientific | Notation::Engineering => {},
+            }
+            ui.horizontal(|ui| {
+                ui.label("Cifras significativas:");
+                ui.add(egui::DragValue::new(&mut format.digits).range(0..=17));
+                if format.digits == 0 {
+                    ui.weak("(todas)");
+                }
+            });
+            if format.notation != Notation::Fixed {
+                ui.weak("Para un número fijo de decimales, elige la notación de decimales fijos");
+            }
+            egui::ComboBox::from_label("Separadores")
+                .selected_text(format.separators.name())
+                .show_ui(ui, |ui| {
+                    for separators in Separators::ALL {
+                        ui.selectable_value(&mut format.separators, separators, separators.name());
+                    }
+                });
+            if format.separators == Separators::Comma {
+                ui.weak("Con coma decimal, los argumentos se separan con ; p. ej. max(1,5; 2)");
+            }
+            ui.checkbox(&mut format.base_units, "Unidades en SI base (kg·m/s^2 en vez de N)");
+            if format != self.format {
+                self.set_format(format);
+            }
+            egui::ComboBox::from_label("Multiplicación implícita")
+                .selected_text(self.calculator.juxtaposition.name())
+                .show_ui(ui, |ui| {
+                    for juxtaposition in Juxtaposition::ALL {
+                        ui.selectable_value(&mut self.calculator.juxtaposition, juxtaposition, juxtaposition.name());
+                    }
+                });
+            egui::ComboBox::from_label("Idioma de los mensajes de error")
+                .selected_text(self.language.name())
+                .show_ui(ui, |ui| {
+                    for locale in Locale::ALL {
+                        ui.selectable_value(&mut self.language, locale, locale.name());
+                    }
+                });
+            egui::ComboBox::from_label("Idioma de words()")
+                .selected_text(self.calculator.locale.name())
+                .show_ui(ui, |ui| {
+                    for locale in Locale::ALL {
+                        ui.selectable_value(&mut self.calculator.locale, locale, locale.name());
+                    }
+                });
+            egui::ComboBox::from_label("Tema")
+                .selected_text(self.theme.name())
+                .show_ui(ui, |ui| {
+                    for theme in Theme::ALL {
+                        ui.selectable_value(&mut self.theme, theme, theme.name());
+                    }
                 });
-                
+            egui::ComboBox::from_label("Tamaño de los botones")
+                .selected_text(self.button_size.name())
+                .show_ui(ui, |ui| {
+                    for size in ButtonSize::ALL {
+                        ui.selectable_value(&mut self.button_size, size, size.name());
+                    }
+                });
+        });
+        self.settings_open = open;
+    }
+
+    // The value field accepts any real expression, e.g. 2*pi
+    fn add_constant(&mut self) -> Result<(), String> {
+        let (name, value) = &self.new_constant;
+        let value = match self.calculator.calculate(value) {
+            Ok(Value::Number(n)) => n,
+            Ok(Value::Integer(n)) => n.to_f64().unwrap_or(f64::INFINITY),
+            Ok(_) => return Err("El valor debe ser un número real".to_string()),
+            Err(e) => return Err(e.to_string()),
+        };
+        self.calculator.define_constant(name, value).map_err(|e| e.to_string())?;
+        self.new_constant = Default::default();
+        Ok(())
+    }
+
+    fn show_calculator(&mut self, ctx: &egui::Context, ui: &mut egui::Ui) {
+        ui.vertical(|ui| {
+            // Title
+            ui.horizontal(|ui| {
+                ui.heading("Calculadora Científica Guizar");
+                if self.calculator.integer_mode {
+                    ui.label(egui::RichText::new("ENTERO").strong().color(egui::Color32::YELLOW));
+                }
+                if self.rpn_mode {
+                    ui.label(egui::RichText::new("RPN").strong().color(egui::Color32::YELLOW));
+                }
+                if self.calculator.angle == AngleMode::Degrees {
+                    ui.label(egui::RichText::new("DEG").strong().color(egui::Color32::YELLOW));
+                }
+                if ui.button("Presentación").on_hover_text("Pantalla grande para proyectar (F5)").clicked() {
+                    self.presentation = true;
+                }
+            });
+            
+            if let Some(reproducibility) = &mut self.reproducibility {
+                if !reproducibility.show(ui, &self.calculator, &self.format, &self.history.entries) {
+                    self.reproducibility = None;
+                }
+            }
+
+            // Display area with border and padding
+            ui.add_space(10.0);
+            egui::Frame::dark_canvas(ui.style()).show(ui, |ui| {
+                ui.add_space(5.0);
+                if self.rpn_mode {
+                    self.rpn.show(ui, &self.calculator, &self.format);
+                } else {
+                    self.show_display(ui);
+                }
+                ui.add_space(5.0);
+            });
+            if !self.rpn_mode {
+                self.show_complexity(ui);
+            }
+            
+            // The algebraic result and expression tools have no use with the stack
+            if !self.rpn_mode {
                 // Result area
+                if let Some(elapsed) = self.evaluation.as_ref().map(Evaluation::elapsed) {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(format!("Calculando… {} s", elapsed.as_secs()));
+                        if ui.small_button("Cancelar").clicked() {
+                            self.evaluation = None;
+                        }
+                    });
+                } else if let Some(error) = &self.error {
+                    ui.colored_label(egui::Color32::RED, error);
+                    if let Some(mismatch) = &self.mismatch {
+                        dimensions::show(ui, mismatch);
+                    }
+                    for hint in &self.hints {
+                        ui.colored_label(egui::Color32::LIGHT_YELLOW, format!("Sugerencia: {}", hint));
+                    }
+                } else if !self.result.is_empty() && self.result_hidden() {
+                    if ui.button("= ••••• (clic para revelar)").clicked() {
+                        self.revealed = true;
+                    }
+                } else if !self.result.is_empty() {
+                    if let Some(Value::Matrix(m)) = &self.value {
+                        matrix::show(ui, m, &self.format, egui::TextStyle::Monospace.resolve(ui.style()).size);
+                    } else if let Some(Value::Eigen(e)) = &self.value {
+                        eigen::show(ui, e, &self.format, egui::TextStyle::Monospace.resolve(ui.style()).size);
+                    } else if self.result.len() > LONG_RESULT {
+                        show_long_result(ui, &self.result, egui::TextStyle::Monospace.resolve(ui.style()).size);
+                    } else {
+                        // Long (e.g. big-integer) results wrap instead of overflowing
+                        ui.add(egui::Label::new(
+                            egui::RichText::new(format!("= {}", self.shown_result())).color(egui::Color32::GREEN)
+                        ).wrap());
+                    }
+                    ui.horizontal(|ui| {
+                        let notation = self.format.notation;
+                        let hover = format!("Notación: {}; clic para {}", notation.name(), notation.next().name().to_lowercase());
+                        if ui.small_button(notation.short_name()).on_hover_text(hover).clicked() {
+                            self.set_format(NumberFormat { notation: notation.next(), ..self.format });
+                        }
+                        if self.exact.is_some() {
+                            let (label, hover) = if self.decimal { ("exacto", "Volver a la forma exacta") } else { ("≈", "Ver el valor decimal aproximado") };
+                            if ui.small_button(label).on_hover_text(hover).clicked() {
+                                self.decimal = !self.decimal;
+                            }
+                        }
+                        if matches!(self.value, Some(Value::Number(_))) {
+                            if ui.small_button("ENG→").on_hover_text("Exponente 3 menor").clicked() {
+                                self.eng_step(false);
+                            }
+                            if ui.small_button("←ENG").on_hover_text("Exponente 3 mayor").clicked() {
+                                self.eng_step(true);
+                            }
+                            ui.toggle_value(&mut self.as_fraction, "a/b").on_hover_text("Mostrar también como fracción");
+                        }
+                        if !self.exam && ui.small_button("Copiar resultado").clicked() {
+                            ctx.copy_text(self.result.clone());
+                        }
+                        if !self.exam && ui.small_button("Copiar como Markdown").on_hover_text("`expresión = resultado`, para README o issues").clicked() {
+                            ctx.copy_text(markdown::inline(&self.display, &self.result));
+                        }
+                    });
+                    if let Some(Value::Number(x)) = self.value.as_ref().filter(|_| self.as_fraction) {
+                        fraction::show(ui, *x);
+                    }
+                    let integer = match &self.value {
+                        Some(Value::Integer(n)) => Some(n.clone()),
+                        Some(Value::Number(x)) if x.fract() == 0.0 => BigInt::from_f64(*x),
+                        _ => None,
+                    };
+                    if let Some(n) = integer.filter(|_| self.bases) {
+                        programmer::show_bases(ui, &n);
+                    }
+                }
+                for warning in &self.warnings {
+                    ui.colored_label(egui::Color32::from_rgb(230, 150, 30), format!("Aviso: {}", warning));
+                }
+                if let Some(notice) = &self.notice {
+                    ui.small(notice);
+                }
+
+                // Expression tools and sharing
                 ui.horizontal(|ui| {
-                    if let Some(error) = &self.error {
-                        ui.colored_label(egui::Color32::RED, error);
-                    } else if !self.result.is_empty() {
-                        ui.colored_label(egui::Color32::GREEN, format!("= {}", self.result));
+                    if ui.add_enabled(self.undo.can_undo(), egui::Button::new("Deshacer")).on_hover_text("Ctrl+Z").clicked() {
+                        self.undo();
+                    }
+                    if ui.add_enabled(self.undo.can_redo(), egui::Button::new("Rehacer")).on_hover_text("Ctrl+Shift+Z").clicked() {
+                        self.redo();
+                    }
+                    if ui.button("Formatear").on_hover_text("Normalizar espacios y quitar paréntesis redundantes").clicked() {
+                        match self.calculator.format(&self.display) {
+                            Ok(formatted) => self.display = formatted,
+                            Err(e) => self.error = Some(e.to_string()),
+                        }
+                    }
+                    if ui.button("Simplificar").on_hover_text("Agrupar términos semejantes, operar los números y quitar x*1, x+0, x^1…").clicked() {
+                        match self.calculator.simplify(&self.display) {
+                            Ok(simplified) => self.display = simplified,
+                            Err(e) => self.error = Some(e.to_string()),
+                        }
+                    }
+                    if !self.exam && ui.button("Compartir enlace").clicked() && !self.display.is_empty() {
+                        self.share(ctx);
+                    }
+                    if let Some(link) = &self.share_link {
+                        ui.small("Copiado al portapapeles");
+                        ui.add(egui::Label::new(egui::RichText::new(link).small().monospace()).truncate());
                     }
                 });
+                if !self.exam {
+                    ui.horizontal(|ui| {
+                        let field = ui.add(
+                            egui::TextEdit::singleline(&mut self.import_link)
+                                .hint_text("Pegar enlace guicalc://")
+                                .desired_width(200.0)
+                        );
+                        let submitted = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                        if ui.button("Abrir").clicked() || submitted {
+                            let link = std::mem::take(&mut self.import_link);
+                            if !self.import(&link) {
+                                self.error = Some("Enlace no válido".to_string());
+                            }
+                        }
+                    });
+                }
+            }
 
-                ui.add_space(20.0);
+            ui.add_space(20.0);
 
-                // Button grid
-                let button_size = egui::vec2(70.0, 40.0);
-                let mut col = 0;
-                
-                ui.horizontal_wrapped(|ui| {
-                    for &button in BUTTONS {
-                        if col > 0 && col % 5 == 0 {
-                            ui.end_row();
+            // Button grid
+            let button_size = self.button_size.size();
+            let mut col = 0;
+            let buttons = if self.second { SECOND_BUTTONS } else { BUTTONS };
+            
+            ui.horizontal_wrapped(|ui| {
+                for &button in buttons {
+                    if col > 0 && col % 5 == 0 {
+                        ui.end_row();
+                    }
+                    
+                    let btn = ui.add_sized(
+                        button_size,
+                        egui::Button::new(button)
+                            .fill(match button {
+                                "=" => egui::Color32::from_rgb(0, 150, 0),
+                                "C" | "⌫" => egui::Color32::from_rgb(150, 0, 0),
+                                "2nd" if self.second => egui::Color32::from_rgb(170, 120, 0),
+                                _ if Function::from_name(button).is_some() =>
+                                    egui::Color32::from_rgb(70, 70, 170),
+                                "pi" | "e" | "i" => egui::Color32::from_rgb(170, 70, 70),
+                                "+" | "-" | "*" | "/" | "^" | "mod" | "!" | "±" =>
+                                    egui::Color32::from_rgb(100, 100, 100),
+                                _ => ui.style().visuals.widgets.inactive.bg_fill,
+                            })
+                    );
+                    
+                    let alternates = ALTERNATES.iter().find(|(name, _)| *name == button).map(|(_, alternates)| *alternates);
+                    if let Some(alternates) = alternates {
+                        let popup = ui.make_persistent_id(("alternates", button));
+                        let held = btn.is_pointer_button_down_on() && ui.input(|i| {
+                            i.pointer.press_start_time().is_some_and(|start| i.time - start >= LONG_PRESS)
+                        });
+                        if btn.is_pointer_button_down_on() {
+                            ui.ctx().request_repaint();
                         }
-                        
-                        let btn = ui.add_sized(
-                            button_size,
-                            egui::Button::new(button)
-                                .fill(match button {
-                                    "=" => egui::Color32::from_rgb(0, 150, 0),
-                                    "C" => egui::Color32::from_rgb(150, 0, 0),
-                                    "sin" | "cos" | "tan" | "log" | "log10" | 
-                                    "sqrt" | "cbrt" | "abs" | "floor" | "ceil" | "round" =>
-                                        egui::Color32::from_rgb(70, 70, 170),
-                                    "pi" | "e" => egui::Color32::from_rgb(170, 70, 70),
-                                    "+" | "-" | "*" | "/" | "^" | "mod" =>
-                                        egui::Color32::from_rgb(100, 100, 100),
-                                    _ => ui.style().visuals.widgets.inactive.bg_fill,
-                                })
-                        );
-                        
-                        if btn.clicked() {
-                            self.handle_input(button);
+                        if held || btn.secondary_clicked() {
+                            ui.memory_mut(|memory| memory.open_popup(popup));
+                        }
+                        // Releasing a long press shouldn't also insert the button itself
+                        if btn.clicked() && !ui.memory(|memory| memory.is_popup_open(popup)) {
+                            self.handle_input(ui.ctx(), button);
+                        }
+                        let chosen = egui::popup::popup_below_widget(ui, popup, &btn, egui::PopupCloseBehavior::CloseOnClickOutside, |ui| {
+                            ui.horizontal(|ui| {
+                                alternates.iter().copied().find(|alternate| ui.button(*alternate).clicked())
+                            }).inner
+                        }).flatten();
+                        if let Some(alternate) = chosen {
+                            ui.memory_mut(|memory| memory.close_popup());
+                            self.handle_input(ui.ctx(), alternate);
                         }
-                        
-                        col += 1;
+                    } else if btn.clicked() {
+                        self.handle_input(ui.ctx(), button);
+                    }
+                    
+                    col += 1;
+                }
+            });
+
+            // Pasting a shared link anywhere opens it
+            let pasted: Vec<String> = ui.input(|i| i.events.iter().filter_map(|event| match event {
+                egui::Event::Paste(text) => Some(text.clone()),
+                _ => None,
+            }).collect());
+            for text in pasted {
+                if text.trim().starts_with(share::SCHEME) && self.import(&text) {
+                    self.import_link.clear();
+                }
+            }
+
+            // Keyboard input handling, with the keys from the editor; the RPN
+            // entry field takes its own Enter
+            for action in ui.input(|i| self.keybindings.pressed(i)) {
+                match action {
+                    keybindings::Action::Evaluate if self.rpn_mode => {},
+                    keybindings::Action::Evaluate => self.calculate(),
+                    keybindings::Action::Clear => self.handle_input(ui.ctx(), "C"),
+                    keybindings::Action::ToggleAngle => self.calculator.angle = match self.calculator.angle {
+                        AngleMode::Radians => AngleMode::Degrees,
+                        AngleMode::Degrees => AngleMode::Radians,
+                    },
+                    keybindings::Action::InsertAns => self.insert_ans(ui.ctx()),
+                }
+            }
+
+            // Backspace edits the display even when no text field has focus
+            if ui.memory(|memory| memory.focused().is_none())
+                && ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Backspace))
+            {
+                self.handle_input(ui.ctx(), "⌫");
+            }
+
+            let real = match &self.value {
+                Some(Value::Number(n)) => Some(*n),
+                Some(Value::Integer(n)) => n.to_f64(),
+                _ => None,
+            };
+            if let Some(x) = real.filter(|_| !self.result_hidden() && !self.rpn_mode) {
+                // Compared with the results before this one
+                if self.magnitude_bar {
+                    let recent: Vec<f64> = self.history.entries.iter().rev().skip(1).take(20)
+                        .filter_map(|entry| entry.result.parse().ok())
+                        .collect();
+                    magnitude::show(ui, x, &recent);
+                }
+                ui.add_space(10.0);
+                if let Some(Value::Number(x)) = self.value {
+                    // The shortest text that reads back as exactly this f64
+                    let full = format!("{:?}", x);
+                    let header = if full == self.result { "Precisión completa" } else { "Precisión completa (resultado redondeado)" };
+                    ui.collapsing(header, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.monospace(&full);
+                            if ui.small_button("Copiar").clicked() {
+                                ui.ctx().copy_text(full.clone());
+                            }
+                        });
+                        ui.monospace(format!("{:.16e}", x)).on_hover_text("17 cifras significativas");
+                    });
+                }
+                ui.collapsing("Inspector IEEE-754", |ui| ieee::show(ui, x));
+            }
+
+            // Practice problems
+            ui.add_space(20.0);
+            ui.collapsing("Práctica", |ui| {
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt("quiz_kind")
+                        .selected_text(self.quiz.kind.name())
+                        .show_ui(ui, |ui| {
+                            for kind in ProblemKind::ALL {
+                                ui.selectable_value(&mut self.quiz.kind, kind, kind.name());
+                            }
+                        });
+                    egui::ComboBox::from_id_salt("quiz_difficulty")
+                        .selected_text(self.quiz.difficulty.name())
+                        .show_ui(ui, |ui| {
+                            for difficulty in Difficulty::ALL {
+                                ui.selectable_value(&mut self.quiz.difficulty, difficulty, difficulty.name());
+                            }
+                        });
+                    if ui.button("Nuevo problema").clicked() {
+                        self.quiz.next_problem();
                     }
                 });
+                if let Some(problem) = &self.quiz.problem {
+                    ui.monospace(&problem.question);
+                    ui.horizontal(|ui| {
+                        let field = ui.add(egui::TextEdit::singleline(&mut self.quiz.answer).hint_text("Tu respuesta"));
+                        let submitted = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                        let check = ui.add_enabled(!self.quiz.answered, egui::Button::new("Comprobar"));
+                        if check.clicked() || submitted {
+                            self.quiz.check();
+                        }
+                    });
+                }
+                if let Some((correct, message)) = &self.quiz.feedback {
+                    let color = if *correct { egui::Color32::GREEN } else { egui::Color32::RED };
+                    ui.colored_label(color, message);
+                }
+                ui.label(format!("Puntuación: {} / {}", self.quiz.correct, self.quiz.attempts));
+            });
+
+            ui.collapsing("Variables", |ui| {
+                if self.calculator.variables.is_empty() {
+                    ui.small("Define una con nombre = expresión, p. ej. x = 2*pi");
+                }
+                let mut removed = None;
+                for (name, value) in &self.calculator.variables {
+                    ui.horizontal(|ui| {
+                        ui.monospace(format!("{} = {}", name, self.format.value(value)));
+                        if ui.small_button("✖").on_hover_text("Borrar").clicked() {
+                            removed = Some(name.clone());
+                        }
+                    });
+                }
+                if let Some(name) = removed {
+                    self.calculator.variables.remove(&name);
+                }
+            });
+
+            ui.collapsing("Historial de variables", |ui| {
+                self.timeline.show(ui, &mut self.calculator);
+            });
 
-                // Keyboard input handling
-                if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-                    self.calculate();
-                }
-                
-                if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
-                    self.display.clear();
-                    self.result.clear();
-                    self.error = None;
-                }
-
-                // Help text
-                ui.add_space(20.0);
-                ui.collapsing("Ayuda", |ui| {
-                    ui.label("Atajos de teclado:");
-                    ui.label("Enter - Calcular");
-                    ui.label("Escape - Borrar");
-                    ui.add_space(10.0);
-                    ui.label("Uso de las funciones:");
-                    ui.label("• Trigonométrico: sin(x), cos(x), tan(x)");
-                    ui.label("• Raíces: sqrt(x), cbrt(x)");
-                    ui.label("• Logarítmico: log(x), log10(x)");
-                    ui.label("• Redondeo: floor(x), ceil(x), round(x)");
-                    ui.label("• Otros: abs(x), mod");
-                    ui.add_space(10.0);
-                    ui.label("Constantes:");
-                    ui.label("• pi ≈ 3.14159...");
-                    ui.label("• e ≈ 2.71828...");
+            ui.collapsing("Constantes", |ui| {
+                let mut inserted = None;
+                ui.horizontal_wrapped(|ui| {
+                    for constant in constants::MATH_CONSTANTS {
+                        let hover = if constant.imaginary {
+                            constant.description.to_string()
+                        } else {
+                            format!("{} ≈ {}", constant.description, constant.value)
+                        };
+                        if ui.button(egui::RichText::new(constant.name).monospace()).on_hover_text(hover).clicked() {
+                            inserted = Some(constant.name);
+                        }
+                    }
+                });
+                if let Some(name) = inserted {
+                    self.handle_input(ui.ctx(), name);
+                }
+            });
+
+            ui.collapsing("Constantes físicas", |ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.constant_search).hint_text("Buscar por símbolo o nombre"));
+                let mut inserted = None;
+                egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    egui::Grid::new("physical_constants").striped(true).show(ui, |ui| {
+                        for constant in constants::search(&self.constant_search) {
+                            if ui.button(egui::RichText::new(constant.name).monospace())
+                                .on_hover_text("Insertar en la expresión")
+                                .clicked()
+                            {
+                                inserted = Some(constant.name);
+                            }
+                            ui.label(constant.description);
+                            ui.monospace(format!("{:e} {}", constant.value, constant.unit));
+                            ui.end_row();
+                        }
+                    });
+                });
+                if let Some(symbol) = inserted {
+                    self.handle_input(ui.ctx(), symbol);
+                }
+            });
+
+            // Help text
+            ui.collapsing("Ayuda", |ui| {
+                if ui.button("Reportar un problema…").clicked() {
+                    let text = report::bundle(&self.display, &self.result, self.error.as_deref(), &self.calculator, self.debug_log);
+                    self.report = Some(report::Report::new(text));
+                }
+                if ui.button("Autodiagnóstico").on_hover_text("Comprueba el motor con resultados conocidos").clicked() {
+                    self.self_test = Some(selftest::run());
+                }
+                if let Some((total, failures)) = &self.self_test {
+                    let color = if failures.is_empty() { egui::Color32::GREEN } else { egui::Color32::RED };
+                    ui.colored_label(color, selftest::summary(*total, failures));
+                }
+                ui.label("Atajos de teclado (los primeros se cambian en ⚙ Configuración → Atajos de teclado…):");
+                for action in keybindings::Action::ALL {
+                    if let Some(shortcut) = self.keybindings.shortcut(action) {
+                        ui.label(format!("{} - {}", ctx.format_shortcut(&shortcut), action.name()));
+                    }
+                }
+                ui.label("Retroceso o ⌫ - Borrar el carácter anterior (o el token, según Configuración)");
+                ui.label("Ctrl+Retroceso - Borrar el token anterior");
+                ui.label("Ctrl+←/→ - Saltar entre tokens");
+                ui.label("↑/↓ - Recorrer las expresiones anteriores");
+                ui.label("Ctrl+Z / Ctrl+Shift+Z - Deshacer y rehacer cambios en la expresión, también C y ⌫");
+                ui.label("Ctrl+V - Pegar, convirtiendo ×, ÷, − y separadores de miles, y revisar la expresión");
+                ui.label("2nd - Alternar funciones secundarias");
+                ui.label("Mantener pulsado o clic derecho - Variantes del botón (sin → asin, sinh...)");
+                ui.label("F5 - Modo presentación");
+                ui.add_space(10.0);
+                ui.label("Uso de las funciones:");
+                ui.label("• Trigonométrico: sin(x), cos(x), tan(x), asin(x), acos(x), atan(x), en radianes o grados según ⚙ Configuración");
+                ui.label("• Ángulo de una sola expresión: @deg sin(30) o @rad, al principio; queda así en el historial");
+                ui.label("• Hiperbólico: sinh(x), cosh(x), tanh(x), asinh(x), acosh(x), atanh(x)");
+                ui.label("• Raíces: sqrt(x), cbrt(x)");
+                ui.label("• Logarítmico: log(x), log10(x)");
+                ui.label("• Redondeo: floor(x), ceil(x), round(x)");
+                ui.label("• Especiales (2nd): gamma(x), lgamma(x), erf(x), erfc(x)");
+                ui.label("• Otros: abs(x), mod, factorial n!");
+                ui.label("• Multiplicación implícita: 2pi, 3(x+1), 2sin(x); ver Configuración");
+                ui.label("• Bases: 0xFF, 0b1010 y 0o17 en cualquier expresión, p. ej. 0xFF + 10");
+                ui.label("• Separadores: 1_000_000, o 1,234.5 / 1.234,5 según ⚙ Configuración; con coma decimal, argumentos con ;");
+                ui.label("• Notación Prefijos SI: resultados como 4.7k o 2.2µ, que se pueden volver a escribir así (u vale por µ)");
+                ui.label("• a/b bajo el resultado: la fracción sencilla más cercana, 0.333333 → 1/3, con su error si no es exacta");
+                ui.label("• Texto: words(x) escribe el número con letra");
+                ui.label("• Tiempo: now() en segundos y today() en días desde 1970; se recalculan solos según Configuración");
+                ui.label("• Complejos: i, exp(x), p. ej. (2+3i)*(1-i), sqrt(-1)");
+                ui.label("• Incertidumbre: 9.81±0.02 se propaga en operaciones y funciones");
+                ui.label("• Unidades: convert(5, \"mi\", \"km\") o la pestaña Conversión");
+                ui.label("• Modo unidades: 5 km + 300 m, 2 kg * 9.81 m/s^2 = 19.62 N; Tab completa el símbolo");
+                ui.label("• Conversión en línea: 32 ft/s^2 to m/s^2, 20 °C to °F; «in» también vale si no es la pulgada (3 in in cm)");
+                ui.label("• Divisas: usd_to_eur(x) con las tasas de la pestaña Divisas");
+                ui.label("• Datos en línea: fetch_num(\"https://…\", \"/ruta/al/número\") lee un número de un JSON; hay que permitirlo en ⚙ Configuración");
+                ui.label("• Variables: x = 2*pi y luego x/2; una variable con nombre de constante (pi, e, h...) la oculta hasta que la borres");
+                ui.label("• Gráfica: f(x) con la variable x en la pestaña Gráfica; el dominio se escribe o se arrastra, y la altura se ajusta sola salvo que la fijes; + Función añade otra curva con su color; Rastrear marca el punto (x, f(x)) bajo el puntero, ←/→ lo mueven y ↑/↓ cambian de curva; Buscar cero lo encuentra entre dos clics a cada lado del cruce; Intersecciones lista dónde se cruzan dos funciones en el dominio visible");
+                ui.label("• Hojas: la pestaña Hoja guarda archivos .calc, una expresión por línea; abrir uno con el programa lo carga ahí");
+                ui.label("• Simplificar: reescribe la expresión agrupando términos semejantes y operando los números, 2x + 3 - x + 0 → x + 3");
+                ui.label("• Resultados exactos (Configuración): sqrt(8) = 2√2, sin(pi/6) = 1/2, pi/2 + pi/3 = 5π/6; ≈ muestra el decimal");
+                ui.label("• Derivadas: nderiv(\"x^3\", \"x\", 2) = 12, por diferencias centrales con paso adaptativo; d/dx en la Gráfica dibuja la derivada de una curva");
+                ui.label("• Ecuaciones: solve(\"x^2 - 2\", \"x\", 1) = 1.41421… por el método de Newton desde el valor inicial; la pestaña Resolver admite f(x) = g(x)");
+                ui.label("• Matrices: [[1, 2], [3, 4]] fila por fila; se suman, restan y multiplican entre sí o por un número, y A^3 o A^-1 eleva a enteros; det, inv, transpose y rank, y lu(A, \"L\") y qr(A, \"Q\") dan un factor de la descomposición; eig(A) lista los valores propios con sus vectores propios; la pestaña Matrices las edita celda a celda y las guarda como A, B, C…");
+                ui.label("• Vectores: [1, 2, 3] con dot(u, v), cross(u, v), norm(v) y angle(u, v); *, /, ^ y sumar un número van elemento a elemento");
+                ui.label("• Listas: {1, 4, 9, 16} con sum, prod, mean, median, mode, sort y len; se operan elemento a elemento como los vectores");
+                ui.label("• Probabilidad: normpdf(x, μ, σ), normcdf(x, μ, σ), binompdf(n, p, k), binomcdf(n, p, k), poisspdf(λ, k), tcdf(t, ν) y chi2cdf(x, ν); las cdf dan P(X ≤ x)");
+                ui.label("• Finanzas: pmt(tasa, n, va), pv(tasa, n, pago), fv(tasa, n, pago, va), nper(tasa, pago, va), rate(n, pago, va), npv(tasa, {flujos}) e irr({flujos}), con la tasa por periodo; la pestaña Finanzas resuelve préstamos y ahorros");
+                ui.label("• Datos: la pestaña Estadística recoge una columna de datos, o pares x e y, escritos, pegados de una hoja de cálculo o importados de un CSV; muestra su resumen, un histograma con curva normal opcional y, con dos columnas, la regresión lineal, polinómica, exponencial o potencial sobre los puntos");
+                ui.label("• Polinomios: en su pestaña, coeficientes (1, -3, 2) o una expresión en x; da todas las raíces, reales y complejas, con su multiplicidad");
+                ui.label("• Comprobar respuestas: assert_eq(x, 2, 0.01) da x si está a 0.01 o menos de 2, y si no un error; en una hoja se cuentan los aciertos");
+                ui.label("• Estadísticas: 📊 arriba cuenta los cálculos, las funciones más usadas y los errores más frecuentes de la sesión o del espacio de trabajo");
+                ui.label("• Exámenes: iniciar con --exam quita portapapeles, descargas, constantes propias, comandos por evento y espacios de trabajo, y no guarda nada al cerrar");
+                ui.label("• Comandos por evento: en ⚙ Configuración, on_startup, on_result y on_error ejecutan un comando con $CALC_EXPRESSION y $CALC_RESULT, p. ej. para anotar los resultados en un archivo");
+                ui.label("• Reproducibilidad: cada resultado guarda la versión del motor, las cifras y los ángulos; si al reabrir no coinciden aparece un aviso y Recalcular muestra qué cambia");
+                ui.add_space(10.0);
+                ui.label("Constantes:");
+                ui.label("• pi ≈ 3.14159...");
+                ui.label("• e ≈ 2.71828...");
+                ui.label("• i = sqrt(-1)");
+                ui.label("• tau = 2·pi, phi ≈ 1.61803, euler_gamma ≈ 0.57722");
+                ui.label("• Físicas (c, G, h, k_B, N_A, m_e...): respetan mayúsculas");
+                ui.label("• Propias: defínelas en Configuración → Constantes propias");
+            });
+        });
+    }
+}
+
+impl eframe::App for CalculatorApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        messages::set_language(self.language);
+        if self.exam {
+            self.lock_exam(ctx);
+        }
+        self.calculator.separators = self.format.separators;
+        self.calculator.si_prefixes = self.format.notation == Notation::SiPrefix;
+        ctx.set_theme(self.theme.preference());
+        self.poll_evaluation(ctx);
+        self.recalculate(ctx);
+        self.track_undo(ctx);
+        self.timeline.track(&self.calculator, &self.format);
+        // Ctrl+Z works on the display's own history rather than the text
+        // field's, so button presses and clears can be undone too
+        let display_keys = ctx.memory(|memory| memory.focused().is_none_or(|id| id == display_id()));
+        if self.tab == Tab::Calculator && display_keys {
+            let shift = egui::Modifiers::COMMAND | egui::Modifiers::SHIFT;
+            if ctx.input_mut(|i| i.consume_key(shift, egui::Key::Z) || i.consume_key(egui::Modifiers::COMMAND, egui::Key::Y)) {
+                self.redo();
+            } else if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::Z)) {
+                self.undo();
+            }
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::F5)) {
+            self.presentation = !self.presentation;
+        }
+        if self.presentation {
+            egui::CentralPanel::default().show(ctx, |ui| self.show_presentation(ui));
+            return;
+        }
+
+        egui::TopBottomPanel::top("tabs").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                for tab in Tab::ALL {
+                    ui.selectable_value(&mut self.tab, tab, tab.name());
+                }
+                if self.tab == Tab::Calculator {
+                    ui.separator();
+                    ui.toggle_value(&mut self.show_history, "Historial");
+                }
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button("⚙").on_hover_text("Configuración").clicked() {
+                        self.settings_open = true;
+                    }
+                    if ui.button("📊").on_hover_text("Estadísticas de la sesión").clicked() {
+                        self.usage_open = true;
+                    }
+                    if self.exam {
+                        ui.label(egui::RichText::new("🔒 Examen").strong())
+                            .on_hover_text("Sin portapapeles, descargas, constantes propias, comandos por evento ni espacios de trabajo; no se guarda nada al cerrar");
+                    } else {
+                        let label = self.workspaces.current.as_deref().unwrap_or("Espacios");
+                        if ui.button(label).on_hover_text("Espacios de trabajo").clicked() {
+                            self.workspaces_open = true;
+                        }
+                    }
                 });
             });
         });
+
+        if self.tab == Tab::Calculator && self.show_history {
+            let action = egui::SidePanel::right("history")
+                .resizable(true)
+                .default_width(220.0)
+                .show(ctx, |ui| self.history.show(ui, self.result_hidden()))
+                .inner;
+            match action {
+                Some(history::Action::Load(expression)) => self.display = expression,
+                Some(history::Action::Compare(before, after)) => self.comparison = Some((before, after)),
+                Some(history::Action::Insert(value)) => self.insert_value(ctx, &value),
+                None => {},
+            }
+        }
+
+        egui::CentralPanel::default().show(ctx, |ui| match self.tab {
+            Tab::Calculator => self.show_calculator(ctx, ui),
+            Tab::Converter => self.converter.show(ui, &self.calculator),
+            Tab::Currency => self.currency.show(ui, &mut self.calculator),
+            Tab::Programmer => self.programmer.show(ui),
+            Tab::Tape => self.tape.show(ui, &self.calculator, &self.format),
+            Tab::Formulas => self.formulas.show(ui, &self.calculator, &self.format),
+            Tab::Worksheet => self.worksheet.show(ui, &self.calculator, &self.format),
+            Tab::Graph => self.graph.show(ui, &self.calculator),
+            Tab::Polynomial => self.polynomials.show(ui, &self.calculator, &self.format),
+            Tab::Solver => self.equations.show(ui, &self.calculator, &self.format),
+            Tab::Matrices => self.matrix_editor.show(ui, &mut self.calculator, &self.format),
+            Tab::Statistics => self.statistics.show(ui, &self.calculator, &self.format),
+            Tab::Finance => self.finance.show(ui, &self.calculator, &self.format),
+        });
+        self.show_settings_window(ctx);
+        self.show_constants_window(ctx);
+        self.show_keybindings_window(ctx);
+        self.show_hooks_window(ctx);
+        self.show_usage_window(ctx);
+        self.show_comparison_window(ctx);
+        if self.workspace_picker {
+            if let Some(action) = self.workspaces.show_picker(ctx) {
+                self.workspace_action(action);
+                self.workspace_picker = false;
+            }
+        }
+        let mut open = self.workspaces_open;
+        if let Some(action) = self.workspaces.show_manager(ctx, &mut open) {
+            self.workspace_action(action);
+        }
+        self.workspaces_open = open && self.workspaces_open;
+        if let Some(report) = &mut self.report {
+            let mut open = true;
+            report.show(ctx, &mut open);
+            if !open {
+                self.report = None;
+            }
+        }
+        if self.debug_log {
+            debug::show_panel(ctx, &mut self.debug_log);
+            if !self.debug_log {
+                debug::set_capture(false);
+            }
+        }
+        // Copy buttons in any tab end up here
+        if self.exam {
+            ctx.output_mut(|output| output.copied_text.clear());
+        }
     }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        // An exam leaves nothing behind for the next user
+        if self.exam {
+            return;
+        }
+        eframe::set_value(storage, CURRENCIES_KEY, &self.calculator.currencies);
+        eframe::set_value(storage, USER_CONSTANTS_KEY, &self.calculator.user_constants);
+        if let Some(current) = self.workspaces.current.clone() {
+            self.workspaces.saved.insert(current, self.workspace());
+        }
+        eframe::set_value(storage, WORKSPACES_KEY, &self.workspaces);
+        eframe::set_value(storage, HISTORY_KEY, &self.history);
+        eframe::set_value(storage, TAPE_KEY, &self.tape);
+        eframe::set_value(storage, SETTINGS_KEY, &self.settings());
+        eframe::set_value(storage, MODES_KEY, &self.modes());
+    }
+}
+
+// Expressions past any of these get a note suggesting a worksheet
+const LARGE_TOKENS: usize = 80;
+const LARGE_DEPTH: usize = 8;
+const LARGE_COST: f64 = 5000.0;
+
+// Results longer than this are shown as scrollable rows, so each frame
+// lays out only the visible part instead of one giant wrapped label
+const LONG_RESULT: usize = 1000;
+
+fn display_id() -> egui::Id {
+    egui::Id::new("display")
+}
+
+fn show_long_result(ui: &mut egui::Ui, result: &str, size: f32) {
+    let font = egui::FontId::monospace(size);
+    let (char_width, row_height) = ui.fonts(|fonts| (fonts.glyph_width(&font, '0'), fonts.row_height(&font)));
+    let per_row = ((ui.available_width() / char_width) as usize).max(10);
+    let starts: Vec<usize> = result.char_indices().step_by(per_row).map(|(i, _)| i).collect();
+    let digits = result.trim_start_matches('-');
+    if digits.bytes().all(|b| b.is_ascii_digit()) {
+        ui.label(format!("= ({} dígitos)", digits.len()));
+    } else {
+        ui.label(format!("= ({} caracteres)", result.chars().count()));
+    }
+    egui::ScrollArea::vertical()
+        .max_height(row_height * 12.0)
+        .auto_shrink([false, true])
+        .show_rows(ui, row_height, starts.len(), |ui, rows| {
+            for row in rows {
+                let end = starts.get(row + 1).copied().unwrap_or(result.len());
+                let text = egui::RichText::new(&result[starts[row]..end]).font(font.clone()).color(egui::Color32::GREEN);
+                ui.add(egui::Label::new(text).extend());
+            }
+        });
 }
 
 fn main() -> eframe::Result<()> {
-    env_logger::init();
+    debug::init();
+
+    if std::env::args().any(|arg| arg == "--self-test") {
+        let (total, failures) = selftest::run();
+        println!("{}", selftest::summary(total, &failures));
+        std::process::exit(if failures.is_empty() { 0 } else { 1 });
+    }
 
     let options = eframe::NativeOptions {
         viewport: ViewportBuilder::default()
@@ -184,9 +1983,108 @@ fn main() -> eframe::Result<()> {
         ..Default::default()
     };
 
+    let mut app = CalculatorApp { exam: std::env::args().any(|arg| arg == EXAM_FLAG), ..Default::default() };
+
     eframe::run_native(
         "Calculadora Científica Guizar",
         options,
-        Box::new(|_cc| Ok(Box::new(CalculatorApp::default())))
+        Box::new(|cc| {
+            if let Some(storage) = cc.storage {
+                app.restore(storage);
+            }
+            // Links and files stay out of an exam too
+            if app.exam {
+                app.lock_exam(&cc.egui_ctx);
+                return Ok(Box::new(app));
+            }
+            app.hooks.run(hooks::Event::Startup, "", "");
+            // Opening a guicalc:// link passes it as the first argument
+            if let Some(shared) = std::env::args().nth(1).and_then(|arg| SharedCalculation::from_link(&arg)) {
+                app.workspace_picker = false;
+                app.load_shared(shared);
+            }
+            // So does opening a .calc file from the file manager
+            if let Some(path) = std::env::args_os().nth(1).map(std::path::PathBuf::from).filter(|path| worksheet::is_worksheet(path)) {
+                app.workspace_picker = false;
+                app.tab = Tab::Worksheet;
+                match Worksheet::open(&path) {
+                    Ok(worksheet) => app.worksheet = worksheet,
+                    Err(e) => app.worksheet.error = Some(e),
+                }
+            }
+            Ok(Box::new(app))
+        })
     )
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use eframe::App;
+
+    // Stands in for eframe's file, to save one session and open the next
+    #[derive(Default)]
+    struct MemoryStorage(HashMap<String, String>);
+
+    impl eframe::Storage for MemoryStorage {
+        fn get_string(&self, key: &str) -> Option<String> {
+            self.0.get(key).cloned()
+        }
+
+        fn set_string(&mut self, key: &str, value: String) {
+            self.0.insert(key.to_string(), value);
+        }
+
+        fn flush(&mut self) {}
+    }
+
+    fn saved_session() -> MemoryStorage {
+        let mut app = CalculatorApp { display: "2 + 2".to_string(), ..Default::default() };
+        app.calculator.angle = AngleMode::Degrees;
+        app.hooks.on_result = "echo".to_string();
+        app.calculator.user_constants.insert("k".to_string(), 3.0);
+        app.converter.value = "42".to_string();
+        let mut storage = MemoryStorage::default();
+        app.save(&mut storage);
+        storage
+    }
+
+    #[test]
+    fn sessions_carry_over() {
+        let mut app = CalculatorApp::default();
+        app.restore(&saved_session());
+        assert_eq!(app.display, "2 + 2");
+        assert_eq!(app.converter.value, "42");
+        assert_eq!(app.hooks.on_result, "echo");
+        assert!(app.calculator.user_constants.contains_key("k"));
+    }
+
+    #[test]
+    fn exams_start_blank() {
+        let mut app = CalculatorApp { exam: true, ..Default::default() };
+        app.restore(&saved_session());
+        assert_eq!(app.calculator.angle, AngleMode::Degrees);
+        assert_eq!(app.display, "");
+        assert_eq!(app.converter.value, Converter::default().value);
+        assert_eq!(app.hooks.on_result, "");
+        assert!(app.calculator.user_constants.is_empty());
+    }
+
+    #[test]
+    fn workspaces_keep_the_worksheet_and_graph() {
+        let mut app = CalculatorApp::default();
+        app.worksheet.lines = vec!["a = 2".to_string(), "a^2".to_string()];
+        app.worksheet.path = Some(std::path::PathBuf::from("notas.calc"));
+        app.graph.curves.push(Curve::new("x^2", egui::Color32::RED));
+        app.graph.view = [-1.0, 1.0, -2.0, 2.0];
+        app.workspace_action(workspace::Action::SaveAs("proyecto".to_string()));
+
+        // As at startup, where nothing is current until the picker opens one
+        let mut other = CalculatorApp { workspaces: std::mem::take(&mut app.workspaces), ..Default::default() };
+        other.workspaces.current = None;
+        other.workspace_action(workspace::Action::Open("proyecto".to_string()));
+        assert_eq!(other.worksheet.lines, ["a = 2", "a^2"]);
+        assert_eq!(other.worksheet.path, app.worksheet.path);
+        assert_eq!(other.graph_state(), app.graph_state());
+    }
+}
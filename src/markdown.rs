@@ -0,0 +1,17 @@
+// Inline code span such as `sin(pi/4) = 0.7071`, widening the fence when
+// the text itself contains backticks
+pub fn inline(expr: &str, result: &str) -> String {
+    let text = format!("{} = {}", expr.trim(), result);
+    let fence = if text.contains('`') { "``" } else { "`" };
+    format!("{0}{1}{0}", fence, text)
+}
+
+// Two-column table with one row per expression/result pair
+pub fn table<'a>(rows: impl IntoIterator<Item = (&'a str, &'a str)>) -> String {
+    let cell = |text: &str| format!("`{}`", text.trim().replace('|', "\\|"));
+    let mut table = String::from("| Expresión | Resultado |\n|---|---|\n");
+    for (expr, result) in rows {
+        table.push_str(&format!("| {} | {} |\n", cell(expr), cell(result)));
+    }
+    table
+}
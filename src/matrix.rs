@@ -0,0 +1,680 @@
+use serde::{Deserialize, Serialize};
+
+use crate::calculator::{Calculator, Error, Function, Operator};
+use crate::format::NumberFormat;
+use crate::limits;
+use crate::value::Value;
+
+// Largest power taken by repeated squaring, as for numbers
+const MAX_POWER: f64 = 1e9;
+// Pivots this small relative to the largest element, times the size, count
+// as 0: the matrix is singular as far as floating point can tell
+const SINGULAR: f64 = 8.0 * f64::EPSILON;
+
+fn invalid(message: &str) -> Error {
+    Error::InvalidOperation(message.to_string())
+}
+
+// Real matrix stored row by row; a row vector is 1×n
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Matrix {
+    pub rows: usize,
+    pub cols: usize,
+    data: Vec<f64>,
+}
+
+impl Matrix {
+    pub fn new(rows: usize, cols: usize, data: Vec<f64>) -> Matrix {
+        debug_assert_eq!(data.len(), rows * cols);
+        Matrix { rows, cols, data }
+    }
+
+    pub fn identity(n: usize) -> Matrix {
+        Matrix::new(n, n, (0..n * n).map(|i| if i / n == i % n { 1.0 } else { 0.0 }).collect())
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> f64 {
+        self.data[row * self.cols + col]
+    }
+
+    pub fn row(&self, row: usize) -> &[f64] {
+        &self.data[row * self.cols..(row + 1) * self.cols]
+    }
+
+    // What a [a, b, …] literal stands for: numbers make a row, rows of the
+    // same length stack into a matrix, [[1, 2], [3, 4]]
+    pub fn from_list(items: Vec<Value>) -> Result<Matrix, Error> {
+        if items.is_empty() {
+            return Err(invalid("La matriz está vacía"));
+        }
+        if items.iter().all(|item| matches!(item, Value::Matrix(_))) {
+            let mut data = Vec::new();
+            let mut cols = None;
+            for item in &items {
+                let Value::Matrix(row) = item else { unreachable!() };
+                if row.rows != 1 || cols.is_some_and(|cols| cols != row.cols) {
+                    return Err(invalid("Todas las filas de una matriz deben tener los mismos elementos"));
+                }
+                cols = Some(row.cols);
+                data.extend_from_slice(&row.data);
+            }
+            limits::check_elements(items.len(), cols.unwrap())?;
+            return Ok(Matrix::new(items.len(), cols.unwrap(), data));
+        }
+        let data = items.into_iter()
+            .map(|item| match item {
+                Value::Matrix(_) => Err(invalid("Todas las filas de una matriz deben tener los mismos elementos")),
+                item => item.real().map_err(|_| invalid("Los elementos de una matriz deben ser números reales")),
+            })
+            .collect::<Result<Vec<f64>, Error>>()?;
+        limits::check_elements(1, data.len())?;
+        Ok(Matrix::new(1, data.len(), data))
+    }
+
+    fn map(&self, f: impl Fn(f64) -> f64) -> Matrix {
+        Matrix::new(self.rows, self.cols, self.data.iter().map(|x| f(*x)).collect())
+    }
+
+    // A single row or column, [1, 2, 3], is a vector
+    fn is_vector(&self) -> bool {
+        self.rows == 1 || self.cols == 1
+    }
+
+    fn vector(&self, func: Function) -> Result<&[f64], Error> {
+        if !self.is_vector() {
+            return Err(Error::InvalidOperation(format!("{} requiere vectores, no una matriz {}×{}", func, self.rows, self.cols)));
+        }
+        Ok(&self.data)
+    }
+
+    // Two vectors of the same length, row or column alike
+    fn pair<'a>(&'a self, other: &'a Matrix, func: Function) -> Result<(&'a [f64], &'a [f64]), Error> {
+        let (u, v) = (self.vector(func)?, other.vector(func)?);
+        if u.len() != v.len() {
+            return Err(Error::InvalidOperation(format!("{} requiere vectores de la misma longitud: {} y {}", func, u.len(), v.len())));
+        }
+        Ok((u, v))
+    }
+
+    pub fn dot(&self, other: &Matrix) -> Result<f64, Error> {
+        let (u, v) = self.pair(other, Function::Dot)?;
+        Ok(u.iter().zip(v).map(|(a, b)| a * b).sum())
+    }
+
+    // Shaped as the first vector
+    pub fn cross(&self, other: &Matrix) -> Result<Matrix, Error> {
+        let (u, v) = self.pair(other, Function::Cross)?;
+        if u.len() != 3 {
+            return Err(invalid("cross requiere vectores de 3 elementos"));
+        }
+        let data = vec![u[1] * v[2] - u[2] * v[1], u[2] * v[0] - u[0] * v[2], u[0] * v[1] - u[1] * v[0]];
+        Ok(Matrix::new(self.rows, self.cols, data))
+    }
+
+    // Euclidean length of a vector; for a matrix, the Frobenius norm
+    pub fn norm(&self) -> f64 {
+        self.data.iter().map(|x| x * x).sum::<f64>().sqrt()
+    }
+
+    // The angle between two vectors, in radians
+    pub fn angle(&self, other: &Matrix) -> Result<f64, Error> {
+        let (u, v) = self.pair(other, Function::Angle)?;
+        let lengths = self.norm() * other.norm();
+        if lengths == 0.0 {
+            return Err(invalid("angle no está definido con el vector cero"));
+        }
+        let cosine = u.iter().zip(v).map(|(a, b)| a * b).sum::<f64>() / lengths;
+        Ok(cosine.clamp(-1.0, 1.0).acos())
+    }
+
+    // Element by element, for vectors of the same shape
+    fn elementwise(&self, other: &Matrix, f: impl Fn(f64, f64) -> f64) -> Matrix {
+        Matrix::new(self.rows, self.cols, self.data.iter().zip(&other.data).map(|(a, b)| f(*a, *b)).collect())
+    }
+
+    fn add(&self, other: &Matrix, sign: f64) -> Result<Matrix, Error> {
+        if (self.rows, self.cols) != (other.rows, other.cols) {
+            return Err(Error::InvalidOperation(format!(
+                "Las matrices deben tener el mismo tamaño: {}×{} y {}×{}", self.rows, self.cols, other.rows, other.cols
+            )));
+        }
+        Ok(Matrix::new(self.rows, self.cols, self.data.iter().zip(&other.data).map(|(a, b)| a + sign * b).collect()))
+    }
+
+    pub fn mul(&self, other: &Matrix) -> Result<Matrix, Error> {
+        if self.cols != other.rows {
+            return Err(Error::InvalidOperation(format!(
+                "No se pueden multiplicar una matriz {}×{} y una {}×{}", self.rows, self.cols, other.rows, other.cols
+            )));
+        }
+        limits::check_elements(self.rows, other.cols)?;
+        let mut data = vec![0.0; self.rows * other.cols];
+        for i in 0..self.rows {
+            for k in 0..self.cols {
+                let a = self.get(i, k);
+                for j in 0..other.cols {
+                    data[i * other.cols + j] += a * other.get(k, j);
+                }
+            }
+        }
+        Ok(Matrix::new(self.rows, other.cols, data))
+    }
+
+    // Whole powers of a square matrix by repeated squaring; negative ones
+    // are powers of the inverse
+    fn pow(&self, exponent: f64) -> Result<Matrix, Error> {
+        if self.rows != self.cols {
+            return Err(invalid("Solo las matrices cuadradas tienen potencias"));
+        }
+        if exponent.fract() != 0.0 {
+            return Err(invalid("Una matriz solo se eleva a enteros"));
+        }
+        if exponent.abs() > MAX_POWER {
+            return Err(invalid("Exponente demasiado grande"));
+        }
+        let base = if exponent < 0.0 { self.inverse()? } else { self.clone() };
+        let (mut power, mut base, mut n) = (Matrix::identity(self.rows), base, exponent.abs() as u64);
+        while n > 0 {
+            if n % 2 == 1 {
+                power = power.mul(&base)?;
+            }
+            base = base.mul(&base)?;
+            n /= 2;
+        }
+        Ok(power)
+    }
+
+    pub fn transpose(&self) -> Matrix {
+        Matrix::new(self.cols, self.rows, (0..self.rows * self.cols).map(|k| self.get(k % self.rows, k / self.rows)).collect())
+    }
+
+    fn largest(&self) -> f64 {
+        self.data.iter().fold(0.0, |largest, x| largest.max(x.abs()))
+    }
+
+    // The threshold under which a pivot is taken as 0
+    fn negligible(&self) -> f64 {
+        SINGULAR * self.rows.max(self.cols) as f64 * self.largest()
+    }
+
+    pub fn square(&self, func: Function) -> Result<(), Error> {
+        if self.rows != self.cols {
+            return Err(Error::InvalidOperation(format!("{} requiere una matriz cuadrada, no {}×{}", func, self.rows, self.cols)));
+        }
+        Ok(())
+    }
+
+    // PA = LU by Gaussian elimination with partial pivoting: the row order
+    // of P, L below the diagonal (its own diagonal is 1s), U on and above it,
+    // and the sign of the permutation. Columns with no usable pivot are
+    // skipped, so singular matrices also have one
+    fn lu(&self) -> (Vec<usize>, Matrix, f64) {
+        let n = self.rows;
+        let mut lu = self.clone();
+        let mut order: Vec<usize> = (0..n).collect();
+        let mut sign = 1.0;
+        let negligible = self.negligible();
+        for k in 0..n {
+            let pivot = (k..n).max_by(|&a, &b| lu.get(a, k).abs().total_cmp(&lu.get(b, k).abs())).unwrap();
+            if lu.get(pivot, k).abs() <= negligible {
+                continue;
+            }
+            if pivot != k {
+                for j in 0..n {
+                    lu.data.swap(k * n + j, pivot * n + j);
+                }
+                order.swap(k, pivot);
+                sign = -sign;
+            }
+            for i in k + 1..n {
+                let factor = lu.get(i, k) / lu.get(k, k);
+                lu.data[i * n + k] = factor;
+                for j in k + 1..n {
+                    lu.data[i * n + j] -= factor * lu.get(k, j);
+                }
+            }
+        }
+        (order, lu, sign)
+    }
+
+    pub fn determinant(&self) -> Result<f64, Error> {
+        self.square(Function::Det)?;
+        let (_, lu, sign) = self.lu();
+        Ok((0..self.rows).fold(sign, |det, i| det * lu.get(i, i)))
+    }
+
+    // By Gauss–Jordan elimination with partial pivoting
+    pub fn inverse(&self) -> Result<Matrix, Error> {
+        self.square(Function::Inv)?;
+        let n = self.rows;
+        let negligible = self.negligible();
+        let (mut a, mut inverse) = (self.clone(), Matrix::identity(n));
+        for k in 0..n {
+            let pivot = (k..n).max_by(|&x, &y| a.get(x, k).abs().total_cmp(&a.get(y, k).abs())).unwrap();
+            if a.get(pivot, k).abs() <= negligible {
+                return Err(invalid("La matriz es singular (determinante 0) y no tiene inversa"));
+            }
+            for j in 0..n {
+                a.data.swap(k * n + j, pivot * n + j);
+                inverse.data.swap(k * n + j, pivot * n + j);
+            }
+            let p = a.get(k, k);
+            for j in 0..n {
+                a.data[k * n + j] /= p;
+                inverse.data[k * n + j] /= p;
+            }
+            for i in (0..n).filter(|&i| i != k) {
+                let factor = a.get(i, k);
+                for j in 0..n {
+                    a.data[i * n + j] -= factor * a.get(k, j);
+                    inverse.data[i * n + j] -= factor * inverse.get(k, j);
+                }
+            }
+        }
+        Ok(inverse)
+    }
+
+    // Pivots found by row reduction, any shape
+    pub fn rank(&self) -> usize {
+        let negligible = self.negligible();
+        let mut a = self.clone();
+        let mut rank = 0;
+        for k in 0..self.cols {
+            if rank == self.rows {
+                break;
+            }
+            let pivot = (rank..self.rows).max_by(|&x, &y| a.get(x, k).abs().total_cmp(&a.get(y, k).abs())).unwrap();
+            if a.get(pivot, k).abs() <= negligible {
+                continue;
+            }
+            for j in 0..self.cols {
+                a.data.swap(rank * self.cols + j, pivot * self.cols + j);
+            }
+            for i in rank + 1..self.rows {
+                let factor = a.get(i, k) / a.get(rank, k);
+                for j in k..self.cols {
+                    a.data[i * self.cols + j] -= factor * a.get(rank, j);
+                }
+            }
+            rank += 1;
+        }
+        rank
+    }
+
+    // A = QR by Householder reflections: Q orthogonal, m×m, and R upper
+    // triangular, m×n
+    pub fn qr(&self) -> (Matrix, Matrix) {
+        let (m, n) = (self.rows, self.cols);
+        let mut r = self.clone();
+        let mut q = Matrix::identity(m);
+        for k in 0..n.min(m.saturating_sub(1)) {
+            let norm = (k..m).map(|i| r.get(i, k).powi(2)).sum::<f64>().sqrt();
+            if norm == 0.0 {
+                continue;
+            }
+            // v = x + sign(x₀)·|x|·e₁, reflecting x onto the axis
+            let alpha = if r.get(k, k) > 0.0 { -norm } else { norm };
+            let mut v: Vec<f64> = (k..m).map(|i| r.get(i, k)).collect();
+            v[0] -= alpha;
+            let vv: f64 = v.iter().map(|x| x * x).sum();
+            if vv == 0.0 {
+                continue;
+            }
+            // R ← (I - 2vvᵀ/vᵀv) R and Q ← Q (I - 2vvᵀ/vᵀv)
+            for j in 0..n {
+                let dot: f64 = (k..m).map(|i| v[i - k] * r.get(i, j)).sum();
+                for i in k..m {
+                    r.data[i * n + j] -= 2.0 * v[i - k] * dot / vv;
+                }
+            }
+            for i in 0..m {
+                let dot: f64 = (k..m).map(|j| q.get(i, j) * v[j - k]).sum();
+                for j in k..m {
+                    q.data[i * m + j] -= 2.0 * dot * v[j - k] / vv;
+                }
+            }
+        }
+        // What lies under the diagonal is rounding left by the reflections
+        for i in 0..m {
+            for j in 0..n.min(i) {
+                r.data[i * n + j] = 0.0;
+            }
+        }
+        (q, r)
+    }
+
+    // One factor of lu(A, "L" | "U" | "P") or qr(A, "Q" | "R")
+    pub fn factor(&self, func: Function, part: &str) -> Result<Matrix, Error> {
+        let part = part.trim().to_ascii_uppercase();
+        match (func, part.as_str()) {
+            (Function::Lu, "L" | "U" | "P") => {
+                self.square(func)?;
+                let n = self.rows;
+                let (order, lu, _) = self.lu();
+                let data = (0..n * n).map(|k| {
+                    let (i, j) = (k / n, k % n);
+                    match part.as_str() {
+                        "L" if i == j => 1.0,
+                        "L" if i > j => lu.get(i, j),
+                        "U" if i <= j => lu.get(i, j),
+                        "P" if order[i] == j => 1.0,
+                        _ => 0.0,
+                    }
+                }).collect();
+                Ok(Matrix::new(n, n, data))
+            },
+            (Function::Qr, "Q") => Ok(self.qr().0),
+            (Function::Qr, "R") => Ok(self.qr().1),
+            (Function::Lu, _) => Err(invalid("lu(A, \"L\"), lu(A, \"U\") o lu(A, \"P\"), con PA = LU")),
+            _ => Err(invalid("qr(A, \"Q\") o qr(A, \"R\"), con A = QR")),
+        }
+    }
+
+    // det, inv, transpose and rank of a matrix
+    pub fn apply(&self, func: Function) -> Result<Value, Error> {
+        match func {
+            Function::Det => self.determinant().map(Value::Number),
+            Function::Inv => self.inverse().map(Value::Matrix),
+            Function::Transpose => Ok(Value::Matrix(self.transpose())),
+            Function::Rank => Ok(Value::Number(self.rank() as f64)),
+            Function::Norm => Ok(Value::Number(self.norm())),
+            _ => Err(Error::InvalidOperation(format!("{} no admite matrices", func))),
+        }
+    }
+
+    // `left op right` where at least one side is a matrix and the other may
+    // be a real number. Vectors also multiply, divide and take powers
+    // element by element, and add numbers to each element
+    pub fn combine(op: Operator, left: Value, right: Value) -> Result<Value, Error> {
+        let scalar = |value: Value| value.real().map_err(|_| invalid("Las matrices solo se combinan con matrices o números reales"));
+        let same = |a: &Matrix, b: &Matrix| a.is_vector() && (a.rows, a.cols) == (b.rows, b.cols);
+        let result = match (op, left, right) {
+            (Operator::Mul | Operator::Juxtapose, Value::Matrix(a), Value::Matrix(b)) if same(&a, &b) => a.elementwise(&b, |x, y| x * y),
+            (Operator::Div, Value::Matrix(a), Value::Matrix(b)) if same(&a, &b) => {
+                if b.data.contains(&0.0) {
+                    return Err(Error::DivisionByZero);
+                }
+                a.elementwise(&b, |x, y| x / y)
+            },
+            (Operator::Add | Operator::Sub, Value::Matrix(a), k) if a.is_vector() && !matches!(k, Value::Matrix(_)) => {
+                let k = if op == Operator::Sub { -scalar(k)? } else { scalar(k)? };
+                a.map(|x| x + k)
+            },
+            (Operator::Add | Operator::Sub, k, Value::Matrix(a)) if a.is_vector() && !matches!(k, Value::Matrix(_)) => {
+                let k = scalar(k)?;
+                a.map(|x| if op == Operator::Sub { k - x } else { k + x })
+            },
+            (Operator::Pow, Value::Matrix(a), n) if a.is_vector() && a.rows * a.cols > 1 && !matches!(n, Value::Matrix(_)) => {
+                let n = scalar(n)?;
+                a.map(|x| x.powf(n))
+            },
+            (Operator::Add, Value::Matrix(a), Value::Matrix(b)) => a.add(&b, 1.0)?,
+            (Operator::Sub, Value::Matrix(a), Value::Matrix(b)) => a.add(&b, -1.0)?,
+            (Operator::Mul | Operator::Juxtapose, Value::Matrix(a), Value::Matrix(b)) => a.mul(&b)?,
+            (Operator::Mul | Operator::Juxtapose, Value::Matrix(a), k) | (Operator::Mul | Operator::Juxtapose, k, Value::Matrix(a)) => {
+                let k = scalar(k)?;
+                a.map(|x| k * x)
+            },
+            (Operator::Div, Value::Matrix(a), k @ (Value::Number(_) | Value::Integer(_))) => {
+                let k = scalar(k)?;
+                if k == 0.0 {
+                    return Err(Error::DivisionByZero);
+                }
+                a.map(|x| x / k)
+            },
+            (Operator::Pow, Value::Matrix(a), n) if !matches!(n, Value::Matrix(_)) => a.pow(scalar(n)?)?,
+            _ => return Err(invalid("Operación no definida para matrices")),
+        };
+        Ok(Value::Matrix(result))
+    }
+
+    // [[1, 2], [3, 4]] with `number` writing each element; `separator` goes
+    // between elements, as between function arguments
+    pub fn format(&self, number: impl Fn(f64) -> String, separator: &str) -> String {
+        let row = |i: usize| format!("[{}]", self.row(i).iter().map(|x| number(*x)).collect::<Vec<_>>().join(separator));
+        if self.rows == 1 {
+            return row(0);
+        }
+        format!("[{}]", (0..self.rows).map(row).collect::<Vec<_>>().join(separator))
+    }
+
+    // One line per row with the columns right-aligned, for a monospace font
+    pub fn aligned(&self, number: impl Fn(f64) -> String) -> Vec<String> {
+        let cells: Vec<String> = self.data.iter().map(|x| number(*x)).collect();
+        let widths: Vec<usize> = (0..self.cols)
+            .map(|j| (0..self.rows).map(|i| cells[i * self.cols + j].chars().count()).max().unwrap_or(0))
+            .collect();
+        (0..self.rows).map(|i| {
+            let row: Vec<String> = (0..self.cols).map(|j| format!("{:>1$}", cells[i * self.cols + j], widths[j])).collect();
+            row.join("  ")
+        }).collect()
+    }
+}
+
+impl std::fmt::Display for Matrix {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.format(|x| x.to_string(), ", "))
+    }
+}
+
+// The matrix as an aligned grid in the result area
+pub fn show(ui: &mut egui::Ui, matrix: &Matrix, format: &NumberFormat, size: f32) {
+    let lines = matrix.aligned(|x| format.number(x));
+    egui::Frame::none()
+        .stroke(egui::Stroke::new(1.0, egui::Color32::GREEN))
+        .inner_margin(egui::Margin::symmetric(8.0, 4.0))
+        .show(ui, |ui| {
+            for line in &lines {
+                ui.label(egui::RichText::new(line).monospace().size(size).color(egui::Color32::GREEN));
+            }
+        });
+}
+
+// Names offered for quick selection in the editor
+const NAMES: [&str; 3] = ["A", "B", "C"];
+const MAX_SIZE: usize = 12;
+
+// State of the matrix tab: a grid of cells, each a number or an expression,
+// saved as a variable so expressions can use it by name
+pub struct MatrixEditor {
+    name: String,
+    rows: usize,
+    cols: usize,
+    // Row by row, rows × cols of them
+    cells: Vec<String>,
+    status: Option<Result<String, String>>,
+}
+
+impl Default for MatrixEditor {
+    fn default() -> Self {
+        MatrixEditor { name: "A".to_string(), rows: 2, cols: 2, cells: vec![String::new(); 4], status: None }
+    }
+}
+
+impl MatrixEditor {
+    // Keeps the cells that are still inside the new size
+    fn resize(&mut self, rows: usize, cols: usize) {
+        let mut cells = vec![String::new(); rows * cols];
+        for i in 0..rows.min(self.rows) {
+            for j in 0..cols.min(self.cols) {
+                cells[i * cols + j] = std::mem::take(&mut self.cells[i * self.cols + j]);
+            }
+        }
+        (self.rows, self.cols, self.cells) = (rows, cols, cells);
+    }
+
+    fn load(&mut self, matrix: &Matrix, format: &NumberFormat) {
+        self.rows = matrix.rows;
+        self.cols = matrix.cols;
+        self.cells = matrix.data.iter().map(|x| format.number(*x)).collect();
+    }
+
+    // Empty cells are 0, as in a spreadsheet
+    fn matrix(&self, calculator: &Calculator) -> Result<Matrix, String> {
+        let data = self.cells.iter().enumerate().map(|(k, cell)| {
+            if cell.trim().is_empty() {
+                return Ok(0.0);
+            }
+            calculator.calculate(cell).and_then(Value::real)
+                .map_err(|e| format!("Fila {}, columna {}: {}", k / self.cols + 1, k % self.cols + 1, e))
+        }).collect::<Result<Vec<f64>, String>>()?;
+        Ok(Matrix::new(self.rows, self.cols, data))
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, calculator: &mut Calculator, format: &NumberFormat) {
+        ui.heading("Matrices");
+        ui.small("Escribe los elementos (números o expresiones; vacío es 0) y guarda la matriz con un nombre para usarla en la calculadora, p. ej. A*B o 2A.");
+        ui.add_space(10.0);
+
+        ui.horizontal(|ui| {
+            ui.label("Nombre:");
+            for name in NAMES {
+                if ui.selectable_label(self.name == name, name).clicked() {
+                    self.name = name.to_string();
+                }
+            }
+            ui.add(egui::TextEdit::singleline(&mut self.name).font(egui::TextStyle::Monospace).desired_width(60.0));
+        });
+        let (mut rows, mut cols) = (self.rows, self.cols);
+        ui.horizontal(|ui| {
+            ui.label("Filas:");
+            ui.add(egui::DragValue::new(&mut rows).range(1..=MAX_SIZE));
+            ui.label("Columnas:");
+            ui.add(egui::DragValue::new(&mut cols).range(1..=MAX_SIZE));
+        });
+        if (rows, cols) != (self.rows, self.cols) {
+            self.resize(rows, cols);
+        }
+        ui.add_space(6.0);
+
+        egui::ScrollArea::both().max_height(320.0).show(ui, |ui| {
+            egui::Grid::new("matrix_cells").spacing([4.0, 4.0]).show(ui, |ui| {
+                for i in 0..self.rows {
+                    for j in 0..self.cols {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.cells[i * self.cols + j])
+                                .font(egui::TextStyle::Monospace)
+                                .horizontal_align(egui::Align::RIGHT)
+                                .hint_text("0")
+                                .desired_width(70.0)
+                        );
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+        ui.add_space(6.0);
+
+        let name = self.name.trim().to_string();
+        ui.horizontal(|ui| {
+            let save = egui::Button::new(format!("Guardar como {}", name)).fill(egui::Color32::from_rgb(0, 150, 0));
+            if ui.add_enabled(!name.is_empty(), save).clicked() {
+                self.status = Some(self.matrix(calculator).and_then(|matrix| {
+                    let size = format!("{}×{}", matrix.rows, matrix.cols);
+                    calculator.assign(&name, Value::Matrix(matrix)).map_err(|e| e.to_string())?;
+                    Ok(format!("{} guardada ({})", name, size))
+                }));
+            }
+            if ui.button("Vaciar").clicked() {
+                self.cells.iter_mut().for_each(String::clear);
+                self.status = None;
+            }
+            if ui.button("Identidad").on_hover_text("Unos en la diagonal y ceros fuera").clicked() {
+                let n = self.rows.min(self.cols);
+                self.cells = (0..self.rows * self.cols)
+                    .map(|k| if k / self.cols == k % self.cols && k / self.cols < n { "1" } else { "0" }.to_string())
+                    .collect();
+            }
+        });
+        match &self.status {
+            Some(Ok(notice)) => {
+                ui.colored_label(egui::Color32::GREEN, notice);
+            },
+            Some(Err(error)) => {
+                ui.colored_label(egui::Color32::RED, error);
+            },
+            None => {},
+        }
+
+        // Matrices already defined, to edit again
+        let saved: Vec<(String, Matrix)> = calculator.variables.iter()
+            .filter_map(|(name, value)| match value {
+                Value::Matrix(matrix) => Some((name.clone(), matrix.clone())),
+                _ => None,
+            })
+            .collect();
+        if !saved.is_empty() {
+            ui.separator();
+            ui.strong("Guardadas");
+            for (name, matrix) in saved {
+                ui.horizontal(|ui| {
+                    ui.monospace(format!("{} ({}×{})", name, matrix.rows, matrix.cols));
+                    if ui.small_button("Editar").clicked() {
+                        self.name = name.clone();
+                        self.load(&matrix, format);
+                        self.status = None;
+                    }
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculator::Calculator;
+    use crate::testing::close;
+
+    fn result(expr: &str) -> Result<String, String> {
+        Calculator::default().calculate(expr).map(|value| value.to_string()).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(result("[[1,2],[3,4]] * [[5,6],[7,8]]").unwrap(), "[[19, 22], [43, 50]]");
+        assert_eq!(result("[[1,2],[3,4]] + [[1,1],[1,1]]").unwrap(), "[[2, 3], [4, 5]]");
+        assert_eq!(result("2 * [[1,2],[3,4]]").unwrap(), "[[2, 4], [6, 8]]");
+        assert_eq!(result("[[1,2],[3,4]]^2").unwrap(), "[[7, 10], [15, 22]]");
+        assert_eq!(result("transpose([[1,2,3],[4,5,6]])").unwrap(), "[[1, 4], [2, 5], [3, 6]]");
+        // Sizes have to agree, and rows have to be the same length
+        assert!(result("[[1,2],[3,4]] * [1,1]").is_err());
+        assert!(result("[[1,2],[3,4]] + [1,2,3]").is_err());
+        assert!(result("[[1,2],[3]]").is_err());
+    }
+
+    #[test]
+    fn determinant_inverse_and_factors() {
+        let a = Matrix::new(2, 2, vec![4.0, 7.0, 2.0, 6.0]);
+        assert!(close(a.determinant().unwrap(), 10.0));
+        let inverse = a.inverse().unwrap();
+        for (found, expected) in inverse.data.iter().zip([0.6, -0.7, -0.2, 0.4]) {
+            assert!(close(*found, expected));
+        }
+        let identity = a.mul(&inverse).unwrap();
+        assert!(identity.data.iter().zip(&Matrix::identity(2).data).all(|(x, y)| close(*x, *y)));
+        // Q orthogonal, R upper triangular, and QR = A
+        let b = Matrix::new(3, 2, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let (q, r) = b.qr();
+        assert_eq!(r.get(1, 0), 0.0);
+        assert!(q.mul(&r).unwrap().data.iter().zip(&b.data).all(|(x, y)| close(*x, *y)));
+        assert!(q.transpose().mul(&q).unwrap().data.iter().zip(&Matrix::identity(3).data).all(|(x, y)| close(*x, *y)));
+    }
+
+    #[test]
+    fn singular_and_non_square() {
+        let singular = Matrix::new(2, 2, vec![1.0, 2.0, 2.0, 4.0]);
+        assert_eq!(singular.rank(), 1);
+        assert!(singular.inverse().is_err());
+        assert!(Matrix::new(2, 3, vec![1.0; 6]).determinant().is_err());
+        assert_eq!(result("det([[1,2],[3,4]])").unwrap(), "-2");
+    }
+
+    #[test]
+    fn products_past_the_element_cap() {
+        let column = Matrix::new(2000, 1, vec![1.0; 2000]);
+        let row = Matrix::new(1, 2000, vec![1.0; 2000]);
+        assert!(column.mul(&row).is_err());
+        assert_eq!(row.mul(&column).unwrap().get(0, 0), 2000.0);
+    }
+}
@@ -0,0 +1,172 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::words::Locale;
+
+// Error messages are written in Spanish where they are raised and put into
+// English when shown, if that is the chosen language
+static ENGLISH: AtomicBool = AtomicBool::new(false);
+
+// Spanish message and its English version; {} stands for the parts that vary
+const ENGLISH_MESSAGES: &[(&str, &str)] = &[
+    ("No hay suficientes operandos", "Not enough operands"),
+    ("No hay suficientes operandos para la función", "Not enough operands for the function"),
+    ("Se esperaba un número", "A number was expected"),
+    ("Se esperaba un número real", "A real number was expected"),
+    ("Se esperaba un texto entre comillas", "A quoted text was expected"),
+    ("Expresión inválida", "Invalid expression"),
+    ("Falta cerrar las comillas", "Missing closing quote"),
+    ("Solo se puede asignar a un nombre, p. ej. x = 2", "Only a name can be assigned to, e.g. x = 2"),
+    ("El factorial no está definido para enteros negativos", "The factorial is not defined for negative integers"),
+    ("El factorial requiere un número real", "The factorial requires a real number"),
+    ("La función gamma no está definida para enteros no positivos", "The gamma function is not defined for non-positive integers"),
+    ("No se puede tomar el logaritmo de un número no positivo", "Cannot take the logarithm of a non-positive number"),
+    ("No se puede tomar el logaritmo de cero", "Cannot take the logarithm of zero"),
+    ("No se puede sacar raíz cuadrada de un número negativo", "Cannot take the square root of a negative number"),
+    ("acosh requiere un valor mayor o igual que 1", "acosh requires a value of at least 1"),
+    ("Exponente demasiado grande", "Exponent too large"),
+    ("El valor debe ser un número finito", "The value must be a finite number"),
+    ("Número demasiado grande para convertir a palabras", "Number too large to write in words"),
+    ("Los números complejos están desactivados", "Complex numbers are turned off"),
+    ("Los números complejos no admiten unidades", "Complex numbers cannot have units"),
+    ("Los números complejos no admiten incertidumbre", "Complex numbers cannot have an uncertainty"),
+    ("Las unidades no admiten incertidumbre", "Quantities with units cannot have an uncertainty"),
+    ("Operador no disponible con incertidumbre", "Operator not available with uncertainties"),
+    ("Operación no disponible con unidades", "Operation not available with units"),
+    ("No se puede anidar ±", "± cannot be nested"),
+    ("mod no está definido para números complejos", "mod is not defined for complex numbers"),
+    ("Los operadores de bits solo están disponibles en el modo programador", "Bitwise operators are only available in programmer mode"),
+    ("El desplazamiento no puede ser negativo", "The shift amount cannot be negative"),
+    ("El modo entero solo admite números enteros", "Integer mode only accepts integers"),
+    ("El modo entero no admite unidades", "Integer mode does not support units"),
+    ("El modo entero no admite exponentes negativos", "Integer mode does not support negative exponents"),
+    ("± no está disponible en modo entero", "± is not available in integer mode"),
+    ("{} no está disponible en modo entero", "{} is not available in integer mode"),
+    ("{} espera {} argumento(s)", "{} expects {} argument(s)"),
+    ("{} requiere un valor entre -1 y 1", "{} requires a value between -1 and 1"),
+    ("{} requiere un valor sin unidades (tiene {})", "{} requires a value without units (it has {})"),
+    ("{} no admite incertidumbre", "{} does not support uncertainties"),
+    ("{} no está definida para números complejos", "{} is not defined for complex numbers"),
+    ("{} solo admite exponentes enteros", "{} only accepts integer exponents"),
+    ("{}({}) no tiene unidades válidas", "{}({}) has no valid units"),
+    ("El exponente no puede tener unidades ({})", "The exponent cannot have units ({})"),
+    ("Dimensiones incompatibles: {} {} {}", "Incompatible dimensions: {} {} {}"),
+    ("No se puede convertir {} ({}) a {} ({})", "Cannot convert {} ({}) to {} ({})"),
+    ("No se puede convertir {} a {} ({})", "Cannot convert {} to {} ({})"),
+    ("El resultado excede el límite de {} dígitos", "The result exceeds the limit of {} digits"),
+    ("El resultado excede el límite de {} elementos", "The result exceeds the limit of {} elements"),
+    ("Moneda desconocida: {}", "Unknown currency: {}"),
+    ("La tolerancia debe ser un número no negativo", "The tolerance must be a non-negative number"),
+    ("Comprobación fallida: {} ≠ {} (tolerancia {})", "Check failed: {} ≠ {} (tolerance {})"),
+    ("fetch_num() está desactivada; actívala en ⚙ Configuración", "fetch_num() is turned off; turn it on in ⚙ Settings"),
+    ("fetch_num solo admite direcciones http:// o https://, no {}", "fetch_num only accepts http:// or https:// addresses, not {}"),
+    ("No se pudo descargar {}: {}", "Could not download {}: {}"),
+    ("{} no devolvió JSON", "{} did not return JSON"),
+    ("{} no es un número en {}", "{} is not a number in {}"),
+    ("No hay nada en {} de {}", "Nothing at {} in {}"),
+    ("La evaluación se interrumpió", "The evaluation was interrupted"),
+    ("nderiv: la función no es derivable o no es real cerca de {} = {}", "nderiv: the function is not differentiable or not real near {} = {}"),
+    ("La función no está definida en el valor inicial {}", "The function is not defined at the initial guess {}"),
+    ("La pendiente se anula en {}; prueba con otro valor inicial", "The slope vanishes at {}; try another initial guess"),
+    ("El método divergió; prueba con otro valor inicial", "The method diverged; try another initial guess"),
+    ("No converge tras {} iteraciones; prueba con otro valor inicial", "Did not converge after {} iterations; try another initial guess"),
+    ("Se esperaba un número, no una matriz", "A number was expected, not a matrix"),
+    ("La matriz está vacía", "The matrix is empty"),
+    ("Todas las filas de una matriz deben tener los mismos elementos", "All rows of a matrix must have the same number of elements"),
+    ("Los elementos de una matriz deben ser números reales", "The elements of a matrix must be real numbers"),
+    ("Las matrices deben tener el mismo tamaño: {}×{} y {}×{}", "The matrices must be the same size: {}×{} and {}×{}"),
+    ("No se pueden multiplicar una matriz {}×{} y una {}×{}", "Cannot multiply a {}×{} matrix by a {}×{} one"),
+    ("Solo las matrices cuadradas tienen potencias", "Only square matrices have powers"),
+    ("Una matriz solo se eleva a enteros", "A matrix can only be raised to integers"),
+    ("Las matrices solo se combinan con matrices o números reales", "Matrices only combine with matrices or real numbers"),
+    ("Operación no definida para matrices", "Operation not defined for matrices"),
+    ("Las matrices no admiten incertidumbre", "Matrices cannot have uncertainty"),
+    ("Las matrices no admiten unidades", "Matrices cannot have units"),
+    ("{} no admite matrices", "{} does not accept matrices"),
+    ("Se esperaba una matriz", "A matrix was expected"),
+    ("{} requiere una matriz cuadrada, no {}×{}", "{} requires a square matrix, not {}×{}"),
+    ("La matriz es singular (determinante 0) y no tiene inversa", "The matrix is singular (determinant 0) and has no inverse"),
+    ("lu(A, \"L\"), lu(A, \"U\") o lu(A, \"P\"), con PA = LU", "lu(A, \"L\"), lu(A, \"U\") or lu(A, \"P\"), with PA = LU"),
+    ("qr(A, \"Q\") o qr(A, \"R\"), con A = QR", "qr(A, \"Q\") or qr(A, \"R\"), with A = QR"),
+    ("Se esperaba un número, no valores propios", "A number was expected, not eigenvalues"),
+    ("eig no converge para esta matriz", "eig does not converge for this matrix"),
+    ("{} requiere vectores, no una matriz {}×{}", "{} requires vectors, not a {}×{} matrix"),
+    ("{} requiere vectores de la misma longitud: {} y {}", "{} requires vectors of the same length: {} and {}"),
+    ("cross requiere vectores de 3 elementos", "cross requires vectors of 3 elements"),
+    ("angle no está definido con el vector cero", "angle is not defined for the zero vector"),
+    ("Falta cerrar una llave }", "Missing closing brace }"),
+    ("Los elementos de una lista deben ser números reales", "The elements of a list must be real numbers"),
+    ("Se esperaba un número, no una lista", "A number was expected, not a list"),
+    ("Las listas no admiten incertidumbre", "Lists cannot have uncertainty"),
+    ("Las listas no admiten unidades", "Lists cannot have units"),
+    ("Se esperaba una lista", "A list was expected"),
+    ("{} no admite listas", "{} does not accept lists"),
+    ("{} de una lista vacía", "{} of an empty list"),
+    ("Las listas deben tener la misma longitud: {} y {}", "The lists must have the same length: {} and {}"),
+    ("Las listas solo se combinan con listas o números reales", "Lists only combine with lists or real numbers"),
+    ("{} requiere σ > 0", "{} requires σ > 0"),
+    ("{} requiere una probabilidad entre 0 y 1", "{} requires a probability between 0 and 1"),
+    ("{} requiere un número entero no negativo de casos", "{} requires a non-negative whole number of trials"),
+    ("{} requiere grados de libertad > 0", "{} requires degrees of freedom > 0"),
+    ("{} requiere λ > 0", "{} requires λ > 0"),
+    ("El tipo de interés debe ser mayor que -100 %", "The interest rate must be greater than -100 %"),
+    ("Hace falta al menos un periodo", "At least one period is needed"),
+    ("Ningún número de periodos cumple estos valores", "No number of periods matches these values"),
+    ("rate no converge para estos valores", "rate does not converge for these values"),
+    ("irr requiere flujos de caja positivos y negativos", "irr requires both positive and negative cash flows"),
+    ("Tiene que haber al menos un pago al año", "There must be at least one payment a year"),
+    ("irr no converge para estos flujos", "irr does not converge for these cash flows"),
+    ("Falta cerrar un corchete ]", "Missing closing bracket ]"),
+    ("Unidad desconocida: {}", "Unknown unit: {}"),
+    ("@deg y @rad van al principio, p. ej. @deg x = sin(30)", "@deg and @rad go at the start, e.g. @deg x = sin(30)"),
+    ("«{}» es un nombre reservado", "'{}' is a reserved name"),
+    ("«{}» es una función incorporada", "'{}' is a built-in function"),
+    ("«{}» ya es una constante incorporada", "'{}' is already a built-in constant"),
+    ("«{}» no es un entero", "'{}' is not an integer"),
+    ("«{}» no es una unidad válida", "'{}' is not a valid unit"),
+];
+
+pub fn set_language(locale: Locale) {
+    ENGLISH.store(locale == Locale::En, Ordering::Relaxed);
+}
+
+pub fn language() -> Locale {
+    if ENGLISH.load(Ordering::Relaxed) { Locale::En } else { Locale::Es }
+}
+
+// `to` with its {} replaced by what stands in `message` where `from` has {},
+// if `message` has the shape of `from`
+fn fill(from: &str, to: &str, message: &str) -> Option<String> {
+    let parts: Vec<&str> = from.split("{}").collect();
+    let mut rest = message.strip_prefix(parts[0])?;
+    let mut pieces = Vec::new();
+    for (i, part) in parts.iter().enumerate().skip(1) {
+        let end = if i == parts.len() - 1 {
+            rest.strip_suffix(part)?.len()
+        } else {
+            rest.find(part)?
+        };
+        pieces.push(&rest[..end]);
+        rest = &rest[end + part.len()..];
+    }
+    if !rest.is_empty() {
+        return None;
+    }
+    let mut filled = String::new();
+    for (i, part) in to.split("{}").enumerate() {
+        if i > 0 {
+            filled.push_str(pieces.get(i - 1)?);
+        }
+        filled.push_str(part);
+    }
+    Some(filled)
+}
+
+// An engine message in the current language; ones without a translation stay in Spanish
+pub fn translate(message: &str) -> String {
+    if language() == Locale::Es {
+        return message.to_string();
+    }
+    ENGLISH_MESSAGES.iter()
+        .find_map(|(from, to)| fill(from, to, message))
+        .unwrap_or_else(|| message.to_string())
+}
@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+use crate::programmer::{Radix, WordSize};
+use crate::Tab;
+
+// A graph curve's f(x), color and visibility
+type CurveState = (String, egui::Color32, bool);
+
+// The curves, the visible ranges and whether y follows the curves
+pub type GraphState = (Vec<CurveState>, [f64; 4], bool);
+
+// What each tab was left with, kept apart from the others and from any
+// workspace, so the next session opens every mode as it was
+#[derive(Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Modes {
+    pub tab: Tab,
+    // The scientific calculator's expression
+    pub display: String,
+    pub converter: (String, String, String),
+    pub currency: (String, String, String),
+    pub programmer: (Radix, WordSize, String),
+    pub graph: Option<GraphState>,
+}
@@ -0,0 +1,375 @@
+use crate::ast::Expr;
+use crate::calculator::{Calculator, Error, Operator};
+use crate::complex::Complex;
+use crate::format::NumberFormat;
+use crate::separators::Separators;
+use crate::value::Value;
+
+const MAX_ITERATIONS: usize = 1000;
+// Relative change of every root at which the iteration stops
+const TOLERANCE: f64 = 1e-14;
+// Roots this close, relative to their size, may be one repeated root: a
+// root of multiplicity m only comes out to about the m-th root of the
+// machine precision
+const CLUSTER: f64 = 1e-2;
+// How small, relative to the rounding of the terms, p and its derivatives
+// must be at a repeated root
+const REPEATED: f64 = 1e-9;
+// Newton steps to polish each root
+const POLISH: usize = 20;
+// Parts this much smaller than the root are rounding noise, shown as 0
+const NOISE: f64 = 1e-10;
+// Highest power written out, so x^1000000 isn't expanded
+const MAX_DEGREE: usize = 100;
+
+// Coefficients from the constant term up, without zero leading ones
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polynomial(Vec<f64>);
+
+impl Polynomial {
+    fn new(mut coefficients: Vec<f64>) -> Polynomial {
+        while coefficients.last() == Some(&0.0) {
+            coefficients.pop();
+        }
+        Polynomial(coefficients)
+    }
+
+    fn constant(c: f64) -> Polynomial {
+        Polynomial::new(vec![c])
+    }
+
+    pub fn degree(&self) -> usize {
+        self.0.len().saturating_sub(1)
+    }
+
+    fn as_constant(&self) -> Option<f64> {
+        match self.0.as_slice() {
+            [] => Some(0.0),
+            [c] => Some(*c),
+            _ => None,
+        }
+    }
+
+    fn add(&self, other: &Polynomial, sign: f64) -> Polynomial {
+        let mut sum = vec![0.0; self.0.len().max(other.0.len())];
+        for (i, c) in self.0.iter().enumerate() {
+            sum[i] += c;
+        }
+        for (i, c) in other.0.iter().enumerate() {
+            sum[i] += sign * c;
+        }
+        Polynomial::new(sum)
+    }
+
+    fn mul(&self, other: &Polynomial) -> Result<Polynomial, String> {
+        if self.degree() + other.degree() > MAX_DEGREE {
+            return Err(format!("El grado pasa de {}", MAX_DEGREE));
+        }
+        let mut product = vec![0.0; self.0.len() + other.0.len()];
+        for (i, a) in self.0.iter().enumerate() {
+            for (j, b) in other.0.iter().enumerate() {
+                product[i + j] += a * b;
+            }
+        }
+        Ok(Polynomial::new(product))
+    }
+
+    fn eval(&self, z: Complex) -> Complex {
+        self.0.iter().rev().fold(Complex::from(0.0), |sum, c| sum * z + Complex::from(*c))
+    }
+
+    // The size of the terms at z, which rounding errors in eval scale with
+    fn magnitude(&self, z: Complex) -> f64 {
+        self.0.iter().rev().fold(0.0, |sum, c| sum * z.abs() + c.abs())
+    }
+
+    fn derivative(&self) -> Polynomial {
+        Polynomial::new(self.0.iter().enumerate().skip(1).map(|(i, c)| i as f64 * c).collect())
+    }
+
+    // Newton's method from z, which must be close to a simple root; z as it
+    // was if the steps wander off
+    fn polish(&self, z: Complex) -> Complex {
+        let derivative = self.derivative();
+        let mut x = z;
+        for _ in 0..POLISH {
+            let slope = derivative.eval(x);
+            if slope.is_zero() {
+                break;
+            }
+            let step = self.eval(x) / slope;
+            x = x - step;
+            if step.abs() <= f64::EPSILON * x.abs() {
+                break;
+            }
+        }
+        if (x - z).abs() <= CLUSTER * z.abs().max(1.0) { x } else { z }
+    }
+
+    // Written from the highest power down, 2x^3 - x + 1
+    pub fn describe(&self, format: &NumberFormat) -> String {
+        let mut text = String::new();
+        for (power, c) in self.0.iter().enumerate().rev().filter(|(_, c)| **c != 0.0) {
+            let sign = if c < &0.0 { "-" } else { "+" };
+            if text.is_empty() {
+                text.push_str(if c < &0.0 { "-" } else { "" });
+            } else {
+                text.push_str(&format!(" {} ", sign));
+            }
+            let magnitude = c.abs();
+            if magnitude != 1.0 || power == 0 {
+                text.push_str(&format.number(magnitude));
+            }
+            match power {
+                0 => {},
+                1 => text.push('x'),
+                _ => text.push_str(&format!("x^{}", power)),
+            }
+        }
+        if text.is_empty() { "0".to_string() } else { text }
+    }
+}
+
+// Reads `input` as a polynomial: either its coefficients from the highest
+// power down, "1, -3, 2" (";" with a decimal comma), or an expression in x
+// such as (x - 1)^2*(x + 2); other names keep their current values
+pub fn parse(calculator: &Calculator, input: &str) -> Result<Polynomial, String> {
+    let separator = if calculator.separators == Separators::Comma { ';' } else { ',' };
+    let items: Vec<&str> = input.split(separator).collect();
+    if items.len() > 1 {
+        let coefficients = items.iter().rev()
+            .map(|item| calculator.calculate(item).and_then(Value::real).map_err(|e| e.to_string()))
+            .collect::<Result<Vec<f64>, String>>()?;
+        return Ok(Polynomial::new(coefficients));
+    }
+    let expr = calculator.ast(input).map_err(|e| e.to_string())?;
+    polynomial(calculator, &expr)
+}
+
+fn not_polynomial() -> String {
+    "No es un polinomio en x".to_string()
+}
+
+// Parts of the tree without x are worked out to a number
+fn polynomial(calculator: &Calculator, expr: &Expr) -> Result<Polynomial, String> {
+    let constant = |value: Result<Value, Error>| value.and_then(Value::real).map(Polynomial::constant).map_err(|e| e.to_string());
+    match expr {
+        Expr::Number(n) => Ok(Polynomial::constant(*n)),
+        Expr::Integer(n) => constant(Ok(Value::Integer(n.clone()))),
+        Expr::Constant(c) if !c.imaginary => Ok(Polynomial::constant(c.value)),
+        Expr::Variable(name) if name == "x" => Ok(Polynomial::new(vec![0.0, 1.0])),
+        Expr::Variable(name) => match calculator.variables.get(name) {
+            Some(value) => constant(Ok(value.clone())),
+            None => calculator.user_constants.get(name).map(|c| Polynomial::constant(*c)).ok_or_else(|| format!("Variable no definida: {}", name)),
+        },
+        Expr::Neg(inner) => Ok(Polynomial::constant(0.0).add(&polynomial(calculator, inner)?, -1.0)),
+        Expr::Binary(op, left, right) => {
+            let (left, right) = (polynomial(calculator, left)?, polynomial(calculator, right)?);
+            match (op, right.as_constant()) {
+                (Operator::Add, _) => Ok(left.add(&right, 1.0)),
+                (Operator::Sub, _) => Ok(left.add(&right, -1.0)),
+                (Operator::Mul | Operator::Juxtapose, _) => left.mul(&right),
+                (Operator::Div, Some(d)) if d != 0.0 => Ok(Polynomial::new(left.0.iter().map(|c| c / d).collect())),
+                (Operator::Pow, Some(n)) if left.as_constant().is_none() => {
+                    // In f64, as huge exponents don't fit a usize
+                    if n < 0.0 || n.fract() != 0.0 || n * left.degree() as f64 > MAX_DEGREE as f64 {
+                        return Err(not_polynomial());
+                    }
+                    (0..n as usize).try_fold(Polynomial::constant(1.0), |power, _| power.mul(&left))
+                },
+                _ => match (left.as_constant(), right.as_constant()) {
+                    (Some(a), Some(b)) => constant(calculator.apply_operator(*op, Value::Number(a), Value::Number(b))),
+                    _ => Err(not_polynomial()),
+                },
+            }
+        },
+        Expr::Function(func, args) => {
+            let args = args.iter()
+                .map(|arg| polynomial(calculator, arg)?.as_constant().map(Value::Number).ok_or_else(not_polynomial))
+                .collect::<Result<Vec<Value>, String>>()?;
+            constant(calculator.call(*func, args))
+        },
+        Expr::Factorial(inner) => match polynomial(calculator, inner)?.as_constant() {
+            Some(n) => constant(Calculator::factorial(Value::Number(n))),
+            None => Err(not_polynomial()),
+        },
+        _ => Err(not_polynomial()),
+    }
+}
+
+pub struct Root {
+    pub value: Complex,
+    pub multiplicity: usize,
+}
+
+fn clean(x: f64, size: f64) -> f64 {
+    if x.abs() <= NOISE * size { 0.0 } else { x }
+}
+
+// All roots of `p`, complex ones included, by the Durand–Kerner iteration
+// on the monic polynomial; roots that come out together are grouped into
+// one with its multiplicity, at their mean, which is more precise than any
+// of them. Zero roots are factored out first, as they are exact
+pub fn roots(p: &Polynomial) -> Result<Vec<Root>, String> {
+    if p.degree() == 0 {
+        return Err("Hace falta un polinomio de grado 1 o más".to_string());
+    }
+    let zeros = p.0.iter().take_while(|c| **c == 0.0).count();
+    let lead = *p.0.last().unwrap();
+    let monic = Polynomial(p.0[zeros..].iter().map(|c| c / lead).collect());
+    let n = monic.degree();
+    // Starting points spread on a circle about as big as the roots
+    let radius = 1.0 + monic.0.iter().take(n).map(|c| c.abs()).fold(0.0, f64::max);
+    let mut z: Vec<Complex> = (0..n).map(|k| Complex::from_polar(radius, 2.0 * std::f64::consts::PI * k as f64 / n as f64 + 0.4)).collect();
+    let mut converged = false;
+    for _ in 0..MAX_ITERATIONS {
+        let mut change: f64 = 0.0;
+        for i in 0..n {
+            let denominator = (0..n).filter(|&j| j != i).fold(Complex::from(1.0), |product, j| product * (z[i] - z[j]));
+            if denominator.is_zero() {
+                continue;
+            }
+            let step = monic.eval(z[i]) / denominator;
+            z[i] = z[i] - step;
+            change = change.max(step.abs() / z[i].abs().max(1.0));
+        }
+        if !change.is_finite() {
+            return Err("El método no converge para estos coeficientes".to_string());
+        }
+        if change <= TOLERANCE {
+            converged = true;
+            break;
+        }
+    }
+    // Near repeated roots the steps stall at rounding noise rather than
+    // shrink, which is fine as long as p is about 0 at every iterate
+    let roots_found = z.iter().all(|z| monic.eval(*z).abs() <= REPEATED * monic.magnitude(*z));
+    if !converged && !roots_found {
+        return Err(format!("No converge tras {} iteraciones", MAX_ITERATIONS));
+    }
+
+    // A root of multiplicity m is a simple root of the (m-1)-th derivative,
+    // where it's polished; a group of close roots that isn't also a root of
+    // the lower derivatives was distinct roots after all
+    let mut clusters: Vec<Vec<Complex>> = Vec::new();
+    for root in z {
+        let close = |cluster: &&mut Vec<Complex>| cluster.iter().any(|z| (*z - root).abs() <= CLUSTER * root.abs().max(1.0));
+        match clusters.iter_mut().find(close) {
+            Some(cluster) => cluster.push(root),
+            None => clusters.push(vec![root]),
+        }
+    }
+    let mut roots = Vec::new();
+    for cluster in clusters {
+        let m = cluster.len();
+        let mean = cluster.iter().fold(Complex::from(0.0), |sum, z| sum + *z) / Complex::from(m as f64);
+        let derivatives: Vec<Polynomial> = std::iter::successors(Some(monic.clone()), |p| Some(p.derivative())).take(m).collect();
+        let root = derivatives[m - 1].polish(mean);
+        let repeated = derivatives.iter().all(|p| p.eval(root).abs() <= REPEATED * p.magnitude(root));
+        if m == 1 || repeated {
+            roots.push(Root { value: root, multiplicity: m });
+        } else {
+            roots.extend(cluster.into_iter().map(|z| Root { value: monic.polish(z), multiplicity: 1 }));
+        }
+    }
+    for root in &mut roots {
+        let size = root.value.abs().max(NOISE);
+        root.value = Complex::new(clean(root.value.re, size), clean(root.value.im, size));
+    }
+    if zeros > 0 {
+        roots.push(Root { value: Complex::from(0.0), multiplicity: zeros });
+    }
+    // Real roots first, in increasing order, then complex ones
+    roots.sort_by(|a, b| (a.value.im != 0.0).cmp(&(b.value.im != 0.0)).then(a.value.re.total_cmp(&b.value.re)).then(a.value.im.total_cmp(&b.value.im)));
+    Ok(roots)
+}
+
+// State of the polynomial tab: what was typed and the roots found
+#[derive(Default)]
+pub struct Polynomials {
+    input: String,
+    result: Option<Result<(Polynomial, Vec<Root>), String>>,
+}
+
+impl Polynomials {
+    pub fn show(&mut self, ui: &mut egui::Ui, calculator: &Calculator, format: &NumberFormat) {
+        ui.heading("Polinomios");
+        ui.small("Coeficientes del grado mayor al menor (1, -3, 2) o una expresión en x ((x - 1)^2*(x + 2)).");
+        ui.add_space(10.0);
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut self.input)
+                .font(egui::TextStyle::Monospace)
+                .desired_width(360.0)
+                .hint_text("x^3 - 2x + 1")
+        );
+        let enter = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        let solve = egui::Button::new("Hallar raíces").fill(egui::Color32::from_rgb(0, 150, 0));
+        if ui.add(solve).clicked() || enter {
+            self.result = Some(parse(calculator, &self.input).and_then(|p| {
+                let roots = roots(&p)?;
+                Ok((p, roots))
+            }));
+        }
+        ui.add_space(10.0);
+        match &self.result {
+            Some(Ok((p, roots))) => {
+                ui.monospace(format!("p(x) = {}", p.describe(format)));
+                ui.small(format!("Grado {}: {} raíces contando multiplicidad", p.degree(), p.degree()));
+                ui.add_space(6.0);
+                egui::Grid::new("polynomial_roots").num_columns(3).striped(true).show(ui, |ui| {
+                    ui.strong("Raíz");
+                    ui.strong("Multiplicidad");
+                    ui.strong("");
+                    ui.end_row();
+                    for root in roots {
+                        let text = if root.value.im == 0.0 { format.number(root.value.re) } else { root.value.format(|x| format.number(x)) };
+                        ui.monospace(&text);
+                        ui.monospace(root.multiplicity.to_string());
+                        if ui.small_button("Copiar").clicked() {
+                            ui.ctx().copy_text(text);
+                        }
+                        ui.end_row();
+                    }
+                });
+            },
+            Some(Err(error)) => {
+                ui.colored_label(egui::Color32::RED, error);
+            },
+            None => {},
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    fn found(input: &str) -> Result<Vec<(f64, f64, usize)>, String> {
+        let p = parse(&Calculator::default(), input)?;
+        Ok(roots(&p)?.iter().map(|root| (root.value.re, root.value.im, root.multiplicity)).collect())
+    }
+
+    fn close(found: &[(f64, f64, usize)], expected: &[(f64, f64, usize)]) -> bool {
+        found.len() == expected.len() && found.iter().zip(expected).all(|(a, b)| {
+            testing::close(a.0, b.0) && testing::close(a.1, b.1) && a.2 == b.2
+        })
+    }
+
+    #[test]
+    fn simple_and_repeated_roots() {
+        assert!(close(&found("x^2 - 3x + 2").unwrap(), &[(1.0, 0.0, 1), (2.0, 0.0, 1)]));
+        assert!(close(&found("(x - 2)^3*(x + 1)^2").unwrap(), &[(-1.0, 0.0, 2), (2.0, 0.0, 3)]));
+        assert!(close(&found("x^2 + 1").unwrap(), &[(0.0, -1.0, 1), (0.0, 1.0, 1)]));
+        assert!(close(&found("x^3").unwrap(), &[(0.0, 0.0, 3)]));
+        assert!(close(&found("(x^2 + 1)^4").unwrap(), &[(0.0, -1.0, 4), (0.0, 1.0, 4)]));
+    }
+
+    #[test]
+    fn degree_limit() {
+        assert!(parse(&Calculator::default(), "x^100").is_ok());
+        assert!(parse(&Calculator::default(), "x^101").is_err());
+        assert!(parse(&Calculator::default(), "(x^2)^1e300").is_err());
+        assert!(found("5").is_err());
+    }
+}
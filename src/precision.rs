@@ -0,0 +1,128 @@
+use num_traits::ToPrimitive;
+
+use crate::ast::Expr;
+use crate::calculator::{Calculator, Function, Operator};
+use crate::value::Value;
+
+// Relative rounding error of one floating-point operation
+const UNIT_ROUNDOFF: f64 = f64::EPSILON / 2.0;
+// Results with a larger estimated relative error get a warning, i.e. fewer
+// than about 8 correct digits
+const WARN_ERROR: f64 = 1e-8;
+
+// A value with a bound on its relative error, from running error analysis
+#[derive(Clone, Copy)]
+struct Tracked {
+    value: f64,
+    error: f64,
+}
+
+fn exact_or_rounded(value: f64) -> Tracked {
+    let exact = value.fract() == 0.0 && value.abs() < 2f64.powi(53);
+    Tracked { value, error: if exact { 0.0 } else { UNIT_ROUNDOFF } }
+}
+
+struct Tracker<'a> {
+    calculator: &'a Calculator,
+    // The sum or difference that amplified errors the most, and by how much
+    worst: Option<(f64, String)>,
+}
+
+impl Tracker<'_> {
+    // None for anything but real numbers, where no estimate is attempted
+    fn track(&mut self, expr: &Expr) -> Option<Tracked> {
+        match expr {
+            Expr::Number(n) => Some(exact_or_rounded(*n)),
+            Expr::Integer(n) => Some(exact_or_rounded(n.to_f64()?)),
+            Expr::Constant(constant) if !constant.imaginary => {
+                Some(Tracked { value: constant.value, error: UNIT_ROUNDOFF })
+            },
+            Expr::Variable(name) => match self.calculator.variables.get(name) {
+                Some(Value::Number(n)) => Some(exact_or_rounded(*n)),
+                Some(Value::Integer(n)) => Some(exact_or_rounded(n.to_f64()?)),
+                Some(_) => None,
+                None => self.calculator.user_constants.get(name).map(|&n| exact_or_rounded(n)),
+            },
+            Expr::Neg(inner) => self.track(inner).map(|x| Tracked { value: -x.value, ..x }),
+            Expr::Binary(op, left, right) => {
+                let a = self.track(left)?;
+                let b = self.track(right)?;
+                self.binary(*op, a, b, expr)
+            },
+            Expr::Function(func, args) if args.len() == 1 => {
+                let x = self.track(&args[0])?;
+                self.function(*func, x)
+            },
+            _ => None,
+        }
+    }
+
+    fn binary(&mut self, op: Operator, a: Tracked, b: Tracked, expr: &Expr) -> Option<Tracked> {
+        let (value, error) = match op {
+            Operator::Add | Operator::Sub => {
+                let value = if op == Operator::Add { a.value + b.value } else { a.value - b.value };
+                let carried = a.value.abs() * a.error + b.value.abs() * b.error;
+                // How much the operands' errors grow relative to the result
+                let amplification = (a.value.abs() + b.value.abs()) / value.abs();
+                if carried > 0.0 && self.worst.as_ref().is_none_or(|(worst, _)| amplification > *worst) {
+                    self.worst = Some((amplification, expr.to_string()));
+                }
+                let error = if carried == 0.0 { 0.0 } else { carried / value.abs() };
+                (value, error)
+            },
+            Operator::Mul => (a.value * b.value, a.error + b.error),
+            Operator::Div => (a.value / b.value, a.error + b.error),
+            // d(a^b)/(a^b) = b·da/a + b·ln(a)·db/b
+            Operator::Pow => {
+                let value = a.value.powf(b.value);
+                let from_exponent = if b.error == 0.0 { 0.0 } else { (b.value * a.value.abs().ln()).abs() * b.error };
+                (value, b.value.abs() * a.error + from_exponent)
+            },
+            _ => return None,
+        };
+        value.is_finite().then_some(Tracked { value, error: error + UNIT_ROUNDOFF })
+    }
+
+    // The function's condition number |x·f'(x)/f(x)|, from a central difference
+    fn function(&mut self, func: Function, x: Tracked) -> Option<Tracked> {
+        let f = |x: f64| match self.calculator.call(func, vec![Value::Number(x)]) {
+            Ok(Value::Number(y)) => Some(y),
+            _ => None,
+        };
+        let value = f(x.value)?;
+        if x.error == 0.0 || x.value == 0.0 {
+            return Some(Tracked { value, error: UNIT_ROUNDOFF });
+        }
+        let h = x.value.abs() * 1e-6;
+        let slope = (f(x.value + h)? - f(x.value - h)?) / (2.0 * h);
+        let condition = (x.value * slope / value).abs();
+        let error = if condition.is_finite() { condition * x.error } else { f64::INFINITY };
+        Some(Tracked { value, error: error + UNIT_ROUNDOFF })
+    }
+}
+
+// A warning when rounding errors, typically from subtracting nearly equal
+// numbers, may have eaten most of the result's digits
+pub fn check(calculator: &Calculator, expr: &str) -> Option<String> {
+    if calculator.integer_mode {
+        return None;
+    }
+    let tree = calculator.ast(expr).ok()?;
+    let mut tracker = Tracker { calculator, worst: None };
+    let result = tracker.track(&tree)?;
+    if result.error <= WARN_ERROR {
+        return None;
+    }
+    let digits = (-result.error.log10()).floor().max(0.0) as i32;
+    let extent = if digits == 0 {
+        "el resultado puede ser solo error de redondeo".to_string()
+    } else {
+        format!("el resultado puede tener solo ~{} {} correctas", digits, if digits == 1 { "cifra" } else { "cifras" })
+    };
+    Some(match tracker.worst {
+        Some((amplification, at)) if amplification > 1e3 => {
+            format!("Posible pérdida de precisión al restar valores casi iguales en «{}»: {}", at, extent)
+        },
+        _ => format!("Posible pérdida de precisión: {}", extent),
+    })
+}
@@ -0,0 +1,363 @@
+use num_bigint::BigInt;
+use num_traits::Signed;
+use serde::{Deserialize, Serialize};
+
+use crate::calculator::{self, Calculator, Error, Operator, Token};
+use crate::integer;
+use crate::limits;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Radix {
+    Hex,
+    #[default]
+    Dec,
+    Oct,
+    Bin,
+}
+
+impl Radix {
+    pub const ALL: [Radix; 4] = [Radix::Hex, Radix::Dec, Radix::Oct, Radix::Bin];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Radix::Hex => "HEX",
+            Radix::Dec => "DEC",
+            Radix::Oct => "OCT",
+            Radix::Bin => "BIN",
+        }
+    }
+
+    pub fn base(&self) -> u32 {
+        match self {
+            Radix::Hex => 16,
+            Radix::Dec => 10,
+            Radix::Oct => 8,
+            Radix::Bin => 2,
+        }
+    }
+
+    // Digits per group in the readout: bytes in hex, nibbles in binary
+    fn group(&self) -> usize {
+        match self {
+            Radix::Hex => 2,
+            Radix::Dec | Radix::Oct => 3,
+            Radix::Bin => 4,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum WordSize {
+    Byte,
+    Word,
+    DWord,
+    #[default]
+    QWord,
+}
+
+impl WordSize {
+    pub const ALL: [WordSize; 4] = [WordSize::Byte, WordSize::Word, WordSize::DWord, WordSize::QWord];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WordSize::Byte => "8 bits (BYTE)",
+            WordSize::Word => "16 bits (WORD)",
+            WordSize::DWord => "32 bits (DWORD)",
+            WordSize::QWord => "64 bits (QWORD)",
+        }
+    }
+
+    pub fn bits(&self) -> u32 {
+        match self {
+            WordSize::Byte => 8,
+            WordSize::Word => 16,
+            WordSize::DWord => 32,
+            WordSize::QWord => 64,
+        }
+    }
+}
+
+// Lexes `expr` with every literal written in `radix`. Letters are digits
+// here, so there are no functions or constants; only "mod" and "xor" are words.
+pub fn parse(expr: &str, radix: Radix) -> Result<Vec<Token>, Error> {
+    let mut tokens = Vec::new();
+    let mut chars = expr.chars().peekable();
+    let mut depth: usize = 0;
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_ascii_alphanumeric() => {
+                let mut word = String::from(c);
+                while let Some(&next) = chars.peek() {
+                    if next.is_ascii_alphanumeric() || next == '_' {
+                        word.push(chars.next().unwrap());
+                    } else {
+                        break;
+                    }
+                }
+                if word.eq_ignore_ascii_case("mod") {
+                    tokens.push(Token::Op(Operator::Mod));
+                    continue;
+                }
+                if word.eq_ignore_ascii_case("xor") {
+                    tokens.push(Token::Op(Operator::Xor));
+                    continue;
+                }
+                // '_' may separate digit groups, as in 1111_0000
+                let mut digits = word.replace('_', "");
+                let mut base = radix.base();
+                // A 0x/0b/0o prefix overrides the selected base, except that
+                // 0b... is an ordinary number in hex
+                if let Some(prefixed) = calculator::radix_prefix(digits.chars().skip(1)).filter(|_| digits.starts_with('0')) {
+                    if !(prefixed == 2 && radix == Radix::Hex) {
+                        base = prefixed;
+                        digits.drain(..2);
+                    }
+                }
+                if digits.len() as u64 > limits::MAX_DIGITS {
+                    return Err(limits::too_large());
+                }
+                let n = BigInt::parse_bytes(digits.as_bytes(), base)
+                    .ok_or_else(|| Error::InvalidNumber(format!("{} ({})", word, radix.name())))?;
+                tokens.push(Token::Integer(n));
+            },
+            '(' => {
+                depth += 1;
+                tokens.push(Token::Bracket('('));
+            },
+            ')' => {
+                depth = depth.checked_sub(1).ok_or_else(|| Error::MismatchedParens { position: Some(calculator::position(expr, &chars)) })?;
+                tokens.push(Token::Bracket(')'));
+            },
+            '+' => tokens.push(Token::Op(Operator::Add)),
+            '-' => {
+                // Same unary minus encoding as the main parser
+                if tokens.is_empty() || matches!(tokens.last(), Some(Token::Op(_)) | Some(Token::Bracket('('))) {
+                    tokens.push(Token::Number(-1.0));
+                    tokens.push(Token::Op(Operator::Mul));
+                } else {
+                    tokens.push(Token::Op(Operator::Sub));
+                }
+            },
+            '*' => tokens.push(Token::Op(Operator::Mul)),
+            '/' => tokens.push(Token::Op(Operator::Div)),
+            '%' => tokens.push(Token::Op(Operator::Mod)),
+            '^' => tokens.push(Token::Op(Operator::Pow)),
+            '!' => tokens.push(Token::Factorial),
+            '&' => tokens.push(Token::Op(Operator::And)),
+            '|' => tokens.push(Token::Op(Operator::Or)),
+            '~' => {
+                tokens.push(Token::Integer(BigInt::from(-1)));
+                tokens.push(Token::Op(Operator::Not));
+            },
+            '<' | '>' => {
+                if chars.next() != Some(c) {
+                    return Err(Error::BadToken { token: c, position: calculator::position(expr, &chars) });
+                }
+                tokens.push(Token::Op(if c == '<' { Operator::Shl } else { Operator::Shr }));
+            },
+            ' ' | '\n' => {},
+            _ => return Err(Error::BadToken { token: c, position: calculator::position(expr, &chars) }),
+        }
+    }
+
+    if depth != 0 {
+        return Err(Error::MismatchedParens { position: calculator::first_unclosed(expr) });
+    }
+    Ok(tokens)
+}
+
+// Programmer mode always uses integer semantics, wrapping to the word size
+pub fn evaluate(expr: &str, radix: Radix, word: WordSize) -> Result<BigInt, Error> {
+    integer::evaluate_bits(Calculator::expression(parse(expr, radix)?), &|_| None, Some(word.bits()))
+}
+
+// Signed decimal, or the two's complement bit pattern in the other bases
+fn digits(n: &BigInt, radix: Radix, word: WordSize) -> BigInt {
+    match radix {
+        Radix::Dec => n.clone(),
+        _ => integer::unsigned(n, word.bits()),
+    }
+}
+
+// Uppercase digits split into groups from the right, e.g. "1 0110 1001"
+pub fn format(n: &BigInt, radix: Radix) -> String {
+    let digits = n.magnitude().to_str_radix(radix.base()).to_uppercase();
+    let first = match digits.len() % radix.group() {
+        0 => radix.group(),
+        rest => rest,
+    };
+    let mut grouped = String::with_capacity(digits.len() * 5 / 4 + 1);
+    if n.is_negative() {
+        grouped.push('-');
+    }
+    grouped.push_str(&digits[..first]);
+    for (i, digit) in digits[first..].chars().enumerate() {
+        if i % radix.group() == 0 {
+            grouped.push(' ');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+// Beyond this many bits the other bases are too long to read at a glance
+const BASES_MAX_BITS: u64 = 256;
+
+// An integer result of the scientific calculator in the other bases, signed
+// rather than as a word's bit pattern
+pub fn show_bases(ui: &mut egui::Ui, n: &BigInt) {
+    if n.bits() > BASES_MAX_BITS {
+        return;
+    }
+    egui::Grid::new("result_bases").num_columns(2).show(ui, |ui| {
+        for radix in [Radix::Hex, Radix::Oct, Radix::Bin] {
+            ui.label(egui::RichText::new(radix.name()).monospace().weak());
+            ui.add(egui::Label::new(egui::RichText::new(format(n, radix)).monospace()).wrap());
+            ui.end_row();
+        }
+    });
+}
+
+// Laid out in rows of six, with a full-width "=" below
+const BUTTONS: &[&str] = &[
+    "A", "B", "C", "&", "|", "CLR",
+    "D", "E", "F", "xor", "~", "/",
+    "7", "8", "9", "<<", ">>", "*",
+    "4", "5", "6", "(", ")", "-",
+    "1", "2", "3", "0", "mod", "+",
+];
+
+// State of the programmer tab
+#[derive(Default)]
+pub struct Programmer {
+    pub radix: Radix,
+    pub display: String,
+    pub word: WordSize,
+    // Last value the display evaluated to, shown in every base
+    value: Option<BigInt>,
+    error: Option<String>,
+}
+
+impl Programmer {
+    fn evaluate(&mut self) {
+        if self.display.trim().is_empty() {
+            self.value = None;
+            self.error = None;
+            return;
+        }
+        match evaluate(&self.display, self.radix, self.word) {
+            Ok(value) => {
+                self.value = Some(value);
+                self.error = None;
+            },
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+
+    // Rewrites the display in the new base when it holds a valid expression
+    fn set_radix(&mut self, radix: Radix) {
+        if !self.display.trim().is_empty() {
+            if let Ok(value) = evaluate(&self.display, self.radix, self.word) {
+                self.display = digits(&value, radix, self.word).to_str_radix(radix.base()).to_uppercase();
+            }
+        }
+        self.radix = radix;
+    }
+
+    fn handle_input(&mut self, input: &str) {
+        match input {
+            "CLR" => self.display.clear(),
+            "=" => {
+                if let Some(value) = &self.value {
+                    if self.error.is_none() {
+                        self.display = digits(value, self.radix, self.word).to_str_radix(self.radix.base()).to_uppercase();
+                    }
+                }
+            },
+            "mod" | "xor" | "&" | "|" | "<<" | ">>" => self.display.push_str(&format!(" {} ", input)),
+            _ => self.display.push_str(input),
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Programador");
+        ui.add_space(10.0);
+
+        let mut radix = self.radix;
+        ui.horizontal(|ui| {
+            for option in Radix::ALL {
+                ui.selectable_value(&mut radix, option, option.name());
+            }
+        });
+        if radix != self.radix {
+            self.set_radix(radix);
+        }
+        egui::ComboBox::from_label("Tamaño de palabra")
+            .selected_text(self.word.name())
+            .show_ui(ui, |ui| {
+                for word in WordSize::ALL {
+                    ui.selectable_value(&mut self.word, word, word.name());
+                }
+            });
+
+        ui.add(
+            egui::TextEdit::singleline(&mut self.display)
+                .font(egui::TextStyle::Monospace)
+                .desired_width(f32::INFINITY)
+                .hint_text(format!("Expresión en {}", self.radix.name()))
+        );
+        if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            self.handle_input("=");
+        }
+        self.evaluate();
+
+        ui.add_space(10.0);
+        egui::Grid::new("programmer_bases").num_columns(2).show(ui, |ui| {
+            for option in Radix::ALL {
+                let label = egui::RichText::new(option.name()).monospace();
+                ui.label(if option == self.radix { label.strong() } else { label });
+                let text = self.value.as_ref().map_or(String::new(), |value| format(&digits(value, option, self.word), option));
+                ui.add(egui::Label::new(egui::RichText::new(text).monospace().color(egui::Color32::GREEN)).wrap());
+                ui.end_row();
+            }
+        });
+        if let Some(value) = &self.value {
+            let unsigned = integer::unsigned(value, self.word.bits());
+            ui.horizontal(|ui| {
+                ui.label(format!("Con signo: {}", format(value, Radix::Dec)));
+                ui.separator();
+                ui.label(format!("Sin signo: {}", format(&unsigned, Radix::Dec)));
+            });
+        }
+        if let Some(error) = &self.error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+
+        ui.add_space(10.0);
+        let button_size = egui::vec2(55.0, 40.0);
+        ui.horizontal_wrapped(|ui| {
+            for (col, &button) in BUTTONS.iter().enumerate() {
+                if col > 0 && col % 6 == 0 {
+                    ui.end_row();
+                }
+                // Digits outside the current base are disabled
+                let enabled = button.len() > 1
+                    || button.chars().next().and_then(|c| c.to_digit(16)).is_none_or(|d| d < self.radix.base());
+                let fill = match button {
+                    "CLR" => egui::Color32::from_rgb(150, 0, 0),
+                    "+" | "-" | "*" | "/" | "mod" => egui::Color32::from_rgb(100, 100, 100),
+                    "&" | "|" | "xor" | "~" | "<<" | ">>" => egui::Color32::from_rgb(70, 70, 170),
+                    _ => ui.style().visuals.widgets.inactive.bg_fill,
+                };
+                if ui.add_enabled(enabled, egui::Button::new(button).fill(fill).min_size(button_size)).clicked() {
+                    self.handle_input(button);
+                }
+            }
+        });
+        let width = button_size.x * 6.0 + ui.spacing().item_spacing.x * 5.0;
+        if ui.add_sized([width, button_size.y], egui::Button::new("=").fill(egui::Color32::from_rgb(0, 150, 0))).clicked() {
+            self.handle_input("=");
+        }
+    }
+}
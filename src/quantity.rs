@@ -0,0 +1,256 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::calculator::{Error, Function, Operator};
+use crate::units::{self, Unit};
+use crate::value::Value;
+
+// Symbols of the base dimensions, in display order
+const BASE_UNITS: [&str; 5] = ["kg", "m", "s", "K", "B"];
+
+// Named SI units that results are simplified to
+const DERIVED_UNITS: [(&str, Dimension); 5] = [
+    ("N", Dimension::new(1, 1, -2, 0, 0)),
+    ("J", Dimension::new(1, 2, -2, 0, 0)),
+    ("W", Dimension::new(1, 2, -3, 0, 0)),
+    ("Pa", Dimension::new(1, -1, -2, 0, 0)),
+    ("Hz", Dimension::new(0, 0, -1, 0, 0)),
+];
+
+// Writes symbols with exponents as a fraction, e.g. kg·m/s^2
+fn write_units(parts: &[(&str, i8)]) -> String {
+    let part = |&(symbol, exp): &(&str, i8)| match exp.abs() {
+        1 => symbol.to_string(),
+        exp => format!("{}^{}", symbol, exp),
+    };
+    let numerator: Vec<String> = parts.iter().filter(|(_, exp)| *exp > 0).map(part).collect();
+    let denominator: Vec<String> = parts.iter().filter(|(_, exp)| *exp < 0).map(part).collect();
+    let numerator = if numerator.is_empty() { "1".to_string() } else { numerator.join("·") };
+    match denominator.len() {
+        0 => numerator,
+        1 => format!("{}/{}", numerator, denominator[0]),
+        _ => format!("{}/({})", numerator, denominator.join("·")),
+    }
+}
+
+// Exponents of mass, length, time, temperature and information
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dimension(pub [i8; 5]);
+
+impl Dimension {
+    pub const NONE: Dimension = Dimension([0; 5]);
+
+    pub const fn new(mass: i8, length: i8, time: i8, temperature: i8, information: i8) -> Self {
+        Dimension([mass, length, time, temperature, information])
+    }
+
+    pub fn is_none(&self) -> bool {
+        *self == Dimension::NONE
+    }
+
+    fn zip(self, other: Dimension, f: impl Fn(i8, i8) -> i8) -> Dimension {
+        Dimension(std::array::from_fn(|i| f(self.0[i], other.0[i])))
+    }
+
+    pub fn mul(self, other: Dimension) -> Dimension {
+        self.zip(other, |a, b| a + b)
+    }
+
+    pub fn div(self, other: Dimension) -> Dimension {
+        self.zip(other, |a, b| a - b)
+    }
+
+    pub fn pow(self, exp: i8) -> Dimension {
+        Dimension(self.0.map(|a| a * exp))
+    }
+
+    // None when some exponent isn't a multiple of n, as in sqrt(m)
+    pub fn root(self, n: i8) -> Option<Dimension> {
+        self.0.iter().all(|a| a % n == 0).then(|| Dimension(self.0.map(|a| a / n)))
+    }
+
+    fn base_parts(&self) -> Vec<(&'static str, i8)> {
+        (0..5).filter(|&i| self.0[i] != 0).map(|i| (BASE_UNITS[i], self.0[i])).collect()
+    }
+
+    fn weight(&self) -> i32 {
+        self.0.iter().map(|a| a.unsigned_abs() as i32).sum()
+    }
+
+    // With a named unit: kg·m/s^2 as N, and kg·m^2/(s^2·K) as J/K when that
+    // saves at least two symbols, so m/s^2 doesn't turn into N/kg
+    pub fn simplified(&self) -> String {
+        let mut best: Option<(i32, &str, i8, Dimension)> = None;
+        for (symbol, unit) in DERIVED_UNITS {
+            if *self == unit {
+                return symbol.to_string();
+            }
+            for exp in [1, -1] {
+                let rest = self.div(unit.pow(exp));
+                let weight = rest.weight() + 1;
+                if weight + 1 < self.weight() && best.is_none_or(|(best, ..)| weight < best) {
+                    best = Some((weight, symbol, exp, rest));
+                }
+            }
+        }
+        match best {
+            Some((_, symbol, exp, rest)) => {
+                let mut parts = vec![(symbol, exp)];
+                parts.extend(rest.base_parts());
+                write_units(&parts)
+            },
+            None => self.to_string(),
+        }
+    }
+}
+
+// In base units, e.g. kg·m/s^2
+impl fmt::Display for Dimension {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", write_units(&self.base_parts()))
+    }
+}
+
+// A value in base SI units together with its dimension. Units with an
+// offset, like °C, act as differences here.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Quantity {
+    pub value: f64,
+    pub dim: Dimension,
+    // Unit asked for with `to`, and its size in base units; arithmetic drops it
+    #[serde(default)]
+    pub unit: Option<(String, f64)>,
+}
+
+impl Quantity {
+    pub fn new(value: f64, dim: Dimension) -> Self {
+        Self { value, dim, unit: None }
+    }
+
+    // In the requested unit if there is one, otherwise in base units,
+    // simplified to N, J... unless `base_units`
+    pub fn format(&self, number: impl Fn(f64) -> String, base_units: bool) -> String {
+        match &self.unit {
+            Some((unit, size)) => format!("{} {}", number(units::round_significant(self.value / size)), unit),
+            None if base_units => format!("{} {}", number(self.value), self.dim),
+            None => format!("{} {}", number(self.value), self.dim.simplified()),
+        }
+    }
+
+    pub fn unit(unit: &Unit) -> Self {
+        Self::new(unit.factor, unit.category.dimension())
+    }
+
+    // Results without dimension become plain numbers again
+    pub fn into_value(self) -> Value {
+        if self.dim.is_none() {
+            Value::Number(self.value)
+        } else {
+            Value::Quantity(self)
+        }
+    }
+
+    pub fn combine(op: Operator, a: Quantity, b: Quantity) -> Result<Value, Error> {
+        let same = |name: &str| {
+            if a.dim == b.dim {
+                Ok(a.dim)
+            } else {
+                Err(Error::InvalidOperation(format!(
+                    "Dimensiones incompatibles: {} {} {}", a.dim, name, b.dim
+                )))
+            }
+        };
+        let result = match op {
+            Operator::Add => Quantity::new(a.value + b.value, same("+")?),
+            Operator::Sub => Quantity::new(a.value - b.value, same("-")?),
+            Operator::Mul | Operator::Juxtapose => Quantity::new(a.value * b.value, a.dim.mul(b.dim)),
+            Operator::Div => {
+                if b.value == 0.0 {
+                    return Err(Error::DivisionByZero);
+                }
+                Quantity::new(a.value / b.value, a.dim.div(b.dim))
+            },
+            Operator::Mod => {
+                if b.value == 0.0 {
+                    return Err(Error::DivisionByZero);
+                }
+                Quantity::new(a.value % b.value, same("mod")?)
+            },
+            Operator::Pow => {
+                if !b.dim.is_none() {
+                    return Err(Error::InvalidOperation(format!("El exponente no puede tener unidades ({})", b.dim)));
+                }
+                // Only whole exponents keep the dimension exact
+                let exp = b.value;
+                if exp.fract() != 0.0 || exp.abs() > i8::MAX as f64 {
+                    if a.dim.is_none() {
+                        return Ok(Value::Number(a.value.powf(exp)));
+                    }
+                    return Err(Error::InvalidOperation(format!("{} solo admite exponentes enteros", a.dim)));
+                }
+                Quantity::new(a.value.powf(exp), a.dim.pow(exp as i8))
+            },
+            _ => return Err(Error::InvalidOperation("Operación no disponible con unidades".to_string())),
+        };
+        Ok(result.into_value())
+    }
+
+    pub fn apply(func: Function, q: Quantity) -> Result<Value, Error> {
+        let dim = match func {
+            Function::Abs | Function::Floor | Function::Ceil | Function::Round => Some(q.dim),
+            Function::Sqrt => q.dim.root(2),
+            Function::Cbrt => q.dim.root(3),
+            _ => {
+                return Err(Error::InvalidOperation(format!("{} requiere un valor sin unidades (tiene {})", func, q.dim)));
+            },
+        };
+        let dim = dim.ok_or_else(|| Error::InvalidOperation(format!("{}({}) no tiene unidades válidas", func, q.dim)))?;
+        let value = match func {
+            Function::Abs => q.value.abs(),
+            Function::Floor => q.value.floor(),
+            Function::Ceil => q.value.ceil(),
+            Function::Round => q.value.round(),
+            Function::Sqrt if q.value < 0.0 => {
+                return Err(Error::InvalidOperation("No se puede sacar raíz cuadrada de un número negativo".to_string()));
+            },
+            Function::Sqrt => q.value.sqrt(),
+            _ => q.value.cbrt(),
+        };
+        Ok(Quantity::new(value, dim).into_value())
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.format(|x| x.to_string(), false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculator::Calculator;
+
+    #[test]
+    fn printed_units_can_be_typed() {
+        for symbol in BASE_UNITS {
+            assert!(units::symbol(symbol).is_some(), "{}", symbol);
+        }
+        for (symbol, dim) in DERIVED_UNITS {
+            let unit = units::symbol(symbol).unwrap_or_else(|| panic!("{} no es una unidad", symbol));
+            assert_eq!((unit.category.dimension(), unit.factor), (dim, 1.0), "{}", symbol);
+        }
+    }
+
+    #[test]
+    fn results_read_back() {
+        let calculator = Calculator { unit_mode: true, ..Calculator::default() };
+        let calculate = |expr: &str| calculator.calculate(expr).map(|value| value.to_string()).unwrap();
+        for (expr, printed) in [("2 kg * 3 m / s^2", "6 N"), ("5 N * 2 m / s", "10 W"), ("1 / (2 s)", "0.5 Hz"), ("3 Pa * 2 m^2", "6 N")] {
+            assert_eq!(calculate(expr), printed);
+            assert_eq!(calculate(printed), printed);
+        }
+        assert_eq!(calculate("2 W * 3 s"), "6 J");
+    }
+}
@@ -0,0 +1,286 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::calculator::Calculator;
+use crate::value::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Fácil",
+            Difficulty::Medium => "Media",
+            Difficulty::Hard => "Difícil",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProblemKind {
+    Arithmetic,
+    Algebra,
+}
+
+impl ProblemKind {
+    pub const ALL: [ProblemKind; 2] = [ProblemKind::Arithmetic, ProblemKind::Algebra];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProblemKind::Arithmetic => "Aritmética",
+            ProblemKind::Algebra => "Álgebra",
+        }
+    }
+}
+
+pub struct Problem {
+    pub question: String,
+    // Expression whose value is the expected answer
+    pub solution: String,
+}
+
+// Small xorshift generator; quizzes don't need cryptographic randomness
+struct Rng(u64);
+
+impl Rng {
+    fn from_time() -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0x2545_f491_4f6c_dd1d);
+        Rng(seed | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    // Uniform integer in [low, high]
+    fn range(&mut self, low: i64, high: i64) -> i64 {
+        low + (self.next() % (high - low + 1) as u64) as i64
+    }
+
+    fn nonzero(&mut self, low: i64, high: i64) -> i64 {
+        loop {
+            let n = self.range(low, high);
+            if n != 0 {
+                return n;
+            }
+        }
+    }
+}
+
+pub struct Quiz {
+    rng: Rng,
+    pub difficulty: Difficulty,
+    pub kind: ProblemKind,
+    pub problem: Option<Problem>,
+    pub answer: String,
+    pub correct: u32,
+    pub attempts: u32,
+    // Graded already; further checks are ignored until the next problem
+    pub answered: bool,
+    // (was correct, message)
+    pub feedback: Option<(bool, String)>,
+}
+
+impl Default for Quiz {
+    fn default() -> Self {
+        Self {
+            rng: Rng::from_time(),
+            difficulty: Difficulty::Easy,
+            kind: ProblemKind::Arithmetic,
+            problem: None,
+            answer: String::new(),
+            correct: 0,
+            attempts: 0,
+            answered: false,
+            feedback: None,
+        }
+    }
+}
+
+impl Quiz {
+    pub fn next_problem(&mut self) {
+        self.problem = Some(match self.kind {
+            ProblemKind::Arithmetic => self.arithmetic(),
+            ProblemKind::Algebra => self.algebra(),
+        });
+        self.answer.clear();
+        self.answered = false;
+        self.feedback = None;
+    }
+
+    fn arithmetic(&mut self) -> Problem {
+        let rng = &mut self.rng;
+        let question = match self.difficulty {
+            Difficulty::Easy => {
+                let op = if rng.range(0, 1) == 0 { "+" } else { "-" };
+                format!("{} {} {}", rng.range(1, 20), op, rng.range(1, 20))
+            },
+            Difficulty::Medium => {
+                let (a, b) = (rng.range(2, 12), rng.range(2, 12));
+                match rng.range(0, 2) {
+                    0 => format!("{} * {}", a, b),
+                    // Exact quotients only
+                    1 => format!("{} / {}", a * b, b),
+                    _ => format!("{} * {} + {}", a, b, rng.range(1, 50)),
+                }
+            },
+            Difficulty::Hard => {
+                let (a, b, c) = (rng.range(2, 15), rng.range(2, 15), rng.range(2, 9));
+                match rng.range(0, 2) {
+                    0 => format!("({} + {}) * {} - {}", a, b, c, rng.range(1, 30)),
+                    1 => format!("{}^{} - {} * {}", c, rng.range(2, 3), a, b),
+                    _ => format!("sqrt({}) + {} * {}", a * a, b, c),
+                }
+            },
+        };
+        Problem {
+            solution: question.clone(),
+            question,
+        }
+    }
+
+    // Builds equations backwards from an integer solution
+    fn algebra(&mut self) -> Problem {
+        let rng = &mut self.rng;
+        let x = rng.range(-10, 10);
+        let (question, solution) = match self.difficulty {
+            Difficulty::Easy => {
+                let a = rng.nonzero(1, 20);
+                (format!("x + {} = {}", a, x + a), format!("{} - {}", x + a, a))
+            },
+            Difficulty::Medium => {
+                let (a, b) = (rng.nonzero(2, 9), rng.range(1, 20));
+                (format!("{}x + {} = {}", a, b, a * x + b), format!("({} - {}) / {}", a * x + b, b, a))
+            },
+            Difficulty::Hard => {
+                let (a, b) = (rng.nonzero(2, 6), rng.range(1, 9));
+                let c = loop {
+                    let c = rng.nonzero(1, 9);
+                    if c != a {
+                        break c;
+                    }
+                };
+                let d = a * (x + b) - c * x;
+                let right = if d < 0 { format!("{}x - {}", c, -d) } else { format!("{}x + {}", c, d) };
+                (format!("{}(x + {}) = {}", a, b, right), format!("({} - {} * {}) / ({} - {})", d, a, b, a, c))
+            },
+        };
+        Problem {
+            question: format!("Resuelve para x: {}", question),
+            solution,
+        }
+    }
+
+    // Uses a default engine so answers don't depend on the current mode
+    pub fn check(&mut self) {
+        let Some(problem) = self.problem.as_ref().filter(|_| !self.answered) else {
+            return;
+        };
+        // Retyping the question only makes the calculator answer it
+        if restates(&self.answer, &problem.question) || restates(&self.answer, &problem.solution) {
+            self.feedback = Some((false, "Escribe el resultado, no la expresión".to_string()));
+            return;
+        }
+        let calculator = Calculator::default();
+        let expected = match calculator.calculate(&problem.solution) {
+            Ok(Value::Number(n)) => n,
+            _ => return,
+        };
+        let given = match calculator.calculate(&self.answer) {
+            Ok(Value::Number(n)) => n,
+            Ok(_) | Err(_) => {
+                self.feedback = Some((false, "Respuesta no válida".to_string()));
+                return;
+            },
+        };
+        self.attempts += 1;
+        self.answered = true;
+        if (given - expected).abs() <= 1e-9 * expected.abs().max(1.0) {
+            self.correct += 1;
+            self.feedback = Some((true, "¡Correcto!".to_string()));
+        } else {
+            self.feedback = Some((false, format!("Incorrecto, la respuesta es {}", expected)));
+        }
+    }
+}
+
+// Same expression up to spacing and enclosing parentheses
+fn restates(answer: &str, expr: &str) -> bool {
+    let normalize = |s: &str| {
+        let mut s: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+        while s.starts_with('(') && s.ends_with(')') && encloses(&s) {
+            s = s[1..s.len() - 1].to_string();
+        }
+        s
+    };
+    normalize(answer) == normalize(expr)
+}
+
+// Whether the first '(' closes at the last character, as in (1 + 2) but not (1) + (2)
+fn encloses(s: &str) -> bool {
+    let mut depth = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {},
+        }
+        if depth == 0 {
+            return i == s.len() - 1;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quiz(question: &str) -> Quiz {
+        Quiz {
+            problem: Some(Problem { question: question.to_string(), solution: question.to_string() }),
+            ..Quiz::default()
+        }
+    }
+
+    #[test]
+    fn graded_once() {
+        let mut quiz = quiz("3 + 4");
+        quiz.answer = "7".to_string();
+        quiz.check();
+        quiz.check();
+        quiz.check();
+        assert_eq!((quiz.correct, quiz.attempts), (1, 1));
+        quiz.next_problem();
+        quiz.answer = "1e9".to_string();
+        quiz.check();
+        quiz.check();
+        assert_eq!((quiz.correct, quiz.attempts), (1, 2));
+    }
+
+    #[test]
+    fn restating_the_question() {
+        let mut quiz = quiz("(2 + 3) * 4");
+        for answer in ["(2 + 3) * 4", "(2+3)*4", "((2 + 3) * 4)"] {
+            quiz.answer = answer.to_string();
+            quiz.check();
+            assert_eq!(quiz.attempts, 0, "{}", answer);
+        }
+        quiz.answer = "20".to_string();
+        quiz.check();
+        assert_eq!((quiz.correct, quiz.attempts), (1, 1));
+        assert!(!restates("(2) + (3)", "2) + (3"));
+    }
+}
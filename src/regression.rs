@@ -0,0 +1,186 @@
+use crate::format::NumberFormat;
+use crate::matrix::Matrix;
+
+pub const MAX_DEGREE: usize = 6;
+
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum Model {
+    #[default]
+    Linear,
+    Polynomial,
+    // y = a·e^(b·x)
+    Exponential,
+    // y = a·x^b
+    Power,
+}
+
+impl Model {
+    pub const ALL: [Model; 4] = [Model::Linear, Model::Polynomial, Model::Exponential, Model::Power];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Model::Linear => "Lineal",
+            Model::Polynomial => "Polinómica",
+            Model::Exponential => "Exponencial",
+            Model::Power => "Potencial",
+        }
+    }
+}
+
+// A fitted curve. Polynomials keep their coefficients lowest degree first;
+// exponential and power fits keep a and b. R² is that of the least-squares
+// fit made, so for exponential and power fits that of ln y, as calculators
+// and spreadsheets report it
+#[derive(Debug, Clone)]
+pub struct Fit {
+    pub model: Model,
+    pub coefficients: Vec<f64>,
+    pub r_squared: f64,
+}
+
+// Least squares for y ≈ c₀ + c₁x + … + c_d x^d by Householder QR of the
+// Vandermonde matrix, which keeps more digits than the normal equations
+fn polynomial(x: &[f64], y: &[f64], degree: usize) -> Result<Vec<f64>, String> {
+    let (n, k) = (x.len(), degree + 1);
+    if n < k {
+        return Err(format!("Un polinomio de grado {} necesita al menos {} puntos", degree, k));
+    }
+    let vandermonde = Matrix::new(n, k, x.iter().flat_map(|x| (0..k).map(|j| x.powi(j as i32))).collect());
+    let (q, r) = vandermonde.qr();
+    let qty: Vec<f64> = (0..k).map(|j| (0..n).map(|i| q.get(i, j) * y[i]).sum()).collect();
+    let largest = (0..k).map(|j| r.get(j, j).abs()).fold(0.0, f64::max);
+    let mut c = vec![0.0; k];
+    for j in (0..k).rev() {
+        if r.get(j, j).abs() <= 1e-12 * largest || largest == 0.0 {
+            return Err("Los valores de x no bastan para este ajuste (¿son casi todos iguales?)".to_string());
+        }
+        let sum: f64 = (j + 1..k).map(|l| r.get(j, l) * c[l]).sum();
+        c[j] = (qty[j] - sum) / r.get(j, j);
+    }
+    Ok(c)
+}
+
+fn r_squared(y: &[f64], fitted: impl Fn(usize) -> f64) -> f64 {
+    let mean = y.iter().sum::<f64>() / y.len() as f64;
+    let total: f64 = y.iter().map(|y| (y - mean).powi(2)).sum();
+    let residual: f64 = y.iter().enumerate().map(|(i, y)| (y - fitted(i)).powi(2)).sum();
+    // Every y the same and matched exactly
+    if total == 0.0 { 1.0 } else { 1.0 - residual / total }
+}
+
+fn evaluate(c: &[f64], x: f64) -> f64 {
+    c.iter().rev().fold(0.0, |sum, c| sum * x + c)
+}
+
+pub fn fit(model: Model, degree: usize, x: &[f64], y: &[f64]) -> Result<Fit, String> {
+    if x.len() < 2 {
+        return Err("Hacen falta al menos dos puntos".to_string());
+    }
+    let (coefficients, r_squared) = match model {
+        Model::Linear | Model::Polynomial => {
+            let degree = if model == Model::Linear { 1 } else { degree.clamp(1, MAX_DEGREE) };
+            let c = polynomial(x, y, degree)?;
+            let r2 = r_squared(y, |i| evaluate(&c, x[i]));
+            (c, r2)
+        },
+        Model::Exponential | Model::Power => {
+            if y.iter().any(|y| *y <= 0.0) {
+                return Err(format!("El ajuste {} requiere y > 0", model.name().to_lowercase()));
+            }
+            if model == Model::Power && x.iter().any(|x| *x <= 0.0) {
+                return Err("El ajuste potencial requiere x > 0".to_string());
+            }
+            let u: Vec<f64> = if model == Model::Power { x.iter().map(|x| x.ln()).collect() } else { x.to_vec() };
+            let v: Vec<f64> = y.iter().map(|y| y.ln()).collect();
+            let c = polynomial(&u, &v, 1)?;
+            let r2 = r_squared(&v, |i| evaluate(&c, u[i]));
+            (vec![c[0].exp(), c[1]], r2)
+        },
+    };
+    Ok(Fit { model, coefficients, r_squared })
+}
+
+impl Fit {
+    pub fn evaluate(&self, x: f64) -> f64 {
+        let c = &self.coefficients;
+        match self.model {
+            Model::Linear | Model::Polynomial => evaluate(c, x),
+            Model::Exponential => c[0] * (c[1] * x).exp(),
+            Model::Power => c[0] * x.powf(c[1]),
+        }
+    }
+
+    // Each coefficient with the name the equation gives it
+    pub fn named(&self) -> Vec<(String, f64)> {
+        let c = &self.coefficients;
+        match self.model {
+            Model::Linear => vec![("a".to_string(), c[1]), ("b".to_string(), c[0])],
+            Model::Polynomial => c.iter().enumerate().rev().map(|(i, c)| (format!("a{}", i), *c)).collect(),
+            Model::Exponential | Model::Power => vec![("a".to_string(), c[0]), ("b".to_string(), c[1])],
+        }
+    }
+
+    pub fn equation(&self) -> &'static str {
+        match self.model {
+            Model::Linear => "y = a·x + b",
+            Model::Polynomial => "y = aₙ·xⁿ + … + a1·x + a0",
+            Model::Exponential => "y = a·e^(b·x)",
+            Model::Power => "y = a·x^b",
+        }
+    }
+
+    // The fitted curve written out, e.g. y = 2x + 1, for copying into the
+    // calculator or the graph
+    pub fn expression(&self, format: &NumberFormat) -> String {
+        let c = &self.coefficients;
+        // Negative numbers in parentheses, so the result still reads right
+        let number = |x: f64| if x < 0.0 { format!("({})", format.number(x)) } else { format.number(x) };
+        match self.model {
+            Model::Linear | Model::Polynomial => {
+                let terms: Vec<String> = c.iter().enumerate().rev().map(|(i, c)| match i {
+                    0 => number(*c),
+                    1 => format!("{}*x", number(*c)),
+                    _ => format!("{}*x^{}", number(*c), i),
+                }).collect();
+                terms.join(" + ")
+            },
+            Model::Exponential => format!("{}*exp({}*x)", number(c[0]), number(c[1])),
+            Model::Power => format!("{}*x^{}", number(c[0]), number(c[1])),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::close;
+
+    #[test]
+    fn exact_fits() {
+        let x = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let line = fit(Model::Linear, 1, &x, &x.map(|x| 2.0 * x + 1.0)).unwrap();
+        assert!(close(line.coefficients[0], 1.0) && close(line.coefficients[1], 2.0) && close(line.r_squared, 1.0));
+        let parabola = fit(Model::Polynomial, 2, &x, &x.map(|x| x * x - 3.0 * x + 2.0)).unwrap();
+        assert!(parabola.coefficients.iter().zip([2.0, -3.0, 1.0]).all(|(c, e)| close(*c, e)));
+        let exponential = fit(Model::Exponential, 1, &x, &x.map(|x| 3.0 * (0.5 * x).exp())).unwrap();
+        assert!(close(exponential.coefficients[0], 3.0) && close(exponential.coefficients[1], 0.5));
+        let power = fit(Model::Power, 1, &x, &x.map(|x| 2.0 * x.powf(1.5))).unwrap();
+        assert!(close(power.evaluate(9.0), 54.0));
+    }
+
+    #[test]
+    fn least_squares() {
+        // The textbook line through (1, 1), (2, 3), (3, 2): y = 0.5x + 1
+        let line = fit(Model::Linear, 1, &[1.0, 2.0, 3.0], &[1.0, 3.0, 2.0]).unwrap();
+        assert!(close(line.coefficients[1], 0.5) && close(line.coefficients[0], 1.0) && close(line.r_squared, 0.25));
+    }
+
+    #[test]
+    fn rejected_data() {
+        assert!(fit(Model::Linear, 1, &[1.0], &[1.0]).is_err());
+        assert!(fit(Model::Linear, 1, &[2.0, 2.0, 2.0], &[1.0, 2.0, 3.0]).is_err());
+        assert!(fit(Model::Polynomial, 3, &[1.0, 2.0, 3.0], &[1.0, 2.0, 3.0]).is_err());
+        assert!(fit(Model::Exponential, 1, &[1.0, 2.0], &[1.0, -2.0]).is_err());
+        assert!(fit(Model::Power, 1, &[0.0, 2.0], &[1.0, 2.0]).is_err());
+    }
+}
@@ -0,0 +1,90 @@
+use serde_json::json;
+
+use crate::calculator::Calculator;
+use crate::debug;
+use crate::engine;
+
+// Snapshot of everything needed to reproduce a problem, as pretty JSON
+pub fn bundle(expression: &str, result: &str, error: Option<&str>, calculator: &Calculator, debug_log: bool) -> String {
+    let variables: serde_json::Map<String, serde_json::Value> = calculator.variables.iter()
+        .map(|(name, value)| (name.clone(), json!(value.to_string())))
+        .collect();
+    let logs: Vec<serde_json::Value> = debug::entries().iter()
+        .map(|entry| json!({
+            "level": entry.level.to_string(),
+            "target": entry.target,
+            "message": entry.message,
+        }))
+        .collect();
+    let report = json!({
+        "version": engine::VERSION,
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "expression": expression,
+        "result": result,
+        "error": error,
+        "settings": {
+            "real_only": calculator.real_only,
+            "integer_mode": calculator.integer_mode,
+            "unit_mode": calculator.unit_mode,
+            "juxtaposition": format!("{:?}", calculator.juxtaposition),
+            "words_locale": format!("{:?}", calculator.locale),
+            "debug_log": debug_log,
+        },
+        "state": {
+            "variables": variables,
+            "user_constants": calculator.user_constants,
+            "currencies": calculator.currencies.rates,
+        },
+        "logs": logs,
+    });
+    serde_json::to_string_pretty(&report).unwrap_or_default()
+}
+
+// "Reportar un problema" dialog: the bundle, ready to copy or save
+pub struct Report {
+    text: String,
+    path: String,
+    // Outcome of the last save
+    status: Option<Result<String, String>>,
+}
+
+impl Report {
+    pub fn new(text: String) -> Self {
+        Self {
+            text,
+            path: "informe-calculadora.json".to_string(),
+            status: None,
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        egui::Window::new("Reportar un problema").open(open).default_width(380.0).show(ctx, |ui| {
+            ui.label("Adjunta este informe al abrir un issue. Incluye la expresión, la configuración, tus variables y el registro de depuración.");
+            if !self.text.contains("\"message\"") {
+                ui.small("Activa «Registro de depuración del motor» y repite el cálculo para incluir los pasos del motor.");
+            }
+            egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                ui.add(egui::TextEdit::multiline(&mut self.text.as_str())
+                    .font(egui::TextStyle::Monospace)
+                    .desired_width(f32::INFINITY));
+            });
+            ui.horizontal(|ui| {
+                if ui.button("Copiar").clicked() {
+                    ui.ctx().copy_text(self.text.clone());
+                }
+                ui.add(egui::TextEdit::singleline(&mut self.path).desired_width(180.0));
+                if ui.button("Guardar").clicked() {
+                    self.status = Some(std::fs::write(self.path.trim(), &self.text)
+                        .map(|_| format!("Guardado en {}", self.path.trim()))
+                        .map_err(|e| format!("No se pudo guardar: {}", e)));
+                }
+            });
+            match &self.status {
+                Some(Ok(message)) => { ui.small(message); },
+                Some(Err(message)) => { ui.colored_label(egui::Color32::RED, message); },
+                None => {},
+            }
+        });
+    }
+}
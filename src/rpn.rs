@@ -0,0 +1,170 @@
+use crate::calculator::{Calculator, Function, Operator};
+use crate::format::NumberFormat;
+use crate::value::Value;
+
+// Names of the levels of a classic four-level stack, bottom first
+const LEVELS: [&str; 4] = ["X", "Y", "Z", "T"];
+
+fn operator(key: &str) -> Option<Operator> {
+    match key {
+        "+" => Some(Operator::Add),
+        "-" => Some(Operator::Sub),
+        "*" => Some(Operator::Mul),
+        "/" => Some(Operator::Div),
+        "^" => Some(Operator::Pow),
+        "mod" => Some(Operator::Mod),
+        "±" => Some(Operator::PlusMinus),
+        _ => None,
+    }
+}
+
+// Reverse Polish entry: numbers go onto a stack and operators take their
+// operands from it, evaluated with the calculator's own operators and functions
+#[derive(Default)]
+pub struct Rpn {
+    // Bottom (X) last
+    pub stack: Vec<Value>,
+    pub entry: String,
+    // Keep only X, Y, Z and T, dropping what falls off the top
+    pub four_levels: bool,
+    pub error: Option<String>,
+}
+
+impl Rpn {
+    fn push(&mut self, value: Value) {
+        self.stack.push(value);
+        if self.four_levels && self.stack.len() > LEVELS.len() {
+            self.stack.remove(0);
+        }
+    }
+
+    // Pushes what has been typed, if anything
+    fn commit(&mut self, calculator: &Calculator) -> Result<(), String> {
+        if self.entry.trim().is_empty() {
+            return Ok(());
+        }
+        let value = calculator.calculate(&self.entry).map_err(|e| e.to_string())?;
+        self.entry.clear();
+        self.push(value);
+        Ok(())
+    }
+
+    // ENTER pushes the entry, or repeats X when there is none
+    pub fn enter(&mut self, calculator: &Calculator) {
+        self.error = None;
+        if !self.entry.trim().is_empty() {
+            self.error = self.commit(calculator).err();
+        } else if let Some(x) = self.stack.last().cloned() {
+            self.push(x);
+        }
+    }
+
+    pub fn drop_x(&mut self) {
+        self.error = None;
+        self.stack.pop();
+    }
+
+    pub fn swap(&mut self) {
+        self.error = None;
+        if let [.., y, x] = self.stack.as_mut_slice() {
+            std::mem::swap(x, y);
+        }
+    }
+
+    // Takes `count` operands off the stack, X last, and pushes what `apply`
+    // makes of them; on an error the operands go back
+    fn apply(&mut self, calculator: &Calculator, count: usize, apply: impl FnOnce(Vec<Value>) -> Result<Value, String>) {
+        if let Err(e) = self.commit(calculator) {
+            self.error = Some(e);
+            return;
+        }
+        if self.stack.len() < count {
+            self.error = Some(format!("Hacen falta {} valor(es) en la pila", count));
+            return;
+        }
+        let operands = self.stack.split_off(self.stack.len() - count);
+        match apply(operands.clone()) {
+            Ok(result) => {
+                self.error = None;
+                self.push(result);
+            },
+            Err(e) => {
+                self.stack.extend(operands);
+                self.error = Some(e);
+            },
+        }
+    }
+
+    // A keypad key: digits and constants are typed, operators and functions
+    // act on the stack
+    pub fn key(&mut self, key: &str, calculator: &Calculator) {
+        match key {
+            "=" => self.enter(calculator),
+            "C" if self.entry.is_empty() => {
+                self.stack.clear();
+                self.error = None;
+            },
+            "C" => self.entry.clear(),
+            "⌫" if self.entry.is_empty() => self.drop_x(),
+            "⌫" => {
+                self.entry.pop();
+            },
+            "!" => self.apply(calculator, 1, |mut operands| {
+                Calculator::factorial(operands.pop().unwrap()).map_err(|e| e.to_string())
+            }),
+            _ => match (operator(key), Function::from_name(key)) {
+                (Some(op), _) => self.apply(calculator, 2, |mut operands| {
+                    let x = operands.pop().unwrap();
+                    let y = operands.pop().unwrap();
+                    calculator.apply_operator(op, y, x).map_err(|e| e.to_string())
+                }),
+                (None, Some(func)) => self.apply(calculator, func.arity(), |operands| {
+                    calculator.call(func, operands).map_err(|e| e.to_string())
+                }),
+                (None, None) => self.entry.push_str(key),
+            },
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, calculator: &Calculator, format: &NumberFormat) {
+        let shown = if self.four_levels { LEVELS.len() } else { self.stack.len().max(LEVELS.len()) };
+        egui::Grid::new("rpn_stack").num_columns(2).min_col_width(24.0).show(ui, |ui| {
+            for level in (0..shown).rev() {
+                let name = LEVELS.get(level).map_or_else(|| (level + 1).to_string(), |name| name.to_string());
+                ui.label(egui::RichText::new(format!("{}:", name)).monospace().weak());
+                let value = self.stack.len().checked_sub(level + 1).map(|i| format.value(&self.stack[i]));
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.monospace(value.unwrap_or_default());
+                });
+                ui.end_row();
+            }
+        });
+        let field = ui.add(
+            egui::TextEdit::singleline(&mut self.entry)
+                .id(egui::Id::new("rpn_entry"))
+                .font(egui::TextStyle::Monospace)
+                .frame(false)
+                .desired_width(f32::INFINITY)
+                .hint_text("Número o expresión; Enter apila")
+        );
+        if field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            self.enter(calculator);
+            field.request_focus();
+        }
+        ui.horizontal(|ui| {
+            if ui.button("ENTER").clicked() {
+                self.enter(calculator);
+            }
+            if ui.button("DROP").clicked() {
+                self.drop_x();
+            }
+            if ui.button("SWAP").on_hover_text("Intercambiar X e Y").clicked() {
+                self.swap();
+            }
+            ui.checkbox(&mut self.four_levels, "4 niveles");
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+    }
+}
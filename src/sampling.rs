@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+use num_traits::ToPrimitive;
+use rayon::prelude::*;
+
+use crate::calculator::{AngleMode, Calculator, Token};
+use crate::value::Value;
+
+// Samples are taken over this many widths of the visible range, centered on
+// it, so panning reuses them until the view leaves the sampled part
+const MARGIN_WIDTHS: f64 = 1.0;
+// The sampled range moves in steps of this fraction of the visible width
+const SNAP: f64 = 0.25;
+
+// y of a sample, NaN where the curve has no real value, which breaks the line
+fn real(value: Result<Value, crate::calculator::Error>) -> f64 {
+    match value {
+        Ok(Value::Number(y)) => y,
+        Ok(Value::Integer(y)) => y.to_f64().unwrap_or(f64::NAN),
+        Ok(Value::Quantity(q)) if q.dim.is_none() => q.value,
+        _ => f64::NAN,
+    }
+}
+
+// Evaluates `compiled` at `count` evenly spaced x across `range`, in parallel
+pub fn sample(calculator: &Calculator, compiled: &[Token], range: RangeInclusive<f64>, count: usize) -> Vec<[f64; 2]> {
+    let (start, end) = (*range.start(), *range.end());
+    let step = (end - start) / (count.max(2) - 1) as f64;
+    (0..count.max(2)).into_par_iter()
+        .map(|i| {
+            let x = start + i as f64 * step;
+            [x, real(calculator.evaluate_at(compiled, "x", x))]
+        })
+        .collect()
+}
+
+// f compiled once as a plain function of x, evaluated as `sample` would,
+// for the graph's tools
+pub fn function(calculator: &Calculator, expr: &str) -> Result<impl Fn(f64) -> f64, String> {
+    let calculator = Calculator { integer_mode: false, ..calculator.clone() };
+    let compiled = calculator.compile(expr).map_err(|e| e.to_string())?;
+    Ok(move |x| real(calculator.evaluate_at(&compiled, "x", x)))
+}
+
+// f(x) at a single point such as the trace cursor's
+pub fn value_at(calculator: &Calculator, expr: &str, x: f64) -> f64 {
+    function(calculator, expr).map_or(f64::NAN, |f| f(x))
+}
+
+// What the samples depend on besides the range
+#[derive(PartialEq)]
+struct Source {
+    expr: String,
+    angle: AngleMode,
+    variables: BTreeMap<String, Value>,
+    user_constants: BTreeMap<String, f64>,
+}
+
+// One curve's samples, kept until its expression or the calculator's state
+// changes or the view moves out of them or zooms
+#[derive(Default)]
+pub struct SampleCache {
+    source: Option<Source>,
+    // Spacing between samples, and the samples over a range wider than the view
+    step: f64,
+    points: Vec<[f64; 2]>,
+}
+
+impl SampleCache {
+    // Samples of `expr` covering `view` with about `count` points across it
+    pub fn get(&mut self, calculator: &Calculator, expr: &str, view: RangeInclusive<f64>, count: usize) -> Result<&[[f64; 2]], String> {
+        let source = Source {
+            expr: expr.to_string(),
+            angle: calculator.angle,
+            variables: calculator.variables.clone(),
+            user_constants: calculator.user_constants.clone(),
+        };
+        let width = view.end() - view.start();
+        let step = width / count.max(1) as f64;
+        let covered = self.points.first().is_some_and(|first| first[0] <= *view.start())
+            && self.points.last().is_some_and(|last| last[0] >= *view.end());
+        // Zooming by less than this keeps the same samples
+        let same_scale = self.step > 0.0 && (step / self.step - 1.0).abs() < 0.25;
+        if self.source.as_ref() != Some(&source) || !covered || !same_scale {
+            // Exact integers would reject fractional x
+            let calculator = Calculator { integer_mode: false, ..calculator.clone() };
+            let compiled = calculator.compile(expr).map_err(|e| e.to_string())?;
+            let snap = width * SNAP;
+            let start = ((view.start() - width * MARGIN_WIDTHS) / snap).floor() * snap;
+            let end = ((view.end() + width * MARGIN_WIDTHS) / snap).ceil() * snap;
+            let total = ((end - start) / step).round() as usize + 1;
+            self.points = sample(&calculator, &compiled, start..=end, total);
+            self.step = step;
+            self.source = Some(source);
+        }
+        Ok(&self.points)
+    }
+}
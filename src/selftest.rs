@@ -0,0 +1,125 @@
+use crate::calculator::Calculator;
+use crate::programmer::{self, Radix, WordSize};
+
+// Which engine a case runs through
+#[derive(Clone, Copy)]
+enum Engine {
+    Real,
+    Integer,
+    Programmer(Radix, WordSize),
+    Units,
+}
+
+struct Case {
+    engine: Engine,
+    expr: &'static str,
+    expected: &'static str,
+}
+
+const fn real(expr: &'static str, expected: &'static str) -> Case {
+    Case { engine: Engine::Real, expr, expected }
+}
+
+const fn integer(expr: &'static str, expected: &'static str) -> Case {
+    Case { engine: Engine::Integer, expr, expected }
+}
+
+// Known results; numbers match within a relative 1e-12, anything else exactly
+const CASES: &[Case] = &[
+    real("2 + 3 * 4", "14"),
+    real("(2 + 3) * 4", "20"),
+    real("-3^2", "-9"),
+    real("10 / 4", "2.5"),
+    real("7 mod 3", "1"),
+    real("1e3", "1000"),
+    real("0xFF + 0b1010 + 0o17", "280"),
+    real("sqrt(16)", "4"),
+    real("cbrt(27)", "3"),
+    real("sin(0)", "0"),
+    real("cos(pi)", "-1"),
+    real("tan(pi/4)", "1"),
+    real("asin(1)", "1.5707963267948966"),
+    real("tanh(atanh(0.5))", "0.5"),
+    real("acos(2)", "1.3169578969248166i"),
+    real("log(e)", "1"),
+    real("log10(1000)", "3"),
+    real("exp(1)", "2.718281828459045"),
+    real("abs(-5)", "5"),
+    real("floor(2.7)", "2"),
+    real("ceil(2.1)", "3"),
+    real("round(2.5)", "3"),
+    real("5!", "120"),
+    real("gamma(5)", "24"),
+    real("erf(0)", "0"),
+    real("2pi", "6.283185307179586"),
+    real("1/2pi", "0.15915494309189535"),
+    real("sqrt(-1)", "i"),
+    real("(2+3i)*(1-i)", "5+i"),
+    real("(9.81±0.02) * 2", "19.62 ± 0.04"),
+    real("convert(1, \"km\", \"m\")", "1000"),
+    real("convert(212, \"F\", \"C\")", "100"),
+    real("nderiv(\"x^3\", \"x\", 2)", "12"),
+    real("solve(\"x^2 - 2\", \"x\", 1)", "1.4142135623730951"),
+    real("[[1, 2], [3, 4]]^2", "[[7, 10], [15, 22]]"),
+    real("det([[1, 2], [3, 4]])", "-2"),
+    real("eig([[2, 0], [0, 3]])", "λ = 3, 2"),
+    real("cross([1, 0, 0], [0, 1, 0])", "[0, 0, 1]"),
+    real("median({5, 1, 4, 2})", "3"),
+    real("normcdf(0, 0, 1)", "0.5"),
+    real("fv(0, 12, -100, 0)", "1200"),
+    real("words(21)", "veintiuno"),
+    integer("2^100", "1267650600228229401496703205376"),
+    integer("20!", "2432902008176640000"),
+    integer("7/2", "3"),
+    integer("-7 mod 3", "-1"),
+    Case { engine: Engine::Units, expr: "2 km + 300 m", expected: "2300 m" },
+    Case { engine: Engine::Units, expr: "2 kg * 3 m / (1 s)^2", expected: "6 N" },
+    Case { engine: Engine::Units, expr: "3 kJ / (2 s)", expected: "1500 W" },
+    real("0.75 in to mm", "19.05 mm"),
+    real("3 in in cm", "7.62 cm"),
+    real("20 °C to °F", "68 °F"),
+    Case { engine: Engine::Programmer(Radix::Hex, WordSize::QWord), expr: "FF + 1", expected: "256" },
+    Case { engine: Engine::Programmer(Radix::Bin, WordSize::Byte), expr: "1111_0000 >> 100", expected: "15" },
+    Case { engine: Engine::Programmer(Radix::Dec, WordSize::Byte), expr: "127 + 1", expected: "-128" },
+    Case { engine: Engine::Programmer(Radix::Dec, WordSize::Word), expr: "~0 & 6 xor 3", expected: "5" },
+];
+
+fn matches(actual: &str, expected: &str) -> bool {
+    match (actual.parse::<f64>(), expected.parse::<f64>()) {
+        (Ok(a), Ok(b)) => a == b || (a - b).abs() <= 1e-12 * b.abs().max(1.0),
+        _ => actual == expected,
+    }
+}
+
+// Runs every case with default settings; returns how many ran and a line per discrepancy
+pub fn run() -> (usize, Vec<String>) {
+    let calculator = Calculator::default();
+    let exact = Calculator { integer_mode: true, ..Calculator::default() };
+    let units = Calculator { unit_mode: true, ..Calculator::default() };
+    let failures = CASES.iter()
+        .filter_map(|case| {
+            let actual = match case.engine {
+                Engine::Real => calculator.calculate(case.expr).map(|value| value.to_string()),
+                Engine::Integer => exact.calculate(case.expr).map(|value| value.to_string()),
+                Engine::Units => units.calculate(case.expr).map(|value| value.to_string()),
+                Engine::Programmer(radix, word) => programmer::evaluate(case.expr, radix, word).map(|n| n.to_string()),
+            };
+            match actual {
+                Ok(actual) if matches(&actual, case.expected) => None,
+                Ok(actual) => Some(format!("{} = {}, se esperaba {}", case.expr, actual, case.expected)),
+                Err(e) => Some(format!("{}: {}, se esperaba {}", case.expr, e, case.expected)),
+            }
+        })
+        .collect();
+    (CASES.len(), failures)
+}
+
+// One-line verdict followed by the failures, for the CLI and the in-app check
+pub fn summary(total: usize, failures: &[String]) -> String {
+    let mut text = format!("Autodiagnóstico: {} de {} casos correctos", total - failures.len(), total);
+    for failure in failures {
+        text.push_str("\n✖ ");
+        text.push_str(failure);
+    }
+    text
+}
@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+
+// Thousands and decimal separators for results and typed numbers
+#[derive(Debug, Default, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum Separators {
+    #[default]
+    Plain,
+    // 1,234,567.89
+    Point,
+    // 1.234.567,89, with ; between function arguments
+    Comma,
+}
+
+impl Separators {
+    pub const ALL: [Separators; 3] = [Separators::Plain, Separators::Point, Separators::Comma];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Separators::Plain => "1234567.89",
+            Separators::Point => "1,234,567.89",
+            Separators::Comma => "1.234.567,89",
+        }
+    }
+
+    fn marks(&self) -> Option<(char, char)> {
+        match self {
+            Separators::Plain => None,
+            Separators::Point => Some((',', '.')),
+            Separators::Comma => Some(('.', ',')),
+        }
+    }
+
+    // Groups the integer digits of a number as written by NumberFormat,
+    // e.g. -1234567.5e3 → -1.234.567,5e3
+    pub fn group(&self, number: &str) -> String {
+        let Some((thousands, decimal)) = self.marks() else {
+            return number.to_string();
+        };
+        let start = number.find(|c: char| c.is_ascii_digit()).unwrap_or(number.len());
+        let end = number[start..].find(|c: char| !c.is_ascii_digit()).map_or(number.len(), |i| start + i);
+        let digits = &number[start..end];
+        let mut grouped = String::from(&number[..start]);
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                grouped.push(thousands);
+            }
+            grouped.push(digit);
+        }
+        let rest = &number[end..];
+        match rest.strip_prefix('.') {
+            Some(fraction) => format!("{}{}{}", grouped, decimal, fraction),
+            None => grouped + rest,
+        }
+    }
+
+    // What the lexer reads for `input` typed with these separators: grouping
+    // marks become '_', which numbers may contain, and with a decimal comma
+    // ',' becomes '.' and ';' separates arguments. Char for char, so error
+    // positions still point into `input`.
+    pub fn canonical(&self, input: &str) -> String {
+        let chars: Vec<char> = input.chars().collect();
+        let digit = |i: usize| chars.get(i).is_some_and(|c| c.is_ascii_digit());
+        // A mark after a digit and before exactly three more is grouping
+        let groups = |i: usize| i > 0 && digit(i - 1) && (1..=3).all(|k| digit(i + k)) && !digit(i + 4);
+        // Innermost bracket first: whether commas in it separate arguments or
+        // elements, as in a function call or a [ ] or { } literal
+        let mut calls = Vec::new();
+        let mut canonical = String::with_capacity(input.len());
+        for (i, &c) in chars.iter().enumerate() {
+            let mapped = match (self, c) {
+                (_, '(') => {
+                    calls.push(i > 0 && (chars[i - 1].is_alphanumeric() || chars[i - 1] == '_'));
+                    c
+                },
+                (_, '[' | '{') => {
+                    calls.push(true);
+                    c
+                },
+                (_, ')' | ']' | '}') => {
+                    calls.pop();
+                    c
+                },
+                // Inside max(1,234) or {1,234} the comma still separates arguments
+                (Separators::Point, ',') if groups(i) && calls.last() != Some(&true) => '_',
+                (Separators::Comma, '.') if groups(i) => '_',
+                (Separators::Comma, ',') if digit(i + 1) => '.',
+                (Separators::Comma, ';') => ',',
+                _ => c,
+            };
+            canonical.push(mapped);
+        }
+        canonical
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculator::Calculator;
+    use crate::format::NumberFormat;
+    use crate::value::Value;
+
+    fn calculate(separators: Separators, expr: &str) -> String {
+        let calculator = Calculator { separators, ..Calculator::default() };
+        calculator.calculate(expr).map_or_else(|e| e.to_string(), |value| value.to_string())
+    }
+
+    #[test]
+    fn grouped_numbers() {
+        assert_eq!(calculate(Separators::Point, "1,234,567.89 + 1"), "1234568.89");
+        assert_eq!(calculate(Separators::Point, "(1,234) + 1"), "1235");
+        assert_eq!(calculate(Separators::Comma, "1.234.567,5 * 2"), "2469135");
+        assert_eq!(Separators::Comma.group("-1234567.5e3"), "-1.234.567,5e3");
+    }
+
+    #[test]
+    fn lists_and_matrices_in_point_mode() {
+        assert_eq!(calculate(Separators::Point, "{1,234,567}"), "{1, 234, 567}");
+        assert_eq!(calculate(Separators::Point, "[1,234,567]"), "[1, 234, 567]");
+        assert_eq!(calculate(Separators::Point, "[[1,234],[5,678]]"), "[[1, 234], [5, 678]]");
+        assert_eq!(calculate(Separators::Point, "2 * {1,000}"), "{2, 0}");
+        // Grouping still applies once the literal is closed
+        assert_eq!(calculate(Separators::Point, "{1} * 1,000"), "{1000}");
+    }
+
+    #[test]
+    fn lists_in_comma_mode() {
+        assert_eq!(calculate(Separators::Comma, "{1;2,5}"), "{1, 2.5}");
+        assert_eq!(calculate(Separators::Comma, "[1.234;2,5]"), "[1234, 2.5]");
+    }
+
+    #[test]
+    fn results_as_typed() {
+        let shown = |separators: Separators, value: &Value| NumberFormat { separators, ..NumberFormat::default() }.value(value);
+        assert_eq!(shown(Separators::Plain, &Value::Number(1234567.89)), "1234567.89");
+        assert_eq!(shown(Separators::Point, &Value::Number(1234567.89)), "1,234,567.89");
+        assert_eq!(shown(Separators::Comma, &Value::Number(-1000.0)), "-1.000");
+        assert_eq!(shown(Separators::Point, &Value::Number(999.0)), "999");
+        assert_eq!(shown(Separators::Comma, &Value::List(vec![1234.5, 2.0])), "{1.234,5; 2}");
+        // What is shown reads back as the same number
+        assert_eq!(calculate(Separators::Comma, "1.234.567,89"), "1234567.89");
+        assert_eq!(calculate(Separators::Comma, "normcdf(0,5; 0,5; 1)"), "0.5");
+        // A comma that isn't grouping three digits is an error, not a decimal
+        assert!(Calculator { separators: Separators::Point, ..Calculator::default() }.calculate("1,23 + 1").is_err());
+    }
+}
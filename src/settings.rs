@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+use crate::calculator::{AngleMode, Juxtaposition};
+use crate::format::NumberFormat;
+use crate::hooks::Hooks;
+use crate::keybindings::Keybindings;
+use crate::words::Locale;
+
+#[derive(Debug, Default, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum Theme {
+    // Follows the operating system
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::System, Theme::Light, Theme::Dark];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Theme::System => "Del sistema",
+            Theme::Light => "Claro",
+            Theme::Dark => "Oscuro",
+        }
+    }
+
+    pub fn preference(&self) -> egui::ThemePreference {
+        match self {
+            Theme::System => egui::ThemePreference::System,
+            Theme::Light => egui::ThemePreference::Light,
+            Theme::Dark => egui::ThemePreference::Dark,
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum ButtonSize {
+    Small,
+    #[default]
+    Normal,
+    Large,
+}
+
+impl ButtonSize {
+    pub const ALL: [ButtonSize; 3] = [ButtonSize::Small, ButtonSize::Normal, ButtonSize::Large];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ButtonSize::Small => "Pequeños",
+            ButtonSize::Normal => "Normales",
+            ButtonSize::Large => "Grandes",
+        }
+    }
+
+    // Size of a keypad button
+    pub fn size(&self) -> egui::Vec2 {
+        match self {
+            ButtonSize::Small => egui::vec2(56.0, 32.0),
+            ButtonSize::Normal => egui::vec2(70.0, 40.0),
+            ButtonSize::Large => egui::vec2(88.0, 52.0),
+        }
+    }
+}
+
+// Preferences kept between sessions, apart from any workspace
+#[derive(Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub format: NumberFormat,
+    pub angle: AngleMode,
+    pub theme: Theme,
+    pub language: Locale,
+    pub words_locale: Locale,
+    pub button_size: ButtonSize,
+    pub real_only: bool,
+    pub integer_mode: bool,
+    pub unit_mode: bool,
+    pub juxtaposition: Juxtaposition,
+    pub token_backspace: bool,
+    pub magnitude_bar: bool,
+    pub rpn_mode: bool,
+    pub auto_recalc: u32,
+    pub teacher_mode: bool,
+    pub as_fraction: bool,
+    pub bases: bool,
+    pub fetch: bool,
+    pub notify_after: u32,
+    pub keybindings: Keybindings,
+    pub hooks: Hooks,
+    pub exact_results: bool,
+}
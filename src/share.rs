@@ -0,0 +1,130 @@
+use crate::value::Value;
+
+pub const SCHEME: &str = "guicalc://calc";
+
+// What a shared link carries: the expression, the number mode and the
+// variables the expression reads
+#[derive(Debug, PartialEq, Clone)]
+pub struct SharedCalculation {
+    pub expression: String,
+    pub real_only: bool,
+    pub variables: Vec<(String, Value)>,
+}
+
+impl SharedCalculation {
+    pub fn to_link(&self) -> String {
+        let mut link = format!(
+            "{}?expr={}&mode={}",
+            SCHEME,
+            encode(&self.expression),
+            if self.real_only { "real" } else { "complex" }
+        );
+        for (name, value) in &self.variables {
+            link.push_str(&format!("&var.{}={}", encode(name), encode(&encode_value(value))));
+        }
+        link
+    }
+
+    // Accepts guicalc:// links as well as web URLs carrying the same query
+    pub fn from_link(link: &str) -> Option<SharedCalculation> {
+        let (_, query) = link.trim().split_once('?')?;
+        let mut expression = None;
+        let mut real_only = false;
+        let mut variables = Vec::new();
+        for pair in query.split('&') {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            match key {
+                "expr" => expression = Some(decode(value)?),
+                "mode" => real_only = value == "real",
+                _ => {
+                    if let Some(name) = key.strip_prefix("var.") {
+                        variables.push((decode(name)?, decode_value(&decode(value)?)?));
+                    }
+                },
+            }
+        }
+        Some(SharedCalculation {
+            expression: expression?,
+            real_only,
+            variables,
+        })
+    }
+}
+
+// Plain numbers stay readable, as in var.x=3; anything else goes as JSON
+fn encode_value(value: &Value) -> String {
+    match value {
+        Value::Number(n) => n.to_string(),
+        _ => serde_json::to_string(value).unwrap_or_default(),
+    }
+}
+
+fn decode_value(text: &str) -> Option<Value> {
+    match text.parse() {
+        Ok(n) => Some(Value::Number(n)),
+        Err(_) => serde_json::from_str(text).ok(),
+    }
+}
+
+fn encode(text: &str) -> String {
+    let mut encoded = String::new();
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char);
+            },
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+fn decode(text: &str) -> Option<String> {
+    let mut bytes = Vec::new();
+    let mut iter = text.bytes();
+    while let Some(byte) = iter.next() {
+        match byte {
+            b'%' => {
+                let hex = [iter.next()?, iter.next()?];
+                let hex = std::str::from_utf8(&hex).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            },
+            b'+' => bytes.push(b' '),
+            _ => bytes.push(byte),
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculator::Calculator;
+
+    #[test]
+    fn round_trip() {
+        let mut calculator = Calculator::default();
+        calculator.execute("x = 3").unwrap();
+        calculator.execute("v = [1, 2.5]").unwrap();
+        calculator.execute("unused = 9").unwrap();
+        let expression = "y = x^2 + sum(v) + x";
+        let shared = SharedCalculation {
+            expression: expression.to_string(),
+            real_only: true,
+            variables: calculator.referenced(expression),
+        };
+        assert_eq!(shared.variables.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(), ["x", "v"]);
+        let link = shared.to_link();
+        assert!(link.contains("&var.x=3"), "{}", link);
+        assert_eq!(SharedCalculation::from_link(&link), Some(shared));
+    }
+
+    #[test]
+    fn web_urls_and_bad_links() {
+        let shared = SharedCalculation::from_link("https://example.com/calc?expr=2%2Bx&var.x=0.1").unwrap();
+        assert_eq!(shared.expression, "2+x");
+        assert_eq!(shared.variables, [("x".to_string(), Value::Number(0.1))]);
+        assert_eq!(SharedCalculation::from_link("guicalc://calc?mode=real"), None);
+        assert_eq!(SharedCalculation::from_link("guicalc://calc?expr=x&var.x=%7Bbad"), None);
+    }
+}
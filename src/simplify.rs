@@ -0,0 +1,239 @@
+use num_bigint::BigInt;
+use num_traits::{FromPrimitive, ToPrimitive};
+
+use crate::ast::Expr;
+use crate::calculator::{Calculator, Function, Juxtaposition, Operator};
+use crate::value::Value;
+
+// Folded numbers longer than this stay as they were written, so 1/3 isn't
+// replaced by 0.3333333333333333 and sin(0) still becomes 0
+const MAX_FOLDED_LENGTH: usize = 12;
+
+// The number a literal stands for, including a negated one
+fn literal(expr: &Expr) -> Option<Value> {
+    match expr {
+        Expr::Number(n) => Some(Value::Number(*n)),
+        Expr::Integer(n) => Some(Value::Integer(n.clone())),
+        Expr::Neg(inner) => match literal(inner)? {
+            Value::Number(n) => Some(Value::Number(-n)),
+            Value::Integer(n) => Some(Value::Integer(-n)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// A literal as an f64, when that loses nothing
+fn numeric(expr: &Expr) -> Option<f64> {
+    match literal(expr)? {
+        Value::Number(n) => Some(n),
+        Value::Integer(n) => n.to_f64().filter(|n| n.abs() < 2f64.powi(53)),
+        _ => None,
+    }
+}
+
+fn short(n: f64) -> bool {
+    n.is_finite() && n.abs().to_string().len() <= MAX_FOLDED_LENGTH
+}
+
+fn negate(expr: Expr) -> Expr {
+    match expr {
+        Expr::Neg(inner) => *inner,
+        _ => Expr::Neg(Box::new(expr)),
+    }
+}
+
+// Rewrites an expression tree into an equivalent simpler one: numbers are
+// folded, identities such as x*1, x+0 and x^1 dropped, like terms of a sum
+// and repeated factors of a product combined, e.g. 2x + 3 - x + 0 → x + 3
+pub fn simplify(calculator: &Calculator, expr: Expr) -> Expr {
+    Simplifier { calculator }.simplify(expr)
+}
+
+struct Simplifier<'a> {
+    calculator: &'a Calculator,
+}
+
+impl Simplifier<'_> {
+    // A number in the calculator's own representation, negatives as -n so
+    // they print with the parentheses they need
+    fn number(&self, n: f64) -> Expr {
+        if n < 0.0 {
+            return negate(self.number(-n));
+        }
+        match BigInt::from_f64(n).filter(|_| self.calculator.integer_mode && n.fract() == 0.0) {
+            Some(n) => Expr::Integer(n),
+            None => Expr::Number(n),
+        }
+    }
+
+    fn value(&self, value: Value) -> Option<Expr> {
+        match value {
+            Value::Number(n) if short(n) => Some(self.number(n)),
+            Value::Integer(n) => Some(if n < BigInt::from(0) { negate(Expr::Integer(-n)) } else { Expr::Integer(n) }),
+            _ => None,
+        }
+    }
+
+    fn simplify(&self, expr: Expr) -> Expr {
+        match expr {
+            Expr::Neg(inner) => {
+                let inner = self.simplify(*inner);
+                match numeric(&inner) {
+                    Some(n) => self.number(-n),
+                    None => negate(inner),
+                }
+            },
+            Expr::Binary(op, left, right) => self.binary(op, self.simplify(*left), self.simplify(*right)),
+            Expr::Function(func, args) => {
+                let args: Vec<Expr> = args.into_iter().map(|arg| self.simplify(arg)).collect();
+                self.function(func, args)
+            },
+            Expr::Factorial(inner) => {
+                let inner = self.simplify(*inner);
+                numeric(&inner)
+                    .and_then(|n| Calculator::factorial(Value::Number(n)).ok())
+                    .and_then(|value| self.value(value))
+                    .unwrap_or_else(|| Expr::Factorial(Box::new(inner)))
+            },
+            other => other,
+        }
+    }
+
+    // Functions of a single number are folded when the result is short, as
+    // sin(0) or sqrt(16); currency rates may change, so those stay
+    fn function(&self, func: Function, args: Vec<Expr>) -> Expr {
+        if let ([arg], 1) = (args.as_slice(), func.arity()) {
+            if !matches!(func, Function::Currency(..)) {
+                let folded = literal(arg)
+                    .and_then(|value| self.calculator.call(func, vec![value]).ok())
+                    .and_then(|value| self.value(value));
+                if let Some(folded) = folded {
+                    return folded;
+                }
+            }
+        }
+        Expr::Function(func, args)
+    }
+
+    fn binary(&self, op: Operator, left: Expr, right: Expr) -> Expr {
+        if let (Some(a), Some(b)) = (literal(&left), literal(&right)) {
+            if let Some(folded) = self.calculator.apply_operator(op, a, b).ok().and_then(|value| self.value(value)) {
+                return folded;
+            }
+        }
+        match op {
+            Operator::Add | Operator::Sub => self.sum(Expr::Binary(op, Box::new(left), Box::new(right))),
+            Operator::Mul | Operator::Juxtapose => self.product(Expr::Binary(op, Box::new(left), Box::new(right))),
+            Operator::Div if numeric(&right) == Some(1.0) => left,
+            Operator::Pow => match (numeric(&left), numeric(&right)) {
+                (_, Some(1.0)) => left,
+                (_, Some(0.0)) | (Some(1.0), _) => self.number(1.0),
+                _ => Expr::Binary(op, Box::new(left), Box::new(right)),
+            },
+            _ => Expr::Binary(op, Box::new(left), Box::new(right)),
+        }
+    }
+
+    // Splits a sum into coefficient × term, None as the term of plain numbers
+    fn terms(&self, expr: Expr, sign: f64, terms: &mut Vec<(f64, Option<Expr>)>) {
+        match expr {
+            Expr::Binary(Operator::Add, left, right) => {
+                self.terms(*left, sign, terms);
+                self.terms(*right, sign, terms);
+            },
+            Expr::Binary(Operator::Sub, left, right) => {
+                self.terms(*left, sign, terms);
+                self.terms(*right, -sign, terms);
+            },
+            Expr::Neg(inner) => self.terms(*inner, -sign, terms),
+            other => {
+                let (coefficient, term) = self.coefficient(other);
+                // Like terms add up, unless the total would be a long decimal
+                let like = terms.iter_mut().find(|(c, t)| *t == term && short(*c + sign * coefficient));
+                match like {
+                    Some((c, _)) => *c += sign * coefficient,
+                    None => terms.push((sign * coefficient, term)),
+                }
+            },
+        }
+    }
+
+    // A product is already simplified with its number first
+    fn coefficient(&self, expr: Expr) -> (f64, Option<Expr>) {
+        if let Some(n) = numeric(&expr) {
+            return (n, None);
+        }
+        match expr {
+            Expr::Binary(Operator::Mul | Operator::Juxtapose, left, right) if numeric(&left).is_some() => (numeric(&left).unwrap(), Some(*right)),
+            other => (1.0, Some(other)),
+        }
+    }
+
+    fn sum(&self, expr: Expr) -> Expr {
+        let mut terms = Vec::new();
+        self.terms(expr, 1.0, &mut terms);
+        // The plain number goes last, as in x + 3
+        terms.sort_by_key(|(_, term)| term.is_none());
+        let mut sum: Option<Expr> = None;
+        for (coefficient, term) in terms.into_iter().filter(|(c, _)| *c != 0.0) {
+            let magnitude = match term {
+                None => self.number(coefficient.abs()),
+                Some(term) if coefficient.abs() == 1.0 => term,
+                Some(term) => self.scaled(coefficient.abs(), term),
+            };
+            sum = Some(match sum {
+                None if coefficient < 0.0 => negate(magnitude),
+                None => magnitude,
+                Some(sum) => Expr::Binary(if coefficient < 0.0 { Operator::Sub } else { Operator::Add }, Box::new(sum), Box::new(magnitude)),
+            });
+        }
+        sum.unwrap_or_else(|| self.number(0.0))
+    }
+
+    fn factors(&self, expr: Expr, coefficient: &mut f64, factors: &mut Vec<(Expr, u32)>) {
+        match expr {
+            Expr::Binary(Operator::Mul | Operator::Juxtapose, left, right) => {
+                self.factors(*left, coefficient, factors);
+                self.factors(*right, coefficient, factors);
+            },
+            Expr::Neg(inner) => {
+                *coefficient = -*coefficient;
+                self.factors(*inner, coefficient, factors);
+            },
+            other => match numeric(&other).filter(|n| short(*coefficient * n)) {
+                Some(n) => *coefficient *= n,
+                // Repeated factors become powers, x*x → x^2
+                None => match factors.iter_mut().find(|(factor, _)| *factor == other) {
+                    Some((_, count)) => *count += 1,
+                    None => factors.push((other, 1)),
+                },
+            },
+        }
+    }
+
+    fn product(&self, expr: Expr) -> Expr {
+        let mut coefficient = 1.0;
+        let mut factors = Vec::new();
+        self.factors(expr, &mut coefficient, &mut factors);
+        if coefficient == 0.0 {
+            return self.number(0.0);
+        }
+        let product = factors.into_iter()
+            .map(|(factor, count)| if count == 1 { factor } else { Expr::Binary(Operator::Pow, Box::new(factor), Box::new(self.number(count as f64))) })
+            .reduce(|product, factor| Expr::Binary(Operator::Mul, Box::new(product), Box::new(factor)));
+        let Some(product) = product else {
+            return self.number(coefficient);
+        };
+        let scaled = if coefficient.abs() == 1.0 { product } else { self.scaled(coefficient.abs(), product) };
+        if coefficient < 0.0 { negate(scaled) } else { scaled }
+    }
+
+    // n·term, written 2x when implicit multiplication binds tightly and the
+    // term is a single name or call; 2 * term otherwise
+    fn scaled(&self, n: f64, term: Expr) -> Expr {
+        let atomic = matches!(term, Expr::Variable(_) | Expr::Constant(_) | Expr::Function(..));
+        let op = if atomic && self.calculator.juxtaposition == Juxtaposition::Tight { Operator::Juxtapose } else { Operator::Mul };
+        Expr::Binary(op, Box::new(self.number(n)), Box::new(term))
+    }
+}
@@ -0,0 +1,176 @@
+use crate::derivative;
+
+const MAX_ITERATIONS: usize = 100;
+// Relative step size at which the root counts as found
+const TOLERANCE: f64 = 1e-12;
+
+// Root of `f` near `guess` by the secant method
+pub fn secant(f: impl Fn(f64) -> Result<f64, String>, guess: f64) -> Result<f64, String> {
+    let mut x0 = guess;
+    let mut x1 = if guess == 0.0 { 0.1 } else { guess * 1.1 };
+    let mut f0 = f(x0)?;
+    if f0 == 0.0 {
+        return Ok(x0);
+    }
+    for _ in 0..MAX_ITERATIONS {
+        let f1 = f(x1)?;
+        if f1 == 0.0 {
+            return Ok(x1);
+        }
+        if f1 == f0 {
+            return Err("El método se estancó; prueba con otro valor inicial".to_string());
+        }
+        let x2 = x1 - f1 * (x1 - x0) / (f1 - f0);
+        if !x2.is_finite() {
+            return Err("El método divergió; prueba con otro valor inicial".to_string());
+        }
+        if (x2 - x1).abs() <= TOLERANCE * x2.abs().max(1.0) {
+            return Ok(x2);
+        }
+        (x0, f0, x1) = (x1, f1, x2);
+    }
+    Err(format!("No converge tras {} iteraciones", MAX_ITERATIONS))
+}
+
+// Times a Newton step is halved when it makes |f| bigger
+const MAX_HALVINGS: usize = 30;
+
+// Root of `f` near `guess` by Newton's method with the slope from numeric
+// differentiation; steps that would make |f| grow are cut in half, so it
+// doesn't shoot off where f is flat
+pub fn newton(f: impl Fn(f64) -> f64, guess: f64) -> Result<f64, String> {
+    let mut x = guess;
+    let mut fx = f(x);
+    if !fx.is_finite() {
+        return Err(format!("La función no está definida en el valor inicial {}", guess));
+    }
+    for _ in 0..MAX_ITERATIONS {
+        if fx == 0.0 {
+            return Ok(x);
+        }
+        let slope = derivative::central(&f, x).filter(|slope| *slope != 0.0)
+            .ok_or_else(|| format!("La pendiente se anula en {}; prueba con otro valor inicial", x))?;
+        let full = fx / slope;
+        let mut step = full;
+        let mut next = (x - step, f(x - step));
+        for _ in 0..MAX_HALVINGS {
+            if next.1.is_finite() && next.1.abs() <= fx.abs() {
+                break;
+            }
+            step /= 2.0;
+            next = (x - step, f(x - step));
+        }
+        if !next.0.is_finite() || !next.1.is_finite() {
+            return Err("El método divergió; prueba con otro valor inicial".to_string());
+        }
+        (x, fx) = next;
+        // Only a full step that small means a root; halved ones also get
+        // small where |f| has a minimum above 0
+        if full.abs() <= TOLERANCE * x.abs().max(1.0) {
+            return Ok(x);
+        }
+    }
+    Err(format!("No converge tras {} iteraciones; prueba con otro valor inicial", MAX_ITERATIONS))
+}
+
+// Brent's method only bisects after interpolation has stalled, so flat roots
+// such as that of (x - a)^7 take more steps than the other methods get
+const BRENT_ITERATIONS: usize = 1000;
+
+// Root of `f` between `a` and `b`, where it must change sign, by Brent's
+// method: inverse quadratic interpolation or secant steps while they stay
+// inside the bracket, bisection otherwise
+pub fn brent(f: impl Fn(f64) -> f64, a: f64, b: f64) -> Result<f64, String> {
+    let (mut a, mut b) = (a, b);
+    let (mut fa, mut fb) = (f(a), f(b));
+    if !fa.is_finite() || !fb.is_finite() {
+        return Err("La función no está definida en un extremo del intervalo".to_string());
+    }
+    if fa == 0.0 {
+        return Ok(a);
+    }
+    if fb == 0.0 {
+        return Ok(b);
+    }
+    if fa.signum() == fb.signum() {
+        return Err("La función tiene el mismo signo en los dos extremos; elige un intervalo donde cruce el eje".to_string());
+    }
+    // c is the other end of the bracket, d the last step and e the one before
+    let (mut c, mut fc) = (a, fa);
+    let (mut d, mut e) = (b - a, b - a);
+    for _ in 0..BRENT_ITERATIONS {
+        if fb.signum() == fc.signum() {
+            (c, fc) = (a, fa);
+            (d, e) = (b - a, b - a);
+        }
+        // b stays the best guess so far
+        if fc.abs() < fb.abs() {
+            (a, fa) = (b, fb);
+            (b, fb) = (c, fc);
+            (c, fc) = (a, fa);
+        }
+        let tolerance = 2.0 * f64::EPSILON * b.abs() + 0.5 * TOLERANCE * b.abs().max(1.0);
+        let middle = 0.5 * (c - b);
+        if middle.abs() <= tolerance || fb == 0.0 {
+            return Ok(b);
+        }
+        if e.abs() >= tolerance && fa.abs() > fb.abs() {
+            let s = fb / fa;
+            let (mut p, mut q) = if a == c {
+                (2.0 * middle * s, 1.0 - s)
+            } else {
+                let (q, r) = (fa / fc, fb / fc);
+                (s * (2.0 * middle * q * (q - r) - (b - a) * (r - 1.0)), (q - 1.0) * (r - 1.0) * (s - 1.0))
+            };
+            if p > 0.0 {
+                q = -q;
+            } else {
+                p = -p;
+            }
+            // Interpolate only when the step lands well inside and keeps shrinking
+            if 2.0 * p < (3.0 * middle * q - (tolerance * q).abs()).min((e * q).abs()) {
+                (e, d) = (d, p / q);
+            } else {
+                (d, e) = (middle, middle);
+            }
+        } else {
+            (d, e) = (middle, middle);
+        }
+        (a, fa) = (b, fb);
+        b += if d.abs() > tolerance { d } else { tolerance.copysign(middle) };
+        fb = f(b);
+        if !fb.is_finite() {
+            return Err(format!("La función no está definida en x = {} dentro del intervalo", b));
+        }
+    }
+    Err(format!("No converge tras {} iteraciones", BRENT_ITERATIONS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    fn close(x: Result<f64, String>, expected: f64) -> bool {
+        x.is_ok_and(|x| testing::close(x, expected))
+    }
+
+    #[test]
+    fn brent_brackets() {
+        assert!(close(brent(|x| x * x - 2.0, 0.0, 2.0), std::f64::consts::SQRT_2));
+        assert!(close(brent(f64::cos, 1.0, 2.0), std::f64::consts::FRAC_PI_2));
+        // A root at an end, and a steep one
+        assert!(close(brent(|x| x - 1.0, 1.0, 3.0), 1.0));
+        assert!(close(brent(|x| (x - 0.3).powi(7), 0.0, 1.0), 0.3));
+        assert!(brent(|x| x * x + 1.0, -1.0, 1.0).is_err());
+        assert!(brent(f64::ln, -1.0, 2.0).is_err());
+    }
+
+    #[test]
+    fn newton_and_secant() {
+        assert!(close(newton(|x| x * x * x - 8.0, 1.0), 2.0));
+        assert!(close(newton(|x| x.exp() - 3.0, 0.0), 3f64.ln()));
+        assert!(newton(|x| x * x + 1.0, 1.0).is_err());
+        assert!(close(secant(|x| Ok(x * x - 9.0), 1.0), 3.0));
+    }
+}
@@ -0,0 +1,568 @@
+use crate::calculator::Calculator;
+use crate::csv::CsvImport;
+use crate::format::NumberFormat;
+use crate::graph;
+use crate::regression::{self, Fit, Model};
+use crate::separators::Separators;
+use crate::value::Value;
+
+// Rows shown in the table below the data before it scrolls
+const TABLE_HEIGHT: f32 = 240.0;
+const PLOT_HEIGHT: f32 = 320.0;
+// Points of the fitted curve drawn across the plot
+const CURVE_SAMPLES: usize = 200;
+const POINT_COLOR: egui::Color32 = egui::Color32::from_rgb(90, 170, 255);
+const CURVE_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 150, 60);
+const MAX_BINS: usize = 100;
+
+// How the histogram's classes are chosen
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum Binning {
+    #[default]
+    Count,
+    Width,
+}
+
+impl Binning {
+    pub const ALL: [Binning; 2] = [Binning::Count, Binning::Width];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Binning::Count => "Número de clases",
+            Binning::Width => "Ancho de clase",
+        }
+    }
+}
+
+// Sturges' rule, a usual starting point
+fn default_bins(n: usize) -> usize {
+    ((n.max(1) as f64).log2().ceil() as usize + 1).min(MAX_BINS)
+}
+
+// Classes [start + i·width, start + (i + 1)·width) and how many values
+// fall in each, the last one closed so the maximum counts. By width, the
+// classes start at a multiple of it; too narrow a width is widened to keep
+// the count of classes bounded
+pub fn histogram(values: &[f64], binning: Binning, count: usize, width: f64) -> Option<(f64, f64, Vec<usize>)> {
+    let (min, max) = values.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| (min.min(*v), max.max(*v)));
+    if min > max {
+        return None;
+    }
+    let (start, width, bins) = match binning {
+        _ if min == max => (min - 0.5, 1.0, 1),
+        Binning::Count => {
+            let bins = count.clamp(1, MAX_BINS);
+            (min, (max - min) / bins as f64, bins)
+        },
+        Binning::Width => {
+            let width = width.max((max - min) / MAX_BINS as f64);
+            let start = (min / width).floor() * width;
+            (start, width, (((max - start) / width).floor() as usize + 1).min(MAX_BINS))
+        },
+    };
+    let mut counts = vec![0; bins];
+    for v in values {
+        let i = (((v - start) / width).floor().max(0.0) as usize).min(bins - 1);
+        counts[i] += 1;
+    }
+    Some((start, width, counts))
+}
+
+fn normal_pdf(x: f64, mean: f64, deviation: f64) -> f64 {
+    (-0.5 * ((x - mean) / deviation).powi(2)).exp() / (deviation * (2.0 * std::f64::consts::PI).sqrt())
+}
+
+// Bars of frequency or relative frequency, with the normal curve of the
+// data's mean and sample deviation scaled to the same area when asked
+fn histogram_plot(ui: &mut egui::Ui, (start, width, counts): (f64, f64, Vec<usize>), relative: bool, normal: Option<(f64, f64)>) {
+    let (response, painter) = ui.allocate_painter(egui::vec2(ui.available_width(), PLOT_HEIGHT), egui::Sense::hover());
+    let rect = response.rect;
+    painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+    let n: usize = counts.iter().sum();
+    let scale = if relative { 1.0 / n as f64 } else { 1.0 };
+    let heights: Vec<f64> = counts.iter().map(|c| *c as f64 * scale).collect();
+    let end = start + width * counts.len() as f64;
+    let margin = width * 0.5;
+    let (x0, x1) = (start - margin, end + margin);
+    let curve: Vec<[f64; 2]> = normal.map_or_else(Vec::new, |(mean, deviation)| {
+        (0..=CURVE_SAMPLES).map(|i| {
+            let x = x0 + (x1 - x0) * i as f64 / CURVE_SAMPLES as f64;
+            [x, normal_pdf(x, mean, deviation) * width * n as f64 * scale]
+        }).collect()
+    });
+    let top = heights.iter().chain(curve.iter().map(|[_, y]| y)).fold(0.0, |top: f64, y| top.max(*y));
+    let (y0, y1) = (-top * 0.05, if top > 0.0 { top * 1.1 } else { 1.0 });
+    let to_screen = |x: f64, y: f64| egui::pos2(
+        rect.left() + ((x - x0) / (x1 - x0)) as f32 * rect.width(),
+        rect.bottom() - ((y - y0) / (y1 - y0)) as f32 * rect.height(),
+    );
+    graph::draw_grid(ui, &painter, rect, [x0, x1, y0, y1], to_screen);
+    let painter = painter.with_clip_rect(rect);
+    for (i, height) in heights.iter().enumerate() {
+        let left = start + width * i as f64;
+        let bar = egui::Rect::from_two_pos(to_screen(left, 0.0), to_screen(left + width, *height));
+        painter.rect(bar, 0.0, POINT_COLOR.gamma_multiply(0.6), egui::Stroke::new(1.0, POINT_COLOR));
+    }
+    graph::draw_curve(&painter, &curve, to_screen, CURVE_COLOR);
+}
+
+// Lowest and highest of some values with a margin, or around a single one
+fn padded_range(values: impl Iterator<Item = f64>) -> Option<(f64, f64)> {
+    let (low, high) = values.filter(|v| v.is_finite()).fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), v| (low.min(v), high.max(v)));
+    if low > high {
+        return None;
+    }
+    let margin = if high > low { (high - low) * 0.08 } else { low.abs().max(1.0) * 0.5 };
+    Some((low - margin, high + margin))
+}
+
+// The data as points with the fitted curve over them
+fn scatter_plot(ui: &mut egui::Ui, x: &[f64], y: &[f64], fit: Option<&Fit>) {
+    let (response, painter) = ui.allocate_painter(egui::vec2(ui.available_width(), PLOT_HEIGHT), egui::Sense::hover());
+    let rect = response.rect;
+    painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+    let Some((x0, x1)) = padded_range(x.iter().copied()) else {
+        return;
+    };
+    let curve: Vec<[f64; 2]> = fit.map_or_else(Vec::new, |fit| {
+        (0..=CURVE_SAMPLES).map(|i| {
+            let x = x0 + (x1 - x0) * i as f64 / CURVE_SAMPLES as f64;
+            [x, fit.evaluate(x)]
+        }).collect()
+    });
+    // The curve only widens the view as far as it stays near the data
+    let Some((low, high)) = padded_range(y.iter().copied()) else {
+        return;
+    };
+    let span = high - low;
+    let near = curve.iter().map(|[_, y]| *y).filter(|y| *y >= low - span && *y <= high + span);
+    let (y0, y1) = padded_range(y.iter().copied().chain(near)).unwrap_or((low, high));
+    let to_screen = |x: f64, y: f64| egui::pos2(
+        rect.left() + ((x - x0) / (x1 - x0)) as f32 * rect.width(),
+        rect.bottom() - ((y - y0) / (y1 - y0)) as f32 * rect.height(),
+    );
+    graph::draw_grid(ui, &painter, rect, [x0, x1, y0, y1], to_screen);
+    let painter = painter.with_clip_rect(rect);
+    graph::draw_curve(&painter, &curve, to_screen, CURVE_COLOR);
+    for (x, y) in x.iter().zip(y) {
+        painter.circle_filled(to_screen(*x, *y), 3.5, POINT_COLOR);
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum Columns {
+    #[default]
+    One,
+    // x and y pairs, for regression and scatter plots
+    Two,
+}
+
+impl Columns {
+    pub const ALL: [Columns; 2] = [Columns::One, Columns::Two];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Columns::One => "Una columna",
+            Columns::Two => "Dos columnas (x, y)",
+        }
+    }
+}
+
+// The data set the statistics work on: x alone, or x with y of the same
+// length in two-column mode
+#[derive(Debug, Default, Clone)]
+pub struct Data {
+    pub x: Vec<f64>,
+    pub y: Option<Vec<f64>>,
+}
+
+fn median(sorted: &[f64]) -> f64 {
+    let n = sorted.len();
+    if n % 2 == 1 { sorted[n / 2] } else { (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0 }
+}
+
+// Descriptive statistics of one column. Quartiles are the medians of the
+// lower and upper halves, leaving out the median itself when n is odd, as
+// school calculators do; skewness is the adjusted sample one, as in
+// spreadsheets. Those needing more data than there is are None
+#[derive(Debug, Clone)]
+pub struct Summary {
+    pub n: usize,
+    pub sum: f64,
+    pub mean: f64,
+    pub median: f64,
+    pub sample_deviation: Option<f64>,
+    pub population_deviation: f64,
+    pub sample_variance: Option<f64>,
+    pub population_variance: f64,
+    pub min: f64,
+    pub max: f64,
+    pub quartiles: Option<(f64, f64)>,
+    pub skewness: Option<f64>,
+}
+
+impl Summary {
+    pub fn of(values: &[f64]) -> Option<Summary> {
+        let n = values.len();
+        if n == 0 {
+            return None;
+        }
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let sum: f64 = values.iter().sum();
+        let mean = sum / n as f64;
+        let squares: f64 = values.iter().map(|x| (x - mean).powi(2)).sum();
+        let cubes: f64 = values.iter().map(|x| (x - mean).powi(3)).sum();
+        let population_variance = squares / n as f64;
+        let sample_variance = (n > 1).then(|| squares / (n - 1) as f64);
+        let skewness = sample_variance.filter(|&variance| n > 2 && variance > 0.0).map(|variance| {
+            let n = n as f64;
+            n / ((n - 1.0) * (n - 2.0)) * cubes / variance.powf(1.5)
+        });
+        let quartiles = (n > 1).then(|| (median(&sorted[..n / 2]), median(&sorted[n.div_ceil(2)..])));
+        Some(Summary {
+            n,
+            sum,
+            mean,
+            median: median(&sorted),
+            sample_deviation: sample_variance.map(f64::sqrt),
+            population_deviation: population_variance.sqrt(),
+            sample_variance,
+            population_variance,
+            min: sorted[0],
+            max: sorted[n - 1],
+            quartiles,
+            skewness,
+        })
+    }
+
+    // Name, symbol and value of each row of the card
+    fn rows(&self) -> Vec<(&'static str, &'static str, Option<f64>)> {
+        vec![
+            ("Número de datos", "n", Some(self.n as f64)),
+            ("Suma", "Σx", Some(self.sum)),
+            ("Media", "x̄", Some(self.mean)),
+            ("Mediana", "Me", Some(self.median)),
+            ("Desviación típica muestral", "s", self.sample_deviation),
+            ("Desviación típica poblacional", "σ", Some(self.population_deviation)),
+            ("Varianza muestral", "s²", self.sample_variance),
+            ("Varianza poblacional", "σ²", Some(self.population_variance)),
+            ("Mínimo", "min", Some(self.min)),
+            ("Primer cuartil", "Q1", self.quartiles.map(|(q1, _)| q1)),
+            ("Tercer cuartil", "Q3", self.quartiles.map(|(_, q3)| q3)),
+            ("Máximo", "max", Some(self.max)),
+            ("Asimetría", "g", self.skewness),
+        ]
+    }
+}
+
+// The summary as a card, each value with a button to copy it
+fn show_summary(ui: &mut egui::Ui, title: &str, summary: &Summary, format: &NumberFormat) {
+    egui::Frame::group(ui.style()).show(ui, |ui| {
+        ui.strong(title);
+        egui::Grid::new(title).num_columns(4).striped(true).show(ui, |ui| {
+            for (name, symbol, value) in summary.rows() {
+                ui.label(name);
+                ui.monospace(symbol);
+                match value {
+                    Some(value) => {
+                        ui.monospace(format.number(value));
+                        if ui.small_button("Copiar").clicked() {
+                            ui.ctx().copy_text(format.number(value));
+                        }
+                    },
+                    None => {
+                        ui.weak("—").on_hover_text("Faltan datos");
+                    },
+                }
+                ui.end_row();
+            }
+        });
+    });
+}
+
+// The cells of a line: split at tabs as pasted from a spreadsheet, else at
+// ';', else at ',' unless it is the decimal mark, else at spaces
+fn cells(line: &str, separators: Separators) -> Vec<&str> {
+    let cells: Vec<&str> = if line.contains('\t') {
+        line.split('\t').collect()
+    } else if line.contains(';') {
+        line.split(';').collect()
+    } else if line.contains(',') && separators != Separators::Comma {
+        line.split(',').collect()
+    } else {
+        line.split_whitespace().collect()
+    };
+    cells.into_iter().map(str::trim).filter(|cell| !cell.is_empty()).collect()
+}
+
+// Reads one value per line, or an x and a y; blank lines and lines starting
+// with # are skipped, and a first line that isn't numbers is a header
+pub fn parse(text: &str, columns: Columns, calculator: &Calculator) -> (Data, Vec<(usize, String)>) {
+    let width = if columns == Columns::Two { 2 } else { 1 };
+    let (mut x, mut y, mut errors) = (Vec::new(), Vec::new(), Vec::new());
+    let mut first = true;
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        let cells = cells(line, calculator.separators);
+        let values: Result<Vec<f64>, String> = cells.iter()
+            .map(|cell| calculator.calculate(cell).and_then(Value::real).map_err(|e| format!("«{}»: {}", cell, e)))
+            .collect();
+        let header = std::mem::take(&mut first) && values.is_err() && cells.iter().all(|cell| cell.starts_with(|c: char| c.is_alphabetic()));
+        match values {
+            _ if header => {},
+            Ok(values) if values.len() == width => {
+                x.push(values[0]);
+                y.extend(values.get(1));
+            },
+            Ok(values) => errors.push((i + 1, format!("se esperaban {} valor(es) y hay {}", width, values.len()))),
+            Err(e) => errors.push((i + 1, e)),
+        }
+    }
+    (Data { x, y: (columns == Columns::Two).then_some(y) }, errors)
+}
+
+// State of the statistics tab: the data, typed or pasted, and what it reads as
+pub struct Statistics {
+    columns: Columns,
+    text: String,
+    data: Data,
+    // Lines that couldn't be read, numbered from 1
+    errors: Vec<(usize, String)>,
+    // Of x and, with two columns, of y
+    summaries: (Option<Summary>, Option<Summary>),
+    model: Model,
+    degree: usize,
+    fit: Option<Result<Fit, String>>,
+    binning: Binning,
+    bins: usize,
+    bin_width: f64,
+    relative: bool,
+    normal: bool,
+    import: Option<CsvImport>,
+    // Rows of the last imported file that weren't numbers
+    import_errors: Vec<String>,
+}
+
+impl Default for Statistics {
+    fn default() -> Self {
+        Statistics {
+            columns: Columns::One,
+            text: String::new(),
+            data: Data::default(),
+            errors: Vec::new(),
+            summaries: (None, None),
+            model: Model::Linear,
+            degree: 2,
+            fit: None,
+            binning: Binning::Count,
+            bins: 0,
+            bin_width: 1.0,
+            relative: false,
+            normal: false,
+            import: None,
+            import_errors: Vec::new(),
+        }
+    }
+}
+
+impl Statistics {
+    fn update(&mut self, calculator: &Calculator) {
+        let before = self.data.x.len();
+        (self.data, self.errors) = parse(&self.text, self.columns, calculator);
+        // Until the user picks one, the count of classes follows the data
+        if self.bins == default_bins(before) || self.bins == 0 {
+            self.bins = default_bins(self.data.x.len());
+        }
+        self.summaries = (Summary::of(&self.data.x), self.data.y.as_deref().and_then(Summary::of));
+        self.refit();
+    }
+
+    fn refit(&mut self) {
+        self.fit = self.data.y.as_ref()
+            .filter(|_| !self.data.x.is_empty())
+            .map(|y| regression::fit(self.model, self.degree, &self.data.x, y));
+    }
+
+    fn show_regression(&mut self, ui: &mut egui::Ui, format: &NumberFormat) {
+        ui.heading("Regresión");
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            for model in Model::ALL {
+                changed |= ui.radio_value(&mut self.model, model, model.name()).changed();
+            }
+            if self.model == Model::Polynomial {
+                ui.label("Grado");
+                changed |= ui.add(egui::DragValue::new(&mut self.degree).range(1..=regression::MAX_DEGREE)).changed();
+            }
+        });
+        if changed {
+            self.refit();
+        }
+        let fit = match &self.fit {
+            Some(Ok(fit)) => Some(fit),
+            Some(Err(error)) => {
+                ui.colored_label(egui::Color32::RED, error);
+                None
+            },
+            None => None,
+        };
+        if let Some(fit) = fit {
+            ui.monospace(fit.equation());
+            egui::Grid::new("coefficients").num_columns(3).show(ui, |ui| {
+                let rows = fit.named().into_iter().chain([("R²".to_string(), fit.r_squared)]);
+                for (name, value) in rows {
+                    ui.monospace(name);
+                    ui.monospace(format.number(value));
+                    if ui.small_button("Copiar").clicked() {
+                        ui.ctx().copy_text(format.number(value));
+                    }
+                    ui.end_row();
+                }
+            });
+            ui.horizontal(|ui| {
+                let expression = fit.expression(format);
+                ui.label(egui::RichText::new(format!("y = {}", expression)).monospace().color(CURVE_COLOR));
+                if ui.small_button("Copiar").on_hover_text("Para pegarla en la calculadora o en la gráfica").clicked() {
+                    ui.ctx().copy_text(expression);
+                }
+            });
+        }
+        if let Some(y) = &self.data.y {
+            scatter_plot(ui, &self.data.x, y, fit);
+        }
+    }
+
+    fn show_histogram(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Histograma");
+        ui.horizontal(|ui| {
+            for binning in Binning::ALL {
+                ui.radio_value(&mut self.binning, binning, binning.name());
+            }
+            match self.binning {
+                Binning::Count => ui.add(egui::DragValue::new(&mut self.bins).range(1..=MAX_BINS)),
+                Binning::Width => ui.add(egui::DragValue::new(&mut self.bin_width).range(1e-9..=f64::MAX).speed(0.1)),
+            };
+            ui.separator();
+            ui.checkbox(&mut self.relative, "Frecuencia relativa");
+            ui.checkbox(&mut self.normal, "Curva normal");
+        });
+        let normal = match &self.summaries.0 {
+            Some(summary) if self.normal => match summary.sample_deviation.filter(|s| *s > 0.0) {
+                Some(deviation) => Some((summary.mean, deviation)),
+                None => {
+                    ui.weak("La curva normal necesita datos que no sean todos iguales");
+                    None
+                },
+            },
+            _ => None,
+        };
+        if let Some(histogram) = histogram(&self.data.x, self.binning, self.bins, self.bin_width) {
+            histogram_plot(ui, histogram, self.relative, normal);
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, calculator: &Calculator, format: &NumberFormat) {
+        ui.heading("Estadística");
+        ui.small("Un dato por línea, o x e y separados por tabulador, punto y coma, coma o espacio; se pueden pegar columnas de una hoja de cálculo.");
+        ui.add_space(10.0);
+        egui::ScrollArea::vertical().id_salt("statistics").show(ui, |ui| self.body(ui, calculator, format));
+    }
+
+    fn body(&mut self, ui: &mut egui::Ui, calculator: &Calculator, format: &NumberFormat) {
+
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            for columns in Columns::ALL {
+                changed |= ui.radio_value(&mut self.columns, columns, columns.name()).changed();
+            }
+            if ui.button("Vaciar").clicked() {
+                self.text.clear();
+                self.import_errors.clear();
+                changed = true;
+            }
+            if ui.button("Importar CSV…").clicked() {
+                match CsvImport::pick() {
+                    Some(Ok(import)) => self.import = Some(import),
+                    Some(Err(e)) => self.import_errors = vec![e],
+                    None => {},
+                }
+            }
+        });
+        if let Some(import) = &mut self.import {
+            match import.show(ui, self.columns == Columns::Two) {
+                Some(true) => {
+                    (self.text, self.import_errors) = import.read(self.columns == Columns::Two, calculator);
+                    self.import = None;
+                    changed = true;
+                },
+                Some(false) => self.import = None,
+                None => {},
+            }
+        }
+        for error in &self.import_errors {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+        let hint = if self.columns == Columns::Two { "x\ty\n1\t2.1\n2\t3.9\n3\t6.2" } else { "4.5\n3.2\n5.1" };
+        ui.horizontal_top(|ui| {
+            changed |= ui.add(
+                egui::TextEdit::multiline(&mut self.text)
+                    .font(egui::TextStyle::Monospace)
+                    .desired_width(220.0)
+                    .desired_rows(12)
+                    .hint_text(hint)
+            ).changed();
+            if changed {
+                self.update(calculator);
+            }
+            ui.vertical(|ui| self.table(ui, format));
+        });
+        for (line, error) in &self.errors {
+            ui.colored_label(egui::Color32::RED, format!("Línea {}: {}", line, error));
+        }
+        ui.add_space(10.0);
+        ui.horizontal_top(|ui| {
+            match &self.summaries {
+                (Some(x), Some(y)) => {
+                    show_summary(ui, "x", x, format);
+                    show_summary(ui, "y", y, format);
+                },
+                (Some(x), None) => show_summary(ui, "Resumen", x, format),
+                _ => {},
+            }
+        });
+        if !self.data.x.is_empty() {
+            ui.add_space(10.0);
+            self.show_histogram(ui);
+        }
+        if self.columns == Columns::Two {
+            ui.add_space(10.0);
+            self.show_regression(ui, format);
+        }
+    }
+
+    // The values as read, to check a paste came through right
+    fn table(&self, ui: &mut egui::Ui, format: &NumberFormat) {
+        ui.label(format!("n = {}", self.data.x.len()));
+        egui::ScrollArea::vertical().id_salt("data").max_height(TABLE_HEIGHT).show(ui, |ui| {
+            egui::Grid::new("data").striped(true).num_columns(3).show(ui, |ui| {
+                ui.strong("#");
+                ui.strong("x");
+                if self.data.y.is_some() {
+                    ui.strong("y");
+                }
+                ui.end_row();
+                for (i, x) in self.data.x.iter().enumerate() {
+                    ui.label((i + 1).to_string());
+                    ui.monospace(format.number(*x));
+                    if let Some(y) = &self.data.y {
+                        ui.monospace(format.number(y[i]));
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+    }
+}
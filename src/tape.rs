@@ -0,0 +1,199 @@
+use num_traits::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use crate::calculator::Calculator;
+use crate::format::NumberFormat;
+use crate::value::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    // Prints the running total and keeps accumulating
+    Subtotal,
+    // Prints the running total and starts over from zero
+    Total,
+}
+
+impl Op {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Op::Add => "+",
+            Op::Sub => "-",
+            Op::Mul => "×",
+            Op::Div => "÷",
+            Op::Subtotal => "◇",
+            Op::Total => "*",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Line {
+    pub op: Op,
+    pub amount: f64,
+    // Running total after this line
+    pub total: f64,
+    pub note: String,
+}
+
+// A desk calculator's printed roll: one line per operation, kept between sessions
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Tape {
+    pub lines: Vec<Line>,
+    #[serde(skip)]
+    entry: String,
+    #[serde(skip)]
+    error: Option<String>,
+}
+
+impl Tape {
+    // What the next operation starts from
+    pub fn total(&self) -> f64 {
+        match self.lines.last() {
+            Some(line) if line.op == Op::Total => 0.0,
+            Some(line) => line.total,
+            None => 0.0,
+        }
+    }
+
+    fn push(&mut self, op: Op, amount: f64) -> Result<(), String> {
+        let total = self.total();
+        let total = match op {
+            Op::Add => total + amount,
+            Op::Sub => total - amount,
+            Op::Mul => total * amount,
+            Op::Div if amount == 0.0 => return Err("División por cero".to_string()),
+            Op::Div => total / amount,
+            Op::Subtotal | Op::Total => total,
+        };
+        let amount = if matches!(op, Op::Subtotal | Op::Total) { total } else { amount };
+        self.lines.push(Line { op, amount, total, note: String::new() });
+        Ok(())
+    }
+
+    // Evaluates the entry field and applies it with `op`
+    fn enter(&mut self, op: Op, calculator: &Calculator) {
+        let amount = match calculator.calculate(&self.entry) {
+            Ok(Value::Number(n)) => Ok(n),
+            Ok(Value::Integer(n)) => Ok(n.to_f64().unwrap_or(f64::INFINITY)),
+            Ok(_) => Err("La cinta solo admite números reales".to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        self.error = amount.and_then(|amount| self.push(op, amount)).err();
+        if self.error.is_none() {
+            self.entry.clear();
+        }
+    }
+
+    // Plain text as printed: amount and symbol right-aligned, note after them
+    pub fn text(&self, format: &NumberFormat) -> String {
+        let width = self.lines.iter().map(|line| format.number(line.amount).chars().count()).max().unwrap_or(0);
+        let mut text = String::new();
+        for line in &self.lines {
+            let row = format!("{:>width$} {}", format.number(line.amount), line.op.symbol(), width = width);
+            if line.note.is_empty() {
+                text.push_str(&format!("{}\n", row));
+            } else {
+                text.push_str(&format!("{}  {}\n", row, line.note));
+            }
+            if line.op == Op::Total {
+                text.push('\n');
+            }
+        }
+        text
+    }
+
+    fn export(&self, format: &NumberFormat) -> Option<String> {
+        let path = rfd::FileDialog::new()
+            .set_file_name("cinta.txt")
+            .add_filter("Texto", &["txt"])
+            .save_file()?;
+        std::fs::write(&path, self.text(format)).err().map(|e| e.to_string())
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, calculator: &Calculator, format: &NumberFormat) {
+        ui.heading("Cinta");
+        ui.small("Escribe un importe y pulsa una operación; Enter suma.");
+        ui.add_space(10.0);
+
+        let field = ui.add(
+            egui::TextEdit::singleline(&mut self.entry)
+                .font(egui::TextStyle::Monospace)
+                .desired_width(f32::INFINITY)
+                .hint_text("Importe o expresión")
+        );
+        if field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            self.enter(Op::Add, calculator);
+            field.request_focus();
+        }
+        ui.horizontal(|ui| {
+            for op in [Op::Add, Op::Sub, Op::Mul, Op::Div] {
+                let button = egui::Button::new(egui::RichText::new(op.symbol()).size(18.0))
+                    .fill(egui::Color32::from_rgb(100, 100, 100))
+                    .min_size(egui::vec2(40.0, 30.0));
+                if ui.add(button).clicked() {
+                    self.enter(op, calculator);
+                }
+            }
+            let subtotal = egui::Button::new("◇ Subtotal").min_size(egui::vec2(0.0, 30.0));
+            if ui.add_enabled(!self.lines.is_empty(), subtotal).clicked() {
+                self.error = self.push(Op::Subtotal, 0.0).err();
+            }
+            let total = egui::Button::new("* Total")
+                .fill(egui::Color32::from_rgb(0, 150, 0))
+                .min_size(egui::vec2(0.0, 30.0));
+            if ui.add_enabled(!self.lines.is_empty(), total).clicked() {
+                self.error = self.push(Op::Total, 0.0).err();
+            }
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(format!("Total: {}", format.number(self.total()))).size(20.0).strong());
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.small_button("Borrar").clicked() {
+                    self.lines.clear();
+                    self.error = None;
+                }
+                if ui.small_button("Deshacer").on_hover_text("Quitar la última línea").clicked() {
+                    self.lines.pop();
+                }
+                if ui.small_button("Exportar…").clicked() {
+                    self.error = self.export(format);
+                }
+                if ui.small_button("Copiar").clicked() {
+                    ui.ctx().copy_text(self.text(format));
+                }
+            });
+        });
+        ui.separator();
+
+        egui::ScrollArea::vertical().stick_to_bottom(true).auto_shrink([false, false]).show(ui, |ui| {
+            egui::Grid::new("tape").num_columns(4).striped(true).show(ui, |ui| {
+                for line in &mut self.lines {
+                    let totals = matches!(line.op, Op::Subtotal | Op::Total);
+                    let amount = egui::RichText::new(format.number(line.amount)).monospace();
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.label(if totals { amount.strong() } else { amount });
+                    });
+                    ui.monospace(line.op.symbol());
+                    // The running total is implied on total lines
+                    if totals {
+                        ui.label("");
+                    } else {
+                        ui.label(egui::RichText::new(format.number(line.total)).monospace().color(egui::Color32::GRAY));
+                    }
+                    ui.add(egui::TextEdit::singleline(&mut line.note).hint_text("Nota").desired_width(160.0));
+                    ui.end_row();
+                }
+            });
+        });
+    }
+}
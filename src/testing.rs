@@ -0,0 +1,17 @@
+// Helpers shared by the unit tests
+
+// Within `tolerance` of `expected`, relative to its size or to 1 near zero,
+// so large results compare relatively and small ones absolutely
+pub fn close_to(value: f64, expected: f64, tolerance: f64) -> bool {
+    (value - expected).abs() <= tolerance * expected.abs().max(1.0)
+}
+
+// To about nine digits, for results a few floating-point steps from exact
+pub fn close(value: f64, expected: f64) -> bool {
+    close_to(value, expected, 1e-9)
+}
+
+// Relative only, for values far below 1 such as tail probabilities
+pub fn relative(value: f64, expected: f64, tolerance: f64) -> bool {
+    (value - expected).abs() <= tolerance * expected.abs()
+}
@@ -0,0 +1,135 @@
+use std::collections::BTreeMap;
+
+use crate::calculator::Calculator;
+use crate::format::NumberFormat;
+use crate::undo::Undo;
+use crate::value::Value;
+
+// What assignments and the constants editor change
+#[derive(Clone, PartialEq)]
+pub struct EngineState {
+    variables: BTreeMap<String, Value>,
+    user_constants: BTreeMap<String, f64>,
+}
+
+impl EngineState {
+    fn of(calculator: &Calculator) -> Self {
+        Self { variables: calculator.variables.clone(), user_constants: calculator.user_constants.clone() }
+    }
+
+    fn restore(self, calculator: &mut Calculator) {
+        calculator.variables = self.variables;
+        calculator.user_constants = self.user_constants;
+    }
+}
+
+// Changes from `before` to `after`, e.g. "x = 2, borrada y"
+fn describe(before: &EngineState, after: &EngineState, format: &NumberFormat) -> String {
+    let mut changes = Vec::new();
+    for (name, value) in &after.variables {
+        if before.variables.get(name) != Some(value) {
+            changes.push(format!("{} = {}", name, format.value(value)));
+        }
+    }
+    for name in before.variables.keys().filter(|name| !after.variables.contains_key(*name)) {
+        changes.push(format!("borrada {}", name));
+    }
+    for (name, value) in &after.user_constants {
+        if before.user_constants.get(name) != Some(value) {
+            changes.push(format!("constante {} = {}", name, format.number(*value)));
+        }
+    }
+    for name in before.user_constants.keys().filter(|name| !after.user_constants.contains_key(*name)) {
+        changes.push(format!("borrada la constante {}", name));
+    }
+    changes.join(", ")
+}
+
+// Engine-level undo: every change to the variables or user constants, from
+// any source, is a step that can be rolled back on its own
+#[derive(Default)]
+pub struct Timeline {
+    // Each state before a change, with the change that followed it
+    steps: Undo<(String, EngineState)>,
+    // The state last seen; none until the first frame, so what is loaded at
+    // startup isn't a step
+    seen: Option<EngineState>,
+}
+
+impl Timeline {
+    pub fn track(&mut self, calculator: &Calculator, format: &NumberFormat) {
+        let current = EngineState::of(calculator);
+        match self.seen.replace(current.clone()) {
+            Some(previous) if previous != current => {
+                let change = describe(&previous, &current, format);
+                self.steps.record((change, previous), 0.0, false);
+            },
+            _ => {},
+        }
+    }
+
+    // Takes the current state as seen without making a step, for updates
+    // that aren't edits, such as a periodic recalculation
+    pub fn skip(&mut self, calculator: &Calculator) {
+        self.seen = Some(EngineState::of(calculator));
+    }
+
+    pub fn undo(&mut self, calculator: &mut Calculator) {
+        let Some(change) = self.steps.done().last().map(|(change, _)| change.clone()) else {
+            return;
+        };
+        if let Some((_, previous)) = self.steps.undo((change, EngineState::of(calculator))) {
+            self.seen = Some(previous.clone());
+            previous.restore(calculator);
+        }
+    }
+
+    pub fn redo(&mut self, calculator: &mut Calculator) {
+        let Some(change) = self.steps.undone().last().map(|(change, _)| change.clone()) else {
+            return;
+        };
+        if let Some((_, next)) = self.steps.redo((change, EngineState::of(calculator))) {
+            self.seen = Some(next.clone());
+            next.restore(calculator);
+        }
+    }
+
+    // Steps oldest first, with the undone ones greyed after the current
+    // point; clicking one goes to just after it
+    pub fn show(&mut self, ui: &mut egui::Ui, calculator: &mut Calculator) {
+        let done = self.steps.done().len();
+        let undone = self.steps.undone().len();
+        if done + undone == 0 {
+            ui.small("Aquí aparecen las asignaciones y los cambios de constantes");
+            return;
+        }
+        ui.horizontal(|ui| {
+            if ui.add_enabled(done > 0, egui::Button::new("Deshacer cambio")).clicked() {
+                self.undo(calculator);
+            }
+            if ui.add_enabled(undone > 0, egui::Button::new("Rehacer cambio")).clicked() {
+                self.redo(calculator);
+            }
+        });
+        let changes = self.steps.done().iter().chain(self.steps.undone().iter().rev()).map(|(change, _)| change.clone());
+        let mut target = None;
+        egui::ScrollArea::vertical().id_salt("timeline").max_height(160.0).show(ui, |ui| {
+            for (i, change) in changes.enumerate() {
+                let text = egui::RichText::new(format!("{}. {}", i + 1, change)).monospace();
+                let text = if i < done { text } else { text.weak().strikethrough() };
+                let current = i + 1 == done;
+                if ui.selectable_label(current, text).on_hover_text("Volver a este punto").clicked() {
+                    target = Some(i + 1);
+                }
+            }
+        });
+        if let Some(target) = target {
+            for _ in target..done {
+                self.undo(calculator);
+            }
+            for _ in done..target {
+                self.redo(calculator);
+            }
+        }
+    }
+}
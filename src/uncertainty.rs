@@ -0,0 +1,144 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::calculator::{Error, Operator};
+
+// A measured value with its standard uncertainty
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct Measurement {
+    pub value: f64,
+    pub sigma: f64,
+}
+
+impl Measurement {
+    pub fn new(value: f64, sigma: f64) -> Self {
+        Self { value, sigma: sigma.abs() }
+    }
+
+    pub fn exact(value: f64) -> Self {
+        Self::new(value, 0.0)
+    }
+
+    // First-order propagation for uncorrelated operands
+    pub fn combine(op: &Operator, a: Measurement, b: Measurement) -> Result<Measurement, Error> {
+        let (x, y) = (a.value, b.value);
+        // (result, df/da, df/db)
+        let (value, da, db) = match op {
+            Operator::Add => (x + y, 1.0, 1.0),
+            Operator::Sub => (x - y, 1.0, -1.0),
+            Operator::Mul | Operator::Juxtapose => (x * y, y, x),
+            Operator::Div => {
+                if y == 0.0 {
+                    return Err(Error::DivisionByZero);
+                }
+                (x / y, 1.0 / y, -x / (y * y))
+            },
+            Operator::Pow => {
+                let value = x.powf(y);
+                let db = if b.sigma == 0.0 { 0.0 } else { value * x.ln() };
+                (value, y * x.powf(y - 1.0), db)
+            },
+            Operator::Mod => {
+                if y == 0.0 {
+                    return Err(Error::DivisionByZero);
+                }
+                (x % y, 1.0, -(x / y).trunc())
+            },
+            Operator::PlusMinus => return Ok(Measurement::new(x, y)),
+            _ => return Err(Error::InvalidOperation("Operador no disponible con incertidumbre".to_string())),
+        };
+        let sigma = ((da * a.sigma).powi(2) + (db * b.sigma).powi(2)).sqrt();
+        Ok(Measurement::new(value, sigma))
+    }
+
+    // Propagates through a one-argument function using a central difference
+    pub fn apply<F>(self, f: F) -> Result<Measurement, Error>
+    where
+        F: Fn(f64) -> Result<f64, Error>,
+    {
+        let value = f(self.value)?;
+        if self.sigma == 0.0 {
+            return Ok(Measurement::exact(value));
+        }
+        // Relative, so small values near a domain edge like log(1e-7) stay inside it
+        let h = if self.value == 0.0 { 1e-6 } else { 1e-6 * self.value.abs() };
+        let derivative = (f(self.value + h)? - f(self.value - h)?) / (2.0 * h);
+        Ok(Measurement::new(value, derivative * self.sigma))
+    }
+}
+
+impl fmt::Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.sigma == 0.0 || !self.sigma.is_finite() {
+            return write!(f, "{} ± {}", self.value, self.sigma);
+        }
+        // Two significant figures for sigma, value rounded to match
+        let magnitude = self.sigma.log10().floor() as i32;
+        let decimals = (1 - magnitude).max(0) as usize;
+        let sigma = format!("{:.*}", decimals, self.sigma);
+        let sigma = if sigma.contains('.') {
+            sigma.trim_end_matches('0').trim_end_matches('.').to_string()
+        } else {
+            sigma
+        };
+        let decimals = sigma.split_once('.').map_or(0, |(_, frac)| frac.len());
+        write!(f, "{:.*} ± {}", decimals, self.value, sigma)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculator::Calculator;
+    use crate::testing::{close, relative};
+    use crate::value::Value;
+
+    fn measure(expr: &str) -> (f64, f64) {
+        match Calculator::default().calculate(expr) {
+            Ok(Value::Uncertain(m)) => (m.value, m.sigma),
+            other => panic!("{}: {:?}", expr, other),
+        }
+    }
+
+    #[test]
+    fn combined_in_quadrature() {
+        let (a, b) = (Measurement::new(2.0, 0.1), Measurement::new(3.0, 0.2));
+        let sum = Measurement::combine(&Operator::Add, a, b).unwrap();
+        assert!(close(sum.value, 5.0) && close(sum.sigma, 0.05f64.sqrt()));
+        let product = Measurement::combine(&Operator::Mul, a, b).unwrap();
+        assert!(close(product.value, 6.0) && close(product.sigma, 0.25f64.sqrt()));
+        // Relative errors add for quotients: 0.05 and 0.05
+        let quotient = Measurement::combine(&Operator::Div, Measurement::new(4.0, 0.2), Measurement::new(2.0, 0.1)).unwrap();
+        assert!(close(quotient.value, 2.0) && close(quotient.sigma, 2.0 * 0.005f64.sqrt()));
+        let square = Measurement::combine(&Operator::Pow, a, Measurement::exact(2.0)).unwrap();
+        assert!(close(square.sigma, 0.4));
+        assert!(Measurement::combine(&Operator::Div, a, Measurement::exact(0.0)).is_err());
+    }
+
+    #[test]
+    fn shown_to_two_significant_figures() {
+        assert_eq!(Measurement::new(5.0, 0.05f64.sqrt()).to_string(), "5.00 ± 0.22");
+        assert_eq!(Measurement::new(6.0, 0.5).to_string(), "6.0 ± 0.5");
+        assert_eq!(Measurement::new(1234.5, 12.0).to_string(), "1234 ± 12");
+        assert_eq!(Measurement::exact(3.0).to_string(), "3 ± 0");
+        assert_eq!(Calculator::default().calculate("(2±0.1)*(3±0.2)").unwrap().to_string(), "6.0 ± 0.5");
+    }
+
+    #[test]
+    fn small_values_near_a_domain_edge() {
+        // d/dx ln x = 1/x and d/dx sqrt x = 1/(2 sqrt x)
+        let (value, sigma) = measure("log(1e-7 ± 1e-9)");
+        assert!(close(value, 1e-7f64.ln()) && relative(sigma, 1e-2, 1e-6));
+        let (value, sigma) = measure("sqrt(1e-8 ± 1e-10)");
+        assert!(relative(value, 1e-4, 1e-12) && relative(sigma, 5e-7, 1e-6));
+    }
+
+    #[test]
+    fn zero_and_ordinary_values() {
+        let (value, sigma) = measure("sin(0 ± 0.1)");
+        assert!(value == 0.0 && close(sigma, 0.1));
+        let (value, sigma) = measure("sqrt(4 ± 0.2)");
+        assert!(close(value, 2.0) && close(sigma, 0.05));
+    }
+}
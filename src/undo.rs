@@ -0,0 +1,64 @@
+// Typed edits closer together than this make a single undo step
+const MERGE_SECONDS: f64 = 1.0;
+const MAX_STEPS: usize = 200;
+
+// States to go back and forward to, one per edit
+pub struct Undo<T> {
+    done: Vec<T>,
+    undone: Vec<T>,
+    // When the last edit was typed, if it was
+    last_typed: Option<f64>,
+}
+
+impl<T> Default for Undo<T> {
+    fn default() -> Self {
+        Self { done: Vec::new(), undone: Vec::new(), last_typed: None }
+    }
+}
+
+impl<T> Undo<T> {
+    // Records `previous` as the state before an edit made at `time`
+    pub fn record(&mut self, previous: T, time: f64, typed: bool) {
+        let merge = typed && self.last_typed.is_some_and(|last| time - last < MERGE_SECONDS);
+        self.last_typed = typed.then_some(time);
+        self.undone.clear();
+        if !merge {
+            self.done.push(previous);
+            if self.done.len() > MAX_STEPS {
+                self.done.remove(0);
+            }
+        }
+    }
+
+    pub fn undo(&mut self, current: T) -> Option<T> {
+        let previous = self.done.pop()?;
+        self.undone.push(current);
+        self.last_typed = None;
+        Some(previous)
+    }
+
+    pub fn redo(&mut self, current: T) -> Option<T> {
+        let next = self.undone.pop()?;
+        self.done.push(current);
+        self.last_typed = None;
+        Some(next)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.done.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    // States that can be gone back to, oldest first
+    pub fn done(&self) -> &[T] {
+        &self.done
+    }
+
+    // States that can be gone forward to, the next one last
+    pub fn undone(&self) -> &[T] {
+        &self.undone
+    }
+}
@@ -0,0 +1,270 @@
+use crate::calculator::Error;
+use crate::quantity::Dimension;
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum Category {
+    Length,
+    Area,
+    Volume,
+    Mass,
+    Time,
+    Speed,
+    Temperature,
+    Energy,
+    Pressure,
+    Force,
+    Power,
+    Frequency,
+    Data,
+}
+
+impl Category {
+    pub const ALL: [Category; 13] = [
+        Category::Length,
+        Category::Area,
+        Category::Volume,
+        Category::Mass,
+        Category::Time,
+        Category::Speed,
+        Category::Temperature,
+        Category::Energy,
+        Category::Pressure,
+        Category::Force,
+        Category::Power,
+        Category::Frequency,
+        Category::Data,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Category::Length => "Longitud",
+            Category::Area => "Área",
+            Category::Volume => "Volumen",
+            Category::Mass => "Masa",
+            Category::Time => "Tiempo",
+            Category::Speed => "Velocidad",
+            Category::Temperature => "Temperatura",
+            Category::Energy => "Energía",
+            Category::Pressure => "Presión",
+            Category::Force => "Fuerza",
+            Category::Power => "Potencia",
+            Category::Frequency => "Frecuencia",
+            Category::Data => "Datos",
+        }
+    }
+
+    // Exponents of kg, m, s, K and B for the category's base unit
+    pub fn dimension(&self) -> Dimension {
+        match self {
+            Category::Length => Dimension::new(0, 1, 0, 0, 0),
+            Category::Area => Dimension::new(0, 2, 0, 0, 0),
+            Category::Volume => Dimension::new(0, 3, 0, 0, 0),
+            Category::Mass => Dimension::new(1, 0, 0, 0, 0),
+            Category::Time => Dimension::new(0, 0, 1, 0, 0),
+            Category::Speed => Dimension::new(0, 1, -1, 0, 0),
+            Category::Temperature => Dimension::new(0, 0, 0, 1, 0),
+            Category::Energy => Dimension::new(1, 2, -2, 0, 0),
+            Category::Pressure => Dimension::new(1, -1, -2, 0, 0),
+            Category::Force => Dimension::new(1, 1, -2, 0, 0),
+            Category::Power => Dimension::new(1, 2, -3, 0, 0),
+            Category::Frequency => Dimension::new(0, 0, -1, 0, 0),
+            Category::Data => Dimension::new(0, 0, 0, 0, 1),
+        }
+    }
+
+    pub fn units(&self) -> impl Iterator<Item = &'static Unit> + '_ {
+        UNITS.iter().filter(move |unit| unit.category == *self)
+    }
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct Unit {
+    pub symbol: &'static str,
+    pub name: &'static str,
+    pub category: Category,
+    // base = value * factor + offset, in the category's SI base unit
+    pub factor: f64,
+    pub offset: f64,
+}
+
+const fn unit(symbol: &'static str, name: &'static str, category: Category, factor: f64) -> Unit {
+    Unit { symbol, name, category, factor, offset: 0.0 }
+}
+
+pub const UNITS: &[Unit] = &[
+    // Length (m)
+    unit("m", "metro", Category::Length, 1.0),
+    unit("km", "kilómetro", Category::Length, 1000.0),
+    unit("cm", "centímetro", Category::Length, 0.01),
+    unit("mm", "milímetro", Category::Length, 0.001),
+    unit("µm", "micrómetro", Category::Length, 1e-6),
+    unit("nm", "nanómetro", Category::Length, 1e-9),
+    unit("mi", "milla", Category::Length, 1609.344),
+    unit("yd", "yarda", Category::Length, 0.9144),
+    unit("ft", "pie", Category::Length, 0.3048),
+    unit("in", "pulgada", Category::Length, 0.0254),
+    unit("nmi", "milla náutica", Category::Length, 1852.0),
+    unit("au", "unidad astronómica", Category::Length, 1.495_978_707e11),
+    unit("ly", "año luz", Category::Length, 9.460_730_472_580_8e15),
+    // Area (m²)
+    unit("m2", "metro cuadrado", Category::Area, 1.0),
+    unit("km2", "kilómetro cuadrado", Category::Area, 1e6),
+    unit("cm2", "centímetro cuadrado", Category::Area, 1e-4),
+    unit("ha", "hectárea", Category::Area, 1e4),
+    unit("acre", "acre", Category::Area, 4_046.856_422_4),
+    unit("ft2", "pie cuadrado", Category::Area, 0.092_903_04),
+    unit("in2", "pulgada cuadrada", Category::Area, 6.4516e-4),
+    unit("mi2", "milla cuadrada", Category::Area, 2_589_988.110_336),
+    // Volume (m³)
+    unit("m3", "metro cúbico", Category::Volume, 1.0),
+    unit("l", "litro", Category::Volume, 1e-3),
+    unit("ml", "mililitro", Category::Volume, 1e-6),
+    unit("cm3", "centímetro cúbico", Category::Volume, 1e-6),
+    unit("gal", "galón (EE. UU.)", Category::Volume, 3.785_411_784e-3),
+    unit("qt", "cuarto (EE. UU.)", Category::Volume, 9.463_529_46e-4),
+    unit("pt", "pinta (EE. UU.)", Category::Volume, 4.731_764_73e-4),
+    unit("cup", "taza (EE. UU.)", Category::Volume, 2.365_882_365e-4),
+    unit("floz", "onza líquida (EE. UU.)", Category::Volume, 2.957_352_956_25e-5),
+    unit("ft3", "pie cúbico", Category::Volume, 0.028_316_846_592),
+    unit("in3", "pulgada cúbica", Category::Volume, 1.638_706_4e-5),
+    // Mass (kg)
+    unit("kg", "kilogramo", Category::Mass, 1.0),
+    unit("g", "gramo", Category::Mass, 1e-3),
+    unit("mg", "miligramo", Category::Mass, 1e-6),
+    unit("t", "tonelada", Category::Mass, 1000.0),
+    unit("lb", "libra", Category::Mass, 0.453_592_37),
+    unit("oz", "onza", Category::Mass, 0.028_349_523_125),
+    unit("st", "stone", Category::Mass, 6.350_293_18),
+    // Time (s)
+    unit("s", "segundo", Category::Time, 1.0),
+    unit("ms", "milisegundo", Category::Time, 1e-3),
+    unit("min", "minuto", Category::Time, 60.0),
+    unit("h", "hora", Category::Time, 3600.0),
+    unit("d", "día", Category::Time, 86_400.0),
+    unit("wk", "semana", Category::Time, 604_800.0),
+    unit("yr", "año", Category::Time, 31_557_600.0),
+    // Speed (m/s)
+    unit("m/s", "metro por segundo", Category::Speed, 1.0),
+    unit("km/h", "kilómetro por hora", Category::Speed, 1.0 / 3.6),
+    unit("mph", "milla por hora", Category::Speed, 0.447_04),
+    unit("kn", "nudo", Category::Speed, 1852.0 / 3600.0),
+    unit("ft/s", "pie por segundo", Category::Speed, 0.3048),
+    // Temperature (K)
+    Unit { symbol: "K", name: "kelvin", category: Category::Temperature, factor: 1.0, offset: 0.0 },
+    Unit { symbol: "°C", name: "grado Celsius", category: Category::Temperature, factor: 1.0, offset: 273.15 },
+    Unit { symbol: "°F", name: "grado Fahrenheit", category: Category::Temperature, factor: 5.0 / 9.0, offset: 459.67 * 5.0 / 9.0 },
+    // Energy (J)
+    unit("J", "julio", Category::Energy, 1.0),
+    unit("kJ", "kilojulio", Category::Energy, 1000.0),
+    unit("cal", "caloría", Category::Energy, 4.184),
+    unit("kcal", "kilocaloría", Category::Energy, 4184.0),
+    unit("Wh", "vatio hora", Category::Energy, 3600.0),
+    unit("kWh", "kilovatio hora", Category::Energy, 3.6e6),
+    unit("eV", "electronvoltio", Category::Energy, 1.602_176_634e-19),
+    unit("BTU", "unidad térmica británica", Category::Energy, 1_055.055_852_62),
+    // Pressure (Pa)
+    unit("Pa", "pascal", Category::Pressure, 1.0),
+    unit("kPa", "kilopascal", Category::Pressure, 1000.0),
+    unit("bar", "bar", Category::Pressure, 1e5),
+    unit("atm", "atmósfera", Category::Pressure, 101_325.0),
+    unit("psi", "libra por pulgada cuadrada", Category::Pressure, 6_894.757_293_168),
+    unit("mmHg", "milímetro de mercurio", Category::Pressure, 133.322_387_415),
+    // Force (N)
+    unit("N", "newton", Category::Force, 1.0),
+    unit("kN", "kilonewton", Category::Force, 1000.0),
+    unit("lbf", "libra fuerza", Category::Force, 4.448_221_615_260_5),
+    // Power (W)
+    unit("W", "vatio", Category::Power, 1.0),
+    unit("kW", "kilovatio", Category::Power, 1000.0),
+    unit("MW", "megavatio", Category::Power, 1e6),
+    unit("hp", "caballo de fuerza", Category::Power, 745.699_871_582_270_2),
+    // Frequency (Hz)
+    unit("Hz", "hercio", Category::Frequency, 1.0),
+    unit("kHz", "kilohercio", Category::Frequency, 1000.0),
+    unit("MHz", "megahercio", Category::Frequency, 1e6),
+    unit("GHz", "gigahercio", Category::Frequency, 1e9),
+    // Data (byte)
+    unit("bit", "bit", Category::Data, 0.125),
+    unit("B", "byte", Category::Data, 1.0),
+    unit("KB", "kilobyte", Category::Data, 1e3),
+    unit("MB", "megabyte", Category::Data, 1e6),
+    unit("GB", "gigabyte", Category::Data, 1e9),
+    unit("TB", "terabyte", Category::Data, 1e12),
+    unit("KiB", "kibibyte", Category::Data, 1024.0),
+    unit("MiB", "mebibyte", Category::Data, 1_048_576.0),
+    unit("GiB", "gibibyte", Category::Data, 1_073_741_824.0),
+    unit("TiB", "tebibyte", Category::Data, 1_099_511_627_776.0),
+    unit("Mb", "megabit", Category::Data, 1e6 / 8.0),
+    unit("Gb", "gigabit", Category::Data, 1e9 / 8.0),
+];
+
+// Exact symbol first, then case-insensitively; "C"/"F" also find °C/°F
+pub fn find(symbol: &str) -> Option<&'static Unit> {
+    let symbol = symbol.trim();
+    UNITS.iter().find(|unit| unit.symbol == symbol)
+        .or_else(|| UNITS.iter().find(|unit| unit.symbol.trim_start_matches('°') == symbol))
+        .or_else(|| UNITS.iter().find(|unit| unit.symbol.trim_start_matches('°').eq_ignore_ascii_case(symbol)))
+}
+
+// Only the exact symbol, as written in unit mode expressions
+pub fn symbol(symbol: &str) -> Option<&'static Unit> {
+    UNITS.iter().find(|unit| unit.symbol == symbol)
+}
+
+pub fn convert(value: f64, from: &str, to: &str) -> Result<f64, Error> {
+    let unknown = |symbol: &str| Error::InvalidOperation(format!("Unidad desconocida: {}", symbol));
+    let from_unit = find(from).ok_or_else(|| unknown(from))?;
+    let to_unit = find(to).ok_or_else(|| unknown(to))?;
+    if from_unit.category != to_unit.category {
+        return Err(Error::InvalidOperation(format!(
+            "No se puede convertir {} ({}) a {} ({})",
+            from_unit.symbol, from_unit.category.name(), to_unit.symbol, to_unit.category.name()
+        )));
+    }
+    let base = value * from_unit.factor + from_unit.offset;
+    Ok(round_significant((base - to_unit.offset) / to_unit.factor))
+}
+
+// The factors carry about 12 significant digits; hide the float noise beyond them
+pub fn round_significant(x: f64) -> f64 {
+    if x == 0.0 || !x.is_finite() {
+        return x;
+    }
+    let scale = 10f64.powi(11 - x.abs().log10().floor() as i32);
+    if !scale.is_finite() {
+        return x;
+    }
+    (x * scale).round() / scale
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        assert_eq!(convert(100.0, "°C", "°F").unwrap(), 212.0);
+        assert_eq!(convert(32.0, "F", "C").unwrap(), 0.0);
+        assert_eq!(convert(0.0, "K", "°C").unwrap(), -273.15);
+        assert_eq!(convert(1.0, "mi", "km").unwrap(), 1.609344);
+        assert_eq!(convert(1.0, "kWh", "J").unwrap(), 3.6e6);
+        assert_eq!(convert(1.0, "GiB", "MB").unwrap(), 1073.741824);
+        // Float noise past the factors' precision is rounded away
+        assert_eq!(convert(1.0, "km/h", "m/s").unwrap(), 0.277777777778);
+        assert!(convert(1.0, "m", "kg").is_err());
+        assert!(convert(1.0, "xx", "m").is_err());
+    }
+
+    #[test]
+    fn symbols() {
+        // Exact case wins, so megabits and megabytes stay apart
+        assert_eq!(find("Mb").unwrap().name, "megabit");
+        assert_eq!(find("MB").unwrap().name, "megabyte");
+        assert_eq!(find("kwh").unwrap().symbol, "kWh");
+        assert_eq!(find(" C ").unwrap().symbol, "°C");
+        assert!(symbol("kwh").is_none());
+        for category in Category::ALL {
+            assert!(category.units().any(|unit| unit.factor == 1.0 && unit.offset == 0.0), "{}", category.name());
+        }
+    }
+}
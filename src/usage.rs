@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::calculator::{Error, Function};
+
+// Rows in each ranking of the dashboard
+const TOP: usize = 5;
+
+// Evaluations of the session, kept with the workspace, to see which
+// functions get used and which mistakes come up most
+#[derive(Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Usage {
+    pub evaluations: usize,
+    pub errors: usize,
+    pub functions: BTreeMap<String, usize>,
+    // Times each kind of error happened and the last expression that gave it
+    pub mistakes: BTreeMap<String, (usize, String)>,
+}
+
+// The error without the details of one expression, so alike mistakes count together
+fn kind(error: &Error) -> String {
+    match error {
+        Error::BadToken { .. } => "Carácter no válido".to_string(),
+        Error::MismatchedParens { .. } => "Paréntesis sin pareja".to_string(),
+        Error::InvalidNumber(_) => "Número no válido".to_string(),
+        Error::DivisionByZero => "División por cero".to_string(),
+        Error::UnknownFunction(_) => "Función desconocida".to_string(),
+        Error::UndefinedVariable(_) => "Variable no definida".to_string(),
+        // Messages put their particulars after a colon
+        Error::InvalidOperation(message) => message.split(':').next().unwrap_or(message).trim().to_string(),
+    }
+}
+
+// The most frequent entries of `counts`, most frequent first
+fn ranking<'a>(counts: impl Iterator<Item = (&'a String, usize)>) -> Vec<(&'a String, usize)> {
+    let mut ranking: Vec<_> = counts.collect();
+    ranking.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    ranking.truncate(TOP);
+    ranking
+}
+
+impl Usage {
+    pub fn record(&mut self, expression: &str, functions: &[Function], error: Option<&Error>) {
+        self.evaluations += 1;
+        for function in functions {
+            // Currency conversions aren't in the function table
+            let name = match function {
+                Function::Currency(..) => "moneda",
+                _ => function.name(),
+            };
+            *self.functions.entry(name.to_string()).or_default() += 1;
+        }
+        if let Some(error) = error {
+            self.errors += 1;
+            let mistake = self.mistakes.entry(kind(error)).or_default();
+            mistake.0 += 1;
+            mistake.1 = expression.to_string();
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        if self.evaluations == 0 {
+            ui.weak("Todavía no se ha calculado nada en esta sesión");
+            return;
+        }
+        let rate = self.errors as f64 / self.evaluations as f64;
+        egui::Grid::new("usage_totals").num_columns(2).show(ui, |ui| {
+            ui.label("Cálculos");
+            ui.monospace(self.evaluations.to_string());
+            ui.end_row();
+            ui.label("Con error");
+            ui.monospace(format!("{} ({:.0} %)", self.errors, rate * 100.0));
+            ui.end_row();
+        });
+        ui.add(egui::ProgressBar::new(1.0 - rate as f32).text(format!("{:.0} % sin error", (1.0 - rate) * 100.0)));
+
+        ui.separator();
+        ui.strong("Funciones más usadas");
+        let functions = ranking(self.functions.iter().map(|(name, count)| (name, *count)));
+        if functions.is_empty() {
+            ui.weak("Ninguna");
+        }
+        let most = functions.first().map_or(1, |(_, count)| *count);
+        egui::Grid::new("usage_functions").num_columns(2).show(ui, |ui| {
+            for (name, count) in &functions {
+                ui.monospace(name.as_str());
+                ui.add(egui::ProgressBar::new(*count as f32 / most as f32).desired_width(120.0).text(count.to_string()));
+                ui.end_row();
+            }
+        });
+
+        ui.separator();
+        ui.strong("Errores más frecuentes");
+        let mistakes = ranking(self.mistakes.iter().map(|(kind, (count, _))| (kind, *count)));
+        if mistakes.is_empty() {
+            ui.weak("Ninguno");
+        }
+        egui::Grid::new("usage_mistakes").num_columns(2).show(ui, |ui| {
+            for (kind, count) in &mistakes {
+                ui.label(kind.as_str()).on_hover_text(format!("Último: {}", self.mistakes[*kind].1));
+                ui.monospace(count.to_string());
+                ui.end_row();
+            }
+        });
+        ui.separator();
+        if ui.button("Poner a cero").clicked() {
+            *self = Usage::default();
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use std::fmt;
+
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use crate::calculator::Error;
+use crate::complex::Complex;
+use crate::eigen::Eigen;
+use crate::list;
+use crate::matrix::Matrix;
+use crate::quantity::{Dimension, Quantity};
+use crate::uncertainty::Measurement;
+
+// Parts smaller than this fraction of the modulus are rounding noise
+const NOISE: f64 = 8.0 * f64::EPSILON;
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum Value {
+    Number(f64),
+    Integer(BigInt),
+    Complex(Complex),
+    Uncertain(Measurement),
+    Quantity(Quantity),
+    Text(String),
+    Matrix(Matrix),
+    Eigen(Eigen),
+    List(Vec<f64>),
+}
+
+impl Value {
+    // Collapses complex results with a negligible imaginary part back to reals
+    pub fn from_complex(z: Complex) -> Value {
+        let modulus = z.abs();
+        let re = if z.re.abs() <= NOISE * modulus { 0.0 } else { z.re };
+        let im = if z.im.abs() <= NOISE * modulus { 0.0 } else { z.im };
+        if im == 0.0 {
+            Value::Number(re)
+        } else {
+            Value::Complex(Complex::new(re, im))
+        }
+    }
+
+    pub fn complex(self) -> Result<Complex, Error> {
+        match self {
+            Value::Number(n) => Ok(Complex::from(n)),
+            Value::Integer(n) => Ok(Complex::from(n.to_f64().unwrap_or(f64::INFINITY))),
+            Value::Complex(z) => Ok(z),
+            Value::Uncertain(_) => Err(Error::InvalidOperation("Los números complejos no admiten incertidumbre".to_string())),
+            Value::Quantity(_) => Err(Error::InvalidOperation("Los números complejos no admiten unidades".to_string())),
+            Value::Text(_) => Err(Error::InvalidOperation("Se esperaba un número".to_string())),
+            Value::Matrix(_) => Err(Error::InvalidOperation("Se esperaba un número, no una matriz".to_string())),
+            Value::Eigen(_) => Err(Error::InvalidOperation("Se esperaba un número, no valores propios".to_string())),
+            Value::List(_) => Err(Error::InvalidOperation("Se esperaba un número, no una lista".to_string())),
+        }
+    }
+
+    pub fn real(self) -> Result<f64, Error> {
+        match self {
+            Value::Number(n) => Ok(n),
+            Value::Integer(n) => Ok(n.to_f64().unwrap_or(f64::INFINITY)),
+            Value::Quantity(q) if q.dim.is_none() => Ok(q.value),
+            _ => Err(Error::InvalidOperation("Se esperaba un número real".to_string())),
+        }
+    }
+
+    // Numbers pass as 1×1 matrices
+    pub fn matrix(self) -> Result<Matrix, Error> {
+        match self {
+            Value::Matrix(m) => Ok(m),
+            Value::List(items) if !items.is_empty() => Ok(Matrix::new(1, items.len(), items)),
+            value => value.real().map(|x| Matrix::new(1, 1, vec![x])).map_err(|_| Error::InvalidOperation("Se esperaba una matriz".to_string())),
+        }
+    }
+
+    // Vectors and single numbers pass as lists
+    pub fn list(self) -> Result<Vec<f64>, Error> {
+        match self {
+            Value::List(items) => Ok(items),
+            Value::Matrix(m) if m.rows == 1 || m.cols == 1 => Ok((0..m.rows).flat_map(|i| m.row(i).to_vec()).collect()),
+            value => value.real().map(|x| vec![x]).map_err(|_| Error::InvalidOperation("Se esperaba una lista".to_string())),
+        }
+    }
+
+    pub fn text(self) -> Result<String, Error> {
+        match self {
+            Value::Text(text) => Ok(text),
+            _ => Err(Error::InvalidOperation("Se esperaba un texto entre comillas".to_string())),
+        }
+    }
+
+    pub fn measurement(self) -> Result<Measurement, Error> {
+        match self {
+            Value::Number(n) => Ok(Measurement::exact(n)),
+            Value::Integer(n) => Ok(Measurement::exact(n.to_f64().unwrap_or(f64::INFINITY))),
+            Value::Uncertain(m) => Ok(m),
+            Value::Complex(_) => Err(Error::InvalidOperation("Los números complejos no admiten incertidumbre".to_string())),
+            Value::Quantity(_) => Err(Error::InvalidOperation("Las unidades no admiten incertidumbre".to_string())),
+            Value::Text(_) => Err(Error::InvalidOperation("Se esperaba un número".to_string())),
+            Value::Matrix(_) | Value::Eigen(_) => Err(Error::InvalidOperation("Las matrices no admiten incertidumbre".to_string())),
+            Value::List(_) => Err(Error::InvalidOperation("Las listas no admiten incertidumbre".to_string())),
+        }
+    }
+
+    // Plain numbers are dimensionless quantities
+    pub fn quantity(self) -> Result<Quantity, Error> {
+        match self {
+            Value::Number(n) => Ok(Quantity::new(n, Dimension::NONE)),
+            Value::Integer(n) => Ok(Quantity::new(n.to_f64().unwrap_or(f64::INFINITY), Dimension::NONE)),
+            Value::Quantity(q) => Ok(q),
+            Value::Complex(_) => Err(Error::InvalidOperation("Los números complejos no admiten unidades".to_string())),
+            Value::Uncertain(_) => Err(Error::InvalidOperation("Las unidades no admiten incertidumbre".to_string())),
+            Value::Text(_) => Err(Error::InvalidOperation("Se esperaba un número".to_string())),
+            Value::Matrix(_) | Value::Eigen(_) => Err(Error::InvalidOperation("Las matrices no admiten unidades".to_string())),
+            Value::List(_) => Err(Error::InvalidOperation("Las listas no admiten unidades".to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Number(n) => write!(f, "{}", n),
+            Value::Integer(n) => write!(f, "{}", n),
+            Value::Complex(z) => write!(f, "{}", z),
+            Value::Uncertain(m) => write!(f, "{}", m),
+            Value::Quantity(q) => write!(f, "{}", q),
+            Value::Text(text) => write!(f, "{}", text),
+            Value::Matrix(m) => write!(f, "{}", m),
+            Value::Eigen(e) => write!(f, "{}", e),
+            Value::List(items) => write!(f, "{}", list::format(items, |x| x.to_string(), ", ")),
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use serde::{Deserialize, Serialize};
+
+use crate::calculator::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Locale {
+    #[default]
+    Es,
+    En,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::Es, Locale::En];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Locale::Es => "Español",
+            Locale::En => "English",
+        }
+    }
+}
+
+// Largest integer part that f64 still represents exactly
+const MAX_WORDS: f64 = 1e15;
+
+pub fn to_words(n: f64, locale: Locale) -> Result<String, Error> {
+    if !n.is_finite() || n.abs() >= MAX_WORDS {
+        return Err(Error::InvalidOperation("Número demasiado grande para convertir a palabras".to_string()));
+    }
+
+    let text = format!("{}", n.abs());
+    let (int_part, frac_part) = match text.split_once('.') {
+        Some((i, f)) => (i, Some(f)),
+        None => (text.as_str(), None),
+    };
+    let int_value: u64 = int_part.parse().unwrap_or(0);
+
+    let mut words = Vec::new();
+    if n < 0.0 {
+        words.push(match locale {
+            Locale::Es => "menos",
+            Locale::En => "minus",
+        }.to_string());
+    }
+    words.push(match locale {
+        Locale::Es => integer_es(int_value),
+        Locale::En => integer_en(int_value),
+    });
+    if let Some(frac) = frac_part {
+        words.push(match locale {
+            Locale::Es => "punto",
+            Locale::En => "point",
+        }.to_string());
+        for digit in frac.chars().filter_map(|d| d.to_digit(10)) {
+            words.push(match locale {
+                Locale::Es => UNITS_ES[digit as usize],
+                Locale::En => UNITS_EN[digit as usize],
+            }.to_string());
+        }
+    }
+
+    Ok(words.join(" "))
+}
+
+const UNITS_EN: [&str; 20] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+    "ten", "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen",
+    "seventeen", "eighteen", "nineteen",
+];
+
+const TENS_EN: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+const SCALES_EN: [&str; 5] = ["", "thousand", "million", "billion", "trillion"];
+
+fn below_thousand_en(n: u64) -> String {
+    let mut parts = Vec::new();
+    let hundreds = n / 100;
+    let rest = n % 100;
+    if hundreds > 0 {
+        parts.push(format!("{} hundred", UNITS_EN[hundreds as usize]));
+    }
+    if rest >= 20 {
+        let tens = TENS_EN[(rest / 10) as usize];
+        if rest.is_multiple_of(10) {
+            parts.push(tens.to_string());
+        } else {
+            parts.push(format!("{}-{}", tens, UNITS_EN[(rest % 10) as usize]));
+        }
+    } else if rest > 0 {
+        parts.push(UNITS_EN[rest as usize].to_string());
+    }
+    parts.join(" ")
+}
+
+fn integer_en(n: u64) -> String {
+    if n == 0 {
+        return UNITS_EN[0].to_string();
+    }
+    let mut groups = Vec::new();
+    let mut rest = n;
+    let mut scale = 0;
+    while rest > 0 {
+        let group = rest % 1000;
+        if group > 0 {
+            let mut words = below_thousand_en(group);
+            if scale > 0 {
+                words = format!("{} {}", words, SCALES_EN[scale]);
+            }
+            groups.push(words);
+        }
+        rest /= 1000;
+        scale += 1;
+    }
+    groups.reverse();
+    groups.join(" ")
+}
+
+const UNITS_ES: [&str; 30] = [
+    "cero", "uno", "dos", "tres", "cuatro", "cinco", "seis", "siete", "ocho", "nueve",
+    "diez", "once", "doce", "trece", "catorce", "quince", "dieciséis", "diecisiete",
+    "dieciocho", "diecinueve", "veinte", "veintiuno", "veintidós", "veintitrés",
+    "veinticuatro", "veinticinco", "veintiséis", "veintisiete", "veintiocho", "veintinueve",
+];
+
+const TENS_ES: [&str; 10] = [
+    "", "", "", "treinta", "cuarenta", "cincuenta", "sesenta", "setenta", "ochenta", "noventa",
+];
+
+const HUNDREDS_ES: [&str; 10] = [
+    "", "ciento", "doscientos", "trescientos", "cuatrocientos", "quinientos",
+    "seiscientos", "setecientos", "ochocientos", "novecientos",
+];
+
+// `apocope` shortens a trailing "uno" to "un" before "mil"/"millones"
+fn below_thousand_es(n: u64, apocope: bool) -> String {
+    if n == 100 {
+        return "cien".to_string();
+    }
+    let mut parts = Vec::new();
+    let hundreds = n / 100;
+    let rest = n % 100;
+    if hundreds > 0 {
+        parts.push(HUNDREDS_ES[hundreds as usize].to_string());
+    }
+    let rest_words = if rest >= 30 {
+        let tens = TENS_ES[(rest / 10) as usize];
+        if rest.is_multiple_of(10) {
+            tens.to_string()
+        } else {
+            format!("{} y {}", tens, UNITS_ES[(rest % 10) as usize])
+        }
+    } else if rest > 0 {
+        UNITS_ES[rest as usize].to_string()
+    } else {
+        String::new()
+    };
+    if !rest_words.is_empty() {
+        if apocope && rest_words.ends_with("uno") {
+            let shortened = rest_words.trim_end_matches('o');
+            parts.push(if shortened == "veintiun" { "veintiún".to_string() } else { shortened.to_string() });
+        } else {
+            parts.push(rest_words);
+        }
+    }
+    parts.join(" ")
+}
+
+// Words for 0..1_000_000, shared by every "millones" group
+fn below_million_es(n: u64, apocope: bool) -> String {
+    let thousands = n / 1000;
+    let rest = n % 1000;
+    let mut parts = Vec::new();
+    if thousands == 1 {
+        parts.push("mil".to_string());
+    } else if thousands > 1 {
+        parts.push(format!("{} mil", below_thousand_es(thousands, true)));
+    }
+    if rest > 0 {
+        parts.push(below_thousand_es(rest, apocope));
+    }
+    parts.join(" ")
+}
+
+fn integer_es(n: u64) -> String {
+    if n == 0 {
+        return UNITS_ES[0].to_string();
+    }
+    // Spanish uses the long scale: millón = 10^6, billón = 10^12
+    let billions = n / 1_000_000_000_000;
+    let millions = (n / 1_000_000) % 1_000_000;
+    let rest = n % 1_000_000;
+    let mut parts = Vec::new();
+    if billions == 1 {
+        parts.push("un billón".to_string());
+    } else if billions > 1 {
+        parts.push(format!("{} billones", below_million_es(billions, true)));
+    }
+    if millions == 1 {
+        parts.push("un millón".to_string());
+    } else if millions > 1 {
+        parts.push(format!("{} millones", below_million_es(millions, true)));
+    }
+    if rest > 0 {
+        parts.push(below_million_es(rest, false));
+    }
+    parts.join(" ")
+}
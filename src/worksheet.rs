@@ -0,0 +1,271 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use crate::calculator::Calculator;
+use crate::format::NumberFormat;
+
+// Worksheet files are plain text: one expression per line, evaluated top to
+// bottom so assignments carry down, and lines starting with # are notes
+pub const EXTENSION: &str = "calc";
+
+pub fn is_worksheet(path: &Path) -> bool {
+    path.extension().and_then(|extension| extension.to_str()).is_some_and(|extension| extension.eq_ignore_ascii_case(EXTENSION))
+}
+
+fn is_note(line: &str) -> bool {
+    line.trim().is_empty() || line.trim_start().starts_with('#')
+}
+
+// Lines with assert_eq() check an answer, green when it holds
+fn is_check(line: &str) -> bool {
+    !is_note(line) && line.contains("assert_eq(")
+}
+
+const PASSED_COLOR: egui::Color32 = egui::Color32::from_rgb(90, 200, 90);
+
+// Each line's result or error; None for notes
+type Results = Vec<Option<Result<String, String>>>;
+
+pub struct Worksheet {
+    pub lines: Vec<String>,
+    // Where it was opened from or last saved to
+    pub path: Option<PathBuf>,
+    pub error: Option<String>,
+    // Line to focus on the next frame, after Enter adds one
+    focus: Option<usize>,
+    // The last results and what they were, or are being, computed from
+    results: Results,
+    inputs: Option<(Vec<String>, Calculator, NumberFormat)>,
+    pending: Option<mpsc::Receiver<Results>>,
+}
+
+impl Default for Worksheet {
+    fn default() -> Self {
+        Self { lines: vec![String::new()], path: None, error: None, focus: None, results: Vec::new(), inputs: None, pending: None }
+    }
+}
+
+impl Worksheet {
+    pub fn open(path: &Path) -> Result<Worksheet, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("No se pudo abrir {}: {}", path.display(), e))?;
+        let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        Ok(Worksheet { lines, path: Some(path.to_path_buf()), ..Default::default() })
+    }
+
+    // Back to lines kept in a workspace
+    pub fn restore(&mut self, lines: Vec<String>, path: Option<PathBuf>) {
+        *self = Worksheet { lines, path, ..Default::default() };
+        if self.lines.is_empty() {
+            self.lines.push(String::new());
+        }
+    }
+
+    fn save_to(&mut self, path: PathBuf) {
+        let mut text = self.lines.join("\n");
+        text.push('\n');
+        self.error = std::fs::write(&path, text).err().map(|e| e.to_string());
+        if self.error.is_none() {
+            self.path = Some(path);
+        }
+    }
+
+    fn save_as(&mut self) {
+        let name = self.path.as_ref().and_then(|path| path.file_name()).map_or_else(
+            || format!("hoja.{}", EXTENSION),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        if let Some(path) = rfd::FileDialog::new().set_file_name(name).add_filter("Hoja de cálculo", &[EXTENSION]).save_file() {
+            self.save_to(path);
+        }
+    }
+
+    fn pick(&mut self) {
+        if let Some(path) = rfd::FileDialog::new().add_filter("Hoja de cálculo", &[EXTENSION]).pick_file() {
+            match Worksheet::open(&path) {
+                Ok(worksheet) => *self = worksheet,
+                Err(e) => self.error = Some(e),
+            }
+        }
+    }
+
+    // Evaluates the sheet again on its own thread when the lines, the
+    // calculator or the format change, showing the last results meanwhile,
+    // so slow lines like fetch_num() don't hold up every frame
+    fn refresh(&mut self, ctx: &egui::Context, calculator: &Calculator, format: &NumberFormat) {
+        let changed = self.inputs.as_ref().is_none_or(|(lines, evaluated, evaluated_format)| {
+            *lines != self.lines || evaluated != calculator || evaluated_format != format
+        });
+        if changed {
+            let inputs = (self.lines.clone(), calculator.clone(), *format);
+            let (lines, calculator, format) = inputs.clone();
+            let (sender, receiver) = mpsc::channel();
+            std::thread::spawn(move || {
+                // Nobody is waiting any more if the sheet changed again
+                let _ = sender.send(results(&lines, &calculator, &format));
+            });
+            self.inputs = Some(inputs);
+            self.pending = Some(receiver);
+        }
+        if let Some(receiver) = &self.pending {
+            match receiver.try_recv() {
+                Ok(results) => {
+                    self.results = results;
+                    self.pending = None;
+                },
+                Err(mpsc::TryRecvError::Empty) => ctx.request_repaint_after(Duration::from_millis(50)),
+                Err(mpsc::TryRecvError::Disconnected) => self.pending = None,
+            }
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, calculator: &Calculator, format: &NumberFormat) {
+        ui.horizontal(|ui| {
+            let title = self.path.as_ref().and_then(|path| path.file_name()).map_or_else(
+                || "Hoja sin guardar".to_string(),
+                |name| name.to_string_lossy().into_owned(),
+            );
+            ui.heading(title);
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.small_button("Guardar como…").clicked() {
+                    self.save_as();
+                }
+                if ui.small_button("Guardar").clicked() {
+                    match self.path.clone() {
+                        Some(path) => self.save_to(path),
+                        None => self.save_as(),
+                    }
+                }
+                if ui.small_button("Abrir…").clicked() {
+                    self.pick();
+                }
+                if ui.small_button("Nueva").clicked() {
+                    *self = Worksheet::default();
+                }
+            });
+        });
+        ui.small("Una expresión por línea; las variables valen para las líneas de abajo. # para notas. assert_eq(x, 2, 0.01) comprueba una respuesta.");
+        if let Some(error) = &self.error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+        ui.separator();
+
+        self.refresh(ui.ctx(), calculator, format);
+        let results = &self.results;
+        let checks: Vec<bool> = self.lines.iter().zip(results)
+            .filter(|(line, _)| is_check(line))
+            .map(|(_, result)| matches!(result, Some(Ok(_))))
+            .collect();
+        if !checks.is_empty() {
+            let passed = checks.iter().filter(|passed| **passed).count();
+            let color = if passed == checks.len() { PASSED_COLOR } else { egui::Color32::RED };
+            ui.colored_label(color, format!("Comprobaciones: {} de {} correctas", passed, checks.len()));
+        }
+        let mut inserted = None;
+        let mut removed = None;
+        egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+            egui::Grid::new("worksheet").num_columns(4).striped(true).show(ui, |ui| {
+                // Until the new results arrive, added lines have none
+                for (i, line) in self.lines.iter_mut().enumerate() {
+                    let result = results.get(i).and_then(Option::as_ref);
+                    ui.label(egui::RichText::new((i + 1).to_string()).monospace().weak());
+                    let color = is_note(line).then_some(egui::Color32::GRAY);
+                    let mut field = egui::TextEdit::singleline(line)
+                        .id(egui::Id::new(("worksheet_line", i)))
+                        .font(egui::TextStyle::Monospace)
+                        .desired_width(200.0);
+                    if let Some(color) = color {
+                        field = field.text_color(color);
+                    }
+                    let response = ui.add(field);
+                    if self.focus == Some(i) {
+                        response.request_focus();
+                        self.focus = None;
+                    }
+                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        inserted = Some(i + 1);
+                    }
+                    match result {
+                        Some(Ok(value)) if is_check(line) => {
+                            ui.colored_label(PASSED_COLOR, egui::RichText::new(format!("✔ {}", value)).monospace());
+                        },
+                        Some(Ok(value)) => {
+                            ui.monospace(format!("= {}", value));
+                        },
+                        Some(Err(error)) => {
+                            ui.colored_label(egui::Color32::RED, error);
+                        },
+                        None => {
+                            ui.label("");
+                        },
+                    }
+                    if ui.small_button("✖").on_hover_text("Quitar la línea").clicked() {
+                        removed = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+            if ui.small_button("+ Línea").clicked() {
+                inserted = Some(self.lines.len());
+            }
+        });
+        if let Some(i) = inserted {
+            self.lines.insert(i, String::new());
+            self.focus = Some(i);
+        }
+        if let Some(i) = removed {
+            self.lines.remove(i);
+            if self.lines.is_empty() {
+                self.lines.push(String::new());
+            }
+        }
+    }
+}
+
+// Each line's result or error, with the variables the lines above assigned
+fn results(lines: &[String], calculator: &Calculator, format: &NumberFormat) -> Results {
+    let mut sheet = calculator.clone();
+    lines.iter().map(|line| {
+        (!is_note(line)).then(|| match sheet.execute(line) {
+            Ok((value, _)) => Ok(format.value(&value)),
+            Err(e) => Err(e.to_string()),
+        })
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settle(worksheet: &mut Worksheet, calculator: &Calculator) {
+        let ctx = egui::Context::default();
+        worksheet.refresh(&ctx, calculator, &NumberFormat::default());
+        while worksheet.pending.is_some() {
+            std::thread::sleep(Duration::from_millis(5));
+            worksheet.refresh(&ctx, calculator, &NumberFormat::default());
+        }
+    }
+
+    #[test]
+    fn evaluated_when_the_sheet_changes() {
+        let mut worksheet = Worksheet { lines: vec!["a = 2".to_string(), "# nota".to_string(), "a^2 + b".to_string()], ..Default::default() };
+        let mut calculator = Calculator::default();
+        calculator.assign("b", crate::value::Value::Number(1.0)).unwrap();
+        settle(&mut worksheet, &calculator);
+        assert_eq!(worksheet.results, [Some(Ok("2".to_string())), None, Some(Ok("5".to_string()))]);
+
+        // Nothing changed, nothing to do
+        worksheet.refresh(&egui::Context::default(), &calculator, &NumberFormat::default());
+        assert!(worksheet.pending.is_none());
+
+        calculator.assign("b", crate::value::Value::Number(10.0)).unwrap();
+        settle(&mut worksheet, &calculator);
+        assert_eq!(worksheet.results[2], Some(Ok("14".to_string())));
+        worksheet.lines[2] = "a^3".to_string();
+        settle(&mut worksheet, &calculator);
+        assert_eq!(worksheet.results[2], Some(Ok("8".to_string())));
+    }
+}
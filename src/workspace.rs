@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::calculator::Juxtaposition;
+use crate::engine::Conditions;
+use crate::format::NumberFormat;
+use crate::history::Entry;
+use crate::modes::GraphState;
+use crate::programmer::{Radix, WordSize};
+use crate::usage::Usage;
+use crate::value::Value;
+use crate::words::Locale;
+use crate::Tab;
+
+// Everything needed to pick a project back up where it was left
+#[derive(Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Workspace {
+    pub tab: Tab,
+    pub display: String,
+    pub result: String,
+    pub value: Option<Value>,
+    pub variables: BTreeMap<String, Value>,
+    pub history: Vec<Entry>,
+    pub real_only: bool,
+    pub integer_mode: bool,
+    pub unit_mode: bool,
+    pub juxtaposition: Juxtaposition,
+    pub locale: Locale,
+    pub format: NumberFormat,
+    pub converter: (String, String, String),
+    pub currency: (String, String, String),
+    pub programmer: (Radix, WordSize, String),
+    // The worksheet's lines and the file they came from
+    pub worksheet: (Vec<String>, Option<PathBuf>),
+    pub graph: Option<GraphState>,
+    pub usage: Usage,
+    // Engine and settings as of saving, to tell whether results still hold
+    pub conditions: Option<Conditions>,
+}
+
+pub enum Action {
+    Open(String),
+    SaveAs(String),
+    Delete(String),
+    // Startup picker only: keep the blank state
+    Skip,
+}
+
+// Saved workspaces plus the one in use, which is written back on exit
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Workspaces {
+    pub saved: BTreeMap<String, Workspace>,
+    pub current: Option<String>,
+    // The workspace in use when the app last closed, offered first at startup
+    #[serde(skip)]
+    pub last: Option<String>,
+    #[serde(skip)]
+    new_name: String,
+}
+
+impl Workspaces {
+    // Shown once at launch when there is something to choose from
+    pub fn show_picker(&self, ctx: &egui::Context) -> Option<Action> {
+        let mut action = None;
+        egui::Window::new("Elegir espacio de trabajo")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                for name in self.saved.keys() {
+                    let label = if self.last.as_ref() == Some(name) { format!("{} (último)", name) } else { name.clone() };
+                    if ui.button(label).clicked() {
+                        action = Some(Action::Open(name.clone()));
+                    }
+                }
+                ui.separator();
+                if ui.button("Empezar en blanco").clicked() {
+                    action = Some(Action::Skip);
+                }
+            });
+        action
+    }
+
+    pub fn show_manager(&mut self, ctx: &egui::Context, open: &mut bool) -> Option<Action> {
+        let mut action = None;
+        egui::Window::new("Espacios de trabajo").open(open).resizable(false).show(ctx, |ui| {
+            match &self.current {
+                Some(name) => ui.label(format!("Actual: {} (se guarda al salir)", name)),
+                None => ui.label("Sin espacio de trabajo; los cambios no se guardan como proyecto"),
+            };
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.new_name).hint_text("Nombre").desired_width(140.0));
+                let name = self.new_name.trim();
+                if ui.add_enabled(!name.is_empty(), egui::Button::new("Guardar como")).clicked() {
+                    action = Some(Action::SaveAs(name.to_string()));
+                    self.new_name.clear();
+                }
+            });
+            ui.separator();
+            egui::Grid::new("workspaces").striped(true).show(ui, |ui| {
+                for name in self.saved.keys() {
+                    ui.label(name);
+                    if ui.small_button("Abrir").clicked() {
+                        action = Some(Action::Open(name.clone()));
+                    }
+                    if ui.small_button("✖").on_hover_text("Borrar").clicked() {
+                        action = Some(Action::Delete(name.clone()));
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+        action
+    }
+}